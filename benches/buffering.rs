@@ -38,6 +38,9 @@ fn benchmark_buffers(c: &mut Criterion) {
                     config.sinks["out"].buffer = BufferConfig::Memory {
                         max_events: 100,
                         when_full: Default::default(),
+                        overflow_max_size: None,
+                        backend: Default::default(),
+                        fsync: Default::default(),
                     };
 
                     let mut rt = runtime::Runtime::new().unwrap();
@@ -79,6 +82,8 @@ fn benchmark_buffers(c: &mut Criterion) {
                     config.sinks["out"].buffer = BufferConfig::Disk {
                         max_size: 1_000_000,
                         when_full: Default::default(),
+                        backend: Default::default(),
+                        fsync: Default::default(),
                     };
                     config.global.data_dir = Some(data_dir.clone());
 
@@ -121,6 +126,8 @@ fn benchmark_buffers(c: &mut Criterion) {
                     config.sinks["out"].buffer = BufferConfig::Disk {
                         max_size: 10_000,
                         when_full: Default::default(),
+                        backend: Default::default(),
+                        fsync: Default::default(),
                     };
                     config.global.data_dir = Some(data_dir2.clone());
 
@@ -52,6 +52,7 @@ fn benchmark_files_without_partitions(c: &mut Criterion) {
                         path: output.try_into().unwrap(),
                         idle_timeout_secs: None,
                         encoding: sinks::file::Encoding::Text.into(),
+                        schema: None,
                     },
                 );
 
@@ -43,6 +43,7 @@ fn benchmark_http_no_compression(c: &mut Criterion) {
                         headers: Default::default(),
                         batch: Default::default(),
                         encoding: sinks::http::Encoding::Text.into(),
+                        schema: Default::default(),
                         request: Default::default(),
                         tls: Default::default(),
                     },
@@ -101,6 +102,7 @@ fn benchmark_http_gzip(c: &mut Criterion) {
                         headers: Default::default(),
                         batch: Default::default(),
                         encoding: sinks::http::Encoding::Text.into(),
+                        schema: Default::default(),
                         request: Default::default(),
                         tls: Default::default(),
                     },
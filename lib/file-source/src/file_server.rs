@@ -11,6 +11,7 @@ use std::collections::{HashMap, HashSet};
 use std::fs::{self, remove_file, File};
 use std::io::{self, Read, Seek, Write};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use std::time::{self, Duration};
 use tokio::time::delay_for;
 use tracing::field;
@@ -41,6 +42,55 @@ where
     pub fingerprinter: Fingerprinter,
     pub oldest_first: bool,
     pub remove_after: Option<Duration>,
+    pub acknowledgements: Option<Acknowledgements>,
+}
+
+/// The position in a file right after a line read by the `FileServer`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub fingerprint: FileFingerprint,
+    pub position: FilePosition,
+}
+
+/// Positions acknowledged by the consumer of a `FileServer`. When given, file
+/// checkpoints only advance to acknowledged positions rather than to the
+/// positions read, so lines that were never delivered are read again after
+/// a restart.
+#[derive(Clone, Debug, Default)]
+pub struct Acknowledgements(Arc<Mutex<AcknowledgementsState>>);
+
+#[derive(Debug, Default)]
+struct AcknowledgementsState {
+    positions: HashMap<FileFingerprint, FilePosition>,
+    failed: HashSet<FileFingerprint>,
+}
+
+impl Acknowledgements {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Marks the lines of the file up to `checkpoint` as delivered. Must be
+    /// called in the order the lines were read.
+    pub fn acknowledge(&self, checkpoint: Checkpoint) {
+        let mut state = self.0.lock().unwrap();
+        if !state.failed.contains(&checkpoint.fingerprint) {
+            state
+                .positions
+                .insert(checkpoint.fingerprint, checkpoint.position);
+        }
+    }
+
+    /// Marks the line of the file ending at `checkpoint` as not delivered.
+    /// The checkpoint of the file stops advancing from there, so the line and
+    /// all the ones after it are read again after a restart.
+    pub fn fail(&self, checkpoint: Checkpoint) {
+        self.0.lock().unwrap().failed.insert(checkpoint.fingerprint);
+    }
+
+    fn drain(&self) -> Vec<(FileFingerprint, FilePosition)> {
+        self.0.lock().unwrap().positions.drain().collect()
+    }
 }
 
 /// `FileServer` as Source
@@ -64,10 +114,10 @@ where
         self,
         mut chans: C,
         mut shutdown: impl Future + Unpin,
-    ) -> Result<Shutdown, <C as Sink<(Bytes, String)>>::Error>
+    ) -> Result<Shutdown, <C as Sink<(Bytes, String, Checkpoint)>>::Error>
     where
-        C: Sink<(Bytes, String)> + Unpin,
-        <C as Sink<(Bytes, String)>>::Error: std::error::Error,
+        C: Sink<(Bytes, String, Checkpoint)> + Unpin,
+        <C as Sink<(Bytes, String, Checkpoint)>>::Error: std::error::Error,
     {
         let mut line_buffer = Vec::new();
         let mut fingerprint_buffer = Vec::new();
@@ -126,6 +176,12 @@ where
                 // Schedule the next glob time.
                 next_glob_time = now_time.checked_add(self.glob_minimum_cooldown).unwrap();
 
+                if let Some(acknowledgements) = &self.acknowledgements {
+                    for (file_id, position) in acknowledgements.drain() {
+                        checkpointer.set_checkpoint(file_id, position);
+                    }
+                }
+
                 // Write any stored checkpoints (uses glob to find old checkpoints).
                 checkpointer
                     .write_checkpoints()
@@ -213,6 +269,10 @@ where
                             lines.push((
                                 line_buffer.clone().into(),
                                 watcher.path.to_str().expect("not a valid path").to_owned(),
+                                Checkpoint {
+                                    fingerprint: file_id,
+                                    position: watcher.get_file_position(),
+                                },
                             ));
                             line_buffer.clear();
                         }
@@ -243,7 +303,9 @@ where
                 }
                 if bytes_read > 0 {
                     global_bytes_read = global_bytes_read.saturating_add(bytes_read);
-                    checkpointer.set_checkpoint(file_id, watcher.get_file_position());
+                    if self.acknowledgements.is_none() {
+                        checkpointer.set_checkpoint(file_id, watcher.get_file_position());
+                    }
                 }
                 // Do not move on to newer files if we are behind on an older file
                 if self.oldest_first && maxed_out_reading_single_file {
@@ -8,10 +8,12 @@ mod file_watcher;
 mod metadata_ext;
 pub mod paths_provider;
 
-pub use self::file_server::{FileServer, Fingerprinter, Shutdown as FileServerShutdown};
+pub use self::file_server::{
+    Acknowledgements, Checkpoint, FileServer, Fingerprinter, Shutdown as FileServerShutdown,
+};
 
-type FileFingerprint = u64;
-type FilePosition = u64;
+pub type FileFingerprint = u64;
+pub type FilePosition = u64;
 
 #[cfg(test)]
 mod test {
@@ -15,7 +15,7 @@ use prost::Message;
 use snafu::ResultExt;
 use std::{
     collections::VecDeque,
-    convert::TryInto,
+    convert::{TryFrom, TryInto},
     mem::size_of,
     path::PathBuf,
     sync::{
@@ -52,6 +52,22 @@ pub struct Writer {
     batch_size: usize,
     max_size: usize,
     current_size: Arc<AtomicUsize>,
+    recovered: usize,
+    skipped: Arc<AtomicUsize>,
+}
+
+impl Writer {
+    /// The number of events left unread by a previous run when the buffer
+    /// was opened.
+    pub fn recovered(&self) -> usize {
+        self.recovered
+    }
+
+    /// Counts the events the reader dropped because they could not be read
+    /// back.
+    pub fn skipped(&self) -> Arc<AtomicUsize> {
+        Arc::clone(&self.skipped)
+    }
 }
 
 // Writebatch isn't Send, but the leveldb docs explicitly say that it's okay to share across threads
@@ -68,6 +84,8 @@ impl Clone for Writer {
             batch_size: 0,
             max_size: self.max_size,
             current_size: Arc::clone(&self.current_size),
+            recovered: self.recovered,
+            skipped: Arc::clone(&self.skipped),
         }
     }
 }
@@ -97,7 +115,8 @@ impl Sink for Writer {
             self.poll_complete()?;
 
             let buf = Bytes::from(value);
-            let event = proto::EventWrapper::decode(buf).unwrap().into();
+            let event = proto::EventWrapper::decode(buf).unwrap();
+            let event = Event::try_from(event).unwrap();
             return Ok(AsyncSink::NotReady(event));
         }
 
@@ -157,6 +176,7 @@ pub struct Reader {
     ack_counter: Arc<AtomicUsize>,
     unacked_sizes: VecDeque<usize>,
     buffer: Vec<Vec<u8>>,
+    skipped: Arc<AtomicUsize>,
 }
 
 // Writebatch isn't Send, but the leveldb docs explicitly say that it's okay to share across threads
@@ -192,13 +212,14 @@ impl Stream for Reader {
             self.read_offset += 1;
 
             let buf = Bytes::from(value);
-            match proto::EventWrapper::decode(buf) {
-                Ok(event) => {
-                    let event = Event::from(event);
-                    Ok(Async::Ready(Some(event)))
-                }
+            let event = proto::EventWrapper::decode(buf)
+                .map_err(crate::Error::from)
+                .and_then(|event| Event::try_from(event).map_err(Into::into));
+            match event {
+                Ok(event) => Ok(Async::Ready(Some(event))),
                 Err(err) => {
                     error!("Error deserializing proto: {:?}", err);
+                    self.skipped.fetch_add(1, Ordering::SeqCst);
                     debug_assert!(false);
                     self.poll()
                 }
@@ -286,6 +307,8 @@ impl super::DiskBuffer for Buffer {
         let ack_counter = Arc::new(AtomicUsize::new(0));
         let acker = Acker::Disk(Arc::clone(&ack_counter), Arc::clone(&write_notifier));
 
+        let skipped = Arc::new(AtomicUsize::new(0));
+
         let writer = Writer {
             db: Arc::clone(&db),
             write_notifier: Arc::clone(&write_notifier),
@@ -295,6 +318,8 @@ impl super::DiskBuffer for Buffer {
             batch_size: 0,
             max_size,
             current_size: Arc::clone(&current_size),
+            recovered: tail - head,
+            skipped: Arc::clone(&skipped),
         };
 
         let reader = Reader {
@@ -307,6 +332,7 @@ impl super::DiskBuffer for Buffer {
             ack_counter,
             unacked_sizes: VecDeque::new(),
             buffer: Vec::new(),
+            skipped,
         };
 
         Ok((writer, reader, acker))
@@ -1,12 +1,14 @@
-#![cfg(feature = "leveldb")]
-
+use super::{DiskBackend, FsyncPolicy};
 use crate::event::Event;
 use futures01::{Async, AsyncSink, Sink, Stream};
 use snafu::Snafu;
 use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::{atomic::AtomicUsize, Arc};
 
+#[cfg(feature = "leveldb")]
 pub mod leveldb_buffer;
+pub mod segment_buffer;
 
 #[derive(Debug, Snafu)]
 pub enum Error {
@@ -19,11 +21,19 @@ pub enum Error {
         data_dir: PathBuf,
         source: std::io::Error,
     },
+    #[cfg(feature = "leveldb")]
     #[snafu(display("Unable to open data_dir {:?}", data_dir))]
     DataDirOpenError {
         data_dir: PathBuf,
         source: leveldb::database::error::Error,
     },
+    #[snafu(display("Unable to open disk buffer in data_dir {:?}: {}", data_dir, source))]
+    SegmentOpenError {
+        data_dir: PathBuf,
+        source: std::io::Error,
+    },
+    #[snafu(display("The leveldb disk buffer backend is not available in this build of vector, use the segment backend instead"))]
+    LeveldbUnavailable,
 }
 
 pub trait DiskBuffer {
@@ -37,8 +47,32 @@ pub trait DiskBuffer {
 }
 
 #[derive(Clone)]
-pub struct Writer {
-    inner: leveldb_buffer::Writer,
+pub enum Writer {
+    #[cfg(feature = "leveldb")]
+    Leveldb(leveldb_buffer::Writer),
+    Segment(segment_buffer::Writer),
+}
+
+impl Writer {
+    /// The number of events left unread by a previous run when the buffer
+    /// was opened.
+    pub fn recovered(&self) -> usize {
+        match self {
+            #[cfg(feature = "leveldb")]
+            Writer::Leveldb(inner) => inner.recovered(),
+            Writer::Segment(inner) => inner.recovered(),
+        }
+    }
+
+    /// Counts the events the reader dropped because they could not be read
+    /// back.
+    pub fn skipped(&self) -> Arc<AtomicUsize> {
+        match self {
+            #[cfg(feature = "leveldb")]
+            Writer::Leveldb(inner) => inner.skipped(),
+            Writer::Segment(inner) => inner.skipped(),
+        }
+    }
 }
 
 impl Sink for Writer {
@@ -49,11 +83,19 @@ impl Sink for Writer {
         &mut self,
         event: Self::SinkItem,
     ) -> Result<AsyncSink<Self::SinkItem>, Self::SinkError> {
-        self.inner.start_send(event)
+        match self {
+            #[cfg(feature = "leveldb")]
+            Writer::Leveldb(inner) => inner.start_send(event),
+            Writer::Segment(inner) => inner.start_send(event),
+        }
     }
 
     fn poll_complete(&mut self) -> Result<Async<()>, Self::SinkError> {
-        self.inner.poll_complete()
+        match self {
+            #[cfg(feature = "leveldb")]
+            Writer::Leveldb(inner) => inner.poll_complete(),
+            Writer::Segment(inner) => inner.poll_complete(),
+        }
     }
 }
 
@@ -61,6 +103,8 @@ pub fn open(
     data_dir: &Path,
     name: &str,
     max_size: usize,
+    backend: DiskBackend,
+    fsync: FsyncPolicy,
 ) -> Result<
     (
         Writer,
@@ -69,8 +113,6 @@ pub fn open(
     ),
     Error,
 > {
-    let path = data_dir.join(name);
-
     // Check data dir
     std::fs::metadata(&data_dir)
         .map_err(|e| match e.kind() {
@@ -95,6 +137,19 @@ pub fn open(
             }
         })?;
 
-    let (writer, reader, acker) = leveldb_buffer::Buffer::build(path, max_size)?;
-    Ok((Writer { inner: writer }, Box::new(reader), acker))
+    match backend {
+        #[cfg(feature = "leveldb")]
+        DiskBackend::Leveldb => {
+            let path = data_dir.join(name);
+            let (writer, reader, acker) = leveldb_buffer::Buffer::build(path, max_size)?;
+            Ok((Writer::Leveldb(writer), Box::new(reader), acker))
+        }
+        #[cfg(not(feature = "leveldb"))]
+        DiskBackend::Leveldb => Err(Error::LeveldbUnavailable),
+        DiskBackend::Segment => {
+            let path = data_dir.join(format!("{}_segments", name));
+            let (writer, reader, acker) = segment_buffer::Buffer::open(path, max_size, fsync)?;
+            Ok((Writer::Segment(writer), Box::new(reader), acker))
+        }
+    }
 }
@@ -0,0 +1,849 @@
+//! A disk buffer written in pure Rust that stores events in append-only
+//! segment files.
+//!
+//! Every record is framed as a big-endian `u32` payload length and a
+//! big-endian `u32` CRC32 of the payload, followed by the protobuf encoded
+//! event. Writers append to the newest segment and roll over to a new one once
+//! it grows past the segment size. The reader persists the position of the
+//! last acknowledged record in a checkpoint file and deletes segments once all
+//! of their records have been acknowledged.
+
+use crate::{
+    buffers::{Acker, FsyncPolicy},
+    event::{proto, Event},
+};
+use bytes05::Bytes;
+use futures01::{
+    task::{self, AtomicTask, Task},
+    Async, AsyncSink, Poll, Sink, Stream,
+};
+use prost::Message;
+use snafu::ResultExt;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    convert::{TryFrom, TryInto},
+    ffi::OsStr,
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use super::{Error, SegmentOpenError};
+
+const HEADER_LEN: u64 = 8;
+const SEGMENT_EXTENSION: &str = "seg";
+const CHECKPOINT_FILE: &str = "checkpoint";
+const MIN_SEGMENT_SIZE: u64 = 64 * 1024;
+const MAX_SEGMENT_SIZE: u64 = 8 * 1024 * 1024;
+const MAX_PENDING_RECORDS: usize = 100;
+
+struct SegmentInfo {
+    /// Number of bytes flushed to the segment file and visible to the reader.
+    len: u64,
+    /// Number of records visible to the reader, not counting those before
+    /// the checkpoint of a recovered segment.
+    records: usize,
+    /// Whether writers have moved on to a newer segment.
+    sealed: bool,
+}
+
+struct ActiveSegment {
+    id: u64,
+    file: BufWriter<File>,
+    written: u64,
+    pending: usize,
+}
+
+struct State {
+    active: ActiveSegment,
+    segments: BTreeMap<u64, SegmentInfo>,
+}
+
+struct Shared {
+    dir: PathBuf,
+    fsync: FsyncPolicy,
+    segment_size: u64,
+    state: Mutex<State>,
+}
+
+impl Shared {
+    fn segment_path(&self, id: u64) -> PathBuf {
+        segment_path(&self.dir, id)
+    }
+
+    fn append(&self, state: &mut State, value: &[u8]) -> io::Result<()> {
+        let record_len = HEADER_LEN + value.len() as u64;
+        if state.active.written > 0 && state.active.written + record_len > self.segment_size {
+            self.rotate(state)?;
+        }
+
+        let active = &mut state.active;
+        active.file.write_all(&(value.len() as u32).to_be_bytes())?;
+        active
+            .file
+            .write_all(&crc32fast::hash(value).to_be_bytes())?;
+        active.file.write_all(value)?;
+        active.written += record_len;
+        active.pending += 1;
+        Ok(())
+    }
+
+    fn flush(&self, state: &mut State) -> io::Result<()> {
+        state.active.file.flush()?;
+        if self.fsync == FsyncPolicy::Always {
+            state.active.file.get_ref().sync_data()?;
+        }
+        let written = state.active.written;
+        let pending = state.active.pending;
+        state.active.pending = 0;
+
+        if let Some(info) = state.segments.get_mut(&state.active.id) {
+            info.len = written;
+            info.records += pending;
+        }
+        Ok(())
+    }
+
+    fn rotate(&self, state: &mut State) -> io::Result<()> {
+        self.flush(state)?;
+
+        let id = state.active.id + 1;
+        let file = create_segment(&self.segment_path(id))?;
+
+        // The next segment must be visible before the current one is sealed so
+        // the reader never observes a sealed segment without a successor.
+        state.segments.insert(
+            id,
+            SegmentInfo {
+                len: 0,
+                records: 0,
+                sealed: false,
+            },
+        );
+        if let Some(info) = state.segments.get_mut(&state.active.id) {
+            info.sealed = true;
+        }
+        state.active = ActiveSegment {
+            id,
+            file: BufWriter::new(file),
+            written: 0,
+            pending: 0,
+        };
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct Writer {
+    shared: Arc<Shared>,
+    write_notifier: Arc<AtomicTask>,
+    blocked_write_tasks: Arc<Mutex<Vec<Task>>>,
+    max_size: usize,
+    current_size: Arc<AtomicUsize>,
+    recovered: usize,
+    skipped: Arc<AtomicUsize>,
+}
+
+impl Writer {
+    /// The number of events left unread by a previous run when the buffer
+    /// was opened.
+    pub fn recovered(&self) -> usize {
+        self.recovered
+    }
+
+    /// Counts the events the reader dropped because they could not be read
+    /// back.
+    pub fn skipped(&self) -> Arc<AtomicUsize> {
+        Arc::clone(&self.skipped)
+    }
+}
+
+impl Sink for Writer {
+    type SinkItem = Event;
+    type SinkError = ();
+
+    fn start_send(
+        &mut self,
+        event: Self::SinkItem,
+    ) -> Result<AsyncSink<Self::SinkItem>, Self::SinkError> {
+        let event = proto::EventWrapper::from(event);
+        let mut value = Vec::with_capacity(event.encoded_len());
+        event.encode(&mut value).unwrap(); // This will not error when writing to a Vec
+        let event_size = value.len();
+
+        if self.current_size.fetch_add(event_size, Ordering::Relaxed) + (event_size / 2)
+            > self.max_size
+        {
+            self.blocked_write_tasks
+                .lock()
+                .unwrap()
+                .push(task::current());
+
+            self.current_size.fetch_sub(event_size, Ordering::Relaxed);
+
+            self.poll_complete()?;
+
+            return Ok(AsyncSink::NotReady(event.into()));
+        }
+
+        let should_flush = {
+            let mut state = self.shared.state.lock().unwrap();
+            self.shared.append(&mut state, &value).map_err(|error| {
+                error!(message = "Failed to write to disk buffer.", %error);
+            })?;
+            state.active.pending >= MAX_PENDING_RECORDS
+        };
+
+        if should_flush {
+            self.poll_complete()?;
+        }
+
+        Ok(AsyncSink::Ready)
+    }
+
+    fn poll_complete(&mut self) -> Result<Async<()>, Self::SinkError> {
+        self.flush().map_err(|error| {
+            error!(message = "Failed to flush disk buffer.", %error);
+        })?;
+
+        Ok(Async::Ready(()))
+    }
+}
+
+impl Writer {
+    fn flush(&self) -> io::Result<()> {
+        let flushed = {
+            let mut state = self.shared.state.lock().unwrap();
+            if state.active.pending > 0 {
+                self.shared.flush(&mut state)?;
+                true
+            } else {
+                false
+            }
+        };
+
+        if flushed {
+            self.write_notifier.notify();
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Writer {
+    fn drop(&mut self) {
+        if let Err(error) = self.flush() {
+            error!(message = "Failed to flush disk buffer.", %error);
+        }
+
+        // We need to wake up the reader so it can return None if there are no more writers
+        self.write_notifier.notify();
+    }
+}
+
+struct Unacked {
+    segment: u64,
+    end: u64,
+    size: usize,
+}
+
+pub struct Reader {
+    shared: Arc<Shared>,
+    segment: u64,
+    position: u64,
+    /// Number of records read or skipped in the current segment.
+    read: usize,
+    file: Option<BufReader<File>>,
+    checkpoint: (u64, u64),
+    write_notifier: Arc<AtomicTask>,
+    blocked_write_tasks: Arc<Mutex<Vec<Task>>>,
+    current_size: Arc<AtomicUsize>,
+    ack_counter: Arc<AtomicUsize>,
+    unacked: VecDeque<Unacked>,
+    skipped: Arc<AtomicUsize>,
+}
+
+impl Stream for Reader {
+    type Item = Event;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        self.delete_acked();
+
+        // If there's nothing left to read, we return NotReady and rely on Writer
+        // using write_notifier to wake this task up after the next flush.
+        self.write_notifier.register();
+
+        loop {
+            // Checked before looking at the segments, writers flush before
+            // they go away so nothing they wrote can be missed.
+            let writers_gone = Arc::strong_count(&self.shared) == 1;
+
+            let (limit, records, next) = {
+                let state = self.shared.state.lock().unwrap();
+                let next = state
+                    .segments
+                    .range(self.segment + 1..)
+                    .next()
+                    .map(|(id, _)| *id);
+                match state.segments.get(&self.segment) {
+                    Some(info) if info.sealed => (info.len, info.records, next),
+                    Some(info) => (info.len, info.records, None),
+                    None => (self.position, self.read, next),
+                }
+            };
+
+            if self.position < limit {
+                match self.read_record(limit, records) {
+                    Ok(Some(event)) => return Ok(Async::Ready(Some(event))),
+                    Ok(None) => continue,
+                    Err(error) => {
+                        error!(message = "Failed to read from disk buffer.", %error);
+                        return Err(());
+                    }
+                }
+            } else if let Some(next) = next {
+                self.segment = next;
+                self.position = 0;
+                self.read = 0;
+                self.file = None;
+
+                if let Err(error) = self.delete_segments() {
+                    error!(message = "Failed to reclaim disk buffer space.", %error);
+                }
+            } else if writers_gone {
+                return Ok(Async::Ready(None));
+            } else {
+                return Ok(Async::NotReady);
+            }
+        }
+    }
+}
+
+impl Drop for Reader {
+    fn drop(&mut self) {
+        self.delete_acked();
+    }
+}
+
+impl Reader {
+    /// Reads the record at the current position. Returns `None` when the
+    /// record was corrupted and skipped.
+    fn read_record(&mut self, limit: u64, records: usize) -> io::Result<Option<Event>> {
+        if self.file.is_none() {
+            let mut file = File::open(self.shared.segment_path(self.segment))?;
+            file.seek(SeekFrom::Start(self.position))?;
+            self.file = Some(BufReader::new(file));
+        }
+        let file = self.file.as_mut().expect("segment file is open");
+
+        let mut header = [0; HEADER_LEN as usize];
+        file.read_exact(&mut header)?;
+        let len = u32::from_be_bytes(header[..4].try_into().unwrap()) as usize;
+        let checksum = u32::from_be_bytes(header[4..].try_into().unwrap());
+
+        let end = self.position + HEADER_LEN + len as u64;
+        if end > limit {
+            // Once a length is corrupted nothing after it in this segment can
+            // be located reliably.
+            error!(
+                message = "Corrupted record length in disk buffer, skipping rest of segment.",
+                segment = self.segment
+            );
+            self.skipped
+                .fetch_add(records.saturating_sub(self.read), Ordering::SeqCst);
+            self.position = limit;
+            self.read = records;
+            self.file = None;
+            return Ok(None);
+        }
+
+        let mut value = vec![0; len];
+        file.read_exact(&mut value)?;
+        self.position = end;
+        self.read += 1;
+
+        if crc32fast::hash(&value) != checksum {
+            error!(
+                message = "Checksum mismatch in disk buffer, skipping record.",
+                segment = self.segment
+            );
+            self.current_size.fetch_sub(len, Ordering::Relaxed);
+            self.skipped.fetch_add(1, Ordering::SeqCst);
+            return Ok(None);
+        }
+
+        let event = proto::EventWrapper::decode(Bytes::from(value))
+            .map_err(crate::Error::from)
+            .and_then(|event| Event::try_from(event).map_err(Into::into));
+        match event {
+            Ok(event) => {
+                self.unacked.push_back(Unacked {
+                    segment: self.segment,
+                    end,
+                    size: len,
+                });
+                Ok(Some(event))
+            }
+            Err(error) => {
+                error!(message = "Error deserializing proto, skipping record.", %error);
+                self.current_size.fetch_sub(len, Ordering::Relaxed);
+                self.skipped.fetch_add(1, Ordering::SeqCst);
+                Ok(None)
+            }
+        }
+    }
+
+    fn delete_acked(&mut self) {
+        let num_to_delete = self.ack_counter.swap(0, Ordering::Relaxed);
+
+        if num_to_delete > 0 {
+            assert!(
+                num_to_delete <= self.unacked.len(),
+                "Tried to ack beyond read offset"
+            );
+
+            let mut size_deleted = 0;
+            for acked in self.unacked.drain(..num_to_delete) {
+                size_deleted += acked.size;
+                self.checkpoint = (acked.segment, acked.end);
+            }
+            self.current_size.fetch_sub(size_deleted, Ordering::Relaxed);
+
+            if let Err(error) = self.write_checkpoint().and_then(|_| self.delete_segments()) {
+                error!(message = "Failed to reclaim disk buffer space.", %error);
+            }
+        }
+
+        for task in self.blocked_write_tasks.lock().unwrap().drain(..) {
+            task.notify();
+        }
+    }
+
+    fn write_checkpoint(&self) -> io::Result<()> {
+        let mut bytes = [0; 16];
+        bytes[..8].copy_from_slice(&self.checkpoint.0.to_be_bytes());
+        bytes[8..].copy_from_slice(&self.checkpoint.1.to_be_bytes());
+
+        let path = self.shared.dir.join(CHECKPOINT_FILE);
+        let temp_path = path.with_extension("tmp");
+        let mut file = File::create(&temp_path)?;
+        file.write_all(&bytes)?;
+        if self.shared.fsync == FsyncPolicy::Always {
+            file.sync_data()?;
+        }
+        fs::rename(temp_path, path)
+    }
+
+    /// Deletes segments the reader has moved past once all of the events read
+    /// from them have been acknowledged.
+    fn delete_segments(&self) -> io::Result<()> {
+        let oldest_unacked = self
+            .unacked
+            .front()
+            .map_or(self.segment, |unacked| unacked.segment);
+        let mut state = self.shared.state.lock().unwrap();
+
+        let obsolete = state
+            .segments
+            .range(..oldest_unacked)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+
+        for id in obsolete {
+            state.segments.remove(&id);
+            fs::remove_file(self.shared.segment_path(id))?;
+        }
+        Ok(())
+    }
+}
+
+pub struct Buffer;
+
+impl Buffer {
+    pub fn open(
+        path: PathBuf,
+        max_size: usize,
+        fsync: FsyncPolicy,
+    ) -> Result<(Writer, Reader, Acker), Error> {
+        open(&path, max_size, fsync).with_context(|| SegmentOpenError {
+            data_dir: path.parent().expect("always a parent"),
+        })
+    }
+}
+
+impl super::DiskBuffer for Buffer {
+    type Writer = Writer;
+    type Reader = Reader;
+
+    fn build(path: PathBuf, max_size: usize) -> Result<(Self::Writer, Self::Reader, Acker), Error> {
+        Self::open(path, max_size, FsyncPolicy::default())
+    }
+}
+
+fn open(dir: &Path, max_size: usize, fsync: FsyncPolicy) -> io::Result<(Writer, Reader, Acker)> {
+    fs::create_dir_all(dir)?;
+
+    let mut ids = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension() == Some(OsStr::new(SEGMENT_EXTENSION)) {
+            if let Some(id) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse::<u64>().ok())
+            {
+                ids.push(id);
+            }
+        }
+    }
+    ids.sort();
+
+    let checkpoint = read_checkpoint(&dir.join(CHECKPOINT_FILE))?.unwrap_or((0, 0));
+
+    let mut segments = BTreeMap::new();
+    let mut initial_size = 0;
+    let mut recovered = 0;
+    for id in ids {
+        let path = segment_path(dir, id);
+        if id < checkpoint.0 {
+            fs::remove_file(path)?;
+            continue;
+        }
+
+        let skip = if id == checkpoint.0 { checkpoint.1 } else { 0 };
+        let (len, size, count) = recover_segment(&path, skip)?;
+        initial_size += size;
+        recovered += count;
+        segments.insert(
+            id,
+            SegmentInfo {
+                len,
+                records: count,
+                sealed: true,
+            },
+        );
+    }
+
+    // Writers always start a fresh segment, recovered ones are only read.
+    let active_id = segments
+        .keys()
+        .next_back()
+        .map_or(checkpoint.0 + 1, |id| id + 1);
+    let active = ActiveSegment {
+        id: active_id,
+        file: BufWriter::new(create_segment(&segment_path(dir, active_id))?),
+        written: 0,
+        pending: 0,
+    };
+    segments.insert(
+        active_id,
+        SegmentInfo {
+            len: 0,
+            records: 0,
+            sealed: false,
+        },
+    );
+
+    let (segment, position) = if segments.contains_key(&checkpoint.0) {
+        checkpoint
+    } else {
+        let first = *segments.keys().next().expect("active segment is present");
+        (first, 0)
+    };
+
+    let segment_size = (max_size as u64 / 4)
+        .max(MIN_SEGMENT_SIZE)
+        .min(MAX_SEGMENT_SIZE);
+    let shared = Arc::new(Shared {
+        dir: dir.to_path_buf(),
+        fsync,
+        segment_size,
+        state: Mutex::new(State { active, segments }),
+    });
+
+    let current_size = Arc::new(AtomicUsize::new(initial_size));
+
+    let write_notifier = Arc::new(AtomicTask::new());
+
+    let blocked_write_tasks = Arc::new(Mutex::new(Vec::new()));
+
+    let ack_counter = Arc::new(AtomicUsize::new(0));
+    let acker = Acker::Disk(Arc::clone(&ack_counter), Arc::clone(&write_notifier));
+
+    let skipped = Arc::new(AtomicUsize::new(0));
+
+    let writer = Writer {
+        shared: Arc::clone(&shared),
+        write_notifier: Arc::clone(&write_notifier),
+        blocked_write_tasks: Arc::clone(&blocked_write_tasks),
+        max_size,
+        current_size: Arc::clone(&current_size),
+        recovered,
+        skipped: Arc::clone(&skipped),
+    };
+
+    let reader = Reader {
+        shared,
+        segment,
+        position,
+        read: 0,
+        file: None,
+        checkpoint,
+        write_notifier,
+        blocked_write_tasks,
+        current_size,
+        ack_counter,
+        unacked: VecDeque::new(),
+        skipped,
+    };
+
+    Ok((writer, reader, acker))
+}
+
+fn segment_path(dir: &Path, id: u64) -> PathBuf {
+    dir.join(format!("{:020}.{}", id, SEGMENT_EXTENSION))
+}
+
+fn create_segment(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+fn read_checkpoint(path: &Path) -> io::Result<Option<(u64, u64)>> {
+    match fs::read(path) {
+        Ok(bytes) if bytes.len() == 16 => Ok(Some((
+            u64::from_be_bytes(bytes[..8].try_into().unwrap()),
+            u64::from_be_bytes(bytes[8..].try_into().unwrap()),
+        ))),
+        Ok(_) => {
+            warn!(
+                message = "Ignoring malformed disk buffer checkpoint.",
+                ?path
+            );
+            Ok(None)
+        }
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// Scans a segment left over from a previous run, truncating a partially
+/// written record at its end. Returns the length of the valid part of the
+/// segment, and the size and number of the events stored after `skip`.
+fn recover_segment(path: &Path, skip: u64) -> io::Result<(u64, usize, usize)> {
+    let file = OpenOptions::new().read(true).write(true).open(path)?;
+    let file_len = file.metadata()?.len();
+
+    let mut position = 0;
+    let mut size = 0;
+    let mut count = 0;
+    {
+        let mut reader = BufReader::new(&file);
+        let mut header = [0; HEADER_LEN as usize];
+        while position + HEADER_LEN <= file_len {
+            reader.read_exact(&mut header)?;
+            let len = u32::from_be_bytes(header[..4].try_into().unwrap()) as u64;
+            let end = position + HEADER_LEN + len;
+            if end > file_len {
+                break;
+            }
+
+            io::copy(&mut (&mut reader).take(len), &mut io::sink())?;
+            if position >= skip {
+                size += len as usize;
+                count += 1;
+            }
+            position = end;
+        }
+    }
+
+    if position < file_len {
+        warn!(
+            message = "Truncating partially written record in disk buffer.",
+            ?path,
+            valid_len = position,
+            file_len
+        );
+        file.set_len(position)?;
+    }
+
+    Ok((position, size, count))
+}
+
+#[cfg(test)]
+mod test {
+    use super::Buffer;
+    use crate::{
+        buffers::FsyncPolicy,
+        event::{proto, Event},
+        test_util::block_on,
+    };
+    use futures01::{future, Async, AsyncSink, Sink, Stream};
+    use prost::Message;
+    use std::{
+        convert::TryInto,
+        fs::{self, OpenOptions},
+        io::{Seek, SeekFrom, Write},
+        path::{Path, PathBuf},
+    };
+    use tempfile::tempdir;
+
+    fn events(count: usize) -> Vec<Event> {
+        (0..count)
+            .map(|i| Event::from(format!("event {}", i)))
+            .collect()
+    }
+
+    fn write_all(dir: &Path, events: Vec<Event>) {
+        let (mut writer, _reader, _acker) =
+            Buffer::open(dir.to_path_buf(), 1_000_000, FsyncPolicy::Always).unwrap();
+        block_on::<_, _, ()>(future::lazy(move || {
+            for event in events {
+                assert_eq!(writer.start_send(event), Ok(AsyncSink::Ready));
+            }
+            writer.poll_complete()
+        }))
+        .unwrap();
+    }
+
+    fn read_n(dir: &Path, count: usize, ack: usize) -> Vec<Event> {
+        let (writer, mut reader, acker) =
+            Buffer::open(dir.to_path_buf(), 1_000_000, FsyncPolicy::Always).unwrap();
+        drop(writer);
+        block_on::<_, _, ()>(future::lazy(move || {
+            let mut read = Vec::new();
+            while read.len() < count {
+                match reader.poll() {
+                    Ok(Async::Ready(Some(event))) => read.push(event),
+                    other => panic!("unexpected poll result: {:?}", other),
+                }
+            }
+            acker.ack(ack);
+            let _ = reader.poll();
+            Ok(read)
+        }))
+        .unwrap()
+    }
+
+    fn segment_files(dir: &Path) -> Vec<PathBuf> {
+        let mut files = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("seg"))
+            .collect::<Vec<_>>();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn write_read_and_ack() {
+        let dir = tempdir().unwrap();
+        let (mut writer, mut reader, acker) =
+            Buffer::open(dir.path().join("buffer"), 1_000_000, FsyncPolicy::Never).unwrap();
+        let input = events(10);
+
+        let output = block_on::<_, _, ()>(future::lazy(move || {
+            for event in input {
+                assert_eq!(writer.start_send(event), Ok(AsyncSink::Ready));
+            }
+            writer.poll_complete()?;
+            drop(writer);
+
+            let mut output = Vec::new();
+            while let Async::Ready(Some(event)) = reader.poll()? {
+                output.push(event);
+            }
+            acker.ack(output.len());
+            assert_eq!(reader.poll(), Ok(Async::Ready(None)));
+            Ok(output)
+        }))
+        .unwrap();
+
+        assert_eq!(output, events(10));
+    }
+
+    #[test]
+    fn resumes_after_last_ack() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("buffer");
+        write_all(&path, events(10));
+
+        assert_eq!(read_n(&path, 4, 4), events(10)[..4].to_vec());
+        // The unacknowledged read is redelivered after a restart.
+        assert_eq!(read_n(&path, 6, 6), events(10)[4..].to_vec());
+
+        let (_writer, mut reader, _acker) =
+            Buffer::open(path, 1_000_000, FsyncPolicy::Never).unwrap();
+        block_on::<_, _, ()>(future::lazy(move || {
+            assert_eq!(reader.poll(), Ok(Async::NotReady));
+            Ok(())
+        }))
+        .unwrap();
+    }
+
+    #[test]
+    fn skips_corrupted_records() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("buffer");
+        write_all(&path, events(3));
+
+        // Flip a byte in the payload of the second record and leave a torn
+        // record at the end of the segment.
+        let segment = segment_files(&path).remove(0);
+        let contents = fs::read(&segment).unwrap();
+        let first_len = u32::from_be_bytes(contents[..4].try_into().unwrap()) as u64;
+        let second_payload = 8 + first_len + 8;
+        let mut file = OpenOptions::new().write(true).open(&segment).unwrap();
+        file.seek(SeekFrom::Start(second_payload)).unwrap();
+        file.write_all(&[!contents[second_payload as usize]])
+            .unwrap();
+        file.seek(SeekFrom::End(0)).unwrap();
+        file.write_all(&[0, 0, 1]).unwrap();
+        drop(file);
+
+        let input = events(3);
+        assert_eq!(
+            read_n(&path, 2, 2),
+            vec![input[0].clone(), input[2].clone()]
+        );
+    }
+
+    #[test]
+    fn reclaims_acked_segments() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("buffer");
+        // Large enough for the buffer to roll over to new segments.
+        let large = (0..600)
+            .map(|_| Event::from("x".repeat(1000)))
+            .collect::<Vec<_>>();
+        write_all(&path, large);
+        assert!(segment_files(&path).len() > 2);
+
+        read_n(&path, 600, 600);
+        // Only the segment opened by the last run for writing remains.
+        assert_eq!(segment_files(&path).len(), 1);
+    }
+
+    #[test]
+    fn blocks_when_full() {
+        let dir = tempdir().unwrap();
+        let first = Event::from("first");
+        let second = Event::from("second");
+        let max_size = proto::EventWrapper::from(first.clone()).encoded_len() * 2;
+        let (mut writer, _reader, _acker) =
+            Buffer::open(dir.path().join("buffer"), max_size, FsyncPolicy::Never).unwrap();
+
+        block_on::<_, _, ()>(future::lazy(move || {
+            assert_eq!(writer.start_send(first), Ok(AsyncSink::Ready));
+            assert_eq!(
+                writer.start_send(second.clone()),
+                Ok(AsyncSink::NotReady(second))
+            );
+            Ok(())
+        }))
+        .unwrap();
+    }
+}
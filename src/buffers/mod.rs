@@ -1,14 +1,18 @@
-use crate::Event;
+use crate::event::{BatchStatus, Event, EventFinalizers};
 use futures01::{sync::mpsc, task::AtomicTask, AsyncSink, Poll, Sink, StartSend, Stream};
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::path::PathBuf;
 use std::sync::{
     atomic::{AtomicUsize, Ordering},
-    Arc,
+    Arc, Mutex,
 };
 
-#[cfg(feature = "leveldb")]
 pub mod disk;
+pub mod overflow;
+mod usage;
+
+pub use usage::BufferUsage;
 
 #[derive(Deserialize, Serialize, Debug)]
 #[serde(tag = "type")]
@@ -19,12 +23,25 @@ pub enum BufferConfig {
         max_events: usize,
         #[serde(default)]
         when_full: WhenFull,
+        /// Maximum size in bytes of the on-disk buffer events spill to when
+        /// `when_full = "overflow"`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        overflow_max_size: Option<usize>,
+        /// The storage engine and sync policy of the on-disk buffer events
+        /// spill to, as for disk buffers.
+        #[serde(default)]
+        backend: DiskBackend,
+        #[serde(default)]
+        fsync: FsyncPolicy,
     },
-    #[cfg(feature = "leveldb")]
     Disk {
         max_size: usize,
         #[serde(default)]
         when_full: WhenFull,
+        #[serde(default)]
+        backend: DiskBackend,
+        #[serde(default)]
+        fsync: FsyncPolicy,
     },
 }
 
@@ -33,6 +50,9 @@ impl Default for BufferConfig {
         BufferConfig::Memory {
             max_events: BufferConfig::memory_max_events(),
             when_full: Default::default(),
+            overflow_max_size: None,
+            backend: Default::default(),
+            fsync: Default::default(),
         }
     }
 }
@@ -42,6 +62,7 @@ impl Default for BufferConfig {
 pub enum WhenFull {
     Block,
     DropNewest,
+    Overflow,
 }
 
 impl Default for WhenFull {
@@ -50,17 +71,50 @@ impl Default for WhenFull {
     }
 }
 
+/// The storage engine used by an on-disk buffer.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Copy, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum DiskBackend {
+    Leveldb,
+    Segment,
+}
+
+/// Buffers keep using LevelDB unless told otherwise, whatever the features
+/// of the build, as switching backends leaves the buffered events behind.
+impl Default for DiskBackend {
+    fn default() -> Self {
+        DiskBackend::Leveldb
+    }
+}
+
+/// When the segment disk buffer forces its writes to stable storage.
+#[derive(Deserialize, Serialize, Debug, PartialEq, Copy, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum FsyncPolicy {
+    /// Leave flushing to the operating system.
+    Never,
+    /// Sync after every batch of writes and every checkpoint.
+    Always,
+}
+
+impl Default for FsyncPolicy {
+    fn default() -> Self {
+        FsyncPolicy::Never
+    }
+}
+
 pub enum BufferInputCloner {
-    Memory(mpsc::Sender<Event>, WhenFull),
-    #[cfg(feature = "leveldb")]
-    Disk(disk::Writer, WhenFull),
+    Memory(mpsc::Sender<Event>, WhenFull, BufferUsage),
+    Disk(disk::Writer, WhenFull, BufferUsage),
+    Overflow(overflow::Writer, BufferUsage),
 }
 
 impl BufferInputCloner {
     pub fn get(&self) -> Box<dyn Sink<SinkItem = Event, SinkError = ()> + Send> {
         match self {
-            BufferInputCloner::Memory(tx, when_full) => {
+            BufferInputCloner::Memory(tx, when_full, usage) => {
                 let inner = tx.clone().sink_map_err(|e| error!("sender error: {:?}", e));
+                let inner = usage.track_input(inner);
                 if when_full == &WhenFull::DropNewest {
                     Box::new(DropWhenFull { inner })
                 } else {
@@ -68,16 +122,18 @@ impl BufferInputCloner {
                 }
             }
 
-            #[cfg(feature = "leveldb")]
-            BufferInputCloner::Disk(writer, when_full) => {
+            BufferInputCloner::Disk(writer, when_full, usage) => {
+                let inner = usage.track_input(writer.clone());
                 if when_full == &WhenFull::DropNewest {
-                    Box::new(DropWhenFull {
-                        inner: writer.clone(),
-                    })
+                    Box::new(DropWhenFull { inner })
                 } else {
-                    Box::new(writer.clone())
+                    Box::new(inner)
                 }
             }
+
+            BufferInputCloner::Overflow(writer, usage) => {
+                Box::new(usage.track_input(writer.clone()))
+            }
         }
     }
 }
@@ -88,11 +144,11 @@ impl BufferConfig {
         500
     }
 
-    #[cfg_attr(not(feature = "leveldb"), allow(unused))]
     pub fn build(
         &self,
         data_dir: &Option<PathBuf>,
         sink_name: &str,
+        sink_type: &str,
     ) -> Result<
         (
             BufferInputCloner,
@@ -101,31 +157,61 @@ impl BufferConfig {
         ),
         String,
     > {
+        let usage = BufferUsage::new(sink_name, "sink", sink_type);
         match &self {
+            BufferConfig::Memory {
+                max_events,
+                when_full: WhenFull::Overflow,
+                overflow_max_size,
+                backend,
+                fsync,
+            } => {
+                let max_size = overflow_max_size.ok_or_else(|| {
+                    "Must set overflow_max_size to use overflow buffering.".to_string()
+                })?;
+                let data_dir = data_dir
+                    .as_ref()
+                    .ok_or_else(|| "Must set data_dir to use overflow buffering.".to_string())?;
+                let buffer_dir = format!("{}_overflow", sink_name);
+
+                let disk = disk::open(&data_dir, buffer_dir.as_ref(), max_size, *backend, *fsync)
+                    .map_err(|err| err.to_string())?;
+                let (tx, rx, acker) = overflow::build(mpsc::channel(*max_events), disk);
+                let tx = BufferInputCloner::Overflow(tx, usage.clone());
+                let rx = Box::new(usage.track_output(rx));
+                Ok((tx, rx, acker))
+            }
+
             BufferConfig::Memory {
                 max_events,
                 when_full,
+                ..
             } => {
                 let (tx, rx) = mpsc::channel(*max_events);
-                let tx = BufferInputCloner::Memory(tx, *when_full);
-                let rx = Box::new(rx);
+                let tx = BufferInputCloner::Memory(tx, *when_full, usage.clone());
+                let rx = Box::new(usage.track_output(rx));
                 Ok((tx, rx, Acker::Null))
             }
 
-            #[cfg(feature = "leveldb")]
             BufferConfig::Disk {
                 max_size,
                 when_full,
+                backend,
+                fsync,
             } => {
+                if *when_full == WhenFull::Overflow {
+                    return Err("Overflow buffering is only supported by memory buffers.".into());
+                }
                 let data_dir = data_dir
                     .as_ref()
                     .ok_or_else(|| "Must set data_dir to use on-disk buffering.".to_string())?;
                 let buffer_dir = format!("{}_buffer", sink_name);
 
-                let (tx, rx, acker) = disk::open(&data_dir, buffer_dir.as_ref(), *max_size)
-                    .map_err(|err| err.to_string())?;
-                let tx = BufferInputCloner::Disk(tx, *when_full);
-                let rx = Box::new(rx);
+                let (tx, rx, acker) =
+                    disk::open(&data_dir, buffer_dir.as_ref(), *max_size, *backend, *fsync)
+                        .map_err(|err| err.to_string())?;
+                let tx = BufferInputCloner::Disk(tx, *when_full, usage.clone());
+                let rx = Box::new(usage.track_output(rx));
                 Ok((tx, rx, acker))
             }
         }
@@ -135,6 +221,12 @@ impl BufferConfig {
 #[derive(Debug, Clone)]
 pub enum Acker {
     Disk(Arc<AtomicUsize>, Arc<AtomicTask>),
+    /// Acks events read from an overflow buffer, forwarding only those that
+    /// were read from disk. Holds whether each unacked event came from disk.
+    Overflow(Arc<Mutex<VecDeque<bool>>>, Box<Acker>),
+    /// Reports the status of acked events to the sources awaiting them before
+    /// forwarding the ack. Holds the finalizers of each unacked event.
+    Finalizing(Arc<PendingFinalizers>, Box<Acker>),
     Null,
 }
 
@@ -146,6 +238,13 @@ impl Acker {
     // This is primary used by the on-disk buffer to know which events are okay to
     // delete from disk.
     pub fn ack(&self, num: usize) {
+        self.ack_with_status(num, BatchStatus::Delivered)
+    }
+
+    /// Like `ack`, for events the sink is done with but that were not
+    /// necessarily delivered. Sources awaiting the events learn their
+    /// `status`, while buffers release them either way.
+    pub fn ack_with_status(&self, num: usize, status: BatchStatus) {
         // Only ack items if the amount to ack is larger than zero.
         if num > 0 {
             match self {
@@ -154,10 +253,34 @@ impl Acker {
                     counter.fetch_add(num, Ordering::Relaxed);
                     notifier.notify();
                 }
+                Acker::Overflow(origins, disk) => {
+                    let from_disk = origins
+                        .lock()
+                        .unwrap()
+                        .drain(..num)
+                        .filter(|from_disk| *from_disk)
+                        .count();
+                    disk.ack(from_disk);
+                }
+                Acker::Finalizing(pending, inner) => {
+                    pending.finalize(num, status);
+                    inner.ack_with_status(num, status);
+                }
             }
         }
     }
 
+    /// Wraps `self` so that the status of the events a sink acks reaches
+    /// their sources. The finalizers of each event read by the sink must be
+    /// pushed to the returned queue, in order.
+    pub fn finalizing(self) -> (Self, Arc<PendingFinalizers>) {
+        let pending = Arc::new(PendingFinalizers::default());
+        (
+            Acker::Finalizing(Arc::clone(&pending), Box::new(self)),
+            pending,
+        )
+    }
+
     pub fn new_for_testing() -> (Self, Arc<AtomicUsize>) {
         let ack_counter = Arc::new(AtomicUsize::new(0));
         let notifier = Arc::new(AtomicTask::new());
@@ -167,6 +290,37 @@ impl Acker {
     }
 }
 
+/// The finalizers of the events a sink has read but not acked yet, in the
+/// order it read them.
+#[derive(Debug, Default)]
+pub struct PendingFinalizers(Mutex<VecDeque<EventFinalizers>>);
+
+impl PendingFinalizers {
+    pub fn push(&self, finalizers: EventFinalizers) {
+        self.0.lock().unwrap().push_back(finalizers);
+    }
+
+    fn finalize(&self, num: usize, status: BatchStatus) {
+        let mut pending = self.0.lock().unwrap();
+        let num = num.min(pending.len());
+        for finalizers in pending.drain(..num) {
+            finalizers.update_status(status);
+        }
+    }
+}
+
+impl Drop for PendingFinalizers {
+    // Events a sink stopped without acking, for instance because it failed,
+    // were not delivered.
+    fn drop(&mut self) {
+        if let Ok(pending) = self.0.get_mut() {
+            for finalizers in pending.drain(..) {
+                finalizers.update_status(BatchStatus::Failed);
+            }
+        }
+    }
+}
+
 pub struct DropWhenFull<S> {
     inner: S,
 }
@@ -195,10 +349,16 @@ impl<S: Sink> Sink for DropWhenFull<S> {
 
 #[cfg(test)]
 mod test {
-    use super::{Acker, BufferConfig, DropWhenFull, WhenFull};
-    use crate::test_util::block_on;
+    use super::{Acker, BufferConfig, DiskBackend, DropWhenFull, FsyncPolicy, WhenFull};
+    use crate::{
+        event::{BatchNotifier, BatchStatus, EventFinalizers},
+        test_util::block_on,
+    };
     use futures01::{future, sync::mpsc, task::AtomicTask, Async, AsyncSink, Sink, Stream};
-    use std::sync::{atomic::AtomicUsize, Arc};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
     use tokio01_test::task::MockTask;
 
     #[test]
@@ -240,6 +400,41 @@ mod test {
         assert!(mock.is_notified());
     }
 
+    #[test]
+    fn finalizing_acker_reports_status_in_order() {
+        let (inner, counter) = Acker::new_for_testing();
+        let (acker, pending) = inner.finalizing();
+
+        let mut receivers = Vec::new();
+        for _ in 0..3 {
+            let (notifier, receiver) = BatchNotifier::new_with_receiver();
+            pending.push(EventFinalizers::new(notifier));
+            receivers.push(receiver);
+        }
+        pending.push(EventFinalizers::default());
+
+        acker.ack(1);
+        assert_eq!(receivers[0].try_recv(), Some(BatchStatus::Delivered));
+        assert_eq!(receivers[1].try_recv(), None);
+
+        acker.ack_with_status(2, BatchStatus::Failed);
+        assert_eq!(receivers[1].try_recv(), Some(BatchStatus::Failed));
+        assert_eq!(receivers[2].try_recv(), Some(BatchStatus::Failed));
+        assert_eq!(counter.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn finalizing_acker_fails_unacked_events_once_dropped() {
+        let (acker, pending) = Acker::Null.finalizing();
+        let (notifier, mut receiver) = BatchNotifier::new_with_receiver();
+        pending.push(EventFinalizers::new(notifier));
+
+        drop(acker);
+        assert_eq!(receiver.try_recv(), None);
+        drop(pending);
+        assert_eq!(receiver.try_recv(), Some(BatchStatus::Failed));
+    }
+
     #[test]
     fn config_default_values() {
         fn check(source: &str, config: BufferConfig) {
@@ -254,6 +449,9 @@ mod test {
             BufferConfig::Memory {
                 max_events: 500,
                 when_full: WhenFull::Block,
+                overflow_max_size: None,
+                backend: DiskBackend::Leveldb,
+                fsync: FsyncPolicy::Never,
             },
         );
 
@@ -265,6 +463,9 @@ mod test {
             BufferConfig::Memory {
                 max_events: 100,
                 when_full: WhenFull::Block,
+                overflow_max_size: None,
+                backend: DiskBackend::Leveldb,
+                fsync: FsyncPolicy::Never,
             },
         );
 
@@ -276,18 +477,69 @@ mod test {
             BufferConfig::Memory {
                 max_events: 500,
                 when_full: WhenFull::DropNewest,
+                overflow_max_size: None,
+                backend: DiskBackend::Leveldb,
+                fsync: FsyncPolicy::Never,
+            },
+        );
+
+        check(
+            r#"
+          type = "memory"
+          when_full = "overflow"
+          overflow_max_size = 1048576
+          "#,
+            BufferConfig::Memory {
+                max_events: 500,
+                when_full: WhenFull::Overflow,
+                overflow_max_size: Some(1048576),
+                backend: DiskBackend::Leveldb,
+                fsync: FsyncPolicy::Never,
+            },
+        );
+
+        check(
+            r#"
+          type = "memory"
+          when_full = "overflow"
+          overflow_max_size = 1048576
+          backend = "segment"
+          fsync = "always"
+          "#,
+            BufferConfig::Memory {
+                max_events: 500,
+                when_full: WhenFull::Overflow,
+                overflow_max_size: Some(1048576),
+                backend: DiskBackend::Segment,
+                fsync: FsyncPolicy::Always,
+            },
+        );
+
+        check(
+            r#"
+          type = "disk"
+          max_size = 1024
+          "#,
+            BufferConfig::Disk {
+                max_size: 1024,
+                when_full: WhenFull::Block,
+                backend: DiskBackend::Leveldb,
+                fsync: FsyncPolicy::Never,
             },
         );
 
-        #[cfg(feature = "leveldb")]
         check(
             r#"
           type = "disk"
           max_size = 1024
+          backend = "segment"
+          fsync = "always"
           "#,
             BufferConfig::Disk {
                 max_size: 1024,
                 when_full: WhenFull::Block,
+                backend: DiskBackend::Segment,
+                fsync: FsyncPolicy::Always,
             },
         );
     }
@@ -0,0 +1,350 @@
+//! A memory buffer that spills to an on-disk buffer once it is full.
+//!
+//! Writers send to the memory channel until it has no capacity left, from
+//! then on events go to disk until the reader has drained every spilled event
+//! so that ordering is preserved. The reader always empties the memory channel
+//! before reading from disk.
+
+use super::{disk, Acker};
+use crate::Event;
+use futures01::{sync::mpsc, Async, AsyncSink, Poll, Sink, StartSend, Stream};
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+pub fn build(
+    memory: (mpsc::Sender<Event>, mpsc::Receiver<Event>),
+    disk: (
+        disk::Writer,
+        Box<dyn Stream<Item = Event, Error = ()> + Send>,
+        Acker,
+    ),
+) -> (Writer, Reader, Acker) {
+    // Events left on disk by a previous run are older than anything written
+    // from now on, so they are read before the memory channel is used again.
+    let spilled = Arc::new(AtomicUsize::new(disk.0.recovered()));
+    let skipped = disk.0.skipped();
+    let origins = Arc::new(Mutex::new(VecDeque::new()));
+
+    let writer = Writer {
+        memory: memory.0,
+        disk: disk.0,
+        spilled: Arc::clone(&spilled),
+    };
+    let reader = Reader {
+        memory: memory.1,
+        disk: disk.1,
+        memory_done: false,
+        spilled,
+        skipped,
+        origins: Arc::clone(&origins),
+    };
+    let acker = Acker::Overflow(origins, Box::new(disk.2));
+
+    (writer, reader, acker)
+}
+
+#[derive(Clone)]
+pub struct Writer {
+    memory: mpsc::Sender<Event>,
+    disk: disk::Writer,
+    spilled: Arc<AtomicUsize>,
+}
+
+impl Writer {
+    fn spill(&mut self, event: Event) -> StartSend<Event, ()> {
+        // Counted before the write so the reader can never observe more events
+        // on disk than have been counted.
+        self.spilled.fetch_add(1, Ordering::SeqCst);
+        let result = self.disk.start_send(event);
+        if let Ok(AsyncSink::Ready) = result {
+            trace!(message = "Memory buffer is full; spilled event to disk.");
+        } else {
+            decrement(&self.spilled, 1);
+        }
+        result
+    }
+}
+
+impl Sink for Writer {
+    type SinkItem = Event;
+    type SinkError = ();
+
+    fn start_send(&mut self, event: Self::SinkItem) -> StartSend<Self::SinkItem, Self::SinkError> {
+        if self.spilled.load(Ordering::SeqCst) > 0 {
+            return self.spill(event);
+        }
+
+        match self.memory.start_send(event) {
+            Ok(AsyncSink::Ready) => Ok(AsyncSink::Ready),
+            Ok(AsyncSink::NotReady(event)) => self.spill(event),
+            Err(error) => {
+                error!("sender error: {:?}", error);
+                Err(())
+            }
+        }
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
+        let memory = self
+            .memory
+            .poll_complete()
+            .map_err(|error| error!("sender error: {:?}", error))?;
+        let disk = self.disk.poll_complete()?;
+
+        if memory.is_ready() && disk.is_ready() {
+            Ok(Async::Ready(()))
+        } else {
+            Ok(Async::NotReady)
+        }
+    }
+}
+
+pub struct Reader {
+    memory: mpsc::Receiver<Event>,
+    disk: Box<dyn Stream<Item = Event, Error = ()> + Send>,
+    memory_done: bool,
+    spilled: Arc<AtomicUsize>,
+    skipped: Arc<AtomicUsize>,
+    origins: Arc<Mutex<VecDeque<bool>>>,
+}
+
+impl Stream for Reader {
+    type Item = Event;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        if !self.memory_done {
+            match self.memory.poll()? {
+                Async::Ready(Some(event)) => {
+                    self.origins.lock().unwrap().push_back(false);
+                    return Ok(Async::Ready(Some(event)));
+                }
+                Async::Ready(None) => self.memory_done = true,
+                Async::NotReady => {}
+            }
+        }
+
+        let polled = self.disk.poll()?;
+
+        // Records the disk buffer could not read back are gone for good and
+        // must not keep writers spilling to disk.
+        let skipped = self.skipped.swap(0, Ordering::SeqCst);
+        if skipped > 0 {
+            decrement(&self.spilled, skipped);
+        }
+
+        match polled {
+            Async::Ready(Some(event)) => {
+                decrement(&self.spilled, 1);
+                self.origins.lock().unwrap().push_back(true);
+                Ok(Async::Ready(Some(event)))
+            }
+            Async::Ready(None) if self.memory_done => Ok(Async::Ready(None)),
+            Async::Ready(None) | Async::NotReady => Ok(Async::NotReady),
+        }
+    }
+}
+
+fn decrement(counter: &AtomicUsize, amount: usize) {
+    let mut current = counter.load(Ordering::SeqCst);
+    while current > 0 {
+        let new = current.saturating_sub(amount);
+        match counter.compare_exchange(current, new, Ordering::SeqCst, Ordering::SeqCst) {
+            Ok(_) => break,
+            Err(actual) => current = actual,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::build;
+    use crate::{
+        buffers::{disk::segment_buffer, disk::Writer as DiskWriter, Acker, FsyncPolicy},
+        event::Event,
+        test_util::block_on,
+    };
+    use futures01::{future, stream, sync::mpsc, Async, AsyncSink, Sink, Stream};
+    use std::{
+        collections::VecDeque,
+        ffi::OsStr,
+        fs,
+        sync::{atomic::Ordering, Arc, Mutex},
+    };
+    use tempfile::tempdir;
+
+    #[test]
+    fn spills_to_disk_and_preserves_order() {
+        let dir = tempdir().unwrap();
+        let (disk_tx, disk_rx, disk_acker) = segment_buffer::Buffer::open(
+            dir.path().join("overflow"),
+            1_000_000,
+            FsyncPolicy::Never,
+        )
+        .unwrap();
+        let (mut writer, mut reader, acker) = build(
+            mpsc::channel(2),
+            (DiskWriter::Segment(disk_tx), Box::new(disk_rx), disk_acker),
+        );
+        let spilled = Arc::clone(&writer.spilled);
+        let origins = match &acker {
+            Acker::Overflow(origins, _) => Arc::clone(origins),
+            _ => panic!("expected an overflow acker"),
+        };
+
+        let input = (0..10)
+            .map(|i| Event::from(format!("event {}", i)))
+            .collect::<Vec<_>>();
+        let expected = input.clone();
+
+        let output = block_on::<_, _, ()>(future::lazy(move || {
+            for event in input {
+                assert_eq!(writer.start_send(event), Ok(AsyncSink::Ready));
+            }
+            writer.poll_complete()?;
+            assert!(spilled.load(Ordering::SeqCst) > 0);
+
+            let mut output = Vec::new();
+            for _ in 0..10 {
+                match reader.poll()? {
+                    Async::Ready(Some(event)) => output.push(event),
+                    other => panic!("unexpected poll result: {:?}", other),
+                }
+            }
+            assert_eq!(spilled.load(Ordering::SeqCst), 0);
+
+            // With the disk drained, new events go to memory again.
+            assert_eq!(writer.start_send(Event::from("last")), Ok(AsyncSink::Ready));
+            writer.poll_complete()?;
+            assert_eq!(reader.poll(), Ok(Async::Ready(Some(Event::from("last")))));
+            Ok(output)
+        }))
+        .unwrap();
+
+        assert_eq!(output, expected);
+
+        let origins = origins.lock().unwrap();
+        assert!(!origins[0]);
+        assert!(origins[9]);
+        assert!(!origins[10]);
+        assert_eq!(
+            origins.iter().filter(|from_disk| **from_disk).count(),
+            10 - 3
+        );
+    }
+
+    #[test]
+    fn reads_events_left_on_disk_first() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("overflow");
+
+        // A previous run left two events on disk.
+        {
+            let (disk_tx, _disk_rx, _disk_acker) =
+                segment_buffer::Buffer::open(path.clone(), 1_000_000, FsyncPolicy::Never).unwrap();
+            let old = stream::iter_ok(vec![Event::from("old 0"), Event::from("old 1")]);
+            block_on(DiskWriter::Segment(disk_tx).send_all(old)).unwrap();
+        }
+
+        let (disk_tx, disk_rx, disk_acker) =
+            segment_buffer::Buffer::open(path, 1_000_000, FsyncPolicy::Never).unwrap();
+        let (mut writer, mut reader, _acker) = build(
+            mpsc::channel(10),
+            (DiskWriter::Segment(disk_tx), Box::new(disk_rx), disk_acker),
+        );
+        assert_eq!(writer.spilled.load(Ordering::SeqCst), 2);
+
+        let output = block_on::<_, _, ()>(future::lazy(move || {
+            assert_eq!(writer.start_send(Event::from("new")), Ok(AsyncSink::Ready));
+            writer.poll_complete()?;
+
+            let mut output = Vec::new();
+            for _ in 0..3 {
+                match reader.poll()? {
+                    Async::Ready(Some(event)) => output.push(event),
+                    other => panic!("unexpected poll result: {:?}", other),
+                }
+            }
+            Ok(output)
+        }))
+        .unwrap();
+
+        assert_eq!(
+            output,
+            vec![
+                Event::from("old 0"),
+                Event::from("old 1"),
+                Event::from("new")
+            ]
+        );
+    }
+
+    #[test]
+    fn counts_skipped_disk_records_as_drained() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("overflow");
+
+        {
+            let (disk_tx, _disk_rx, _disk_acker) =
+                segment_buffer::Buffer::open(path.clone(), 1_000_000, FsyncPolicy::Never).unwrap();
+            let old = stream::iter_ok(vec![Event::from("old 0"), Event::from("old 1")]);
+            block_on(DiskWriter::Segment(disk_tx).send_all(old)).unwrap();
+        }
+
+        // Corrupt the payload of the last record left on disk.
+        let segment = fs::read_dir(&path)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| fs::metadata(path).unwrap().len() > 0)
+            .find(|path| path.extension() == Some(OsStr::new("seg")))
+            .unwrap();
+        let mut contents = fs::read(&segment).unwrap();
+        let last = contents.len() - 1;
+        contents[last] = !contents[last];
+        fs::write(&segment, contents).unwrap();
+
+        let (disk_tx, disk_rx, disk_acker) =
+            segment_buffer::Buffer::open(path, 1_000_000, FsyncPolicy::Never).unwrap();
+        let (mut writer, mut reader, _acker) = build(
+            mpsc::channel(10),
+            (DiskWriter::Segment(disk_tx), Box::new(disk_rx), disk_acker),
+        );
+        let spilled = Arc::clone(&writer.spilled);
+        assert_eq!(spilled.load(Ordering::SeqCst), 2);
+
+        block_on::<_, _, ()>(future::lazy(move || {
+            assert_eq!(reader.poll(), Ok(Async::Ready(Some(Event::from("old 0")))));
+            assert_eq!(reader.poll(), Ok(Async::NotReady));
+            assert_eq!(spilled.load(Ordering::SeqCst), 0);
+
+            // Nothing readable is left on disk, so new events use memory.
+            assert_eq!(writer.start_send(Event::from("new")), Ok(AsyncSink::Ready));
+            writer.poll_complete()?;
+            assert_eq!(reader.poll(), Ok(Async::Ready(Some(Event::from("new")))));
+            assert_eq!(spilled.load(Ordering::SeqCst), 0);
+            Ok(())
+        }))
+        .unwrap();
+    }
+
+    #[test]
+    fn acks_only_disk_events() {
+        let (disk_acker, counter) = Acker::new_for_testing();
+        let origins = vec![false, true, true, false, true]
+            .into_iter()
+            .collect::<VecDeque<_>>();
+        let acker = Acker::Overflow(Arc::new(Mutex::new(origins)), Box::new(disk_acker));
+
+        acker.ack(2);
+        assert_eq!(counter.load(Ordering::Relaxed), 1);
+        acker.ack(2);
+        assert_eq!(counter.load(Ordering::Relaxed), 2);
+        acker.ack(1);
+        assert_eq!(counter.load(Ordering::Relaxed), 3);
+    }
+}
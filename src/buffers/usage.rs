@@ -0,0 +1,164 @@
+//! Counts the events waiting in a buffer, from when they are written to it
+//! until they are read out of it.
+//!
+//! Events that a disk buffer kept from a previous run were never counted as
+//! written, so the count saturates at zero rather than underflowing while
+//! they are read.
+
+use crate::internal_events::BufferEventsChanged;
+use futures01::{try_ready, Async, AsyncSink, Poll, Sink, StartSend, Stream};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+#[derive(Clone, Debug)]
+pub struct BufferUsage {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    events: AtomicUsize,
+    component_name: String,
+    component_kind: &'static str,
+    component_type: String,
+}
+
+impl BufferUsage {
+    pub fn new(component_name: &str, component_kind: &'static str, component_type: &str) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                events: AtomicUsize::new(0),
+                component_name: component_name.to_owned(),
+                component_kind,
+                component_type: component_type.to_owned(),
+            }),
+        }
+    }
+
+    pub fn track_input<S>(&self, inner: S) -> TrackedInput<S> {
+        TrackedInput {
+            inner,
+            usage: self.clone(),
+        }
+    }
+
+    pub fn track_output<S>(&self, inner: S) -> TrackedOutput<S> {
+        TrackedOutput {
+            inner,
+            usage: self.clone(),
+        }
+    }
+
+    pub fn events(&self) -> usize {
+        self.inner.events.load(Ordering::Relaxed)
+    }
+
+    fn written(&self) {
+        let count = self.inner.events.fetch_add(1, Ordering::Relaxed) + 1;
+        self.emit(count);
+    }
+
+    fn read(&self) {
+        let mut count = self.inner.events.load(Ordering::Relaxed);
+        while count > 0 {
+            match self.inner.events.compare_exchange_weak(
+                count,
+                count - 1,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => {
+                    count -= 1;
+                    break;
+                }
+                Err(current) => count = current,
+            }
+        }
+        self.emit(count);
+    }
+
+    fn emit(&self, count: usize) {
+        emit!(BufferEventsChanged {
+            component_name: &self.inner.component_name,
+            component_kind: self.inner.component_kind,
+            component_type: &self.inner.component_type,
+            count,
+        });
+    }
+}
+
+pub struct TrackedInput<S> {
+    inner: S,
+    usage: BufferUsage,
+}
+
+impl<S: Sink> Sink for TrackedInput<S> {
+    type SinkItem = S::SinkItem;
+    type SinkError = S::SinkError;
+
+    fn start_send(&mut self, item: Self::SinkItem) -> StartSend<Self::SinkItem, Self::SinkError> {
+        let result = self.inner.start_send(item);
+        if let Ok(AsyncSink::Ready) = result {
+            self.usage.written();
+        }
+        result
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
+        self.inner.poll_complete()
+    }
+}
+
+pub struct TrackedOutput<S> {
+    inner: S,
+    usage: BufferUsage,
+}
+
+impl<S: Stream> Stream for TrackedOutput<S> {
+    type Item = S::Item;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        let item = try_ready!(self.inner.poll());
+        if item.is_some() {
+            self.usage.read();
+        }
+        Ok(Async::Ready(item))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::block_on;
+    use futures01::{future, sync::mpsc};
+
+    #[test]
+    fn counts_buffered_events() {
+        block_on::<_, _, ()>(future::lazy(|| {
+            let usage = BufferUsage::new("out", "sink", "console");
+            let (tx, rx) = mpsc::channel(10);
+            let mut tx = usage.track_input(tx);
+            let mut rx = usage.track_output(rx);
+
+            for i in 0..3 {
+                assert_eq!(tx.start_send(i), Ok(AsyncSink::Ready));
+            }
+            assert_eq!(usage.events(), 3);
+
+            assert_eq!(rx.poll(), Ok(Async::Ready(Some(0))));
+            assert_eq!(usage.events(), 2);
+
+            // Events kept from a previous run don't make the count underflow.
+            usage.read();
+            usage.read();
+            usage.read();
+            assert_eq!(usage.events(), 0);
+
+            future::ok(())
+        }))
+        .unwrap();
+    }
+}
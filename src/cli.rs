@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 use structopt::{clap::AppSettings, StructOpt};
-use vector::{generate, list, unit_test, validate};
+use vector::{generate, list, topology::config::Format, unit_test, validate};
 
 #[derive(StructOpt, Debug)]
 #[structopt(rename_all = "kebab-case")]
@@ -56,6 +56,11 @@ pub struct RootOpts {
     #[structopt(name = "config", short, long)]
     pub config_paths: Vec<PathBuf>,
 
+    /// Parse every config file in the given format instead of detecting it from
+    /// the file extension. Files with an unrecognized extension are read as TOML.
+    #[structopt(long, possible_values = &["toml", "yaml", "json"])]
+    pub config_format: Option<Format>,
+
     /// Exit on startup if any sinks fail healthchecks
     #[structopt(short, long)]
     pub require_healthy: bool,
@@ -0,0 +1,738 @@
+//! Avro binary encoding, driven by a schema in the Avro JSON format.
+//!
+//! Only the binary encoding of single datums is implemented, which is what
+//! the Confluent wire format carries. Schema resolution between a writer and
+//! a reader schema is not supported: datums are decoded with the schema they
+//! were written with.
+
+use super::{read_varint, write_varint};
+use crate::event::Value;
+use bytes::Bytes;
+use chrono::{TimeZone, Utc};
+use serde_json::{Map, Value as JsonValue};
+use snafu::{ResultExt, Snafu};
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, Snafu)]
+pub enum AvroError {
+    #[snafu(display("invalid Avro schema: {}", source))]
+    SchemaJson { source: serde_json::Error },
+    #[snafu(display("invalid Avro schema: {}", reason))]
+    InvalidSchema { reason: String },
+    #[snafu(display("unknown Avro type {:?}", name))]
+    UnknownType { name: String },
+    #[snafu(display("value at {:?} does not match the Avro type {}", path, expected))]
+    Mismatch { path: String, expected: String },
+    #[snafu(display("missing required field {:?}", path))]
+    MissingField { path: String },
+    #[snafu(display("truncated or malformed Avro datum"))]
+    Malformed,
+    #[snafu(display("Avro datum is nested deeper than {} levels", MAX_DEPTH))]
+    TooDeep,
+}
+
+type Result<T> = std::result::Result<T, AvroError>;
+
+/// The most items an array of a type encoded as no bytes, such as `null`,
+/// may hold.
+const MAX_EMPTY_ITEMS: usize = 1 << 16;
+
+/// The deepest datums are decoded, as recursive schemas would otherwise let a
+/// datum nest values until the stack overflows.
+const MAX_DEPTH: usize = 100;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Type {
+    Null,
+    Boolean,
+    Int,
+    Long,
+    Float,
+    Double,
+    Bytes,
+    String,
+    TimestampMillis,
+    TimestampMicros,
+    Record(Vec<Field>),
+    Enum(Vec<String>),
+    Array(Box<Type>),
+    Map(Box<Type>),
+    Union(Vec<Type>),
+    Fixed(usize),
+    /// A reference to a named type, by full name.
+    Named(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Field {
+    name: String,
+    schema: Type,
+    default: Option<JsonValue>,
+}
+
+/// A parsed Avro schema.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AvroSchema {
+    root: Type,
+    names: HashMap<String, Type>,
+}
+
+impl AvroSchema {
+    pub fn parse(json: &str) -> Result<Self> {
+        let json: JsonValue = serde_json::from_str(json).context(SchemaJson)?;
+        let mut names = HashMap::new();
+        let root = parse_type(&json, None, &mut names)?;
+        let schema = Self { root, names };
+        schema.check_references(&schema.root)?;
+        Ok(schema)
+    }
+
+    /// Encodes the fields of an event as a datum of a record schema.
+    pub fn encode(&self, value: &Value, buf: &mut Vec<u8>) -> Result<()> {
+        self.encode_value(&self.root, Some(value), "", buf)
+    }
+
+    pub fn decode(&self, mut bytes: &[u8]) -> Result<Value> {
+        self.decode_value(&self.root, &mut bytes, 0)
+    }
+
+    fn resolve<'a>(&'a self, schema: &'a Type) -> &'a Type {
+        match schema {
+            Type::Named(name) => &self.names[name],
+            schema => schema,
+        }
+    }
+
+    /// Whether values of the type are encoded as no bytes at all.
+    fn is_empty<'a>(&'a self, schema: &'a Type, visiting: &mut Vec<&'a str>) -> bool {
+        match schema {
+            Type::Null | Type::Fixed(0) => true,
+            // A record containing itself can't be decoded in the first place.
+            Type::Named(name) if visiting.contains(&name.as_str()) => false,
+            Type::Named(name) => {
+                visiting.push(name);
+                let empty = self.is_empty(&self.names[name], visiting);
+                visiting.pop();
+                empty
+            }
+            Type::Record(fields) => fields
+                .iter()
+                .all(|field| self.is_empty(&field.schema, visiting)),
+            _ => false,
+        }
+    }
+
+    fn check_references(&self, schema: &Type) -> Result<()> {
+        match schema {
+            Type::Named(name) if !self.names.contains_key(name) => {
+                Err(AvroError::UnknownType { name: name.clone() })
+            }
+            Type::Record(fields) => fields
+                .iter()
+                .try_for_each(|field| self.check_references(&field.schema)),
+            Type::Array(items) | Type::Map(items) => self.check_references(items),
+            Type::Union(variants) => variants
+                .iter()
+                .try_for_each(|variant| self.check_references(variant)),
+            _ => Ok(()),
+        }
+    }
+
+    fn matches(&self, schema: &Type, value: Option<&Value>) -> bool {
+        match (self.resolve(schema), value) {
+            (Type::Null, None) | (Type::Null, Some(Value::Null)) => true,
+            (_, None) | (_, Some(Value::Null)) => false,
+            (Type::Boolean, Some(Value::Boolean(_))) => true,
+            (Type::Int, Some(Value::Integer(i))) => *i as i32 as i64 == *i,
+            (Type::Long, Some(Value::Integer(_))) => true,
+            (Type::Float, Some(Value::Float(_))) | (Type::Float, Some(Value::Integer(_))) => true,
+            (Type::Double, Some(Value::Float(_))) | (Type::Double, Some(Value::Integer(_))) => true,
+            (Type::TimestampMillis, Some(Value::Timestamp(_)))
+            | (Type::TimestampMicros, Some(Value::Timestamp(_)))
+            | (Type::TimestampMillis, Some(Value::Integer(_)))
+            | (Type::TimestampMicros, Some(Value::Integer(_))) => true,
+            (Type::Bytes, Some(Value::Bytes(_))) => true,
+            (Type::String, Some(Value::Bytes(bytes))) => std::str::from_utf8(bytes).is_ok(),
+            (Type::String, Some(Value::Timestamp(_))) => true,
+            (Type::Enum(symbols), Some(Value::Bytes(bytes))) => {
+                symbols.iter().any(|symbol| symbol.as_bytes() == &bytes[..])
+            }
+            (Type::Fixed(size), Some(Value::Bytes(bytes))) => bytes.len() == *size,
+            (Type::Record(_), Some(Value::Map(_))) | (Type::Map(_), Some(Value::Map(_))) => true,
+            (Type::Array(_), Some(Value::Array(_))) => true,
+            _ => false,
+        }
+    }
+
+    fn encode_value(
+        &self,
+        schema: &Type,
+        value: Option<&Value>,
+        path: &str,
+        buf: &mut Vec<u8>,
+    ) -> Result<()> {
+        let mismatch = || AvroError::Mismatch {
+            path: path.into(),
+            expected: type_name(schema),
+        };
+
+        match (self.resolve(schema), value) {
+            (Type::Null, None) | (Type::Null, Some(Value::Null)) => (),
+            (Type::Union(variants), value) => {
+                let index = variants
+                    .iter()
+                    .position(|variant| self.matches(variant, value))
+                    .ok_or_else(mismatch)?;
+                write_long(index as i64, buf);
+                self.encode_value(&variants[index], value, path, buf)?;
+            }
+            (_, None) | (_, Some(Value::Null)) => {
+                return Err(AvroError::MissingField { path: path.into() })
+            }
+            (Type::Boolean, Some(Value::Boolean(b))) => buf.push(*b as u8),
+            (Type::Int, Some(Value::Integer(i))) if *i as i32 as i64 == *i => write_long(*i, buf),
+            (Type::Long, Some(Value::Integer(i))) => write_long(*i, buf),
+            (Type::Float, Some(Value::Float(f))) => buf.extend(&(*f as f32).to_le_bytes()),
+            (Type::Float, Some(Value::Integer(i))) => buf.extend(&(*i as f32).to_le_bytes()),
+            (Type::Double, Some(Value::Float(f))) => buf.extend(&f.to_le_bytes()),
+            (Type::Double, Some(Value::Integer(i))) => buf.extend(&(*i as f64).to_le_bytes()),
+            (Type::TimestampMillis, Some(Value::Timestamp(ts))) => {
+                write_long(ts.timestamp_millis(), buf)
+            }
+            (Type::TimestampMicros, Some(Value::Timestamp(ts))) => write_long(
+                ts.timestamp() * 1_000_000 + ts.timestamp_subsec_micros() as i64,
+                buf,
+            ),
+            (Type::TimestampMillis, Some(Value::Integer(i)))
+            | (Type::TimestampMicros, Some(Value::Integer(i))) => write_long(*i, buf),
+            (Type::Bytes, Some(Value::Bytes(bytes))) => write_bytes(bytes, buf),
+            (Type::String, Some(value)) if self.matches(&Type::String, Some(value)) => {
+                write_bytes(&value.as_bytes(), buf)
+            }
+            (Type::Enum(symbols), Some(Value::Bytes(bytes))) => {
+                let index = symbols
+                    .iter()
+                    .position(|symbol| symbol.as_bytes() == &bytes[..])
+                    .ok_or_else(mismatch)?;
+                write_long(index as i64, buf);
+            }
+            (Type::Fixed(size), Some(Value::Bytes(bytes))) if bytes.len() == *size => {
+                buf.extend_from_slice(bytes)
+            }
+            (Type::Record(fields), Some(Value::Map(map))) => {
+                for field in fields {
+                    let path = join_path(path, &field.name);
+                    match (map.get(&field.name), &field.default) {
+                        (None, Some(default)) => {
+                            let default = Value::from(default.clone());
+                            self.encode_value(&field.schema, Some(&default), &path, buf)?;
+                        }
+                        (value, _) => self.encode_value(&field.schema, value, &path, buf)?,
+                    }
+                }
+            }
+            (Type::Array(items), Some(Value::Array(array))) => {
+                if !array.is_empty() {
+                    write_long(array.len() as i64, buf);
+                    for (i, item) in array.iter().enumerate() {
+                        let path = format!("{}[{}]", path, i);
+                        self.encode_value(items, Some(item), &path, buf)?;
+                    }
+                }
+                write_long(0, buf);
+            }
+            (Type::Map(values), Some(Value::Map(map))) => {
+                if !map.is_empty() {
+                    write_long(map.len() as i64, buf);
+                    for (key, value) in map {
+                        write_bytes(key.as_bytes(), buf);
+                        self.encode_value(values, Some(value), &join_path(path, key), buf)?;
+                    }
+                }
+                write_long(0, buf);
+            }
+            _ => return Err(mismatch()),
+        }
+
+        Ok(())
+    }
+
+    fn decode_value(&self, schema: &Type, bytes: &mut &[u8], depth: usize) -> Result<Value> {
+        if depth > MAX_DEPTH {
+            return Err(AvroError::TooDeep);
+        }
+
+        Ok(match self.resolve(schema) {
+            Type::Null => Value::Null,
+            Type::Boolean => match take(bytes, 1)? {
+                [0] => Value::Boolean(false),
+                [1] => Value::Boolean(true),
+                _ => return Err(AvroError::Malformed),
+            },
+            Type::Int | Type::Long => Value::Integer(read_long(bytes)?),
+            Type::Float => {
+                let mut raw = [0; 4];
+                raw.copy_from_slice(take(bytes, 4)?);
+                Value::Float(f32::from_le_bytes(raw) as f64)
+            }
+            Type::Double => {
+                let mut raw = [0; 8];
+                raw.copy_from_slice(take(bytes, 8)?);
+                Value::Float(f64::from_le_bytes(raw))
+            }
+            Type::TimestampMillis => Value::Timestamp(
+                Utc.timestamp_millis_opt(read_long(bytes)?)
+                    .single()
+                    .ok_or(AvroError::Malformed)?,
+            ),
+            Type::TimestampMicros => {
+                let micros = read_long(bytes)?;
+                let secs = micros.div_euclid(1_000_000);
+                let nanos = micros.rem_euclid(1_000_000) as u32 * 1000;
+                Value::Timestamp(
+                    Utc.timestamp_opt(secs, nanos)
+                        .single()
+                        .ok_or(AvroError::Malformed)?,
+                )
+            }
+            Type::Bytes | Type::String => {
+                let len = read_len(bytes)?;
+                Value::Bytes(Bytes::from(take(bytes, len)?))
+            }
+            Type::Fixed(size) => Value::Bytes(Bytes::from(take(bytes, *size)?)),
+            Type::Enum(symbols) => {
+                let index = read_len(bytes)?;
+                let symbol = symbols.get(index).ok_or(AvroError::Malformed)?;
+                Value::Bytes(Bytes::from(symbol.as_str()))
+            }
+            Type::Union(variants) => {
+                let index = read_len(bytes)?;
+                let variant = variants.get(index).ok_or(AvroError::Malformed)?;
+                self.decode_value(variant, bytes, depth + 1)?
+            }
+            Type::Record(fields) => {
+                let mut map = BTreeMap::new();
+                for field in fields {
+                    let value = self.decode_value(&field.schema, bytes, depth + 1)?;
+                    map.insert(field.name.clone(), value);
+                }
+                Value::Map(map)
+            }
+            Type::Array(items) => {
+                // Items taking no bytes can't be bounded by the size of the
+                // datum, so their number is capped instead.
+                let mut max_items = if self.is_empty(items, &mut Vec::new()) {
+                    MAX_EMPTY_ITEMS
+                } else {
+                    usize::max_value()
+                };
+                let mut array = Vec::new();
+                while let Some(count) = read_block_count(bytes)? {
+                    if count > max_items {
+                        return Err(AvroError::Malformed);
+                    }
+                    max_items -= count;
+                    for _ in 0..count {
+                        array.push(self.decode_value(items, bytes, depth + 1)?);
+                    }
+                }
+                Value::Array(array)
+            }
+            Type::Map(values) => {
+                let mut map = BTreeMap::new();
+                while let Some(count) = read_block_count(bytes)? {
+                    for _ in 0..count {
+                        let len = read_len(bytes)?;
+                        let key = String::from_utf8(take(bytes, len)?.to_vec())
+                            .map_err(|_| AvroError::Malformed)?;
+                        map.insert(key, self.decode_value(values, bytes, depth + 1)?);
+                    }
+                }
+                Value::Map(map)
+            }
+            Type::Named(_) => unreachable!("named types are resolved"),
+        })
+    }
+}
+
+fn parse_type(
+    json: &JsonValue,
+    namespace: Option<&str>,
+    names: &mut HashMap<String, Type>,
+) -> Result<Type> {
+    match json {
+        JsonValue::String(name) => parse_type_name(name, namespace, names),
+        JsonValue::Array(variants) => variants
+            .iter()
+            .map(|variant| parse_type(variant, namespace, names))
+            .collect::<Result<_>>()
+            .map(Type::Union),
+        JsonValue::Object(object) => parse_complex_type(object, namespace, names),
+        _ => Err(invalid("a type must be a string, an array or an object")),
+    }
+}
+
+fn parse_type_name(
+    name: &str,
+    namespace: Option<&str>,
+    names: &HashMap<String, Type>,
+) -> Result<Type> {
+    Ok(match name {
+        "null" => Type::Null,
+        "boolean" => Type::Boolean,
+        "int" => Type::Int,
+        "long" => Type::Long,
+        "float" => Type::Float,
+        "double" => Type::Double,
+        "bytes" => Type::Bytes,
+        "string" => Type::String,
+        name => {
+            let full_name = full_name(name, namespace);
+            if names.contains_key(&full_name) {
+                Type::Named(full_name)
+            } else if names.contains_key(name) {
+                Type::Named(name.into())
+            } else {
+                return Err(AvroError::UnknownType { name: name.into() });
+            }
+        }
+    })
+}
+
+fn parse_complex_type(
+    object: &Map<String, JsonValue>,
+    namespace: Option<&str>,
+    names: &mut HashMap<String, Type>,
+) -> Result<Type> {
+    let kind = object
+        .get("type")
+        .ok_or_else(|| invalid("missing \"type\""))?;
+    let kind = match kind {
+        JsonValue::String(kind) => kind.as_str(),
+        // A nested type definition, such as `{"type": {"type": "array", ...}}`.
+        kind => return parse_type(kind, namespace, names),
+    };
+
+    match (kind, object.get("logicalType").and_then(JsonValue::as_str)) {
+        ("long", Some("timestamp-millis")) => return Ok(Type::TimestampMillis),
+        ("long", Some("timestamp-micros")) => return Ok(Type::TimestampMicros),
+        _ => (),
+    }
+
+    match kind {
+        "array" => {
+            let items = object
+                .get("items")
+                .ok_or_else(|| invalid("array without \"items\""))?;
+            Ok(Type::Array(Box::new(parse_type(items, namespace, names)?)))
+        }
+        "map" => {
+            let values = object
+                .get("values")
+                .ok_or_else(|| invalid("map without \"values\""))?;
+            Ok(Type::Map(Box::new(parse_type(values, namespace, names)?)))
+        }
+        "record" | "error" | "enum" | "fixed" => {
+            let name = object
+                .get("name")
+                .and_then(JsonValue::as_str)
+                .ok_or_else(|| invalid("named type without \"name\""))?;
+            let namespace = match object.get("namespace").and_then(JsonValue::as_str) {
+                Some(namespace) => Some(namespace),
+                None => namespace,
+            };
+            let full_name = full_name(name, namespace);
+            let namespace = full_name.rfind('.').map(|dot| &full_name[..dot]);
+
+            let schema = match kind {
+                "enum" => {
+                    let symbols = object
+                        .get("symbols")
+                        .and_then(JsonValue::as_array)
+                        .ok_or_else(|| invalid("enum without \"symbols\""))?
+                        .iter()
+                        .map(|symbol| {
+                            symbol
+                                .as_str()
+                                .map(Into::into)
+                                .ok_or_else(|| invalid("enum symbols must be strings"))
+                        })
+                        .collect::<Result<_>>()?;
+                    Type::Enum(symbols)
+                }
+                "fixed" => {
+                    let size = object
+                        .get("size")
+                        .and_then(JsonValue::as_u64)
+                        .ok_or_else(|| invalid("fixed without \"size\""))?;
+                    Type::Fixed(size as usize)
+                }
+                _ => {
+                    // Registered first so that fields may refer to the record.
+                    names.insert(full_name.clone(), Type::Record(Vec::new()));
+                    let fields = object
+                        .get("fields")
+                        .and_then(JsonValue::as_array)
+                        .ok_or_else(|| invalid("record without \"fields\""))?
+                        .iter()
+                        .map(|field| parse_field(field, namespace, names))
+                        .collect::<Result<_>>()?;
+                    Type::Record(fields)
+                }
+            };
+            names.insert(full_name.clone(), schema);
+            Ok(Type::Named(full_name))
+        }
+        kind => parse_type_name(kind, namespace, names),
+    }
+}
+
+fn parse_field(
+    json: &JsonValue,
+    namespace: Option<&str>,
+    names: &mut HashMap<String, Type>,
+) -> Result<Field> {
+    let name = json
+        .get("name")
+        .and_then(JsonValue::as_str)
+        .ok_or_else(|| invalid("field without \"name\""))?;
+    let schema = json
+        .get("type")
+        .ok_or_else(|| invalid("field without \"type\""))?;
+    Ok(Field {
+        name: name.into(),
+        schema: parse_type(schema, namespace, names)?,
+        default: json.get("default").cloned(),
+    })
+}
+
+fn full_name(name: &str, namespace: Option<&str>) -> String {
+    match namespace {
+        Some(namespace) if !name.contains('.') && !namespace.is_empty() => {
+            format!("{}.{}", namespace, name)
+        }
+        _ => name.into(),
+    }
+}
+
+fn invalid(reason: &str) -> AvroError {
+    AvroError::InvalidSchema {
+        reason: reason.into(),
+    }
+}
+
+fn type_name(schema: &Type) -> String {
+    match schema {
+        Type::Null => "null".into(),
+        Type::Boolean => "boolean".into(),
+        Type::Int => "int".into(),
+        Type::Long => "long".into(),
+        Type::Float => "float".into(),
+        Type::Double => "double".into(),
+        Type::Bytes => "bytes".into(),
+        Type::String => "string".into(),
+        Type::TimestampMillis => "timestamp-millis".into(),
+        Type::TimestampMicros => "timestamp-micros".into(),
+        Type::Record(_) => "record".into(),
+        Type::Enum(_) => "enum".into(),
+        Type::Array(_) => "array".into(),
+        Type::Map(_) => "map".into(),
+        Type::Union(_) => "union".into(),
+        Type::Fixed(size) => format!("fixed({})", size),
+        Type::Named(name) => name.clone(),
+    }
+}
+
+fn join_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.into()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+fn write_long(n: i64, buf: &mut Vec<u8>) {
+    write_varint(((n << 1) ^ (n >> 63)) as u64, buf);
+}
+
+fn write_bytes(bytes: &[u8], buf: &mut Vec<u8>) {
+    write_long(bytes.len() as i64, buf);
+    buf.extend_from_slice(bytes);
+}
+
+fn read_long(bytes: &mut &[u8]) -> Result<i64> {
+    let n = read_varint(bytes).ok_or(AvroError::Malformed)?;
+    Ok((n >> 1) as i64 ^ -((n & 1) as i64))
+}
+
+fn read_len(bytes: &mut &[u8]) -> Result<usize> {
+    match read_long(bytes)? {
+        len if len >= 0 => Ok(len as usize),
+        _ => Err(AvroError::Malformed),
+    }
+}
+
+/// Reads the item count of the next block of an array or a map, or `None`
+/// at the end of the blocks. Most items take at least a byte, so their count
+/// can't exceed the bytes left, while `MAX_EMPTY_ITEMS` bounds the others.
+fn read_block_count(bytes: &mut &[u8]) -> Result<Option<usize>> {
+    let count = match read_long(bytes)? {
+        0 => return Ok(None),
+        count if count < 0 => {
+            // A negative count is followed by the size of the block in bytes.
+            read_long(bytes)?;
+            count.checked_neg().ok_or(AvroError::Malformed)? as u64
+        }
+        count => count as u64,
+    };
+    if count > bytes.len() as u64 && count > MAX_EMPTY_ITEMS as u64 {
+        return Err(AvroError::Malformed);
+    }
+    Ok(Some(count as usize))
+}
+
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+    if bytes.len() < len {
+        return Err(AvroError::Malformed);
+    }
+    let (head, tail) = bytes.split_at(len);
+    *bytes = tail;
+    Ok(head)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"{
+        "type": "record",
+        "name": "Log",
+        "namespace": "vector",
+        "fields": [
+            {"name": "message", "type": "string"},
+            {"name": "timestamp", "type": {"type": "long", "logicalType": "timestamp-millis"}},
+            {"name": "level", "type": {"type": "enum", "name": "Level", "symbols": ["info", "error"]}},
+            {"name": "count", "type": "int", "default": 1},
+            {"name": "tenant", "type": ["null", "string"]},
+            {"name": "tags", "type": {"type": "map", "values": "string"}},
+            {"name": "parent", "type": ["null", "Log"], "default": null}
+        ]
+    }"#;
+
+    fn log() -> BTreeMap<String, Value> {
+        let mut log = BTreeMap::new();
+        log.insert("message".into(), Value::from("hello world"));
+        log.insert(
+            "timestamp".into(),
+            Value::Timestamp(Utc.timestamp_millis(1_590_000_000_123)),
+        );
+        log.insert("level".into(), Value::from("error"));
+        log.insert("tenant".into(), Value::from("acme"));
+        log.insert("tags".into(), Value::Map(BTreeMap::new()));
+        log
+    }
+
+    #[test]
+    fn avro_round_trip() {
+        let schema = AvroSchema::parse(SCHEMA).unwrap();
+        let mut buf = Vec::new();
+        schema.encode(&Value::Map(log()), &mut buf).unwrap();
+
+        let mut expected = log();
+        expected.insert("count".into(), Value::Integer(1));
+        expected.insert("parent".into(), Value::Null);
+        assert_eq!(schema.decode(&buf).unwrap(), Value::Map(expected));
+    }
+
+    #[test]
+    fn avro_encodes_binary_format() {
+        let schema = AvroSchema::parse(
+            r#"{"type": "record", "name": "R", "fields": [
+                {"name": "a", "type": "long"},
+                {"name": "b", "type": "string"},
+                {"name": "c", "type": {"type": "array", "items": "int"}}
+            ]}"#,
+        )
+        .unwrap();
+        let mut map = BTreeMap::new();
+        map.insert("a".into(), Value::Integer(-2));
+        map.insert("b".into(), Value::from("foo"));
+        map.insert(
+            "c".into(),
+            Value::Array(vec![Value::Integer(1), Value::Integer(64)]),
+        );
+
+        let mut buf = Vec::new();
+        schema.encode(&Value::Map(map), &mut buf).unwrap();
+        assert_eq!(
+            buf,
+            vec![0x03, 0x06, b'f', b'o', b'o', 0x04, 0x02, 0x80, 0x01, 0x00]
+        );
+    }
+
+    #[test]
+    fn avro_rejects_mismatched_values() {
+        let schema = AvroSchema::parse(SCHEMA).unwrap();
+
+        let mut missing = log();
+        missing.remove("message");
+        let error = schema.encode(&Value::Map(missing), &mut Vec::new());
+        assert!(matches!(error, Err(AvroError::MissingField { .. })));
+
+        let mut wrong = log();
+        wrong.insert("level".into(), Value::from("debug"));
+        let error = schema.encode(&Value::Map(wrong), &mut Vec::new());
+        assert!(matches!(error, Err(AvroError::Mismatch { .. })));
+    }
+
+    #[test]
+    fn avro_rejects_malformed_datums() {
+        let schema = AvroSchema::parse(r#"{"type": "array", "items": "null"}"#).unwrap();
+        let mut buf = Vec::new();
+        write_long(1 << 40, &mut buf);
+        write_long(0, &mut buf);
+        assert!(matches!(schema.decode(&buf), Err(AvroError::Malformed)));
+
+        let schema = AvroSchema::parse(r#"{"type": "array", "items": "long"}"#).unwrap();
+        let mut buf = Vec::new();
+        write_long(-(1 << 40), &mut buf);
+        write_long(1, &mut buf);
+        assert!(matches!(schema.decode(&buf), Err(AvroError::Malformed)));
+
+        let schema =
+            AvroSchema::parse(r#"{"type": "long", "logicalType": "timestamp-millis"}"#).unwrap();
+        let mut buf = Vec::new();
+        write_long(i64::max_value(), &mut buf);
+        assert!(matches!(schema.decode(&buf), Err(AvroError::Malformed)));
+    }
+
+    #[test]
+    fn avro_rejects_deeply_nested_datums() {
+        let schema = AvroSchema::parse(SCHEMA).unwrap();
+        // Every field of a log is empty, and each has a parent but the last.
+        let nested = |levels: usize| {
+            let mut buf = Vec::new();
+            for level in 0..levels {
+                buf.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+                buf.push(if level + 1 < levels { 2 } else { 0 });
+            }
+            buf
+        };
+
+        assert!(schema.decode(&nested(10)).is_ok());
+        assert!(matches!(
+            schema.decode(&nested(100_000)),
+            Err(AvroError::TooDeep)
+        ));
+    }
+
+    #[test]
+    fn avro_rejects_unknown_types() {
+        let error = AvroSchema::parse(
+            r#"{"type": "record", "name": "R", "fields": [
+            {"name": "a", "type": "Missing"}
+        ]}"#,
+        );
+        assert!(matches!(error, Err(AvroError::UnknownType { .. })));
+    }
+}
@@ -0,0 +1,253 @@
+//! The `decoding` option of sources reading byte streams: how the stream is
+//! split into frames, and which codec decodes each frame into an event.
+
+use super::framing::{Framer, FramingConfig};
+use crate::{
+    event::{self, Event, LogEvent, Value},
+    internal_events::DecoderDeserializeFailed,
+};
+use bytes::Bytes;
+use chrono::{Datelike, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use snafu::{ResultExt, Snafu};
+use syslog_loose::{IncompleteDate, Message, ProcId, Protocol};
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DecodingConfig {
+    /// Newline delimited frames if unspecified.
+    pub framing: Option<FramingConfig>,
+    #[serde(default)]
+    pub codec: Codec,
+}
+
+impl DecodingConfig {
+    /// Builds the framer, limiting frames to `default_max_length` bytes
+    /// unless the framing sets its own `max_length`.
+    pub fn framer(&self, default_max_length: usize) -> crate::Result<Framer> {
+        self.framing
+            .clone()
+            .unwrap_or_default()
+            .build(default_max_length)
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Derivative)]
+#[serde(rename_all = "snake_case")]
+#[derivative(Default)]
+pub enum Codec {
+    /// The frame is the `message` of the event.
+    #[derivative(Default)]
+    Bytes,
+    /// The frame is a JSON object holding the fields of the event.
+    Json,
+    /// The frame is a syslog message, parsed like the `syslog` source does.
+    Syslog,
+    /// The frame is a logfmt line holding the fields of the event.
+    Logfmt,
+}
+
+#[derive(Debug, Snafu)]
+enum DecodeError {
+    #[snafu(display("invalid JSON: {}", source))]
+    InvalidJson { source: serde_json::Error },
+    #[snafu(display("expected a JSON object, got {}", found))]
+    NotAnObject { found: &'static str },
+    #[snafu(display("frame is not valid UTF-8: {}", source))]
+    InvalidUtf8 { source: std::str::Utf8Error },
+}
+
+impl Codec {
+    /// Decodes a frame into an event, timestamped with the current time
+    /// unless the frame holds its own timestamp.
+    pub fn decode(self, frame: Bytes) -> crate::Result<Event> {
+        let mut log = self.parse(frame)?;
+        log.try_insert(event::log_schema().timestamp_key(), Utc::now());
+        Ok(Event::from(log))
+    }
+
+    /// Decodes a frame like `decode`, reporting failures as an internal
+    /// event of the source.
+    pub fn try_decode(self, frame: Bytes, source_type: &'static str) -> Option<Event> {
+        self.decode(frame)
+            .map_err(|error| {
+                emit!(DecoderDeserializeFailed {
+                    source_type,
+                    error: &error
+                })
+            })
+            .ok()
+    }
+
+    /// Decodes a frame into the fields of a log event.
+    pub fn parse(self, frame: Bytes) -> crate::Result<LogEvent> {
+        let mut log = LogEvent::default();
+        match self {
+            Codec::Bytes => {
+                log.insert(event::log_schema().message_key().clone(), frame);
+            }
+            Codec::Json => match serde_json::from_slice(&frame).context(InvalidJson)? {
+                JsonValue::Object(fields) => {
+                    for (key, value) in fields {
+                        log.insert(key, value);
+                    }
+                }
+                value => {
+                    return Err(DecodeError::NotAnObject {
+                        found: json_type_name(&value),
+                    }
+                    .into())
+                }
+            },
+            Codec::Syslog => {
+                let line = std::str::from_utf8(&frame).context(InvalidUtf8)?;
+                log = parse_syslog(line);
+            }
+            Codec::Logfmt => {
+                let line = std::str::from_utf8(&frame).context(InvalidUtf8)?;
+                // Pairs without a value are not logfmt data.
+                for logfmt::Pair { key, val } in logfmt::parse(line) {
+                    if let Some(val) = val {
+                        log.insert(key, val);
+                    }
+                }
+            }
+        }
+        Ok(log)
+    }
+}
+
+/// Parses a syslog message into its `message`, its timestamp if it has one,
+/// and the fields of its header and structured data.
+pub fn parse_syslog(line: &str) -> LogEvent {
+    let parsed = syslog_loose::parse_message_with_year(line.trim(), resolve_year);
+
+    let mut log = LogEvent::default();
+    log.insert(event::log_schema().message_key().clone(), parsed.msg);
+    if let Some(timestamp) = parsed.timestamp {
+        log.insert(
+            event::log_schema().timestamp_key().clone(),
+            timestamp.with_timezone(&Utc),
+        );
+    }
+    insert_fields_from_syslog(&mut log, parsed);
+
+    log
+}
+
+/// Function used to resolve the year for syslog messages that don't include the year.
+/// If the current month is January, and the syslog message is for December, it will take the previous year.
+/// Otherwise, take the current year.
+fn resolve_year((month, _date, _hour, _min, _sec): IncompleteDate) -> i32 {
+    let now = Utc::now();
+    if now.month() == 1 && month == 12 {
+        now.year() - 1
+    } else {
+        now.year()
+    }
+}
+
+fn insert_fields_from_syslog(log: &mut LogEvent, parsed: Message<&str>) {
+    if let Some(host) = parsed.hostname {
+        log.insert("hostname", host);
+    }
+    if let Some(severity) = parsed.severity {
+        log.insert("severity", severity.as_str());
+    }
+    if let Some(facility) = parsed.facility {
+        log.insert("facility", facility.as_str());
+    }
+    if let Protocol::RFC5424(version) = parsed.protocol {
+        log.insert("version", version as i64);
+    }
+    if let Some(app_name) = parsed.appname {
+        log.insert("appname", app_name);
+    }
+    if let Some(msg_id) = parsed.msgid {
+        log.insert("msgid", msg_id);
+    }
+    if let Some(procid) = parsed.procid {
+        let value: Value = match procid {
+            ProcId::PID(pid) => pid.into(),
+            ProcId::Name(name) => name.into(),
+        };
+        log.insert("procid", value);
+    }
+
+    for element in parsed.structured_data.iter() {
+        for (name, value) in element.params.iter() {
+            let key = format!("{}.{}", element.id, name);
+            log.insert(key, *value);
+        }
+    }
+}
+
+fn json_type_name(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Object(_) => "an object",
+        JsonValue::Array(_) => "an array",
+        JsonValue::String(_) => "a string",
+        JsonValue::Number(_) => "a number",
+        JsonValue::Bool(_) => "a boolean",
+        JsonValue::Null => "null",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn decodes_bytes() {
+        let event = Codec::Bytes.decode("hello".into()).unwrap();
+        let log = event.as_log();
+        assert_eq!(log[&event::log_schema().message_key()], "hello".into());
+        assert!(log.contains(&event::log_schema().timestamp_key()));
+    }
+
+    #[test]
+    fn decodes_json_objects() {
+        let log = Codec::Json
+            .parse(r#"{"message":"hi","status":200,"request":{"path":"/"}}"#.into())
+            .unwrap();
+        assert_eq!(log[&"message".into()], "hi".into());
+        assert_eq!(log[&"status".into()], Value::Integer(200));
+        assert_eq!(log[&"request.path".into()], "/".into());
+
+        assert!(Codec::Json.parse("[1, 2]".into()).is_err());
+        assert!(Codec::Json.parse("{".into()).is_err());
+    }
+
+    #[test]
+    fn decodes_syslog() {
+        let line = r#"<13>1 2019-02-13T19:48:34+00:00 74794bfb6795 root 8449 - [meta sequenceId="1"] i am foobar"#;
+        let log = Codec::Syslog.parse(line.into()).unwrap();
+        assert_eq!(
+            log[&event::log_schema().message_key()],
+            "i am foobar".into()
+        );
+        assert_eq!(log[&"hostname".into()], "74794bfb6795".into());
+        assert_eq!(log[&"appname".into()], "root".into());
+        assert_eq!(log[&"meta.sequenceId".into()], "1".into());
+        assert!(log.contains(&event::log_schema().timestamp_key()));
+    }
+
+    #[test]
+    fn decodes_logfmt() {
+        let log = Codec::Logfmt
+            .parse("level=info msg=\"hello world\" garbage".into())
+            .unwrap();
+        assert_eq!(log[&"level".into()], "info".into());
+        assert_eq!(log[&"msg".into()], "hello world".into());
+        assert!(!log.contains(&"garbage".into()));
+    }
+
+    #[test]
+    fn frames_default_to_newlines() {
+        let config: DecodingConfig = toml::from_str(r#"codec = "json""#).unwrap();
+        assert_eq!(config.codec, Codec::Json);
+        let frames = config.framer(1024).unwrap().frames(b"{}\n{}").unwrap();
+        assert_eq!(frames.len(), 2);
+    }
+}
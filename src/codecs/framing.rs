@@ -0,0 +1,296 @@
+//! Splitting byte streams into frames, each of which is decoded into an event.
+
+use bytes::{Bytes, BytesMut};
+use codec01::BytesDelimitedCodec;
+use serde::{Deserialize, Serialize};
+use snafu::Snafu;
+use std::{cmp, io};
+use tokio01::codec::Decoder;
+
+/// The largest number of digits accepted in an octet count.
+const MAX_OCTET_COUNT_DIGITS: usize = 20;
+
+/// The maximum size of a frame for sources that don't limit the length of
+/// their events otherwise.
+pub const DEFAULT_MAX_LENGTH: usize = 100 * 1024;
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum FramingConfig {
+    /// Frames end with a newline.
+    NewlineDelimited { max_length: Option<usize> },
+    /// Frames end with a custom ASCII delimiter.
+    CharacterDelimited {
+        delimiter: char,
+        max_length: Option<usize>,
+    },
+    /// Frames are prefixed by their length in ASCII digits and a space, as
+    /// in RFC 6587. Frames that don't start with a digit end with a newline.
+    OctetCounting { max_length: Option<usize> },
+    /// Frames are prefixed by their length as a 4-byte big-endian integer.
+    LengthDelimited { max_length: Option<usize> },
+}
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("the framing delimiter {:?} is not an ASCII character", delimiter))]
+    NonAsciiDelimiter { delimiter: char },
+}
+
+impl Default for FramingConfig {
+    fn default() -> Self {
+        FramingConfig::NewlineDelimited { max_length: None }
+    }
+}
+
+impl FramingConfig {
+    /// Builds the framer, limiting frames to `default_max_length` bytes
+    /// unless the configuration sets its own `max_length`.
+    pub fn build(&self, default_max_length: usize) -> crate::Result<Framer> {
+        let method = match *self {
+            FramingConfig::NewlineDelimited { max_length } => {
+                Method::Delimited(BytesDelimitedCodec::new_with_max_length(
+                    b'\n',
+                    max_length.unwrap_or(default_max_length),
+                ))
+            }
+            FramingConfig::CharacterDelimited {
+                delimiter,
+                max_length,
+            } => {
+                if !delimiter.is_ascii() {
+                    return Err(BuildError::NonAsciiDelimiter { delimiter }.into());
+                }
+                Method::Delimited(BytesDelimitedCodec::new_with_max_length(
+                    delimiter as u8,
+                    max_length.unwrap_or(default_max_length),
+                ))
+            }
+            FramingConfig::OctetCounting { max_length } => {
+                let max_length = max_length.unwrap_or(default_max_length);
+                Method::OctetCounting {
+                    max_length,
+                    lines: BytesDelimitedCodec::new_with_max_length(b'\n', max_length),
+                }
+            }
+            FramingConfig::LengthDelimited { max_length } => Method::LengthDelimited {
+                max_length: max_length.unwrap_or(default_max_length),
+            },
+        };
+
+        Ok(Framer { method, discard: 0 })
+    }
+}
+
+/// Splits a byte stream into frames. Frames longer than the maximum length
+/// are discarded.
+#[derive(Clone, Debug)]
+pub struct Framer {
+    method: Method,
+    /// The number of bytes left to skip from an oversized counted frame.
+    discard: usize,
+}
+
+#[derive(Clone, Debug)]
+enum Method {
+    Delimited(BytesDelimitedCodec),
+    OctetCounting {
+        max_length: usize,
+        lines: BytesDelimitedCodec,
+    },
+    LengthDelimited {
+        max_length: usize,
+    },
+}
+
+impl Framer {
+    /// Splits a complete payload, such as a request body, into its non-empty
+    /// frames. The state of a stream being decoded is not shared.
+    pub fn frames(&self, payload: &[u8]) -> Result<Vec<Bytes>, io::Error> {
+        let mut framer = self.clone();
+        let mut buf = BytesMut::from(payload);
+        let mut frames = Vec::new();
+        while let Some(frame) = framer.decode_eof(&mut buf)? {
+            if !frame.is_empty() {
+                frames.push(frame);
+            }
+        }
+        Ok(frames)
+    }
+
+    fn decode_frame(&mut self, buf: &mut BytesMut) -> Result<Option<Bytes>, io::Error> {
+        loop {
+            if self.discard > 0 {
+                let skipped = cmp::min(self.discard, buf.len());
+                buf.advance(skipped);
+                self.discard -= skipped;
+                if self.discard > 0 {
+                    return Ok(None);
+                }
+            }
+
+            let (header_len, frame_len, max_length) = match &mut self.method {
+                Method::Delimited(codec) => return codec.decode(buf),
+                Method::OctetCounting { max_length, lines } => {
+                    if !is_octet_counted(buf) {
+                        // Octet counting isn't used so fallback to newlines.
+                        return lines.decode(buf);
+                    }
+                    match octet_count(buf)? {
+                        Some((header_len, frame_len)) => (header_len, frame_len, *max_length),
+                        None => return Ok(None),
+                    }
+                }
+                Method::LengthDelimited { max_length } => match buf.get(..4) {
+                    Some(&[a, b, c, d]) => {
+                        (4, u32::from_be_bytes([a, b, c, d]) as usize, *max_length)
+                    }
+                    _ => return Ok(None),
+                },
+            };
+
+            if frame_len > max_length {
+                warn!(
+                    message = "discarding frame larger than max_length",
+                    frame_len,
+                    max_length,
+                    rate_limit_secs = 30
+                );
+                buf.advance(header_len);
+                self.discard = frame_len;
+                continue;
+            }
+
+            let len = header_len.checked_add(frame_len).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "frame length is too large")
+            })?;
+            if buf.len() < len {
+                return Ok(None);
+            }
+
+            buf.advance(header_len);
+            return Ok(Some(buf.split_to(frame_len).freeze()));
+        }
+    }
+}
+
+/// Whether the next frame starts with a non zero octet count.
+fn is_octet_counted(buf: &[u8]) -> bool {
+    matches!(buf.first(), Some(b'1'..=b'9'))
+}
+
+/// Reads the length of an octet counted frame, and the length of its header.
+fn octet_count(buf: &[u8]) -> Result<Option<(usize, usize)>, io::Error> {
+    let digits = cmp::min(buf.len(), MAX_OCTET_COUNT_DIGITS + 1);
+    match buf[..digits].iter().position(|&b| b == b' ') {
+        Some(space) => std::str::from_utf8(&buf[..space])
+            .ok()
+            .and_then(|count| count.parse().ok())
+            .map(|count| Some((space + 1, count)))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Unable to decode message len as number",
+                )
+            }),
+        None if digits > MAX_OCTET_COUNT_DIGITS => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "octet count is too long",
+        )),
+        None => Ok(None),
+    }
+}
+
+impl Decoder for Framer {
+    type Item = Bytes;
+    type Error = io::Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<Bytes>, io::Error> {
+        self.decode_frame(buf)
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<Bytes>, io::Error> {
+        match &mut self.method {
+            Method::Delimited(codec) => codec.decode_eof(buf),
+            Method::OctetCounting { lines, .. } if self.discard == 0 && !is_octet_counted(buf) => {
+                lines.decode_eof(buf)
+            }
+            _ => match self.decode_frame(buf)? {
+                Some(frame) => Ok(Some(frame)),
+                None if buf.is_empty() => Ok(None),
+                None => Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "frame truncated at end of stream",
+                )),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn frames(config: FramingConfig, payload: &[u8]) -> Vec<Bytes> {
+        config.build(1024).unwrap().frames(payload).unwrap()
+    }
+
+    #[test]
+    fn splits_delimited_frames() {
+        let newline = FramingConfig::default();
+        assert_eq!(frames(newline, b"foo\n\nbar"), vec!["foo", "bar"]);
+
+        let comma = FramingConfig::CharacterDelimited {
+            delimiter: ',',
+            max_length: None,
+        };
+        assert_eq!(frames(comma, b"foo,bar,"), vec!["foo", "bar"]);
+    }
+
+    #[test]
+    fn splits_octet_counted_frames() {
+        let config = FramingConfig::OctetCounting { max_length: None };
+        assert_eq!(
+            frames(config, b"3 foo5 a\nbcplain line\n2 hi"),
+            vec!["foo", "a\nbc", "plain line", "hi"]
+        );
+    }
+
+    #[test]
+    fn splits_length_delimited_frames() {
+        let config = FramingConfig::LengthDelimited { max_length: None };
+        assert_eq!(
+            frames(config, b"\0\0\0\x03foo\0\0\0\0\0\0\0\x02hi"),
+            vec!["foo", "hi"]
+        );
+    }
+
+    #[test]
+    fn discards_oversized_counted_frames() {
+        let config = FramingConfig::OctetCounting {
+            max_length: Some(4),
+        };
+        let mut framer = config.build(1024).unwrap();
+        let mut buf = BytesMut::from(&b"10 0123"[..]);
+        assert_eq!(framer.decode(&mut buf).unwrap(), None);
+        buf.extend_from_slice(b"4567892 ok");
+        assert_eq!(framer.decode(&mut buf).unwrap(), Some("ok".into()));
+    }
+
+    #[test]
+    fn rejects_overflowing_octet_counts() {
+        let config = FramingConfig::OctetCounting {
+            max_length: Some(usize::max_value()),
+        };
+        let framer = config.build(1024).unwrap();
+        let error = framer.frames(b"18446744073709551615 foo").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn rejects_truncated_counted_frames() {
+        let config = FramingConfig::LengthDelimited { max_length: None };
+        let framer = config.build(1024).unwrap();
+        assert!(framer.frames(b"\0\0\0\x05foo").is_err());
+    }
+}
@@ -0,0 +1,456 @@
+//! Codecs shared by sources and sinks.
+//!
+//! Sources reading byte streams split them into frames with `framing`, and
+//! decode each frame into an event with the codecs of `decoding`.
+//!
+//! The schema-based binary codecs load their schemas from a local file or
+//! from a Confluent-compatible schema registry. With a registry, payloads use
+//! the Confluent wire format: a zero magic byte and the big-endian schema id,
+//! followed for Protobuf by the position of the message type in its schema,
+//! and then by the datum.
+
+pub mod avro;
+#[cfg(feature = "codecs-decoding")]
+pub mod decoding;
+pub mod framing;
+pub mod protobuf;
+mod registry;
+
+pub use registry::{RegisteredSchema, SchemaRegistry, SchemaRegistryConfig};
+
+use self::{
+    avro::{AvroError, AvroSchema},
+    protobuf::{ProtobufError, ProtobufSchema},
+};
+use crate::event::{LogEvent, Value};
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+const MAGIC_BYTE: u8 = 0;
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaFormat {
+    Avro,
+    Protobuf,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SchemaConfig {
+    /// An Avro schema, or a Protobuf descriptor set.
+    pub path: Option<PathBuf>,
+    pub registry: Option<SchemaRegistryConfig>,
+    /// The fully qualified name of the Protobuf message type.
+    pub message_type: Option<String>,
+}
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("the {:?} codec requires a `schema`", format))]
+    MissingSchemaConfig { format: SchemaFormat },
+    #[snafu(display("a schema `path` or `registry.subject` is required"))]
+    MissingSchema,
+    #[snafu(display("Protobuf schemas require a descriptor set `path` and a `message_type`"))]
+    MissingDescriptor,
+    #[snafu(display("could not read schema {:?}: {}", path, source))]
+    ReadSchema {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[snafu(display("schema registries are only supported when building the topology"))]
+    RegistryNeedsRuntime,
+    #[snafu(display(
+        "expected a {:?} schema, but the registry holds a {} schema",
+        format,
+        found
+    ))]
+    WrongSchemaType { format: SchemaFormat, found: String },
+    #[snafu(display("{}", source))]
+    InvalidAvro { source: AvroError },
+    #[snafu(display("{}", source))]
+    InvalidProtobuf { source: ProtobufError },
+}
+
+/// Returns the schema to use with `format`, the schema format required by
+/// the codec of a component if any.
+pub fn required_schema(
+    format: Option<SchemaFormat>,
+    schema: &Option<SchemaConfig>,
+) -> crate::Result<Option<(SchemaFormat, &SchemaConfig)>> {
+    match (format, schema) {
+        (Some(format), Some(schema)) => Ok(Some((format, schema))),
+        (Some(format), None) => Err(BuildError::MissingSchemaConfig { format }.into()),
+        (None, _) => Ok(None),
+    }
+}
+
+#[derive(Debug, Snafu)]
+enum DecodeError {
+    #[snafu(display("payload does not start with the schema registry magic byte"))]
+    MissingMagicByte,
+    #[snafu(display("payload is too short for the schema registry wire format"))]
+    TruncatedHeader,
+    #[snafu(display("payload holds message type {:?}, not the configured one", indexes))]
+    UnexpectedMessageType { indexes: Vec<i64> },
+    #[snafu(display("schema {} is not an Avro schema", id))]
+    NotAvro { id: u32 },
+    #[snafu(display("decoded payload is not a record"))]
+    NotARecord,
+}
+
+#[derive(Clone, Debug)]
+enum Schema {
+    Avro(Arc<AvroSchema>),
+    Protobuf(Arc<ProtobufSchema>),
+}
+
+impl Schema {
+    fn encode(&self, value: &Value, buf: &mut Vec<u8>) -> crate::Result<()> {
+        match self {
+            Schema::Avro(schema) => schema.encode(value, buf)?,
+            Schema::Protobuf(schema) => schema.encode(value, buf)?,
+        }
+        Ok(())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> crate::Result<Value> {
+        Ok(match self {
+            Schema::Avro(schema) => schema.decode(bytes)?,
+            Schema::Protobuf(schema) => schema.decode(bytes)?,
+        })
+    }
+}
+
+/// Encodes events with a schema, framed in the wire format when the schema
+/// comes from a registry.
+#[derive(Debug)]
+pub struct SchemaEncoder {
+    schema: Schema,
+    id: Option<u32>,
+}
+
+impl SchemaEncoder {
+    pub fn encode(&self, log: LogEvent) -> crate::Result<Vec<u8>> {
+        let value = Value::Map(log.into_iter().collect());
+        let mut buf = Vec::new();
+        if let Some(id) = self.id {
+            buf.push(MAGIC_BYTE);
+            buf.extend(&id.to_be_bytes());
+            if let Schema::Protobuf(schema) = &self.schema {
+                write_message_indexes(schema.indexes(), &mut buf);
+            }
+        }
+        self.schema.encode(&value, &mut buf)?;
+        Ok(buf)
+    }
+}
+
+/// Decodes payloads into events. With a registry, schemas are looked up by
+/// the id of each payload unless a local schema is configured.
+#[derive(Debug)]
+pub struct SchemaDecoder {
+    format: SchemaFormat,
+    schema: Option<Schema>,
+    registry: Option<SchemaRegistry>,
+    schemas: Mutex<HashMap<u32, Schema>>,
+}
+
+impl SchemaDecoder {
+    /// Decodes a payload into the fields of an event.
+    pub async fn decode(&self, mut bytes: &[u8]) -> crate::Result<LogEvent> {
+        let schema = match &self.registry {
+            None => self.schema.clone().expect("decoder without schema"),
+            Some(registry) => {
+                let id = read_header(&mut bytes)?;
+                if self.format == SchemaFormat::Protobuf {
+                    let indexes = read_message_indexes(&mut bytes)?;
+                    self.check_message_indexes(indexes)?;
+                }
+                match &self.schema {
+                    Some(schema) => schema.clone(),
+                    None => self.schema_by_id(registry, id).await?,
+                }
+            }
+        };
+
+        match schema.decode(bytes)? {
+            Value::Map(fields) => {
+                let mut log = LogEvent::default();
+                for (key, value) in fields {
+                    log.insert_flat(key, value);
+                }
+                Ok(log)
+            }
+            _ => Err(DecodeError::NotARecord.into()),
+        }
+    }
+
+    fn check_message_indexes(&self, indexes: Vec<i64>) -> crate::Result<()> {
+        match &self.schema {
+            Some(Schema::Protobuf(schema)) if schema.indexes() != &indexes[..] => {
+                Err(DecodeError::UnexpectedMessageType { indexes }.into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    async fn schema_by_id(&self, registry: &SchemaRegistry, id: u32) -> crate::Result<Schema> {
+        let cached = self.schemas.lock().unwrap().get(&id).cloned();
+        if let Some(schema) = cached {
+            return Ok(schema);
+        }
+
+        let registered = registry.schema_by_id(id).await?;
+        if registered.schema_type != "AVRO" {
+            return Err(DecodeError::NotAvro { id }.into());
+        }
+        let schema = Schema::Avro(Arc::new(
+            AvroSchema::parse(&registered.schema).context(InvalidAvro)?,
+        ));
+        self.schemas.lock().unwrap().insert(id, schema.clone());
+        Ok(schema)
+    }
+}
+
+impl SchemaConfig {
+    /// Loads the schema, fetching it from the registry if configured.
+    pub async fn build_encoder(&self, format: SchemaFormat) -> crate::Result<SchemaEncoder> {
+        let (registry, subject) = match &self.registry {
+            Some(config) => match &config.subject {
+                Some(subject) => (config, subject),
+                None => return Err(BuildError::MissingSchema.into()),
+            },
+            None => return self.build_local_encoder(format),
+        };
+
+        let registered = SchemaRegistry::new(registry)?
+            .subject_version(subject, registry.version)
+            .await?;
+        let schema = match format {
+            SchemaFormat::Avro if registered.schema_type == "AVRO" => Schema::Avro(Arc::new(
+                AvroSchema::parse(&registered.schema).context(InvalidAvro)?,
+            )),
+            // The registry holds the `.proto` source, so the message is
+            // encoded with the local descriptor set.
+            SchemaFormat::Protobuf if registered.schema_type == "PROTOBUF" => {
+                self.load_protobuf()?
+            }
+            _ => {
+                return Err(BuildError::WrongSchemaType {
+                    format,
+                    found: registered.schema_type,
+                }
+                .into())
+            }
+        };
+
+        Ok(SchemaEncoder {
+            schema,
+            id: Some(registered.id),
+        })
+    }
+
+    /// Loads the schema from the local file only.
+    pub fn build_local_encoder(&self, format: SchemaFormat) -> crate::Result<SchemaEncoder> {
+        if self.registry.is_some() {
+            return Err(BuildError::RegistryNeedsRuntime.into());
+        }
+        let schema = match format {
+            SchemaFormat::Avro => self.load_avro()?,
+            SchemaFormat::Protobuf => self.load_protobuf()?,
+        };
+        Ok(SchemaEncoder { schema, id: None })
+    }
+
+    pub fn build_decoder(&self, format: SchemaFormat) -> crate::Result<SchemaDecoder> {
+        let registry = self
+            .registry
+            .as_ref()
+            .map(SchemaRegistry::new)
+            .transpose()?;
+        let schema = match format {
+            SchemaFormat::Avro if self.path.is_none() && registry.is_some() => None,
+            SchemaFormat::Avro => Some(self.load_avro()?),
+            SchemaFormat::Protobuf => Some(self.load_protobuf()?),
+        };
+        Ok(SchemaDecoder {
+            format,
+            schema,
+            registry,
+            schemas: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn load_avro(&self) -> crate::Result<Schema> {
+        let path = self.path.as_ref().ok_or(BuildError::MissingSchema)?;
+        let json = std::fs::read_to_string(path).context(ReadSchema { path })?;
+        let schema = AvroSchema::parse(&json).context(InvalidAvro)?;
+        Ok(Schema::Avro(Arc::new(schema)))
+    }
+
+    fn load_protobuf(&self) -> crate::Result<Schema> {
+        let (path, message_type) = match (&self.path, &self.message_type) {
+            (Some(path), Some(message_type)) => (path, message_type),
+            _ => return Err(BuildError::MissingDescriptor.into()),
+        };
+        let descriptor_set = std::fs::read(path).context(ReadSchema { path })?;
+        let schema =
+            ProtobufSchema::parse(&descriptor_set, message_type).context(InvalidProtobuf)?;
+        Ok(Schema::Protobuf(Arc::new(schema)))
+    }
+}
+
+/// Prefixes an encoded record with its length as a varint, so records can be
+/// concatenated in a stream, like delimited Protobuf messages.
+pub fn length_delimited(record: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(record.len() + 5);
+    write_varint(record.len() as u64, &mut buf);
+    buf.extend_from_slice(record);
+    buf
+}
+
+fn read_header(bytes: &mut &[u8]) -> crate::Result<u32> {
+    let payload: &[u8] = bytes;
+    match payload {
+        [MAGIC_BYTE, a, b, c, d, rest @ ..] => {
+            let id = u32::from_be_bytes([*a, *b, *c, *d]);
+            *bytes = rest;
+            Ok(id)
+        }
+        [MAGIC_BYTE, ..] | [] => Err(DecodeError::TruncatedHeader.into()),
+        _ => Err(DecodeError::MissingMagicByte.into()),
+    }
+}
+
+/// The message indexes are zigzag varints preceded by their count, with the
+/// common `[0]` written as a single zero.
+fn write_message_indexes(indexes: &[i64], buf: &mut Vec<u8>) {
+    if indexes == [0] {
+        buf.push(0);
+        return;
+    }
+    write_zigzag(indexes.len() as i64, buf);
+    for index in indexes {
+        write_zigzag(*index, buf);
+    }
+}
+
+fn read_message_indexes(bytes: &mut &[u8]) -> crate::Result<Vec<i64>> {
+    let count = read_zigzag(bytes)?;
+    if count == 0 {
+        return Ok(vec![0]);
+    }
+    (0..count).map(|_| read_zigzag(bytes)).collect()
+}
+
+fn write_zigzag(n: i64, buf: &mut Vec<u8>) {
+    write_varint(((n << 1) ^ (n >> 63)) as u64, buf);
+}
+
+fn read_zigzag(bytes: &mut &[u8]) -> crate::Result<i64> {
+    let n = read_varint(bytes).ok_or(DecodeError::TruncatedHeader)?;
+    Ok((n >> 1) as i64 ^ -((n & 1) as i64))
+}
+
+fn write_varint(mut n: u64, buf: &mut Vec<u8>) {
+    while n >= 0x80 {
+        buf.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    buf.push(n as u8);
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Option<u64> {
+    let mut n = 0;
+    for (i, byte) in bytes.iter().enumerate().take(10) {
+        n |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            *bytes = &bytes[i + 1..];
+            return Some(n);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_file;
+
+    const SCHEMA: &str = r#"{"type": "record", "name": "Log", "fields": [
+        {"name": "message", "type": "string"}
+    ]}"#;
+
+    fn log() -> LogEvent {
+        let mut log = LogEvent::default();
+        log.insert("message", "hello");
+        log
+    }
+
+    #[test]
+    fn varint_round_trip() {
+        for n in &[0, 1, 127, 128, 300, u64::max_value()] {
+            let mut buf = Vec::new();
+            write_varint(*n, &mut buf);
+            let mut bytes = &buf[..];
+            assert_eq!(read_varint(&mut bytes), Some(*n));
+            assert!(bytes.is_empty());
+        }
+    }
+
+    #[test]
+    fn message_indexes_round_trip() {
+        for indexes in &[vec![0], vec![1], vec![2, 0, 3]] {
+            let mut buf = Vec::new();
+            write_message_indexes(indexes, &mut buf);
+            assert_eq!(read_message_indexes(&mut &buf[..]).unwrap(), *indexes);
+        }
+        let mut buf = Vec::new();
+        write_message_indexes(&[0], &mut buf);
+        assert_eq!(buf, vec![0]);
+    }
+
+    #[tokio::test]
+    async fn local_schema_round_trip() {
+        let path = temp_file();
+        std::fs::write(&path, SCHEMA).unwrap();
+        let config = SchemaConfig {
+            path: Some(path),
+            ..Default::default()
+        };
+
+        let encoded = config
+            .build_local_encoder(SchemaFormat::Avro)
+            .unwrap()
+            .encode(log())
+            .unwrap();
+        assert_eq!(encoded, b"\x0ahello".to_vec());
+
+        let decoder = config.build_decoder(SchemaFormat::Avro).unwrap();
+        let decoded = decoder.decode(&encoded).await.unwrap();
+        assert_eq!(decoded, log());
+    }
+
+    #[test]
+    fn wire_format_framing() {
+        let encoder = SchemaEncoder {
+            schema: Schema::Avro(Arc::new(AvroSchema::parse(SCHEMA).unwrap())),
+            id: Some(258),
+        };
+        let encoded = encoder.encode(log()).unwrap();
+        assert_eq!(&encoded[..5], &[0, 0, 0, 1, 2]);
+
+        let mut bytes = &encoded[..];
+        assert_eq!(read_header(&mut bytes).unwrap(), 258);
+        assert_eq!(bytes, b"\x0ahello");
+
+        assert!(read_header(&mut &b"\x01\x00\x00\x00\x01"[..]).is_err());
+        assert!(read_header(&mut &b"\x00\x00"[..]).is_err());
+    }
+}
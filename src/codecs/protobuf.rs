@@ -0,0 +1,742 @@
+//! Protobuf encoding of events, driven by a compiled descriptor set.
+//!
+//! The descriptor set is the output of
+//! `protoc --include_imports --descriptor_set_out=<file>`, which lets messages
+//! be encoded and decoded without generating code for them.
+
+use super::{read_varint, write_varint};
+use crate::event::Value;
+use bytes::Bytes;
+use chrono::{TimeZone, Utc};
+use prost::Message;
+use prost_types::{
+    field_descriptor_proto::{Label, Type},
+    DescriptorProto, EnumDescriptorProto, FileDescriptorSet,
+};
+use snafu::{ResultExt, Snafu};
+use std::collections::{BTreeMap, HashMap};
+
+const TIMESTAMP: &str = ".google.protobuf.Timestamp";
+
+/// The deepest messages are decoded, as recursive message types would
+/// otherwise let a message nest fields until the stack overflows.
+const MAX_DEPTH: usize = 100;
+
+#[derive(Debug, Snafu)]
+pub enum ProtobufError {
+    #[snafu(display("invalid Protobuf descriptor set: {}", source))]
+    DescriptorSet { source: prost::DecodeError },
+    #[snafu(display("message type {:?} not found in the descriptor set", name))]
+    UnknownMessage { name: String },
+    #[snafu(display("field {:?} uses groups, which are not supported", name))]
+    UnsupportedGroup { name: String },
+    #[snafu(display("value at {:?} does not match the Protobuf type {}", path, expected))]
+    Mismatch { path: String, expected: String },
+    #[snafu(display("truncated or malformed Protobuf message"))]
+    Malformed,
+    #[snafu(display("Protobuf message is nested deeper than {} levels", MAX_DEPTH))]
+    TooDeep,
+}
+
+type Result<T> = std::result::Result<T, ProtobufError>;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Kind {
+    Double,
+    Float,
+    Int32,
+    Int64,
+    Uint32,
+    Uint64,
+    Sint32,
+    Sint64,
+    Fixed32,
+    Fixed64,
+    Sfixed32,
+    Sfixed64,
+    Bool,
+    String,
+    Bytes,
+    Enum(String),
+    Message(String),
+}
+
+#[derive(Clone, Debug)]
+struct Field {
+    name: String,
+    number: u32,
+    kind: Kind,
+    repeated: bool,
+}
+
+#[derive(Clone, Debug)]
+struct MessageDescriptor {
+    fields: Vec<Field>,
+    map_entry: bool,
+    /// The position of the message in its file, as used by the Confluent
+    /// wire format.
+    indexes: Vec<i64>,
+}
+
+/// A message type, along with every type it depends on.
+#[derive(Clone, Debug)]
+pub struct ProtobufSchema {
+    root: String,
+    messages: HashMap<String, MessageDescriptor>,
+    enums: HashMap<String, Vec<(String, i32)>>,
+}
+
+impl ProtobufSchema {
+    pub fn parse(descriptor_set: &[u8], message_type: &str) -> Result<Self> {
+        let descriptor_set = FileDescriptorSet::decode(descriptor_set).context(DescriptorSet)?;
+
+        let mut schema = Self {
+            root: format!(".{}", message_type.trim_start_matches('.')),
+            messages: HashMap::new(),
+            enums: HashMap::new(),
+        };
+        for file in &descriptor_set.file {
+            let prefix = match file.package() {
+                "" => String::new(),
+                package => format!(".{}", package),
+            };
+            schema.add_enums(&prefix, &file.enum_type);
+            for (index, message) in file.message_type.iter().enumerate() {
+                schema.add_message(&prefix, message, vec![index as i64])?;
+            }
+        }
+
+        if !schema.messages.contains_key(&schema.root) {
+            return Err(ProtobufError::UnknownMessage {
+                name: message_type.into(),
+            });
+        }
+        Ok(schema)
+    }
+
+    /// The position of the message type in its file.
+    pub fn indexes(&self) -> &[i64] {
+        &self.messages[&self.root].indexes
+    }
+
+    pub fn encode(&self, value: &Value, buf: &mut Vec<u8>) -> Result<()> {
+        match value {
+            Value::Map(map) => self.encode_message(&self.root, map, "", buf),
+            _ => Err(ProtobufError::Mismatch {
+                path: String::new(),
+                expected: self.root.clone(),
+            }),
+        }
+    }
+
+    pub fn decode(&self, bytes: &[u8]) -> Result<Value> {
+        self.decode_message(&self.root, bytes, 0)
+    }
+
+    fn add_enums(&mut self, prefix: &str, enums: &[EnumDescriptorProto]) {
+        for descriptor in enums {
+            let values = descriptor
+                .value
+                .iter()
+                .map(|value| (value.name().to_owned(), value.number()))
+                .collect();
+            self.enums
+                .insert(format!("{}.{}", prefix, descriptor.name()), values);
+        }
+    }
+
+    fn add_message(
+        &mut self,
+        prefix: &str,
+        descriptor: &DescriptorProto,
+        indexes: Vec<i64>,
+    ) -> Result<()> {
+        let name = format!("{}.{}", prefix, descriptor.name());
+
+        self.add_enums(&name, &descriptor.enum_type);
+        for (index, nested) in descriptor.nested_type.iter().enumerate() {
+            let mut indexes = indexes.clone();
+            indexes.push(index as i64);
+            self.add_message(&name, nested, indexes)?;
+        }
+
+        let fields = descriptor
+            .field
+            .iter()
+            .map(|field| {
+                let kind = match field.r#type() {
+                    Type::Double => Kind::Double,
+                    Type::Float => Kind::Float,
+                    Type::Int32 => Kind::Int32,
+                    Type::Int64 => Kind::Int64,
+                    Type::Uint32 => Kind::Uint32,
+                    Type::Uint64 => Kind::Uint64,
+                    Type::Sint32 => Kind::Sint32,
+                    Type::Sint64 => Kind::Sint64,
+                    Type::Fixed32 => Kind::Fixed32,
+                    Type::Fixed64 => Kind::Fixed64,
+                    Type::Sfixed32 => Kind::Sfixed32,
+                    Type::Sfixed64 => Kind::Sfixed64,
+                    Type::Bool => Kind::Bool,
+                    Type::String => Kind::String,
+                    Type::Bytes => Kind::Bytes,
+                    Type::Enum => Kind::Enum(field.type_name().to_owned()),
+                    Type::Message => Kind::Message(field.type_name().to_owned()),
+                    Type::Group => {
+                        return Err(ProtobufError::UnsupportedGroup {
+                            name: field.name().to_owned(),
+                        })
+                    }
+                };
+                Ok(Field {
+                    name: field.name().to_owned(),
+                    number: field.number() as u32,
+                    kind,
+                    repeated: field.label() == Label::Repeated,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        let map_entry = descriptor
+            .options
+            .as_ref()
+            .map_or(false, |options| options.map_entry());
+        self.messages.insert(
+            name,
+            MessageDescriptor {
+                fields,
+                map_entry,
+                indexes,
+            },
+        );
+        Ok(())
+    }
+
+    fn encode_message(
+        &self,
+        name: &str,
+        map: &BTreeMap<String, Value>,
+        path: &str,
+        buf: &mut Vec<u8>,
+    ) -> Result<()> {
+        let descriptor = self
+            .messages
+            .get(name)
+            .ok_or_else(|| ProtobufError::UnknownMessage { name: name.into() })?;
+
+        for field in &descriptor.fields {
+            let path = join_path(path, &field.name);
+            match (map.get(&field.name), &field.kind) {
+                (None, _) | (Some(Value::Null), _) => (),
+                (Some(Value::Map(entries)), Kind::Message(entry))
+                    if field.repeated && self.is_map_entry(entry) =>
+                {
+                    for (key, value) in entries {
+                        let mut pair = BTreeMap::new();
+                        pair.insert("key".to_owned(), Value::from(key.as_str()));
+                        pair.insert("value".to_owned(), value.clone());
+                        let mut nested = Vec::new();
+                        self.encode_message(entry, &pair, &join_path(&path, key), &mut nested)?;
+                        write_key(field.number, 2, buf);
+                        write_len_delimited(&nested, buf);
+                    }
+                }
+                (Some(Value::Array(items)), _) if field.repeated => {
+                    for (i, item) in items.iter().enumerate() {
+                        let path = format!("{}[{}]", path, i);
+                        self.encode_field(field, item, &path, buf)?;
+                    }
+                }
+                (Some(_), _) if field.repeated => return Err(self.mismatch(&path, &field.kind)),
+                (Some(value), _) => self.encode_field(field, value, &path, buf)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn encode_field(
+        &self,
+        field: &Field,
+        value: &Value,
+        path: &str,
+        buf: &mut Vec<u8>,
+    ) -> Result<()> {
+        let number = field.number;
+        match (&field.kind, value) {
+            (Kind::Double, Value::Float(f)) => write_fixed64(number, f.to_bits(), buf),
+            (Kind::Double, Value::Integer(i)) => write_fixed64(number, (*i as f64).to_bits(), buf),
+            (Kind::Float, Value::Float(f)) => write_fixed32(number, (*f as f32).to_bits(), buf),
+            (Kind::Float, Value::Integer(i)) => write_fixed32(number, (*i as f32).to_bits(), buf),
+            (Kind::Int32, Value::Integer(i)) if *i as i32 as i64 == *i => {
+                write_varint_field(number, *i as u64, buf)
+            }
+            (Kind::Int64, Value::Integer(i)) => write_varint_field(number, *i as u64, buf),
+            (Kind::Uint32, Value::Integer(i)) if *i >= 0 && *i <= u32::MAX as i64 => {
+                write_varint_field(number, *i as u64, buf)
+            }
+            (Kind::Uint64, Value::Integer(i)) if *i >= 0 => {
+                write_varint_field(number, *i as u64, buf)
+            }
+            (Kind::Sint32, Value::Integer(i)) if *i as i32 as i64 == *i => {
+                let i = *i as i32;
+                write_varint_field(number, ((i << 1) ^ (i >> 31)) as u32 as u64, buf)
+            }
+            (Kind::Sint64, Value::Integer(i)) => {
+                let i = *i;
+                write_varint_field(number, ((i << 1) ^ (i >> 63)) as u64, buf)
+            }
+            (Kind::Fixed32, Value::Integer(i)) if *i >= 0 && *i <= u32::MAX as i64 => {
+                write_fixed32(number, *i as u32, buf)
+            }
+            (Kind::Fixed64, Value::Integer(i)) if *i >= 0 => write_fixed64(number, *i as u64, buf),
+            (Kind::Sfixed32, Value::Integer(i)) if *i as i32 as i64 == *i => {
+                write_fixed32(number, *i as i32 as u32, buf)
+            }
+            (Kind::Sfixed64, Value::Integer(i)) => write_fixed64(number, *i as u64, buf),
+            (Kind::Bool, Value::Boolean(b)) => write_varint_field(number, *b as u64, buf),
+            (Kind::String, Value::Bytes(bytes)) if std::str::from_utf8(bytes).is_ok() => {
+                write_key(number, 2, buf);
+                write_len_delimited(bytes, buf);
+            }
+            (Kind::String, Value::Timestamp(_)) | (Kind::Bytes, Value::Bytes(_)) => {
+                write_key(number, 2, buf);
+                write_len_delimited(&value.as_bytes(), buf);
+            }
+            (Kind::Enum(_), Value::Integer(i)) if *i as i32 as i64 == *i => {
+                write_varint_field(number, *i as u64, buf)
+            }
+            (Kind::Enum(name), Value::Bytes(bytes)) => {
+                let symbol_number = self
+                    .enums
+                    .get(name)
+                    .and_then(|values| {
+                        values
+                            .iter()
+                            .find(|(symbol, _)| symbol.as_bytes() == &bytes[..])
+                    })
+                    .map(|(_, n)| *n)
+                    .ok_or_else(|| self.mismatch(path, &field.kind))?;
+                write_varint_field(number, symbol_number as i64 as u64, buf)
+            }
+            (Kind::Message(name), Value::Timestamp(ts)) if name == TIMESTAMP => {
+                let mut nested = Vec::new();
+                write_varint_field(1, ts.timestamp() as u64, &mut nested);
+                write_varint_field(2, ts.timestamp_subsec_nanos() as u64, &mut nested);
+                write_key(number, 2, buf);
+                write_len_delimited(&nested, buf);
+            }
+            (Kind::Message(name), Value::Map(map)) => {
+                let mut nested = Vec::new();
+                self.encode_message(name, map, path, &mut nested)?;
+                write_key(number, 2, buf);
+                write_len_delimited(&nested, buf);
+            }
+            _ => return Err(self.mismatch(path, &field.kind)),
+        }
+        Ok(())
+    }
+
+    fn decode_message(&self, name: &str, mut bytes: &[u8], depth: usize) -> Result<Value> {
+        if depth > MAX_DEPTH {
+            return Err(ProtobufError::TooDeep);
+        }
+
+        let descriptor = self
+            .messages
+            .get(name)
+            .ok_or_else(|| ProtobufError::UnknownMessage { name: name.into() })?;
+        let mut map = BTreeMap::new();
+
+        while !bytes.is_empty() {
+            let key = read_varint(&mut bytes).ok_or(ProtobufError::Malformed)?;
+            let (number, wire_type) = ((key >> 3) as u32, (key & 7) as u8);
+            let field = match descriptor
+                .fields
+                .iter()
+                .find(|field| field.number == number)
+            {
+                Some(field) => field,
+                None => {
+                    skip_field(wire_type, &mut bytes)?;
+                    continue;
+                }
+            };
+
+            if field.repeated && wire_type == 2 && is_packable(&field.kind) {
+                let mut packed = read_len_delimited(&mut bytes)?;
+                while !packed.is_empty() {
+                    let value = self.decode_field(
+                        &field.kind,
+                        packed_wire_type(&field.kind),
+                        &mut packed,
+                        depth,
+                    )?;
+                    push_repeated(&mut map, &field.name, value);
+                }
+                continue;
+            }
+
+            let value = self.decode_field(&field.kind, wire_type, &mut bytes, depth)?;
+            match &field.kind {
+                Kind::Message(entry) if field.repeated && self.is_map_entry(entry) => {
+                    if let Value::Map(mut pair) = value {
+                        let key = pair
+                            .remove("key")
+                            .map_or_else(String::new, |key| key.to_string_lossy());
+                        let value = pair.remove("value").unwrap_or(Value::Null);
+                        let entries = map
+                            .entry(field.name.clone())
+                            .or_insert_with(|| Value::Map(BTreeMap::new()));
+                        if let Value::Map(entries) = entries {
+                            entries.insert(key, value);
+                        }
+                    }
+                }
+                _ if field.repeated => push_repeated(&mut map, &field.name, value),
+                _ => {
+                    map.insert(field.name.clone(), value);
+                }
+            }
+        }
+
+        Ok(Value::Map(map))
+    }
+
+    fn decode_field(
+        &self,
+        kind: &Kind,
+        wire_type: u8,
+        bytes: &mut &[u8],
+        depth: usize,
+    ) -> Result<Value> {
+        if wire_type != packed_wire_type(kind) {
+            return Err(ProtobufError::Malformed);
+        }
+
+        let varint = |bytes: &mut &[u8]| read_varint(bytes).ok_or(ProtobufError::Malformed);
+        Ok(match kind {
+            Kind::Double => Value::Float(f64::from_bits(read_fixed64(bytes)?)),
+            Kind::Float => Value::Float(f32::from_bits(read_fixed32(bytes)?) as f64),
+            Kind::Int32 => Value::Integer(varint(bytes)? as i32 as i64),
+            Kind::Int64 | Kind::Uint64 => Value::Integer(varint(bytes)? as i64),
+            Kind::Uint32 => Value::Integer(varint(bytes)? as u32 as i64),
+            Kind::Sint32 => {
+                let n = varint(bytes)? as u32;
+                Value::Integer(((n >> 1) as i32 ^ -((n & 1) as i32)) as i64)
+            }
+            Kind::Sint64 => {
+                let n = varint(bytes)?;
+                Value::Integer((n >> 1) as i64 ^ -((n & 1) as i64))
+            }
+            Kind::Fixed32 => Value::Integer(read_fixed32(bytes)? as i64),
+            Kind::Fixed64 => Value::Integer(read_fixed64(bytes)? as i64),
+            Kind::Sfixed32 => Value::Integer(read_fixed32(bytes)? as i32 as i64),
+            Kind::Sfixed64 => Value::Integer(read_fixed64(bytes)? as i64),
+            Kind::Bool => Value::Boolean(varint(bytes)? != 0),
+            Kind::String | Kind::Bytes => Value::Bytes(Bytes::from(read_len_delimited(bytes)?)),
+            Kind::Enum(name) => {
+                let number = varint(bytes)? as i32;
+                match self
+                    .enums
+                    .get(name)
+                    .and_then(|values| values.iter().find(|(_, n)| *n == number))
+                {
+                    Some((symbol, _)) => Value::Bytes(Bytes::from(symbol.as_str())),
+                    // Unknown values are kept as numbers, as in proto3.
+                    None => Value::Integer(number as i64),
+                }
+            }
+            Kind::Message(name) if name == TIMESTAMP => {
+                let mut nested = read_len_delimited(bytes)?;
+                let (mut seconds, mut nanos) = (0, 0);
+                while !nested.is_empty() {
+                    let key = varint(&mut nested)?;
+                    match key {
+                        0x08 => seconds = varint(&mut nested)? as i64,
+                        0x10 => nanos = varint(&mut nested)? as u32,
+                        key => skip_field((key & 7) as u8, &mut nested)?,
+                    }
+                }
+                Value::Timestamp(
+                    Utc.timestamp_opt(seconds, nanos)
+                        .single()
+                        .ok_or(ProtobufError::Malformed)?,
+                )
+            }
+            Kind::Message(name) => {
+                self.decode_message(name, read_len_delimited(bytes)?, depth + 1)?
+            }
+        })
+    }
+
+    fn is_map_entry(&self, name: &str) -> bool {
+        self.messages
+            .get(name)
+            .map_or(false, |descriptor| descriptor.map_entry)
+    }
+
+    fn mismatch(&self, path: &str, kind: &Kind) -> ProtobufError {
+        let expected = match kind {
+            Kind::Enum(name) | Kind::Message(name) => name.trim_start_matches('.').to_owned(),
+            kind => format!("{:?}", kind).to_lowercase(),
+        };
+        ProtobufError::Mismatch {
+            path: path.into(),
+            expected,
+        }
+    }
+}
+
+fn is_packable(kind: &Kind) -> bool {
+    !matches!(kind, Kind::String | Kind::Bytes | Kind::Message(_))
+}
+
+fn packed_wire_type(kind: &Kind) -> u8 {
+    match kind {
+        Kind::Double | Kind::Fixed64 | Kind::Sfixed64 => 1,
+        Kind::Float | Kind::Fixed32 | Kind::Sfixed32 => 5,
+        Kind::String | Kind::Bytes | Kind::Message(_) => 2,
+        _ => 0,
+    }
+}
+
+fn push_repeated(map: &mut BTreeMap<String, Value>, name: &str, value: Value) {
+    let items = map
+        .entry(name.to_owned())
+        .or_insert_with(|| Value::Array(Vec::new()));
+    if let Value::Array(items) = items {
+        items.push(value);
+    }
+}
+
+fn skip_field(wire_type: u8, bytes: &mut &[u8]) -> Result<()> {
+    match wire_type {
+        0 => read_varint(bytes).map(drop).ok_or(ProtobufError::Malformed),
+        1 => read_fixed64(bytes).map(drop),
+        2 => read_len_delimited(bytes).map(drop),
+        5 => read_fixed32(bytes).map(drop),
+        _ => Err(ProtobufError::Malformed),
+    }
+}
+
+fn join_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.into()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+fn write_key(number: u32, wire_type: u8, buf: &mut Vec<u8>) {
+    write_varint(((number as u64) << 3) | wire_type as u64, buf);
+}
+
+fn write_varint_field(number: u32, value: u64, buf: &mut Vec<u8>) {
+    write_key(number, 0, buf);
+    write_varint(value, buf);
+}
+
+fn write_fixed32(number: u32, value: u32, buf: &mut Vec<u8>) {
+    write_key(number, 5, buf);
+    buf.extend(&value.to_le_bytes());
+}
+
+fn write_fixed64(number: u32, value: u64, buf: &mut Vec<u8>) {
+    write_key(number, 1, buf);
+    buf.extend(&value.to_le_bytes());
+}
+
+fn write_len_delimited(bytes: &[u8], buf: &mut Vec<u8>) {
+    write_varint(bytes.len() as u64, buf);
+    buf.extend_from_slice(bytes);
+}
+
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+    if bytes.len() < len {
+        return Err(ProtobufError::Malformed);
+    }
+    let (head, tail) = bytes.split_at(len);
+    *bytes = tail;
+    Ok(head)
+}
+
+fn read_fixed32(bytes: &mut &[u8]) -> Result<u32> {
+    let mut raw = [0; 4];
+    raw.copy_from_slice(take(bytes, 4)?);
+    Ok(u32::from_le_bytes(raw))
+}
+
+fn read_fixed64(bytes: &mut &[u8]) -> Result<u64> {
+    let mut raw = [0; 8];
+    raw.copy_from_slice(take(bytes, 8)?);
+    Ok(u64::from_le_bytes(raw))
+}
+
+fn read_len_delimited<'a>(bytes: &mut &'a [u8]) -> Result<&'a [u8]> {
+    let len = read_varint(bytes).ok_or(ProtobufError::Malformed)?;
+    take(bytes, len as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost_types::{
+        field_descriptor_proto, DescriptorProto, FieldDescriptorProto, FileDescriptorProto,
+        MessageOptions,
+    };
+
+    fn field(name: &str, number: i32, kind: Type, type_name: Option<&str>) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            name: Some(name.into()),
+            number: Some(number),
+            label: Some(field_descriptor_proto::Label::Optional as i32),
+            r#type: Some(kind as i32),
+            type_name: type_name.map(Into::into),
+            ..Default::default()
+        }
+    }
+
+    fn repeated(mut field: FieldDescriptorProto) -> FieldDescriptorProto {
+        field.label = Some(field_descriptor_proto::Label::Repeated as i32);
+        field
+    }
+
+    fn descriptor_set() -> Vec<u8> {
+        let tags_entry = DescriptorProto {
+            name: Some("TagsEntry".into()),
+            field: vec![
+                field("key", 1, Type::String, None),
+                field("value", 2, Type::String, None),
+            ],
+            options: Some(MessageOptions {
+                map_entry: Some(true),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let log = DescriptorProto {
+            name: Some("Log".into()),
+            field: vec![
+                field("message", 1, Type::String, None),
+                field("count", 2, Type::Sint64, None),
+                repeated(field("codes", 3, Type::Int32, None)),
+                field("level", 4, Type::Enum, Some(".test.Level")),
+                repeated(field("tags", 5, Type::Message, Some(".test.Log.TagsEntry"))),
+                field("parent", 6, Type::Message, Some(".test.Log")),
+            ],
+            nested_type: vec![tags_entry],
+            ..Default::default()
+        };
+        let file = FileDescriptorProto {
+            name: Some("test.proto".into()),
+            package: Some("test".into()),
+            message_type: vec![
+                DescriptorProto {
+                    name: Some("Other".into()),
+                    ..Default::default()
+                },
+                log,
+            ],
+            enum_type: vec![EnumDescriptorProto {
+                name: Some("Level".into()),
+                value: vec![
+                    prost_types::EnumValueDescriptorProto {
+                        name: Some("INFO".into()),
+                        number: Some(0),
+                        ..Default::default()
+                    },
+                    prost_types::EnumValueDescriptorProto {
+                        name: Some("ERROR".into()),
+                        number: Some(1),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let mut buf = Vec::new();
+        FileDescriptorSet { file: vec![file] }
+            .encode(&mut buf)
+            .unwrap();
+        buf
+    }
+
+    fn log() -> BTreeMap<String, Value> {
+        let mut tags = BTreeMap::new();
+        tags.insert("region".into(), Value::from("eu"));
+        let mut log = BTreeMap::new();
+        log.insert("message".into(), Value::from("hello"));
+        log.insert("count".into(), Value::Integer(-3));
+        log.insert(
+            "codes".into(),
+            Value::Array(vec![Value::Integer(1), Value::Integer(300)]),
+        );
+        log.insert("level".into(), Value::from("ERROR"));
+        log.insert("tags".into(), Value::Map(tags));
+        log
+    }
+
+    #[test]
+    fn protobuf_round_trip() {
+        let schema = ProtobufSchema::parse(&descriptor_set(), "test.Log").unwrap();
+        assert_eq!(schema.indexes(), &[1]);
+
+        let mut buf = Vec::new();
+        schema.encode(&Value::Map(log()), &mut buf).unwrap();
+        assert_eq!(&buf[..7], &[0x0a, 0x05, b'h', b'e', b'l', b'l', b'o']);
+        assert_eq!(&buf[7..9], &[0x10, 0x05]);
+
+        assert_eq!(schema.decode(&buf).unwrap(), Value::Map(log()));
+    }
+
+    #[test]
+    fn protobuf_decodes_packed_fields() {
+        let schema = ProtobufSchema::parse(&descriptor_set(), ".test.Log").unwrap();
+        let bytes = [0x1a, 0x03, 0x01, 0xac, 0x02];
+
+        let mut expected = BTreeMap::new();
+        expected.insert(
+            "codes".into(),
+            Value::Array(vec![Value::Integer(1), Value::Integer(300)]),
+        );
+        assert_eq!(schema.decode(&bytes).unwrap(), Value::Map(expected));
+    }
+
+    #[test]
+    fn protobuf_rejects_mismatched_values() {
+        let schema = ProtobufSchema::parse(&descriptor_set(), "test.Log").unwrap();
+        let mut log = log();
+        log.insert("level".into(), Value::from("DEBUG"));
+
+        let error = schema.encode(&Value::Map(log), &mut Vec::new());
+        assert!(matches!(error, Err(ProtobufError::Mismatch { .. })));
+    }
+
+    #[test]
+    fn protobuf_rejects_deeply_nested_messages() {
+        let schema = ProtobufSchema::parse(&descriptor_set(), "test.Log").unwrap();
+        let nested = |depth: usize| {
+            (0..depth).fold(Vec::new(), |inner, _| {
+                let mut buf = Vec::new();
+                write_key(6, 2, &mut buf);
+                write_len_delimited(&inner, &mut buf);
+                buf
+            })
+        };
+
+        assert!(schema.decode(&nested(10)).is_ok());
+        assert!(matches!(
+            schema.decode(&nested(100_000)),
+            Err(ProtobufError::TooDeep)
+        ));
+    }
+
+    #[test]
+    fn protobuf_rejects_unknown_message_types() {
+        let error = ProtobufSchema::parse(&descriptor_set(), "test.Missing");
+        assert!(matches!(error, Err(ProtobufError::UnknownMessage { .. })));
+    }
+}
@@ -0,0 +1,124 @@
+//! A client for the Confluent schema registry API.
+
+use crate::{
+    dns::Resolver,
+    hyper::body_to_bytes,
+    sinks::util::http::{Auth, HttpClient},
+    tls::{TlsOptions, TlsSettings},
+};
+use http::{Request, StatusCode, Uri};
+use hyper::Body;
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Snafu};
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct SchemaRegistryConfig {
+    pub url: String,
+    /// The subject whose schema is used to encode events.
+    pub subject: Option<String>,
+    /// The version of the subject, the latest one if unspecified.
+    pub version: Option<u32>,
+    pub auth: Option<Auth>,
+    pub tls: Option<TlsOptions>,
+}
+
+#[derive(Debug, Snafu)]
+enum RegistryError {
+    #[snafu(display("invalid schema registry URL {:?}: {}", url, source))]
+    InvalidUrl {
+        url: String,
+        source: http::uri::InvalidUri,
+    },
+    #[snafu(display("schema registry returned {} for {}", status, uri))]
+    UnexpectedStatus { status: StatusCode, uri: Uri },
+    #[snafu(display("invalid schema registry response: {}", source))]
+    InvalidResponse { source: serde_json::Error },
+    #[snafu(display("schema registry response has no schema id"))]
+    MissingId,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SchemaResponse {
+    id: Option<u32>,
+    schema: String,
+    /// Absent for Avro schemas.
+    schema_type: Option<String>,
+}
+
+/// A schema as stored in the registry.
+#[derive(Debug)]
+pub struct RegisteredSchema {
+    pub id: u32,
+    /// `AVRO`, `PROTOBUF` or `JSON`.
+    pub schema_type: String,
+    pub schema: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct SchemaRegistry {
+    client: HttpClient,
+    url: String,
+    auth: Option<Auth>,
+}
+
+impl SchemaRegistry {
+    pub fn new(config: &SchemaRegistryConfig) -> crate::Result<Self> {
+        let tls = TlsSettings::from_options(&config.tls)?;
+        Ok(Self {
+            client: HttpClient::new(Resolver, tls)?,
+            url: config.url.trim_end_matches('/').to_owned(),
+            auth: config.auth.clone(),
+        })
+    }
+
+    /// Fetches a version of a subject, or its latest version.
+    pub async fn subject_version(
+        &self,
+        subject: &str,
+        version: Option<u32>,
+    ) -> crate::Result<RegisteredSchema> {
+        let version = version.map_or_else(|| "latest".to_owned(), |version| version.to_string());
+        let path = format!("/subjects/{}/versions/{}", subject, version);
+        let response = self.get(&path).await?;
+        let id = response.id.ok_or(RegistryError::MissingId)?;
+        Ok(RegisteredSchema::new(id, response))
+    }
+
+    pub async fn schema_by_id(&self, id: u32) -> crate::Result<RegisteredSchema> {
+        let response = self.get(&format!("/schemas/ids/{}", id)).await?;
+        Ok(RegisteredSchema::new(id, response))
+    }
+
+    async fn get(&self, path: &str) -> crate::Result<SchemaResponse> {
+        let url = format!("{}{}", self.url, path);
+        let uri = url.parse::<Uri>().context(InvalidUrl { url })?;
+
+        let mut request = Request::get(uri.clone())
+            .header("Accept", "application/vnd.schemaregistry.v1+json")
+            .body(Body::empty())?;
+        if let Some(auth) = &self.auth {
+            auth.apply(&mut request);
+        }
+
+        let response = self.client.clone().send(request).await?;
+        let status = response.status();
+        if status != StatusCode::OK {
+            return Err(RegistryError::UnexpectedStatus { status, uri }.into());
+        }
+
+        let body = body_to_bytes(response.into_body()).await?;
+        Ok(serde_json::from_slice(&body).context(InvalidResponse)?)
+    }
+}
+
+impl RegisteredSchema {
+    fn new(id: u32, response: SchemaResponse) -> Self {
+        Self {
+            id,
+            schema_type: response.schema_type.unwrap_or_else(|| "AVRO".to_owned()),
+            schema: response.schema,
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! End-to-end acknowledgement of events.
+//!
+//! A source that must only commit its position once its events are delivered
+//! attaches a `BatchNotifier` to them. Copies of an event made by fanout share
+//! the notifier. The topology keeps the finalizers of the events given to a
+//! sink until the sink acknowledges them, with the status of the write or
+//! request they went out in. Once every copy is finalized, the source receives
+//! the combined status through the `BatchStatusReceiver`.
+//!
+//! Events dropped by a filtering transform count as delivered, while events
+//! that a sink never acknowledges, for instance because it was shut down,
+//! count as failed.
+
+use crate::shutdown::ShutdownSignal;
+use futures::{
+    channel::{mpsc, oneshot},
+    compat::Future01CompatExt,
+    FutureExt, StreamExt,
+};
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BatchStatus {
+    /// Every copy of the events was accepted by its sink.
+    Delivered,
+    /// At least one copy of the events was permanently rejected.
+    Failed,
+}
+
+/// Reports the status of a batch to its source once dropped by the last
+/// event referencing it.
+pub struct BatchNotifier {
+    failed: AtomicBool,
+    tx: Mutex<Option<oneshot::Sender<BatchStatus>>>,
+}
+
+impl BatchNotifier {
+    pub fn new_with_receiver() -> (Arc<Self>, BatchStatusReceiver) {
+        let (tx, rx) = oneshot::channel();
+        let notifier = Self {
+            failed: AtomicBool::new(false),
+            tx: Mutex::new(Some(tx)),
+        };
+        (Arc::new(notifier), BatchStatusReceiver(rx))
+    }
+
+    fn update_status(&self, status: BatchStatus) {
+        if status == BatchStatus::Failed {
+            self.failed.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Drop for BatchNotifier {
+    fn drop(&mut self) {
+        let status = if self.failed.load(Ordering::Relaxed) {
+            BatchStatus::Failed
+        } else {
+            BatchStatus::Delivered
+        };
+        if let Some(tx) = self.tx.lock().unwrap().take() {
+            // The source may have shut down and stopped waiting.
+            let _ = tx.send(status);
+        }
+    }
+}
+
+impl fmt::Debug for BatchNotifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BatchNotifier")
+            .field("failed", &self.failed.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+/// Resolves to the status of a batch once all its events are finalized.
+#[derive(Debug)]
+pub struct BatchStatusReceiver(oneshot::Receiver<BatchStatus>);
+
+impl BatchStatusReceiver {
+    /// Returns the status if the batch is already finalized.
+    pub fn try_recv(&mut self) -> Option<BatchStatus> {
+        match self.0.try_recv() {
+            Ok(status) => status,
+            Err(oneshot::Canceled) => Some(BatchStatus::Failed),
+        }
+    }
+}
+
+impl Future for BatchStatusReceiver {
+    type Output = BatchStatus;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.0
+            .poll_unpin(cx)
+            .map(|result| result.unwrap_or(BatchStatus::Failed))
+    }
+}
+
+/// The batches an event belongs to.
+///
+/// Finalizers are not part of the event data, so they are ignored when
+/// comparing events.
+#[derive(Clone, Debug, Default)]
+pub struct EventFinalizers(Vec<Arc<BatchNotifier>>);
+
+impl EventFinalizers {
+    pub fn new(notifier: Arc<BatchNotifier>) -> Self {
+        Self(vec![notifier])
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn add(&mut self, notifier: Arc<BatchNotifier>) {
+        self.0.push(notifier);
+    }
+
+    pub fn merge(&mut self, other: Self) {
+        self.0.extend(other.0);
+    }
+
+    /// Records the status of the request the events were sent in and
+    /// releases them.
+    pub fn update_status(self, status: BatchStatus) {
+        for notifier in &self.0 {
+            notifier.update_status(status);
+        }
+    }
+}
+
+impl PartialEq for EventFinalizers {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+/// Hands entries to `on_status` in the order they were added, each once
+/// its batch is finalized, so sources can commit positions in order even
+/// when sinks complete requests out of order.
+pub struct OrderedFinalizer<T> {
+    tx: mpsc::UnboundedSender<(BatchStatusReceiver, T)>,
+}
+
+impl<T: Send + 'static> OrderedFinalizer<T> {
+    /// Must be called within a Tokio runtime. The finalizer stops on
+    /// `shutdown`, leaving the entries still pending unacknowledged, or once
+    /// it and all the batches added to it are dropped.
+    pub fn new(
+        shutdown: ShutdownSignal,
+        mut on_status: impl FnMut(BatchStatus, T) + Send + 'static,
+    ) -> Self {
+        let (tx, rx) = mpsc::unbounded::<(BatchStatusReceiver, T)>();
+        let statuses = rx
+            .then(|(receiver, entry)| receiver.map(move |status| (status, entry)))
+            .take_until(shutdown.compat());
+        tokio::spawn(async move {
+            let mut statuses = Box::pin(statuses);
+            while let Some((status, entry)) = statuses.next().await {
+                on_status(status, entry);
+            }
+        });
+        Self { tx }
+    }
+
+    pub fn add(&self, entry: T, receiver: BatchStatusReceiver) {
+        // The task only stops early on shutdown, when nothing is committed
+        // anymore.
+        let _ = self.tx.unbounded_send((receiver, entry));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notifies_once_all_finalizers_are_dropped() {
+        let (notifier, mut receiver) = BatchNotifier::new_with_receiver();
+        let finalizers = EventFinalizers::new(notifier);
+        let copy = finalizers.clone();
+        assert_eq!(receiver.try_recv(), None);
+
+        finalizers.update_status(BatchStatus::Delivered);
+        assert_eq!(receiver.try_recv(), None);
+
+        drop(copy);
+        assert_eq!(receiver.try_recv(), Some(BatchStatus::Delivered));
+    }
+
+    #[test]
+    fn failed_status_is_sticky() {
+        let (notifier, mut receiver) = BatchNotifier::new_with_receiver();
+        let finalizers = EventFinalizers::new(notifier);
+        let copy = finalizers.clone();
+
+        finalizers.update_status(BatchStatus::Failed);
+        copy.update_status(BatchStatus::Delivered);
+        assert_eq!(receiver.try_recv(), Some(BatchStatus::Failed));
+    }
+}
@@ -15,6 +15,7 @@ pub fn merge_log_event(current: &mut LogEvent, mut incoming: LogEvent, merge_fie
             Some(current_val) => merge_value(current_val, incoming_val),
         }
     }
+    current.merge_finalizers(incoming.take_finalizers());
 }
 
 /// Merges `incoming` value into `current` value.
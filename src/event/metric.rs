@@ -1,3 +1,4 @@
+use super::sketch::DDSketch;
 use chrono::{DateTime, Utc};
 use derive_is_enum_variant::is_enum_variant;
 use serde::{Deserialize, Serialize};
@@ -6,6 +7,8 @@ use std::collections::{BTreeMap, BTreeSet};
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Metric {
     pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
     pub timestamp: Option<DateTime<Utc>>,
     pub tags: Option<BTreeMap<String, String>>,
     pub kind: MetricKind,
@@ -48,12 +51,33 @@ pub enum MetricValue {
         count: u32,
         sum: f64,
     },
+    /// A bounded size summary of a distribution's samples.
+    Sketch(DDSketch),
+}
+
+impl MetricValue {
+    /// Converts a distribution into a sketch with the given accuracy, other
+    /// values are left as they are.
+    pub fn into_sketch(self, relative_accuracy: f64) -> Self {
+        match self {
+            MetricValue::Distribution {
+                values,
+                sample_rates,
+            } => MetricValue::Sketch(DDSketch::from_distribution(
+                &values,
+                &sample_rates,
+                relative_accuracy,
+            )),
+            value => value,
+        }
+    }
 }
 
 impl Metric {
     pub fn to_absolute(&self) -> Self {
         Self {
             name: self.name.clone(),
+            namespace: self.namespace.clone(),
             timestamp: self.timestamp,
             tags: self.tags.clone(),
             kind: MetricKind::Absolute,
@@ -61,6 +85,21 @@ impl Metric {
         }
     }
 
+    /// Moves the part of the name before the first `separator` into the
+    /// namespace, unless the metric already has one.
+    pub fn split_namespace(mut self, separator: char) -> Self {
+        if self.namespace.is_none() {
+            if let Some(i) = self.name.find(separator) {
+                if i > 0 && i + separator.len_utf8() < self.name.len() {
+                    let name = self.name.split_off(i + separator.len_utf8());
+                    self.name.truncate(i);
+                    self.namespace = Some(std::mem::replace(&mut self.name, name));
+                }
+            }
+        }
+        self
+    }
+
     pub fn add(&mut self, other: &Self) {
         if other.kind.is_absolute() {
             return;
@@ -113,6 +152,20 @@ impl Metric {
                     *sum += sum2;
                 }
             }
+            (MetricValue::Sketch(ref mut sketch), MetricValue::Sketch(sketch2)) => {
+                sketch.merge(sketch2);
+            }
+            (
+                MetricValue::Sketch(ref mut sketch),
+                MetricValue::Distribution {
+                    values,
+                    sample_rates,
+                },
+            ) => {
+                for (value, rate) in values.iter().zip(sample_rates.iter()) {
+                    sketch.insert_n(*value, u64::from(*rate));
+                }
+            }
             _ => {}
         }
     }
@@ -159,6 +212,9 @@ impl Metric {
                 *count = 0;
                 *sum = 0.0;
             }
+            MetricValue::Sketch(ref mut sketch) => {
+                sketch.clear();
+            }
         }
     }
 }
@@ -182,10 +238,41 @@ mod test {
         .collect()
     }
 
+    #[test]
+    fn split_namespace() {
+        let metric = |name: &str| Metric {
+            name: name.into(),
+            namespace: None,
+            timestamp: None,
+            tags: None,
+            kind: MetricKind::Incremental,
+            value: MetricValue::Counter { value: 1.0 },
+        };
+
+        let split = metric("app.requests.total").split_namespace('.');
+        assert_eq!(split.namespace, Some("app".into()));
+        assert_eq!(split.name, "requests.total");
+
+        let unsplit = metric("requests").split_namespace('.');
+        assert_eq!(unsplit.namespace, None);
+        assert_eq!(unsplit.name, "requests");
+
+        let leading = metric("_requests").split_namespace('_');
+        assert_eq!(leading.namespace, None);
+        assert_eq!(leading.name, "_requests");
+
+        let mut existing = metric("app_requests");
+        existing.namespace = Some("other".into());
+        let existing = existing.split_namespace('_');
+        assert_eq!(existing.namespace, Some("other".into()));
+        assert_eq!(existing.name, "app_requests");
+    }
+
     #[test]
     fn merge_counters() {
         let mut counter = Metric {
             name: "counter".into(),
+            namespace: None,
             timestamp: None,
             tags: None,
             kind: MetricKind::Incremental,
@@ -194,6 +281,7 @@ mod test {
 
         let delta = Metric {
             name: "counter".into(),
+            namespace: None,
             timestamp: Some(ts()),
             tags: Some(tags()),
             kind: MetricKind::Incremental,
@@ -205,6 +293,7 @@ mod test {
             counter,
             Metric {
                 name: "counter".into(),
+                namespace: None,
                 timestamp: None,
                 tags: None,
                 kind: MetricKind::Incremental,
@@ -217,6 +306,7 @@ mod test {
     fn merge_gauges() {
         let mut gauge = Metric {
             name: "gauge".into(),
+            namespace: None,
             timestamp: None,
             tags: None,
             kind: MetricKind::Incremental,
@@ -225,6 +315,7 @@ mod test {
 
         let delta = Metric {
             name: "gauge".into(),
+            namespace: None,
             timestamp: Some(ts()),
             tags: Some(tags()),
             kind: MetricKind::Incremental,
@@ -236,6 +327,7 @@ mod test {
             gauge,
             Metric {
                 name: "gauge".into(),
+                namespace: None,
                 timestamp: None,
                 tags: None,
                 kind: MetricKind::Incremental,
@@ -248,6 +340,7 @@ mod test {
     fn merge_sets() {
         let mut set = Metric {
             name: "set".into(),
+            namespace: None,
             timestamp: None,
             tags: None,
             kind: MetricKind::Incremental,
@@ -258,6 +351,7 @@ mod test {
 
         let delta = Metric {
             name: "set".into(),
+            namespace: None,
             timestamp: Some(ts()),
             tags: Some(tags()),
             kind: MetricKind::Incremental,
@@ -271,6 +365,7 @@ mod test {
             set,
             Metric {
                 name: "set".into(),
+                namespace: None,
                 timestamp: None,
                 tags: None,
                 kind: MetricKind::Incremental,
@@ -285,6 +380,7 @@ mod test {
     fn merge_histograms() {
         let mut dist = Metric {
             name: "hist".into(),
+            namespace: None,
             timestamp: None,
             tags: None,
             kind: MetricKind::Incremental,
@@ -296,6 +392,7 @@ mod test {
 
         let delta = Metric {
             name: "hist".into(),
+            namespace: None,
             timestamp: Some(ts()),
             tags: Some(tags()),
             kind: MetricKind::Incremental,
@@ -310,6 +407,7 @@ mod test {
             dist,
             Metric {
                 name: "hist".into(),
+                namespace: None,
                 timestamp: None,
                 tags: None,
                 kind: MetricKind::Incremental,
@@ -320,4 +418,40 @@ mod test {
             }
         )
     }
+
+    #[test]
+    fn merge_sketches() {
+        let mut sketch = Metric {
+            name: "sketch".into(),
+            namespace: None,
+            timestamp: None,
+            tags: None,
+            kind: MetricKind::Incremental,
+            value: MetricValue::Distribution {
+                values: vec![1.0, 2.0],
+                sample_rates: vec![1, 1],
+            }
+            .into_sketch(0.01),
+        };
+
+        let delta = Metric {
+            name: "sketch".into(),
+            namespace: None,
+            timestamp: Some(ts()),
+            tags: Some(tags()),
+            kind: MetricKind::Incremental,
+            value: MetricValue::Distribution {
+                values: vec![3.0],
+                sample_rates: vec![2],
+            },
+        };
+
+        sketch.add(&delta);
+
+        let mut expected = DDSketch::new(0.01);
+        expected.insert(1.0);
+        expected.insert(2.0);
+        expected.insert_n(3.0, 2);
+        assert_eq!(sketch.value, MetricValue::Sketch(expected));
+    }
 }
@@ -7,15 +7,20 @@ use metric::{MetricKind, MetricValue};
 use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize, Serializer};
 use serde_json::Value as JsonValue;
-use std::{collections::BTreeMap, iter::FromIterator};
+use sketch::{is_valid_relative_accuracy, DDSketch, MIN_RELATIVE_ACCURACY};
+use snafu::Snafu;
+use std::{collections::BTreeMap, convert::TryFrom, iter::FromIterator, sync::Arc};
 use string_cache::DefaultAtom as Atom;
 
 pub mod discriminant;
+pub mod finalization;
 pub mod merge;
 pub mod merge_state;
 pub mod metric;
+pub mod sketch;
 mod util;
 
+pub use finalization::{BatchNotifier, BatchStatus, BatchStatusReceiver, EventFinalizers};
 pub use metric::Metric;
 pub(crate) use util::log::PathComponent;
 pub(crate) use util::log::PathIter;
@@ -45,6 +50,7 @@ pub enum Event {
 #[derive(PartialEq, Debug, Clone, Default)]
 pub struct LogEvent {
     fields: BTreeMap<String, Value>,
+    finalizers: EventFinalizers,
 }
 
 impl Event {
@@ -73,6 +79,21 @@ impl Event {
         }
     }
 
+    /// Ties the delivery of this event to the batch of `notifier`. Only log
+    /// events are acknowledged, so this is a no-op for metrics.
+    pub fn add_batch_notifier(&mut self, notifier: Arc<BatchNotifier>) {
+        if let Event::Log(log) = self {
+            log.add_batch_notifier(notifier);
+        }
+    }
+
+    pub fn take_finalizers(&mut self) -> EventFinalizers {
+        match self {
+            Event::Log(log) => log.take_finalizers(),
+            Event::Metric(_) => EventFinalizers::default(),
+        }
+    }
+
     pub fn as_metric(&self) -> &Metric {
         match self {
             Event::Metric(metric) => metric,
@@ -163,6 +184,20 @@ impl LogEvent {
     pub fn is_empty(&self) -> bool {
         self.fields.is_empty()
     }
+
+    pub fn add_batch_notifier(&mut self, notifier: Arc<BatchNotifier>) {
+        self.finalizers.add(notifier);
+    }
+
+    /// Detaches the finalizers, for instance once the event is encoded and
+    /// only the request it was sent in can report its status.
+    pub fn take_finalizers(&mut self) -> EventFinalizers {
+        std::mem::replace(&mut self.finalizers, EventFinalizers::default())
+    }
+
+    pub fn merge_finalizers(&mut self, finalizers: EventFinalizers) {
+        self.finalizers.merge(finalizers);
+    }
 }
 
 impl std::ops::Index<&Atom> for LogEvent {
@@ -495,11 +530,26 @@ fn decode_value(input: proto::Value) -> Option<Value> {
     }
 }
 
-impl From<proto::EventWrapper> for Event {
-    fn from(proto: proto::EventWrapper) -> Self {
+/// Reasons a decoded protobuf message does not hold a valid event.
+#[derive(Debug, Snafu)]
+pub enum ProtoError {
+    #[snafu(display(
+        "Sketch relative accuracy {} is not within [{}, 1)",
+        relative_accuracy,
+        MIN_RELATIVE_ACCURACY
+    ))]
+    InvalidRelativeAccuracy { relative_accuracy: f64 },
+    #[snafu(display("Sketch counts overflow"))]
+    SketchCountOverflow,
+}
+
+impl TryFrom<proto::EventWrapper> for Event {
+    type Error = ProtoError;
+
+    fn try_from(proto: proto::EventWrapper) -> Result<Self, Self::Error> {
         let event = proto.event.unwrap();
 
-        match event {
+        Ok(match event {
             EventProto::Log(proto) => {
                 let fields = proto
                     .fields
@@ -507,7 +557,10 @@ impl From<proto::EventWrapper> for Event {
                     .filter_map(|(k, v)| decode_value(v).map(|value| (k, value)))
                     .collect::<BTreeMap<_, _>>();
 
-                Event::Log(LogEvent { fields })
+                Event::Log(LogEvent {
+                    fields,
+                    finalizers: EventFinalizers::default(),
+                })
             }
             EventProto::Metric(proto) => {
                 let kind = match proto.kind() {
@@ -527,6 +580,12 @@ impl From<proto::EventWrapper> for Event {
                     None
                 };
 
+                let namespace = if !proto.namespace.is_empty() {
+                    Some(proto.namespace)
+                } else {
+                    None
+                };
+
                 let value = match proto.value.unwrap() {
                     MetricProto::Counter(counter) => MetricValue::Counter {
                         value: counter.value,
@@ -551,17 +610,43 @@ impl From<proto::EventWrapper> for Event {
                         count: summary.count,
                         sum: summary.sum,
                     },
+                    MetricProto::Sketch(sketch) => {
+                        let relative_accuracy = sketch.relative_accuracy;
+                        if !is_valid_relative_accuracy(relative_accuracy) {
+                            return Err(ProtoError::InvalidRelativeAccuracy { relative_accuracy });
+                        }
+                        let sketch = DDSketch::from_parts(
+                            relative_accuracy,
+                            sketch
+                                .positive_keys
+                                .into_iter()
+                                .zip(sketch.positive_counts)
+                                .collect(),
+                            sketch
+                                .negative_keys
+                                .into_iter()
+                                .zip(sketch.negative_counts)
+                                .collect(),
+                            sketch.zero_count,
+                            sketch.sum,
+                            sketch.min,
+                            sketch.max,
+                        )
+                        .ok_or(ProtoError::SketchCountOverflow)?;
+                        MetricValue::Sketch(sketch)
+                    }
                 };
 
                 Event::Metric(Metric {
                     name,
+                    namespace,
                     timestamp,
                     tags,
                     kind,
                     value,
                 })
             }
-        }
+        })
     }
 }
 
@@ -601,7 +686,7 @@ fn encode_array(items: Vec<Value>) -> proto::ValueArray {
 impl From<Event> for proto::EventWrapper {
     fn from(event: Event) -> Self {
         match event {
-            Event::Log(LogEvent { fields }) => {
+            Event::Log(LogEvent { fields, .. }) => {
                 let fields = fields
                     .into_iter()
                     .map(|(k, v)| (k, encode_value(v)))
@@ -613,6 +698,7 @@ impl From<Event> for proto::EventWrapper {
             }
             Event::Metric(Metric {
                 name,
+                namespace,
                 timestamp,
                 tags,
                 kind,
@@ -668,10 +754,22 @@ impl From<Event> for proto::EventWrapper {
                         count,
                         sum,
                     }),
+                    MetricValue::Sketch(sketch) => MetricProto::Sketch(proto::Sketch {
+                        relative_accuracy: sketch.relative_accuracy(),
+                        positive_keys: sketch.positive_bins().keys().cloned().collect(),
+                        positive_counts: sketch.positive_bins().values().cloned().collect(),
+                        negative_keys: sketch.negative_bins().keys().cloned().collect(),
+                        negative_counts: sketch.negative_bins().values().cloned().collect(),
+                        zero_count: sketch.zero_count(),
+                        sum: sketch.sum(),
+                        min: sketch.min().unwrap_or_default(),
+                        max: sketch.max().unwrap_or_default(),
+                    }),
                 };
 
                 let event = EventProto::Metric(proto::Metric {
                     name,
+                    namespace: namespace.unwrap_or_default(),
                     timestamp,
                     tags,
                     kind,
@@ -698,9 +796,7 @@ impl From<Event> for Vec<u8> {
 
 impl From<Bytes> for Event {
     fn from(message: Bytes) -> Self {
-        let mut event = Event::Log(LogEvent {
-            fields: BTreeMap::new(),
-        });
+        let mut event = Event::new_empty_log();
 
         event
             .as_mut_log()
@@ -715,9 +811,7 @@ impl From<Bytes> for Event {
 
 impl From<bytes05::Bytes> for Event {
     fn from(message: bytes05::Bytes) -> Self {
-        let mut event = Event::Log(LogEvent {
-            fields: BTreeMap::new(),
-        });
+        let mut event = Event::new_empty_log();
 
         event
             .as_mut_log()
@@ -857,4 +951,70 @@ timestamp_key = "timestamp"
 "#;
         let _ = toml::from_str::<LogSchema>(toml).unwrap();
     }
+
+    #[test]
+    fn metric_proto_roundtrip() {
+        use super::{
+            metric::{Metric, MetricKind, MetricValue},
+            proto,
+        };
+        use std::convert::TryFrom;
+
+        let metric = Event::Metric(Metric {
+            name: "latency".into(),
+            namespace: Some("app".into()),
+            timestamp: None,
+            tags: None,
+            kind: MetricKind::Incremental,
+            value: MetricValue::Distribution {
+                values: vec![-1.0, 0.0, 2.5, 100.0],
+                sample_rates: vec![1, 2, 3, 1],
+            }
+            .into_sketch(0.01),
+        });
+
+        let roundtripped = Event::try_from(proto::EventWrapper::from(metric.clone())).unwrap();
+        assert_eq!(roundtripped, metric);
+    }
+    #[test]
+    fn metric_proto_rejects_invalid_sketch_accuracy() {
+        use super::proto::{self, event_wrapper, metric};
+        use std::convert::TryFrom;
+
+        for relative_accuracy in &[0.0, 1e-5, 1.0, -0.5, std::f64::NAN] {
+            let proto = proto::EventWrapper {
+                event: Some(event_wrapper::Event::Metric(proto::Metric {
+                    name: "latency".into(),
+                    value: Some(metric::Value::Sketch(proto::Sketch {
+                        relative_accuracy: *relative_accuracy,
+                        ..Default::default()
+                    })),
+                    ..Default::default()
+                })),
+            };
+
+            assert!(Event::try_from(proto).is_err());
+        }
+    }
+
+    #[test]
+    fn metric_proto_rejects_overflowing_sketch_counts() {
+        use super::proto::{self, event_wrapper, metric};
+        use std::convert::TryFrom;
+
+        let proto = proto::EventWrapper {
+            event: Some(event_wrapper::Event::Metric(proto::Metric {
+                name: "latency".into(),
+                value: Some(metric::Value::Sketch(proto::Sketch {
+                    relative_accuracy: 0.01,
+                    positive_keys: vec![1, 2],
+                    positive_counts: vec![u64::max_value(), 1],
+                    ..Default::default()
+                })),
+                ..Default::default()
+            })),
+        };
+
+        assert!(Event::try_from(proto).is_err());
+    }
 }
@@ -0,0 +1,457 @@
+//! A mergeable quantile sketch with relative accuracy guarantees, following
+//! [DDSketch](https://arxiv.org/abs/1908.10693).
+//!
+//! Samples are counted in logarithmically sized bins, so any quantile is
+//! estimated within `relative_accuracy` of its true value while memory only
+//! grows with the logarithm of the range of the observed values. The number
+//! of bins is additionally capped by collapsing the bins closest to zero.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Relative accuracy used when none is configured.
+pub const DEFAULT_RELATIVE_ACCURACY: f64 = 0.01;
+
+/// Finest relative accuracy accepted, finer sketches would need more bins
+/// than they are allowed to keep.
+pub const MIN_RELATIVE_ACCURACY: f64 = 1e-4;
+
+/// Maximum number of bins kept per sign before the lowest ones are collapsed.
+pub const MAX_BINS: usize = 2048;
+
+/// Values closer to zero than this are counted as zero.
+const MIN_INDEXABLE_VALUE: f64 = 1e-9;
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct DDSketch {
+    relative_accuracy: f64,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    positive: BTreeMap<i32, u64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    negative: BTreeMap<i32, u64>,
+    zero_count: u64,
+    count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+impl DDSketch {
+    /// Creates an empty sketch. `relative_accuracy` must be within
+    /// `[MIN_RELATIVE_ACCURACY, 1)`.
+    pub fn new(relative_accuracy: f64) -> Self {
+        assert!(
+            is_valid_relative_accuracy(relative_accuracy),
+            "Relative accuracy must be within [{}, 1)",
+            MIN_RELATIVE_ACCURACY
+        );
+        Self {
+            relative_accuracy,
+            positive: BTreeMap::new(),
+            negative: BTreeMap::new(),
+            zero_count: 0,
+            count: 0,
+            sum: 0.0,
+            min: 0.0,
+            max: 0.0,
+        }
+    }
+
+    /// Builds a sketch from the samples of a distribution, each counted
+    /// `sample_rate` times.
+    pub fn from_distribution(values: &[f64], sample_rates: &[u32], relative_accuracy: f64) -> Self {
+        let mut sketch = Self::new(relative_accuracy);
+        for (value, rate) in values.iter().zip(sample_rates.iter()) {
+            sketch.insert_n(*value, u64::from(*rate));
+        }
+        sketch
+    }
+
+    /// Rebuilds a sketch from its parts, as produced by the accessors.
+    /// Returns `None` if the accuracy is out of range or the total count
+    /// doesn't fit in a `u64`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_parts(
+        relative_accuracy: f64,
+        mut positive: BTreeMap<i32, u64>,
+        mut negative: BTreeMap<i32, u64>,
+        zero_count: u64,
+        sum: f64,
+        min: f64,
+        max: f64,
+    ) -> Option<Self> {
+        if !is_valid_relative_accuracy(relative_accuracy) {
+            return None;
+        }
+
+        let count = positive
+            .values()
+            .chain(negative.values())
+            .try_fold(zero_count, |count, n| count.checked_add(*n))?;
+        collapse(&mut positive);
+        collapse(&mut negative);
+
+        Some(Self {
+            relative_accuracy,
+            positive,
+            negative,
+            zero_count,
+            count,
+            sum,
+            min,
+            max,
+        })
+    }
+
+    pub fn relative_accuracy(&self) -> f64 {
+        self.relative_accuracy
+    }
+
+    pub fn positive_bins(&self) -> &BTreeMap<i32, u64> {
+        &self.positive
+    }
+
+    pub fn negative_bins(&self) -> &BTreeMap<i32, u64> {
+        &self.negative
+    }
+
+    pub fn zero_count(&self) -> u64 {
+        self.zero_count
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    /// The smallest sample, or `None` if the sketch is empty.
+    pub fn min(&self) -> Option<f64> {
+        if self.count > 0 {
+            Some(self.min)
+        } else {
+            None
+        }
+    }
+
+    /// The largest sample, or `None` if the sketch is empty.
+    pub fn max(&self) -> Option<f64> {
+        if self.count > 0 {
+            Some(self.max)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn insert(&mut self, value: f64) {
+        self.insert_n(value, 1);
+    }
+
+    pub fn insert_n(&mut self, value: f64, n: u64) {
+        if n == 0 || !value.is_finite() {
+            return;
+        }
+
+        if value > MIN_INDEXABLE_VALUE {
+            let key = self.key(value);
+            add_to_bin(&mut self.positive, key, n);
+            collapse(&mut self.positive);
+        } else if value < -MIN_INDEXABLE_VALUE {
+            let key = self.key(-value);
+            add_to_bin(&mut self.negative, key, n);
+            collapse(&mut self.negative);
+        } else {
+            self.zero_count = self.zero_count.saturating_add(n);
+        }
+
+        self.update_bounds(value, value);
+        self.count = self.count.saturating_add(n);
+        self.sum += value * n as f64;
+    }
+
+    /// Adds the samples of `other` to this sketch. Sketches with a different
+    /// accuracy are merged by re-inserting their bins, at the cost of the
+    /// accuracy of both.
+    pub fn merge(&mut self, other: &Self) {
+        if other.is_empty() {
+            return;
+        }
+        if !is_valid_relative_accuracy(other.relative_accuracy) {
+            // Its bins can't be mapped back to values, such sketches can only
+            // come from malformed input.
+            error!(
+                message = "Dropping sketch with invalid relative accuracy.",
+                relative_accuracy = other.relative_accuracy,
+                rate_limit_secs = 30
+            );
+            return;
+        }
+
+        let same_accuracy = self.relative_accuracy == other.relative_accuracy;
+        let rekey = |key: i32| {
+            if same_accuracy {
+                key
+            } else {
+                self.key(other.value(key))
+            }
+        };
+        let positive = other.positive.iter().map(|(key, n)| (rekey(*key), *n));
+        let negative = other.negative.iter().map(|(key, n)| (rekey(*key), *n));
+        let (positive, negative) = (positive.collect::<Vec<_>>(), negative.collect::<Vec<_>>());
+
+        for (key, n) in positive {
+            add_to_bin(&mut self.positive, key, n);
+        }
+        for (key, n) in negative {
+            add_to_bin(&mut self.negative, key, n);
+        }
+        collapse(&mut self.positive);
+        collapse(&mut self.negative);
+        self.zero_count = self.zero_count.saturating_add(other.zero_count);
+
+        self.update_bounds(other.min, other.max);
+        self.count = self.count.saturating_add(other.count);
+        self.sum += other.sum;
+    }
+
+    /// Estimates the value at quantile `q` within `[0, 1]`, or `None` if the
+    /// sketch is empty.
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        if self.is_empty() || q < 0.0 || q > 1.0 {
+            return None;
+        }
+        if q == 0.0 {
+            return Some(self.min);
+        }
+        if q == 1.0 {
+            return Some(self.max);
+        }
+
+        let rank = q * (self.count - 1) as f64;
+        let mut seen = 0u64;
+
+        // Negative values are ordered by decreasing magnitude.
+        for (key, n) in self.negative.iter().rev() {
+            seen += n;
+            if seen as f64 > rank {
+                return Some(self.clamp(-self.value(*key)));
+            }
+        }
+
+        seen += self.zero_count;
+        if seen as f64 > rank {
+            return Some(self.clamp(0.0));
+        }
+
+        for (key, n) in &self.positive {
+            seen += n;
+            if seen as f64 > rank {
+                return Some(self.clamp(self.value(*key)));
+            }
+        }
+
+        Some(self.max)
+    }
+
+    /// Removes all samples, keeping the accuracy.
+    pub fn clear(&mut self) {
+        *self = Self::new(self.relative_accuracy);
+    }
+
+    /// Must be called before the count is updated.
+    fn update_bounds(&mut self, min: f64, max: f64) {
+        if self.is_empty() {
+            self.min = min;
+            self.max = max;
+        } else {
+            self.min = self.min.min(min);
+            self.max = self.max.max(max);
+        }
+    }
+
+    fn gamma_ln(&self) -> f64 {
+        ((1.0 + self.relative_accuracy) / (1.0 - self.relative_accuracy)).ln()
+    }
+
+    fn key(&self, value: f64) -> i32 {
+        let key = (value.ln() / self.gamma_ln()).ceil();
+        // Casting a float that doesn't fit is undefined behaviour, the bounds
+        // are only reached by sketches with an invalid accuracy.
+        if key.is_nan() {
+            0
+        } else {
+            key.max(f64::from(i32::MIN)).min(f64::from(i32::MAX)) as i32
+        }
+    }
+
+    /// The value representing bin `key`, within the relative accuracy of
+    /// every value counted in it.
+    fn value(&self, key: i32) -> f64 {
+        let gamma = self.gamma_ln().exp();
+        2.0 * (self.gamma_ln() * f64::from(key)).exp() / (gamma + 1.0)
+    }
+
+    fn clamp(&self, value: f64) -> f64 {
+        value.max(self.min).min(self.max)
+    }
+}
+
+/// Whether sketches can be built with `relative_accuracy`.
+pub fn is_valid_relative_accuracy(relative_accuracy: f64) -> bool {
+    relative_accuracy >= MIN_RELATIVE_ACCURACY && relative_accuracy < 1.0
+}
+
+fn add_to_bin(bins: &mut BTreeMap<i32, u64>, key: i32, n: u64) {
+    let count = bins.entry(key).or_insert(0);
+    *count = count.saturating_add(n);
+}
+
+/// Folds the bins closest to zero into their neighbour until at most
+/// `MAX_BINS` are left.
+fn collapse(bins: &mut BTreeMap<i32, u64>) {
+    while bins.len() > MAX_BINS {
+        let lowest = *bins.keys().next().unwrap();
+        let n = bins.remove(&lowest).unwrap();
+        let next = *bins.keys().next().unwrap();
+        add_to_bin(bins, next, n);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_relative(actual: f64, expected: f64, accuracy: f64) {
+        assert!(
+            (actual - expected).abs() <= expected.abs() * accuracy,
+            "{} is not within {} of {}",
+            actual,
+            accuracy,
+            expected
+        );
+    }
+
+    #[test]
+    fn sketch_quantiles_within_accuracy() {
+        let mut sketch = DDSketch::new(0.01);
+        for i in 1..=1000 {
+            sketch.insert(f64::from(i));
+        }
+
+        assert_eq!(sketch.count(), 1000);
+        assert_eq!(sketch.sum(), 500_500.0);
+        assert_eq!(sketch.min(), Some(1.0));
+        assert_eq!(sketch.max(), Some(1000.0));
+        assert_eq!(sketch.quantile(0.0), Some(1.0));
+        assert_eq!(sketch.quantile(1.0), Some(1000.0));
+        assert_relative(sketch.quantile(0.5).unwrap(), 500.0, 0.01);
+        assert_relative(sketch.quantile(0.99).unwrap(), 990.0, 0.01);
+    }
+
+    #[test]
+    fn sketch_handles_negative_and_zero_values() {
+        let mut sketch = DDSketch::new(0.01);
+        for value in &[-10.0, -1.0, 0.0, 1.0, 10.0] {
+            sketch.insert(*value);
+        }
+
+        assert_eq!(sketch.quantile(0.0), Some(-10.0));
+        assert_relative(sketch.quantile(0.25).unwrap(), -1.0, 0.01);
+        assert_eq!(sketch.quantile(0.5), Some(0.0));
+        assert_relative(sketch.quantile(0.75).unwrap(), 1.0, 0.01);
+        assert_eq!(sketch.quantile(1.0), Some(10.0));
+    }
+
+    #[test]
+    fn sketch_merge_matches_single_sketch() {
+        let mut single = DDSketch::new(0.02);
+        let mut left = DDSketch::new(0.02);
+        let mut right = DDSketch::new(0.02);
+        for i in 1..=100 {
+            single.insert(f64::from(i));
+            if i % 2 == 0 {
+                left.insert(f64::from(i));
+            } else {
+                right.insert(f64::from(i));
+            }
+        }
+
+        left.merge(&right);
+        assert_eq!(left, single);
+    }
+
+    #[test]
+    fn sketch_from_distribution_uses_sample_rates() {
+        let sketch = DDSketch::from_distribution(&[1.0, 100.0], &[3, 1], 0.01);
+
+        assert_eq!(sketch.count(), 4);
+        assert_eq!(sketch.sum(), 103.0);
+        assert_relative(sketch.quantile(0.5).unwrap(), 1.0, 0.01);
+    }
+
+    #[test]
+    fn sketch_bins_are_bounded() {
+        let mut sketch = DDSketch::new(0.01);
+        let mut value = 1e-6;
+        while value < 1e300 {
+            sketch.insert(value);
+            value *= 1.05;
+        }
+
+        assert!(sketch.positive_bins().len() <= MAX_BINS);
+        assert_eq!(sketch.quantile(1.0), sketch.max());
+    }
+
+    #[test]
+    fn sketch_from_parts_is_validated() {
+        let bins = |entries: &[(i32, u64)]| entries.iter().cloned().collect::<BTreeMap<_, _>>();
+
+        assert!(DDSketch::from_parts(1e-5, bins(&[]), bins(&[]), 1, 0.0, 0.0, 0.0).is_none());
+        assert!(DDSketch::from_parts(
+            0.01,
+            bins(&[(1, u64::max_value())]),
+            bins(&[(1, 1)]),
+            0,
+            0.0,
+            1.0,
+            1.0
+        )
+        .is_none());
+
+        let many = (0..MAX_BINS as i32 * 2)
+            .map(|key| (key, 1))
+            .collect::<Vec<_>>();
+        let sketch = DDSketch::from_parts(0.01, bins(&many), bins(&[]), 0, 0.0, 1.0, 2.0).unwrap();
+        assert_eq!(sketch.count(), MAX_BINS as u64 * 2);
+        assert_eq!(sketch.positive_bins().len(), MAX_BINS);
+    }
+
+    #[test]
+    fn sketch_keys_are_clamped() {
+        // Only reachable through deserialization, which doesn't validate.
+        let mut sketch: DDSketch = serde_json::from_str(
+            r#"{"relative_accuracy":1e-300,"zero_count":0,"count":0,"sum":0,"min":0,"max":0}"#,
+        )
+        .unwrap();
+        sketch.insert(1e300);
+        sketch.insert(1e-8);
+
+        assert_eq!(
+            sketch.positive_bins().keys().cloned().collect::<Vec<_>>(),
+            vec![i32::MIN, i32::MAX]
+        );
+    }
+
+    #[test]
+    fn empty_sketch_has_no_quantiles() {
+        let sketch = DDSketch::new(0.01);
+        assert_eq!(sketch.quantile(0.5), None);
+        assert_eq!(sketch.min(), None);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::topology::config::{
-    component::ExampleError, GlobalOptions, SinkDescription, SourceDescription,
+    component::ExampleError, format, Format, GlobalOptions, SinkDescription, SourceDescription,
     TransformDescription,
 };
 use colored::*;
@@ -16,6 +16,10 @@ pub struct Opts {
     #[structopt(short, long)]
     fragment: bool,
 
+    /// Format of the generated config.
+    #[structopt(long, default_value = "toml", possible_values = &["toml", "yaml", "json"])]
+    format: Format,
+
     /// Generate expression, e.g. 'stdin/json_parser,add_fields/console'
     ///
     /// Three comma-separated lists of sources, transforms and sinks, divided by
@@ -66,12 +70,28 @@ pub struct TransformOuter {
 
 #[derive(Serialize, Default)]
 pub struct Config {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub sources: Option<IndexMap<String, Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub transforms: Option<IndexMap<String, TransformOuter>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub sinks: Option<IndexMap<String, SinkOuter>>,
 }
 
-fn generate_example(include_globals: bool, expression: &str) -> Result<String, Vec<String>> {
+/// A whole generated config, used for formats that are serialized in one pass.
+#[derive(Serialize)]
+struct Document<'a> {
+    #[serde(flatten)]
+    global: Option<&'a GlobalOptions>,
+    #[serde(flatten)]
+    config: &'a Config,
+}
+
+fn generate_example(
+    include_globals: bool,
+    expression: &str,
+    format: Format,
+) -> Result<String, Vec<String>> {
     let components: Vec<Vec<_>> = expression
         .split(|c| c == '|' || c == '/')
         .map(|s| {
@@ -267,6 +287,21 @@ fn generate_example(include_globals: bool, expression: &str) -> Result<String, V
         return Err(errs);
     }
 
+    // TOML output is rendered section by section so that each component
+    // group stays readable; other formats serialize the whole document.
+    if format != Format::Toml {
+        let document = Document {
+            global: if include_globals {
+                Some(&globals)
+            } else {
+                None
+            },
+            config: &config,
+        };
+        return format::serialize(&document, format)
+            .map_err(|e| vec![format!("failed to marshal config: {}", e)]);
+    }
+
     let mut builder = if include_globals {
         match toml::to_string(&globals) {
             Ok(s) => s,
@@ -317,7 +352,7 @@ fn generate_example(include_globals: bool, expression: &str) -> Result<String, V
 }
 
 pub fn cmd(opts: &Opts) -> exitcode::ExitCode {
-    match generate_example(!opts.fragment, &opts.expression) {
+    match generate_example(!opts.fragment, &opts.expression, opts.format) {
         Ok(s) => {
             println!("{}", s);
             exitcode::OK
@@ -336,7 +371,7 @@ mod tests {
     #[test]
     fn generate_basic() {
         assert_eq!(
-            generate_example(true, "stdin/json_parser/console"),
+            generate_example(true, "stdin/json_parser/console", Format::Toml),
             Ok(r#"data_dir = "/var/lib/vector/"
 
 [sources.source0]
@@ -363,7 +398,7 @@ when_full = "block"
         );
 
         assert_eq!(
-            generate_example(true, "stdin|json_parser|console"),
+            generate_example(true, "stdin|json_parser|console", Format::Toml),
             Ok(r#"data_dir = "/var/lib/vector/"
 
 [sources.source0]
@@ -390,7 +425,7 @@ when_full = "block"
         );
 
         assert_eq!(
-            generate_example(true, "stdin//console"),
+            generate_example(true, "stdin//console", Format::Toml),
             Ok(r#"data_dir = "/var/lib/vector/"
 
 [sources.source0]
@@ -411,7 +446,7 @@ when_full = "block"
         );
 
         assert_eq!(
-            generate_example(true, "//console"),
+            generate_example(true, "//console", Format::Toml),
             Ok(r#"data_dir = "/var/lib/vector/"
 
 [sinks.sink0]
@@ -428,7 +463,7 @@ when_full = "block"
         );
 
         assert_eq!(
-            generate_example(true, "/add_fields,json_parser,remove_fields"),
+            generate_example(true, "/add_fields,json_parser,remove_fields", Format::Toml),
             Ok(r#"data_dir = "/var/lib/vector/"
 
 [transforms.transform0]
@@ -449,7 +484,7 @@ type = "remove_fields"
         );
 
         assert_eq!(
-            generate_example(false, "/add_fields,json_parser,remove_fields"),
+            generate_example(false, "/add_fields,json_parser,remove_fields", Format::Toml),
             Ok(r#"
 [transforms.transform0]
 inputs = []
@@ -468,4 +503,31 @@ type = "remove_fields"
             .to_string())
         );
     }
+
+    #[test]
+    fn generate_json_and_yaml() {
+        let expected = serde_json::json!({
+            "data_dir": "/var/lib/vector/",
+            "sinks": {
+                "sink0": {
+                    "healthcheck": true,
+                    "inputs": ["TODO"],
+                    "type": "console",
+                    "buffer": {
+                        "type": "memory",
+                        "max_events": 500,
+                        "when_full": "block"
+                    }
+                }
+            }
+        });
+
+        let json = generate_example(true, "//console", Format::Json).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, expected);
+
+        let yaml = generate_example(true, "//console", Format::Yaml).unwrap();
+        let parsed: serde_json::Value = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(parsed, expected);
+    }
 }
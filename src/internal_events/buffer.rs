@@ -0,0 +1,20 @@
+use super::InternalEvent;
+use metrics::gauge;
+
+#[derive(Debug)]
+pub struct BufferEventsChanged<'a> {
+    pub component_name: &'a str,
+    pub component_kind: &'static str,
+    pub component_type: &'a str,
+    pub count: usize,
+}
+
+impl InternalEvent for BufferEventsChanged<'_> {
+    fn emit_metrics(&self) {
+        gauge!("buffer_events", self.count as i64,
+            "component_name" => self.component_name.to_owned(),
+            "component_kind" => self.component_kind,
+            "component_type" => self.component_type.to_owned(),
+        );
+    }
+}
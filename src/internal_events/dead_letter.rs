@@ -0,0 +1,26 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub struct DeadLetterEventsSent<'a> {
+    pub count: usize,
+    pub reason: &'a str,
+}
+
+impl<'a> InternalEvent for DeadLetterEventsSent<'a> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "sending undeliverable events to dead-letter output.",
+            count = %self.count,
+            reason = %self.reason,
+            rate_limit_secs = 30,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!(
+            "dead_letter_events", self.count as u64,
+            "component_kind" => "sink",
+        );
+    }
+}
@@ -0,0 +1,52 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub struct DecoderFramingFailed<'a> {
+    pub source_type: &'static str,
+    pub error: &'a std::io::Error,
+}
+
+impl InternalEvent for DecoderFramingFailed<'_> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Failed framing bytes.",
+            source_type = self.source_type,
+            error = %self.error,
+            rate_limit_secs = 30
+        )
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processing_errors", 1,
+            "component_kind" => "source",
+            "component_type" => self.source_type,
+            "error_type" => "framing_failed",
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct DecoderDeserializeFailed<'a> {
+    pub source_type: &'static str,
+    pub error: &'a crate::Error,
+}
+
+impl InternalEvent for DecoderDeserializeFailed<'_> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "Failed deserializing frame.",
+            source_type = self.source_type,
+            error = %self.error,
+            rate_limit_secs = 30
+        )
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processing_errors", 1,
+            "component_kind" => "source",
+            "component_type" => self.source_type,
+            "error_type" => "deserialize_failed",
+        );
+    }
+}
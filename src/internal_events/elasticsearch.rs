@@ -43,9 +43,10 @@ impl InternalEvent for ElasticSearchMissingKeys {
 
     fn emit_metrics(&self) {
         counter!(
-            "missing_keys", 1,
+            "processing_errors", 1,
             "component_kind" => "sink",
             "component_type" => "elasticsearch",
+            "error_type" => "missing_keys",
         );
     }
 }
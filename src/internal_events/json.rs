@@ -32,7 +32,7 @@ impl InternalEvent for JsonFailedParse<'_> {
     }
 
     fn emit_metrics(&self) {
-        counter!("processing_error", 1,
+        counter!("processing_errors", 1,
             "component_kind" => "transform",
             "component_type" => "json_parser",
             "error_type" => "failed_parse",
@@ -43,6 +43,7 @@ impl InternalEvent for LuaScriptError {
         counter!("processing_errors", 1,
             "component_kind" => "transform",
             "component_type" => "lua",
+            "error_type" => "script_failed",
         );
     }
 }
@@ -1,17 +1,24 @@
 mod add_fields;
 mod aws_kinesis_streams;
 mod blackhole;
+mod buffer;
+mod dead_letter;
+mod decoder;
 mod elasticsearch;
 mod file;
 mod json;
 #[cfg(feature = "transforms-lua")]
 mod lua;
+#[cfg(any(feature = "sources-opentelemetry", feature = "sinks-opentelemetry"))]
+mod opentelemetry;
 #[cfg(feature = "sources-prometheus")]
 mod prometheus;
 mod regex;
 mod splunk_hec;
 mod syslog;
 mod tcp;
+#[cfg(feature = "transforms-throttle")]
+mod throttle;
 mod udp;
 mod unix;
 mod vector;
@@ -21,17 +28,24 @@ mod wasm;
 pub use self::add_fields::*;
 pub use self::aws_kinesis_streams::*;
 pub use self::blackhole::*;
+pub use self::buffer::*;
+pub use self::dead_letter::*;
+pub use self::decoder::*;
 pub use self::elasticsearch::*;
 pub use self::file::*;
 pub use self::json::*;
 #[cfg(feature = "transforms-lua")]
 pub use self::lua::*;
+#[cfg(any(feature = "sources-opentelemetry", feature = "sinks-opentelemetry"))]
+pub use self::opentelemetry::*;
 #[cfg(feature = "sources-prometheus")]
 pub use self::prometheus::*;
 pub use self::regex::*;
 pub use self::splunk_hec::*;
 pub use self::syslog::*;
 pub use self::tcp::*;
+#[cfg(feature = "transforms-throttle")]
+pub use self::throttle::*;
 pub use self::udp::*;
 pub use self::unix::*;
 pub use self::vector::*;
@@ -0,0 +1,79 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub struct OpenTelemetryEventsReceived {
+    pub count: usize,
+    pub byte_size: usize,
+}
+
+impl InternalEvent for OpenTelemetryEventsReceived {
+    fn emit_logs(&self) {
+        trace!(message = "received events.", count = %self.count);
+    }
+
+    fn emit_metrics(&self) {
+        counter!(
+            "events_processed", self.count as u64,
+            "component_kind" => "source",
+            "component_type" => "opentelemetry",
+        );
+        counter!(
+            "bytes_processed", self.byte_size as u64,
+            "component_kind" => "source",
+            "component_type" => "opentelemetry",
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct OpenTelemetryDecodeFailed<'a> {
+    pub error: &'a crate::Error,
+}
+
+impl<'a> InternalEvent for OpenTelemetryDecodeFailed<'a> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "failed to decode OTLP request.",
+            error = %self.error,
+            rate_limit_secs = 30,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!(
+            "processing_errors", 1,
+            "component_kind" => "source",
+            "component_type" => "opentelemetry",
+            "error_type" => "decode_failed",
+        );
+    }
+}
+
+/// A metric type that has no counterpart on the other side of the conversion.
+#[derive(Debug)]
+pub struct OpenTelemetryMetricUnsupported<'a> {
+    pub component_kind: &'static str,
+    pub name: &'a str,
+    pub metric_type: &'static str,
+}
+
+impl<'a> InternalEvent for OpenTelemetryMetricUnsupported<'a> {
+    fn emit_logs(&self) {
+        warn!(
+            message = "dropping metric of unsupported type.",
+            name = %self.name,
+            metric_type = %self.metric_type,
+            rate_limit_secs = 30,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!(
+            "processing_errors", 1,
+            "component_kind" => self.component_kind,
+            "component_type" => "opentelemetry",
+            "error_type" => "unsupported_metric",
+        );
+    }
+}
@@ -26,21 +26,23 @@ impl InternalEvent for PrometheusParseError {
     }
 
     fn emit_metrics(&self) {
-        counter!("parse_errors", 1,
+        counter!("processing_errors", 1,
             "component_kind" => "source",
             "component_type" => "prometheus",
+            "error_type" => "parse_failed",
         );
     }
 }
 
 #[derive(Debug)]
-pub struct PrometheusHttpError {
-    pub error: hyper::Error,
+pub struct PrometheusHttpError<'a> {
+    pub url: &'a str,
+    pub error: crate::Error,
 }
 
-impl InternalEvent for PrometheusHttpError {
+impl<'a> InternalEvent for PrometheusHttpError<'a> {
     fn emit_logs(&self) {
-        error!(message = "http request processing error", error = %self.error);
+        error!(message = "http request processing error", url = %self.url, error = %self.error);
     }
 
     fn emit_metrics(&self) {
@@ -50,3 +52,49 @@ impl InternalEvent for PrometheusHttpError {
         );
     }
 }
+
+#[derive(Debug)]
+pub struct PrometheusRemoteWriteReceived {
+    pub count: usize,
+    pub byte_size: usize,
+}
+
+impl InternalEvent for PrometheusRemoteWriteReceived {
+    fn emit_logs(&self) {
+        trace!(message = "received remote write request.", count = %self.count);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("events_processed", self.count as u64,
+            "component_kind" => "source",
+            "component_type" => "prometheus_remote_write",
+        );
+        counter!("bytes_processed", self.byte_size as u64,
+            "component_kind" => "source",
+            "component_type" => "prometheus_remote_write",
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct PrometheusRemoteWriteParseError<'a> {
+    pub error: &'a crate::Error,
+}
+
+impl<'a> InternalEvent for PrometheusRemoteWriteParseError<'a> {
+    fn emit_logs(&self) {
+        error!(
+            message = "could not decode remote write request.",
+            error = %self.error,
+            rate_limit_secs = 30,
+        );
+    }
+
+    fn emit_metrics(&self) {
+        counter!("processing_errors", 1,
+            "component_kind" => "source",
+            "component_type" => "prometheus_remote_write",
+            "error_type" => "parse_failed",
+        );
+    }
+}
@@ -30,7 +30,7 @@ impl InternalEvent for RegexFailedMatch<'_> {
     }
 
     fn emit_metrics(&self) {
-        counter!("processing_error", 1,
+        counter!("processing_errors", 1,
             "component_kind" => "transform",
             "component_type" => "regex_parser",
             "error_type" => "failed_match",
@@ -49,7 +49,7 @@ impl InternalEvent for RegexMissingField<'_> {
     }
 
     fn emit_metrics(&self) {
-        counter!("processing_error", 1,
+        counter!("processing_errors", 1,
             "component_kind" => "transform",
             "component_type" => "regex_parser",
             "error_type" => "missing_field",
@@ -38,9 +38,10 @@ impl InternalEvent for SplunkEventEncodeError {
 
     fn emit_metrics(&self) {
         counter!(
-            "encode_errors", 1,
+            "processing_errors", 1,
             "component_kind" => "sink",
             "component_type" => "splunk_hec",
+            "error_type" => "encode_failed",
         );
     }
 }
@@ -0,0 +1,32 @@
+use super::InternalEvent;
+use metrics::counter;
+
+#[derive(Debug)]
+pub struct ThrottleEventProcessed;
+
+impl InternalEvent for ThrottleEventProcessed {
+    fn emit_metrics(&self) {
+        counter!("events_processed", 1,
+            "component_kind" => "transform",
+            "component_type" => "throttle",
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct ThrottleEventDiscarded {
+    pub key: String,
+}
+
+impl InternalEvent for ThrottleEventDiscarded {
+    fn emit_logs(&self) {
+        debug!(message = "rate limit exceeded; discarding event.", key = %self.key, rate_limit_secs = 30);
+    }
+
+    fn emit_metrics(&self) {
+        counter!("events_discarded", 1,
+            "component_kind" => "transform",
+            "component_type" => "throttle",
+        );
+    }
+}
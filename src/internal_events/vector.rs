@@ -1,4 +1,5 @@
 use super::InternalEvent;
+use crate::event::ProtoError;
 use metrics::counter;
 use prost::DecodeError;
 
@@ -35,7 +36,7 @@ impl InternalEvent for VectorEventReceived {
     fn emit_metrics(&self) {
         counter!(
             "events_processed", 1,
-            "component_kind" => "sink",
+            "component_kind" => "source",
             "component_type" => "vector",
         );
         counter!(
@@ -58,9 +59,30 @@ impl InternalEvent for VectorProtoDecodeError {
 
     fn emit_metrics(&self) {
         counter!(
-            "protobuf_decode_errors", 1,
+            "processing_errors", 1,
+            "component_kind" => "source",
+            "component_type" => "vector",
+            "error_type" => "decode_failed",
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct VectorInvalidEvent {
+    pub error: ProtoError,
+}
+
+impl InternalEvent for VectorInvalidEvent {
+    fn emit_logs(&self) {
+        error!(message = "received an invalid event", error = %self.error);
+    }
+
+    fn emit_metrics(&self) {
+        counter!(
+            "processing_errors", 1,
             "component_kind" => "source",
             "component_type" => "vector",
+            "error_type" => "invalid_event",
         );
     }
 }
@@ -18,23 +18,26 @@ extern crate derivative;
 #[global_allocator]
 static ALLOC: jemallocator::Jemalloc = jemallocator::Jemalloc;
 
+#[macro_use]
+pub mod internal_events;
+pub mod async_read;
 pub mod buffers;
+pub mod codecs;
 pub mod conditions;
 pub mod config_paths;
 pub mod dns;
 pub mod event;
 pub mod expiring_hash_map;
 pub mod generate;
-#[cfg(feature = "wasm")]
-pub mod wasm;
-#[macro_use]
-pub mod internal_events;
-pub mod async_read;
 pub mod hyper;
 #[cfg(feature = "rdkafka")]
 pub mod kafka;
 pub mod list;
 pub mod metrics;
+#[cfg(any(feature = "sources-opentelemetry", feature = "sinks-opentelemetry"))]
+pub mod opentelemetry;
+#[cfg(any(feature = "sources-prometheus", feature = "sinks-prometheus"))]
+pub mod prometheus;
 pub mod region;
 pub mod runtime;
 pub mod serde;
@@ -51,6 +54,8 @@ pub mod transforms;
 pub mod types;
 pub mod unit_test;
 pub mod validate;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 pub use event::Event;
 
@@ -13,7 +13,7 @@ use std::{
 };
 #[cfg(unix)]
 use tokio_signal::unix::{Signal, SIGHUP, SIGINT, SIGQUIT, SIGTERM};
-use topology::Config;
+use topology::{config::Format, Config};
 use vector::{
     config_paths, event, generate, list, metrics, runtime, topology, trace, unit_test, validate,
 };
@@ -105,7 +105,7 @@ fn main() {
             path = ?config_paths
         );
 
-        let read_config = read_configs(&config_paths);
+        let read_config = read_configs(&config_paths, opts.config_format);
         let maybe_config = handle_config_errors(read_config);
         let config = maybe_config.unwrap_or_else(|| {
             std::process::exit(exitcode::CONFIG);
@@ -170,7 +170,7 @@ fn main() {
                     message = "Reloading configs.",
                     path = ?config_paths
                 );
-                let new_config = read_configs(&config_paths);
+                let new_config = read_configs(&config_paths, opts.config_format);
 
                 trace!("Parsing config");
                 let new_config = handle_config_errors(new_config);
@@ -260,7 +260,10 @@ fn handle_config_errors(config: Result<Config, Vec<String>>) -> Option<Config> {
     }
 }
 
-fn read_configs(config_paths: &[PathBuf]) -> Result<Config, Vec<String>> {
+fn read_configs(
+    config_paths: &[PathBuf],
+    config_format: Option<Format>,
+) -> Result<Config, Vec<String>> {
     let mut config = vector::topology::Config::empty();
     let mut errors = Vec::new();
 
@@ -277,7 +280,8 @@ fn read_configs(config_paths: &[PathBuf]) -> Result<Config, Vec<String>> {
             path = ?p
         );
 
-        if let Err(errs) = Config::load(file).and_then(|n| config.append(n)) {
+        let format = Format::resolve(&p, config_format);
+        if let Err(errs) = Config::load_with_format(file, format).and_then(|n| config.append(n)) {
             errors.extend(errs.iter().map(|e| format!("{:?}: {}", p, e)));
         }
     });
@@ -1,5 +1,15 @@
+use metrics_core::{Key, Label, Recorder};
 use metrics_runtime::{Controller, Receiver};
 use once_cell::sync::OnceCell;
+use std::fmt;
+use tracing::{
+    field::{Field, Visit},
+    span, Subscriber,
+};
+use tracing_subscriber::{
+    layer::{Context, Layer},
+    registry::{LookupSpan, Registry},
+};
 
 pub static CONTROLLER: OnceCell<Controller> = OnceCell::new();
 
@@ -12,7 +22,181 @@ pub fn init() -> crate::Result<()> {
         .set(receiver.controller())
         .map_err(|_| "failed to set receiver. metrics system already initialized.")?;
 
-    receiver.install();
+    metrics::set_boxed_recorder(Box::new(ComponentRecorder { inner: receiver }))
+        .map_err(|_| "failed to set recorder. metrics system already initialized.")?;
 
     Ok(())
 }
+
+/// The component whose task a span belongs to, as recorded by the topology
+/// when it instruments the task.
+#[derive(Clone, Debug)]
+struct Component {
+    kind: &'static str,
+    name: String,
+    r#type: String,
+}
+
+#[derive(Default)]
+struct ComponentVisitor {
+    name: Option<String>,
+    r#type: Option<String>,
+}
+
+impl Visit for ComponentVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "name" => self.name = Some(value.to_string()),
+            "type" | "r#type" => self.r#type = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "name" => self.name = Some(format!("{:?}", value)),
+            "type" | "r#type" => self.r#type = Some(format!("{:?}", value)),
+            _ => {}
+        }
+    }
+}
+
+/// Remembers the component of the `source`, `transform` and `sink` spans of
+/// the topology, so that metrics recorded within them can be tagged with it.
+pub struct ComponentLayer;
+
+impl<S> Layer<S> for ComponentLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let kind = match attrs.metadata().name() {
+            "source" => "source",
+            "transform" => "transform",
+            "sink" => "sink",
+            _ => return,
+        };
+
+        let mut visitor = ComponentVisitor::default();
+        attrs.record(&mut visitor);
+        if let (Some(name), Some(r#type), Some(span)) = (visitor.name, visitor.r#type, ctx.span(id))
+        {
+            span.extensions_mut()
+                .insert(Component { kind, name, r#type });
+        }
+    }
+}
+
+fn current_component() -> Option<Component> {
+    tracing::dispatcher::get_default(|dispatch| {
+        let registry = dispatch.downcast_ref::<Registry>()?;
+        let id = registry.current_span().id()?.clone();
+
+        let mut span = registry.span(&id);
+        while let Some(current) = span {
+            if let Some(component) = current.extensions().get::<Component>() {
+                return Some(component.clone());
+            }
+            span = current.parent();
+        }
+        None
+    })
+}
+
+/// Tags metrics with the `component_name`, `component_kind` and
+/// `component_type` of the component they are recorded in. Metrics that name
+/// their component themselves are left as they are.
+struct ComponentRecorder {
+    inner: Receiver,
+}
+
+impl ComponentRecorder {
+    fn tag(&self, key: Key) -> Key {
+        if key.labels().any(|label| label.key() == "component_name") {
+            return key;
+        }
+        let component = match current_component() {
+            Some(component) => component,
+            None => return key,
+        };
+
+        let (name, labels) = key.into_parts();
+        let mut labels = labels
+            .into_iter()
+            .filter(|label| !matches!(label.key(), "component_kind" | "component_type"))
+            .collect::<Vec<_>>();
+        labels.push(Label::new("component_name", component.name));
+        labels.push(Label::new("component_kind", component.kind));
+        labels.push(Label::new("component_type", component.r#type));
+        Key::from_name_and_labels(name, labels)
+    }
+}
+
+impl Recorder for ComponentRecorder {
+    fn record_counter(&self, key: Key, value: u64) {
+        self.inner.record_counter(self.tag(key), value);
+    }
+
+    fn record_gauge(&self, key: Key, value: i64) {
+        self.inner.record_gauge(self.tag(key), value);
+    }
+
+    fn record_histogram(&self, key: Key, value: u64) {
+        self.inner.record_histogram(self.tag(key), value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    fn labels(key: &Key) -> Vec<(&str, &str)> {
+        let mut labels = key
+            .labels()
+            .map(|label| (label.key(), label.value()))
+            .collect::<Vec<_>>();
+        labels.sort();
+        labels
+    }
+
+    #[test]
+    fn tags_metrics_with_their_component() {
+        let recorder = ComponentRecorder {
+            inner: Receiver::builder().build().unwrap(),
+        };
+        let subscriber = Registry::default().with(ComponentLayer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            let key = Key::from_name_and_labels(
+                "processing_errors",
+                vec![
+                    Label::new("component_kind", "source"),
+                    Label::new("error_type", "failed_parse"),
+                ],
+            );
+            assert_eq!(recorder.tag(key.clone()), key);
+
+            let span = info_span!("transform", name = "parse", r#type = "json_parser");
+            let _enter = span.enter();
+            let nested = info_span!("request");
+            let _enter = nested.enter();
+
+            assert_eq!(
+                labels(&recorder.tag(key)),
+                vec![
+                    ("component_kind", "transform"),
+                    ("component_name", "parse"),
+                    ("component_type", "json_parser"),
+                    ("error_type", "failed_parse"),
+                ]
+            );
+
+            let named = Key::from_name_and_labels(
+                "buffer_events",
+                vec![Label::new("component_name", "out")],
+            );
+            assert_eq!(recorder.tag(named.clone()), named);
+        });
+    }
+}
@@ -0,0 +1,760 @@
+//! Conversions between events and the OTLP messages of OpenTelemetry.
+//!
+//! Log records become log events holding their body as the `message`, their
+//! `attributes` and the attributes of their resource as maps, and the other
+//! parts of the record as fields of their own. Metric data points become
+//! metrics tagged with their attributes, the attributes of their resource
+//! prefixed with `resource.`, and the name and version of their scope.
+
+pub mod proto;
+
+use self::proto::{
+    any_value, metric::Data, number_data_point, AggregationTemporality, AnyValue, ArrayValue,
+    ExportLogsServiceRequest, ExportMetricsServiceRequest, Gauge, Histogram, HistogramDataPoint,
+    InstrumentationScope, KeyValue, KeyValueList, LogRecord, NumberDataPoint, Resource,
+    ResourceLogs, ResourceMetrics, ScopeLogs, ScopeMetrics, Sum, Summary, SummaryDataPoint,
+    ValueAtQuantile,
+};
+use crate::{
+    event::{
+        self,
+        metric::{MetricKind, MetricValue},
+        Event, LogEvent, Metric, Value,
+    },
+    internal_events::OpenTelemetryMetricUnsupported,
+};
+use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
+use lazy_static::lazy_static;
+use std::collections::BTreeMap;
+use string_cache::DefaultAtom as Atom;
+
+pub const ATTRIBUTES_KEY: &str = "attributes";
+pub const RESOURCES_KEY: &str = "resources";
+const RESOURCE_TAG_PREFIX: &str = "resource.";
+const SCOPE_NAME_TAG: &str = "scope.name";
+const SCOPE_VERSION_TAG: &str = "scope.version";
+
+lazy_static! {
+    static ref ATTRIBUTES: Atom = Atom::from(ATTRIBUTES_KEY);
+    static ref RESOURCES: Atom = Atom::from(RESOURCES_KEY);
+    static ref SCOPE: Atom = Atom::from("scope");
+    static ref OBSERVED_TIMESTAMP: Atom = Atom::from("observed_timestamp");
+    static ref SEVERITY_TEXT: Atom = Atom::from("severity_text");
+    static ref SEVERITY_NUMBER: Atom = Atom::from("severity_number");
+    static ref TRACE_ID: Atom = Atom::from("trace_id");
+    static ref SPAN_ID: Atom = Atom::from("span_id");
+    static ref FLAGS: Atom = Atom::from("flags");
+    static ref DROPPED_ATTRIBUTES_COUNT: Atom = Atom::from("dropped_attributes_count");
+}
+
+/// Converts the log records of a request into log events.
+pub fn logs_into_events(request: ExportLogsServiceRequest) -> Vec<Event> {
+    let mut events = Vec::new();
+    for resource_logs in request.resource_logs {
+        let resources = resource_logs
+            .resource
+            .map(|resource| attributes_into_map(resource.attributes))
+            .unwrap_or_default();
+        for scope_logs in resource_logs.scope_logs {
+            let scope = scope_logs.scope.unwrap_or_default();
+            for record in scope_logs.log_records {
+                let mut log = record_into_log(record);
+                if !resources.is_empty() {
+                    log.insert_flat(RESOURCES_KEY, resources.clone());
+                }
+                if !scope.name.is_empty() {
+                    log.insert("scope.name", scope.name.as_str());
+                }
+                if !scope.version.is_empty() {
+                    log.insert("scope.version", scope.version.as_str());
+                }
+                events.push(Event::from(log));
+            }
+        }
+    }
+    events
+}
+
+fn record_into_log(record: LogRecord) -> LogEvent {
+    let mut log = LogEvent::default();
+    if let Some(body) = record.body {
+        log.insert(event::log_schema().message_key().clone(), body);
+    }
+    let observed = timestamp_from_nanos(record.observed_time_unix_nano);
+    let timestamp = timestamp_from_nanos(record.time_unix_nano)
+        .or(observed)
+        .unwrap_or_else(Utc::now);
+    log.insert(event::log_schema().timestamp_key().clone(), timestamp);
+    if let Some(observed) = observed {
+        log.insert(OBSERVED_TIMESTAMP.clone(), observed);
+    }
+    if !record.attributes.is_empty() {
+        log.insert_flat(ATTRIBUTES_KEY, attributes_into_map(record.attributes));
+    }
+    if !record.severity_text.is_empty() {
+        log.insert(SEVERITY_TEXT.clone(), record.severity_text);
+    }
+    if record.severity_number != 0 {
+        log.insert(SEVERITY_NUMBER.clone(), record.severity_number);
+    }
+    if !record.trace_id.is_empty() {
+        log.insert(TRACE_ID.clone(), encode_hex(&record.trace_id));
+    }
+    if !record.span_id.is_empty() {
+        log.insert(SPAN_ID.clone(), encode_hex(&record.span_id));
+    }
+    if record.flags != 0 {
+        log.insert(FLAGS.clone(), record.flags as i64);
+    }
+    if record.dropped_attributes_count != 0 {
+        log.insert(
+            DROPPED_ATTRIBUTES_COUNT.clone(),
+            record.dropped_attributes_count as i64,
+        );
+    }
+    log
+}
+
+/// Converts a log event into a request holding a single log record. Fields
+/// without a part of the record of their own are added to its attributes.
+pub fn log_into_request(mut log: LogEvent) -> ExportLogsServiceRequest {
+    let body = log
+        .remove(&event::log_schema().message_key())
+        .map(AnyValue::from);
+    let time_unix_nano = log
+        .remove(&event::log_schema().timestamp_key())
+        .and_then(|value| value.as_timestamp().map(timestamp_to_nanos))
+        .unwrap_or(0);
+    let observed_time_unix_nano = log
+        .remove(&OBSERVED_TIMESTAMP)
+        .and_then(|value| value.as_timestamp().map(timestamp_to_nanos))
+        .unwrap_or(0);
+    let severity_text = log
+        .remove(&SEVERITY_TEXT)
+        .map(|value| value.to_string_lossy())
+        .unwrap_or_default();
+    let severity_number = integer(log.remove(&SEVERITY_NUMBER)) as i32;
+    let trace_id = log
+        .remove(&TRACE_ID)
+        .and_then(|value| decode_hex(&value.to_string_lossy()))
+        .unwrap_or_default();
+    let span_id = log
+        .remove(&SPAN_ID)
+        .and_then(|value| decode_hex(&value.to_string_lossy()))
+        .unwrap_or_default();
+    let flags = integer(log.remove(&FLAGS)) as u32;
+    let dropped_attributes_count = integer(log.remove(&DROPPED_ATTRIBUTES_COUNT)) as u32;
+
+    let resource = match log.remove(&RESOURCES) {
+        Some(Value::Map(resources)) => Some(Resource {
+            attributes: map_into_attributes(resources),
+        }),
+        _ => None,
+    };
+    let scope = match log.remove(&SCOPE) {
+        Some(Value::Map(mut scope)) => Some(InstrumentationScope {
+            name: string(scope.remove("name")),
+            version: string(scope.remove("version")),
+            attributes: Vec::new(),
+        }),
+        _ => None,
+    };
+
+    let mut attributes = match log.remove(&ATTRIBUTES) {
+        Some(Value::Map(attributes)) => attributes,
+        _ => BTreeMap::new(),
+    };
+    log.remove(&event::log_schema().source_type_key());
+    for (key, value) in log {
+        attributes.entry(key).or_insert(value);
+    }
+
+    let record = LogRecord {
+        time_unix_nano,
+        observed_time_unix_nano,
+        severity_number,
+        severity_text,
+        body,
+        attributes: map_into_attributes(attributes),
+        dropped_attributes_count,
+        flags,
+        trace_id,
+        span_id,
+    };
+    ExportLogsServiceRequest {
+        resource_logs: vec![ResourceLogs {
+            resource,
+            scope_logs: vec![ScopeLogs {
+                scope,
+                log_records: vec![record],
+            }],
+        }],
+    }
+}
+
+/// Converts the data points of a request into metrics. Exponential
+/// histograms are dropped.
+pub fn metrics_into_events(request: ExportMetricsServiceRequest) -> Vec<Event> {
+    let mut events = Vec::new();
+    for resource_metrics in request.resource_metrics {
+        let mut tags = BTreeMap::new();
+        if let Some(resource) = resource_metrics.resource {
+            for (key, value) in attributes_into_map(resource.attributes) {
+                tags.insert(
+                    format!("{}{}", RESOURCE_TAG_PREFIX, key),
+                    value.to_string_lossy(),
+                );
+            }
+        }
+        for scope_metrics in resource_metrics.scope_metrics {
+            let mut tags = tags.clone();
+            if let Some(scope) = scope_metrics.scope {
+                if !scope.name.is_empty() {
+                    tags.insert(SCOPE_NAME_TAG.into(), scope.name);
+                }
+                if !scope.version.is_empty() {
+                    tags.insert(SCOPE_VERSION_TAG.into(), scope.version);
+                }
+            }
+            for metric in scope_metrics.metrics {
+                metric_into_events(metric, &tags, &mut events);
+            }
+        }
+    }
+    events
+}
+
+fn metric_into_events(
+    metric: proto::Metric,
+    tags: &BTreeMap<String, String>,
+    events: &mut Vec<Event>,
+) {
+    let name = metric.name;
+    let new_metric = |attributes, time_unix_nano, kind, value| {
+        let mut tags = tags.clone();
+        for (key, value) in attributes_into_map(attributes) {
+            tags.insert(key, value.to_string_lossy());
+        }
+        Event::Metric(Metric {
+            name: name.clone(),
+            namespace: None,
+            timestamp: timestamp_from_nanos(time_unix_nano),
+            tags: if tags.is_empty() { None } else { Some(tags) },
+            kind,
+            value,
+        })
+    };
+
+    match metric.data {
+        Some(Data::Gauge(gauge)) => events.extend(gauge.data_points.into_iter().map(|point| {
+            let value = number_value(&point);
+            new_metric(
+                point.attributes,
+                point.time_unix_nano,
+                MetricKind::Absolute,
+                MetricValue::Gauge { value },
+            )
+        })),
+        Some(Data::Sum(sum)) => {
+            let kind = metric_kind(sum.aggregation_temporality);
+            events.extend(sum.data_points.into_iter().map(|point| {
+                let value = number_value(&point);
+                let value = if sum.is_monotonic {
+                    MetricValue::Counter { value }
+                } else {
+                    MetricValue::Gauge { value }
+                };
+                new_metric(point.attributes, point.time_unix_nano, kind.clone(), value)
+            }))
+        }
+        Some(Data::Histogram(histogram)) => {
+            let kind = metric_kind(histogram.aggregation_temporality);
+            events.extend(histogram.data_points.into_iter().map(|point| {
+                // Bucket counts are made cumulative, and the count of the
+                // values above the last bound is only part of the total.
+                let mut cumulative = 0;
+                let counts = point
+                    .bucket_counts
+                    .iter()
+                    .take(point.explicit_bounds.len())
+                    .map(|count| {
+                        cumulative += count;
+                        cumulative as u32
+                    })
+                    .collect();
+                let value = MetricValue::AggregatedHistogram {
+                    buckets: point.explicit_bounds,
+                    counts,
+                    count: point.count as u32,
+                    sum: point.sum.unwrap_or(0.0),
+                };
+                new_metric(point.attributes, point.time_unix_nano, kind.clone(), value)
+            }))
+        }
+        Some(Data::Summary(summary)) => {
+            events.extend(summary.data_points.into_iter().map(|point| {
+                let value = MetricValue::AggregatedSummary {
+                    quantiles: point.quantile_values.iter().map(|q| q.quantile).collect(),
+                    values: point.quantile_values.iter().map(|q| q.value).collect(),
+                    count: point.count as u32,
+                    sum: point.sum,
+                };
+                new_metric(
+                    point.attributes,
+                    point.time_unix_nano,
+                    MetricKind::Absolute,
+                    value,
+                )
+            }))
+        }
+        Some(Data::ExponentialHistogram(_)) => emit!(OpenTelemetryMetricUnsupported {
+            component_kind: "source",
+            name: &name,
+            metric_type: "exponential_histogram",
+        }),
+        None => (),
+    }
+}
+
+/// Converts a metric into a request holding a single data point, unless
+/// OTLP has no matching metric type.
+pub fn metric_into_request(metric: Metric) -> Option<ExportMetricsServiceRequest> {
+    let name = match metric.namespace {
+        Some(namespace) => format!("{}.{}", namespace, metric.name),
+        None => metric.name,
+    };
+
+    let mut resource = Vec::new();
+    let mut scope = InstrumentationScope::default();
+    let mut attributes = Vec::new();
+    for (key, value) in metric.tags.unwrap_or_default() {
+        if key.starts_with(RESOURCE_TAG_PREFIX) {
+            resource.push(string_attribute(&key[RESOURCE_TAG_PREFIX.len()..], value));
+        } else if key == SCOPE_NAME_TAG {
+            scope.name = value;
+        } else if key == SCOPE_VERSION_TAG {
+            scope.version = value;
+        } else {
+            attributes.push(string_attribute(&key, value));
+        }
+    }
+
+    let time_unix_nano = timestamp_to_nanos(&metric.timestamp.unwrap_or_else(Utc::now));
+    let temporality = match metric.kind {
+        MetricKind::Incremental => AggregationTemporality::Delta,
+        MetricKind::Absolute => AggregationTemporality::Cumulative,
+    } as i32;
+    let number_point = |value| NumberDataPoint {
+        attributes: attributes.clone(),
+        start_time_unix_nano: 0,
+        time_unix_nano,
+        value: Some(number_data_point::Value::AsDouble(value)),
+    };
+
+    let data = match metric.value {
+        MetricValue::Counter { value } => Data::Sum(Sum {
+            data_points: vec![number_point(value)],
+            aggregation_temporality: temporality,
+            is_monotonic: true,
+        }),
+        MetricValue::Gauge { value } if metric.kind.is_absolute() => Data::Gauge(Gauge {
+            data_points: vec![number_point(value)],
+        }),
+        MetricValue::Gauge { value } => Data::Sum(Sum {
+            data_points: vec![number_point(value)],
+            aggregation_temporality: temporality,
+            is_monotonic: false,
+        }),
+        MetricValue::AggregatedHistogram {
+            buckets,
+            counts,
+            count,
+            sum,
+        } => {
+            let mut previous = 0;
+            let mut bucket_counts: Vec<u64> = counts
+                .iter()
+                .map(|&cumulative| {
+                    let count = cumulative.saturating_sub(previous);
+                    previous = cumulative;
+                    count as u64
+                })
+                .collect();
+            bucket_counts.push(count.saturating_sub(previous) as u64);
+            Data::Histogram(Histogram {
+                data_points: vec![HistogramDataPoint {
+                    attributes,
+                    start_time_unix_nano: 0,
+                    time_unix_nano,
+                    count: count as u64,
+                    sum: Some(sum),
+                    bucket_counts,
+                    explicit_bounds: buckets,
+                }],
+                aggregation_temporality: temporality,
+            })
+        }
+        MetricValue::AggregatedSummary {
+            quantiles,
+            values,
+            count,
+            sum,
+        } => Data::Summary(Summary {
+            data_points: vec![SummaryDataPoint {
+                attributes,
+                start_time_unix_nano: 0,
+                time_unix_nano,
+                count: count as u64,
+                sum,
+                quantile_values: quantiles
+                    .into_iter()
+                    .zip(values)
+                    .map(|(quantile, value)| ValueAtQuantile { quantile, value })
+                    .collect(),
+            }],
+        }),
+        MetricValue::Set { .. } | MetricValue::Distribution { .. } | MetricValue::Sketch(_) => {
+            emit!(OpenTelemetryMetricUnsupported {
+                component_kind: "sink",
+                name: &name,
+                metric_type: metric_type_name(&metric.value),
+            });
+            return None;
+        }
+    };
+
+    Some(ExportMetricsServiceRequest {
+        resource_metrics: vec![ResourceMetrics {
+            resource: Some(Resource {
+                attributes: resource,
+            }),
+            scope_metrics: vec![ScopeMetrics {
+                scope: Some(scope),
+                metrics: vec![proto::Metric {
+                    name,
+                    description: String::new(),
+                    unit: String::new(),
+                    data: Some(data),
+                }],
+            }],
+        }],
+    })
+}
+
+fn metric_kind(aggregation_temporality: i32) -> MetricKind {
+    if aggregation_temporality == AggregationTemporality::Delta as i32 {
+        MetricKind::Incremental
+    } else {
+        MetricKind::Absolute
+    }
+}
+
+fn metric_type_name(value: &MetricValue) -> &'static str {
+    match value {
+        MetricValue::Counter { .. } => "counter",
+        MetricValue::Gauge { .. } => "gauge",
+        MetricValue::Set { .. } => "set",
+        MetricValue::Distribution { .. } => "distribution",
+        MetricValue::AggregatedHistogram { .. } => "aggregated_histogram",
+        MetricValue::AggregatedSummary { .. } => "aggregated_summary",
+        MetricValue::Sketch(_) => "sketch",
+    }
+}
+
+fn number_value(point: &NumberDataPoint) -> f64 {
+    match point.value {
+        Some(number_data_point::Value::AsDouble(value)) => value,
+        Some(number_data_point::Value::AsInt(value)) => value as f64,
+        None => 0.0,
+    }
+}
+
+impl From<AnyValue> for Value {
+    fn from(value: AnyValue) -> Self {
+        match value.value {
+            Some(any_value::Value::StringValue(s)) => s.into(),
+            Some(any_value::Value::BoolValue(b)) => b.into(),
+            Some(any_value::Value::IntValue(i)) => i.into(),
+            Some(any_value::Value::DoubleValue(f)) => f.into(),
+            Some(any_value::Value::ArrayValue(array)) => {
+                Value::Array(array.values.into_iter().map(Value::from).collect())
+            }
+            Some(any_value::Value::KvlistValue(list)) => {
+                Value::Map(attributes_into_map(list.values))
+            }
+            Some(any_value::Value::BytesValue(bytes)) => bytes.into(),
+            None => Value::Null,
+        }
+    }
+}
+
+impl From<Value> for AnyValue {
+    fn from(value: Value) -> Self {
+        let value = match value {
+            Value::Bytes(bytes) => match String::from_utf8(bytes.to_vec()) {
+                Ok(s) => any_value::Value::StringValue(s),
+                Err(error) => any_value::Value::BytesValue(error.into_bytes()),
+            },
+            Value::Integer(i) => any_value::Value::IntValue(i),
+            Value::Float(f) => any_value::Value::DoubleValue(f),
+            Value::Boolean(b) => any_value::Value::BoolValue(b),
+            Value::Timestamp(timestamp) => any_value::Value::StringValue(
+                timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+            ),
+            Value::Map(map) => any_value::Value::KvlistValue(KeyValueList {
+                values: map_into_attributes(map),
+            }),
+            Value::Array(array) => any_value::Value::ArrayValue(ArrayValue {
+                values: array.into_iter().map(AnyValue::from).collect(),
+            }),
+            Value::Null => return AnyValue { value: None },
+        };
+        AnyValue { value: Some(value) }
+    }
+}
+
+fn attributes_into_map(attributes: Vec<KeyValue>) -> BTreeMap<String, Value> {
+    attributes
+        .into_iter()
+        .map(|kv| (kv.key, kv.value.map(Value::from).unwrap_or(Value::Null)))
+        .collect()
+}
+
+fn map_into_attributes(map: BTreeMap<String, Value>) -> Vec<KeyValue> {
+    map.into_iter()
+        .map(|(key, value)| KeyValue {
+            key,
+            value: Some(value.into()),
+        })
+        .collect()
+}
+
+fn string_attribute(key: &str, value: String) -> KeyValue {
+    KeyValue {
+        key: key.into(),
+        value: Some(AnyValue {
+            value: Some(any_value::Value::StringValue(value)),
+        }),
+    }
+}
+
+fn string(value: Option<Value>) -> String {
+    value
+        .map(|value| value.to_string_lossy())
+        .unwrap_or_default()
+}
+
+fn integer(value: Option<Value>) -> i64 {
+    match value {
+        Some(Value::Integer(i)) => i,
+        Some(value) => value.to_string_lossy().parse().unwrap_or(0),
+        None => 0,
+    }
+}
+
+/// OTLP leaves unknown times as zero.
+fn timestamp_from_nanos(nanos: u64) -> Option<DateTime<Utc>> {
+    if nanos == 0 {
+        None
+    } else {
+        Some(Utc.timestamp(
+            (nanos / 1_000_000_000) as i64,
+            (nanos % 1_000_000_000) as u32,
+        ))
+    }
+}
+
+fn timestamp_to_nanos(timestamp: &DateTime<Utc>) -> u64 {
+    timestamp.timestamp_nanos() as u64
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if s.len() % 2 != 0 {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| {
+            s.get(i..i + 2)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attribute(key: &str, value: any_value::Value) -> KeyValue {
+        KeyValue {
+            key: key.into(),
+            value: Some(AnyValue { value: Some(value) }),
+        }
+    }
+
+    #[test]
+    fn log_round_trip() {
+        let request: ExportLogsServiceRequest = serde_json::from_str(
+            r#"{"resourceLogs": [{
+                "resource": {"attributes": [{"key": "service.name", "value": {"stringValue": "api"}}]},
+                "scopeLogs": [{
+                    "scope": {"name": "io.opentelemetry", "version": "1.0"},
+                    "logRecords": [{
+                        "timeUnixNano": "1600000000000000000",
+                        "severityNumber": 9,
+                        "severityText": "INFO",
+                        "body": {"stringValue": "hello"},
+                        "attributes": [{"key": "http.status", "value": {"intValue": "200"}}],
+                        "traceId": "5b8efff798038103d269b633813fc60c",
+                        "spanId": "eee19b7ec3c1b174"
+                    }]
+                }]
+            }]}"#,
+        )
+        .unwrap();
+
+        let events = logs_into_events(request.clone());
+        assert_eq!(events.len(), 1);
+        let log = events[0].as_log();
+        assert_eq!(log[&event::log_schema().message_key()], "hello".into());
+        assert_eq!(
+            log[&event::log_schema().timestamp_key()],
+            Utc.timestamp(1_600_000_000, 0).into()
+        );
+        assert_eq!(
+            log.get_flat(ATTRIBUTES_KEY),
+            Some(&Value::Map(
+                vec![("http.status".to_owned(), Value::Integer(200))]
+                    .into_iter()
+                    .collect()
+            ))
+        );
+        assert_eq!(
+            log.get_flat(RESOURCES_KEY),
+            Some(&Value::Map(
+                vec![("service.name".to_owned(), "api".into())]
+                    .into_iter()
+                    .collect()
+            ))
+        );
+        assert_eq!(log[&"scope.name".into()], "io.opentelemetry".into());
+        assert_eq!(
+            log[&"trace_id".into()],
+            "5b8efff798038103d269b633813fc60c".into()
+        );
+        assert_eq!(log[&"severity_number".into()], Value::Integer(9));
+
+        assert_eq!(log_into_request(log.clone()), request);
+    }
+
+    #[test]
+    fn metrics_into_events_maps_types() {
+        let point = |value| NumberDataPoint {
+            attributes: vec![attribute("host", any_value::Value::StringValue("a".into()))],
+            start_time_unix_nano: 0,
+            time_unix_nano: 1_600_000_000_000_000_000,
+            value: Some(value),
+        };
+        let metric = |name: &str, data| proto::Metric {
+            name: name.into(),
+            description: String::new(),
+            unit: String::new(),
+            data: Some(data),
+        };
+        let request = ExportMetricsServiceRequest {
+            resource_metrics: vec![ResourceMetrics {
+                resource: Some(Resource {
+                    attributes: vec![attribute(
+                        "service.name",
+                        any_value::Value::StringValue("api".into()),
+                    )],
+                }),
+                scope_metrics: vec![ScopeMetrics {
+                    scope: None,
+                    metrics: vec![
+                        metric(
+                            "requests",
+                            Data::Sum(Sum {
+                                data_points: vec![point(number_data_point::Value::AsInt(3))],
+                                aggregation_temporality: AggregationTemporality::Delta as i32,
+                                is_monotonic: true,
+                            }),
+                        ),
+                        metric(
+                            "memory",
+                            Data::Gauge(Gauge {
+                                data_points: vec![point(number_data_point::Value::AsDouble(1.5))],
+                            }),
+                        ),
+                        metric(
+                            "latency",
+                            Data::Histogram(Histogram {
+                                data_points: vec![HistogramDataPoint {
+                                    attributes: Vec::new(),
+                                    start_time_unix_nano: 0,
+                                    time_unix_nano: 0,
+                                    count: 6,
+                                    sum: Some(4.5),
+                                    bucket_counts: vec![1, 2, 3],
+                                    explicit_bounds: vec![0.5, 1.0],
+                                }],
+                                aggregation_temporality: AggregationTemporality::Cumulative as i32,
+                            }),
+                        ),
+                    ],
+                }],
+            }],
+        };
+
+        let metrics: Vec<_> = metrics_into_events(request)
+            .into_iter()
+            .map(Event::into_metric)
+            .collect();
+        assert_eq!(metrics.len(), 3);
+
+        let tags = metrics[0].tags.as_ref().unwrap();
+        assert_eq!(tags["host"], "a");
+        assert_eq!(tags["resource.service.name"], "api");
+        assert_eq!(metrics[0].kind, MetricKind::Incremental);
+        assert_eq!(metrics[0].value, MetricValue::Counter { value: 3.0 });
+        assert_eq!(metrics[1].kind, MetricKind::Absolute);
+        assert_eq!(metrics[1].value, MetricValue::Gauge { value: 1.5 });
+        assert_eq!(
+            metrics[2].value,
+            MetricValue::AggregatedHistogram {
+                buckets: vec![0.5, 1.0],
+                counts: vec![1, 3],
+                count: 6,
+                sum: 4.5,
+            }
+        );
+
+        let request = metric_into_request(metrics[2].clone()).unwrap();
+        let metric = &request.resource_metrics[0].scope_metrics[0].metrics[0];
+        match &metric.data {
+            Some(Data::Histogram(histogram)) => {
+                assert_eq!(histogram.data_points[0].bucket_counts, vec![1, 2, 3])
+            }
+            _ => panic!("expected a histogram"),
+        }
+    }
+
+    #[test]
+    fn unsupported_metrics_are_dropped() {
+        let metric = Metric {
+            name: "users".into(),
+            namespace: None,
+            timestamp: None,
+            tags: None,
+            kind: MetricKind::Incremental,
+            value: MetricValue::Set {
+                values: vec!["a".to_owned()].into_iter().collect(),
+            },
+        };
+        assert!(metric_into_request(metric).is_none());
+    }
+}
@@ -0,0 +1,398 @@
+//! The OTLP messages exchanged with OpenTelemetry collectors, limited to the
+//! logs and metrics signals.
+//!
+//! Fields unknown to these definitions, such as exemplars, are skipped when
+//! decoding. Messages also deserialize from the OTLP/JSON mapping, which
+//! writes field names in lower camel case, 64-bit integers as strings, and
+//! trace and span ids as hex strings.
+
+use serde::Deserialize;
+
+#[derive(Clone, PartialEq, Deserialize, prost::Message)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExportLogsServiceRequest {
+    #[prost(message, repeated, tag = "1")]
+    pub resource_logs: Vec<ResourceLogs>,
+}
+
+#[derive(Clone, PartialEq, Deserialize, prost::Message)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExportMetricsServiceRequest {
+    #[prost(message, repeated, tag = "1")]
+    pub resource_metrics: Vec<ResourceMetrics>,
+}
+
+#[derive(Clone, PartialEq, Deserialize, prost::Message)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AnyValue {
+    #[serde(flatten)]
+    #[prost(oneof = "any_value::Value", tags = "1, 2, 3, 4, 5, 6, 7")]
+    pub value: Option<any_value::Value>,
+}
+
+pub mod any_value {
+    use super::json;
+    use serde::Deserialize;
+
+    #[derive(Clone, PartialEq, Deserialize, prost::Oneof)]
+    #[serde(rename_all = "camelCase")]
+    pub enum Value {
+        #[prost(string, tag = "1")]
+        StringValue(String),
+        #[prost(bool, tag = "2")]
+        BoolValue(bool),
+        #[serde(deserialize_with = "json::integer")]
+        #[prost(int64, tag = "3")]
+        IntValue(i64),
+        #[prost(double, tag = "4")]
+        DoubleValue(f64),
+        #[prost(message, tag = "5")]
+        ArrayValue(super::ArrayValue),
+        #[prost(message, tag = "6")]
+        KvlistValue(super::KeyValueList),
+        #[serde(deserialize_with = "json::base64")]
+        #[prost(bytes, tag = "7")]
+        BytesValue(Vec<u8>),
+    }
+}
+
+#[derive(Clone, PartialEq, Deserialize, prost::Message)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ArrayValue {
+    #[prost(message, repeated, tag = "1")]
+    pub values: Vec<AnyValue>,
+}
+
+#[derive(Clone, PartialEq, Deserialize, prost::Message)]
+#[serde(rename_all = "camelCase", default)]
+pub struct KeyValueList {
+    #[prost(message, repeated, tag = "1")]
+    pub values: Vec<KeyValue>,
+}
+
+#[derive(Clone, PartialEq, Deserialize, prost::Message)]
+#[serde(rename_all = "camelCase", default)]
+pub struct KeyValue {
+    #[prost(string, tag = "1")]
+    pub key: String,
+    #[prost(message, optional, tag = "2")]
+    pub value: Option<AnyValue>,
+}
+
+#[derive(Clone, PartialEq, Deserialize, prost::Message)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Resource {
+    #[prost(message, repeated, tag = "1")]
+    pub attributes: Vec<KeyValue>,
+}
+
+#[derive(Clone, PartialEq, Deserialize, prost::Message)]
+#[serde(rename_all = "camelCase", default)]
+pub struct InstrumentationScope {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub version: String,
+    #[prost(message, repeated, tag = "3")]
+    pub attributes: Vec<KeyValue>,
+}
+
+#[derive(Clone, PartialEq, Deserialize, prost::Message)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ResourceLogs {
+    #[prost(message, optional, tag = "1")]
+    pub resource: Option<Resource>,
+    #[prost(message, repeated, tag = "2")]
+    pub scope_logs: Vec<ScopeLogs>,
+}
+
+#[derive(Clone, PartialEq, Deserialize, prost::Message)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ScopeLogs {
+    #[prost(message, optional, tag = "1")]
+    pub scope: Option<InstrumentationScope>,
+    #[prost(message, repeated, tag = "2")]
+    pub log_records: Vec<LogRecord>,
+}
+
+#[derive(Clone, PartialEq, Deserialize, prost::Message)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LogRecord {
+    #[serde(deserialize_with = "json::integer")]
+    #[prost(fixed64, tag = "1")]
+    pub time_unix_nano: u64,
+    #[serde(deserialize_with = "json::integer")]
+    #[prost(fixed64, tag = "11")]
+    pub observed_time_unix_nano: u64,
+    #[prost(int32, tag = "2")]
+    pub severity_number: i32,
+    #[prost(string, tag = "3")]
+    pub severity_text: String,
+    #[prost(message, optional, tag = "5")]
+    pub body: Option<AnyValue>,
+    #[prost(message, repeated, tag = "6")]
+    pub attributes: Vec<KeyValue>,
+    #[prost(uint32, tag = "7")]
+    pub dropped_attributes_count: u32,
+    #[prost(fixed32, tag = "8")]
+    pub flags: u32,
+    #[serde(deserialize_with = "json::hex")]
+    #[prost(bytes, tag = "9")]
+    pub trace_id: Vec<u8>,
+    #[serde(deserialize_with = "json::hex")]
+    #[prost(bytes, tag = "10")]
+    pub span_id: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Deserialize, prost::Message)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ResourceMetrics {
+    #[prost(message, optional, tag = "1")]
+    pub resource: Option<Resource>,
+    #[prost(message, repeated, tag = "2")]
+    pub scope_metrics: Vec<ScopeMetrics>,
+}
+
+#[derive(Clone, PartialEq, Deserialize, prost::Message)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ScopeMetrics {
+    #[prost(message, optional, tag = "1")]
+    pub scope: Option<InstrumentationScope>,
+    #[prost(message, repeated, tag = "2")]
+    pub metrics: Vec<Metric>,
+}
+
+#[derive(Clone, PartialEq, Deserialize, prost::Message)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Metric {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub description: String,
+    #[prost(string, tag = "3")]
+    pub unit: String,
+    #[serde(flatten)]
+    #[prost(oneof = "metric::Data", tags = "5, 7, 9, 10, 11")]
+    pub data: Option<metric::Data>,
+}
+
+pub mod metric {
+    use serde::Deserialize;
+
+    #[derive(Clone, PartialEq, Deserialize, prost::Oneof)]
+    #[serde(rename_all = "camelCase")]
+    pub enum Data {
+        #[prost(message, tag = "5")]
+        Gauge(super::Gauge),
+        #[prost(message, tag = "7")]
+        Sum(super::Sum),
+        #[prost(message, tag = "9")]
+        Histogram(super::Histogram),
+        /// Only recognized so they can be reported as unsupported.
+        #[prost(message, tag = "10")]
+        ExponentialHistogram(super::ExponentialHistogram),
+        #[prost(message, tag = "11")]
+        Summary(super::Summary),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum AggregationTemporality {
+    Unspecified = 0,
+    Delta = 1,
+    Cumulative = 2,
+}
+
+#[derive(Clone, PartialEq, Deserialize, prost::Message)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Gauge {
+    #[prost(message, repeated, tag = "1")]
+    pub data_points: Vec<NumberDataPoint>,
+}
+
+#[derive(Clone, PartialEq, Deserialize, prost::Message)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Sum {
+    #[prost(message, repeated, tag = "1")]
+    pub data_points: Vec<NumberDataPoint>,
+    #[prost(enumeration = "AggregationTemporality", tag = "2")]
+    pub aggregation_temporality: i32,
+    #[prost(bool, tag = "3")]
+    pub is_monotonic: bool,
+}
+
+#[derive(Clone, PartialEq, Deserialize, prost::Message)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Histogram {
+    #[prost(message, repeated, tag = "1")]
+    pub data_points: Vec<HistogramDataPoint>,
+    #[prost(enumeration = "AggregationTemporality", tag = "2")]
+    pub aggregation_temporality: i32,
+}
+
+#[derive(Clone, PartialEq, Deserialize, prost::Message)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ExponentialHistogram {
+    #[prost(enumeration = "AggregationTemporality", tag = "2")]
+    pub aggregation_temporality: i32,
+}
+
+#[derive(Clone, PartialEq, Deserialize, prost::Message)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Summary {
+    #[prost(message, repeated, tag = "1")]
+    pub data_points: Vec<SummaryDataPoint>,
+}
+
+#[derive(Clone, PartialEq, Deserialize, prost::Message)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NumberDataPoint {
+    #[prost(message, repeated, tag = "7")]
+    pub attributes: Vec<KeyValue>,
+    #[serde(deserialize_with = "json::integer")]
+    #[prost(fixed64, tag = "2")]
+    pub start_time_unix_nano: u64,
+    #[serde(deserialize_with = "json::integer")]
+    #[prost(fixed64, tag = "3")]
+    pub time_unix_nano: u64,
+    #[serde(flatten)]
+    #[prost(oneof = "number_data_point::Value", tags = "4, 6")]
+    pub value: Option<number_data_point::Value>,
+}
+
+pub mod number_data_point {
+    use super::json;
+    use serde::Deserialize;
+
+    #[derive(Clone, PartialEq, Deserialize, prost::Oneof)]
+    #[serde(rename_all = "camelCase")]
+    pub enum Value {
+        #[prost(double, tag = "4")]
+        AsDouble(f64),
+        #[serde(deserialize_with = "json::integer")]
+        #[prost(sfixed64, tag = "6")]
+        AsInt(i64),
+    }
+}
+
+#[derive(Clone, PartialEq, Deserialize, prost::Message)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HistogramDataPoint {
+    #[prost(message, repeated, tag = "9")]
+    pub attributes: Vec<KeyValue>,
+    #[serde(deserialize_with = "json::integer")]
+    #[prost(fixed64, tag = "2")]
+    pub start_time_unix_nano: u64,
+    #[serde(deserialize_with = "json::integer")]
+    #[prost(fixed64, tag = "3")]
+    pub time_unix_nano: u64,
+    #[serde(deserialize_with = "json::integer")]
+    #[prost(fixed64, tag = "4")]
+    pub count: u64,
+    #[prost(double, optional, tag = "5")]
+    pub sum: Option<f64>,
+    /// The count of each bucket, with one more bucket than bounds for the
+    /// values above the last bound.
+    #[serde(deserialize_with = "json::integers")]
+    #[prost(fixed64, repeated, tag = "6")]
+    pub bucket_counts: Vec<u64>,
+    #[prost(double, repeated, tag = "7")]
+    pub explicit_bounds: Vec<f64>,
+}
+
+#[derive(Clone, PartialEq, Deserialize, prost::Message)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SummaryDataPoint {
+    #[prost(message, repeated, tag = "7")]
+    pub attributes: Vec<KeyValue>,
+    #[serde(deserialize_with = "json::integer")]
+    #[prost(fixed64, tag = "2")]
+    pub start_time_unix_nano: u64,
+    #[serde(deserialize_with = "json::integer")]
+    #[prost(fixed64, tag = "3")]
+    pub time_unix_nano: u64,
+    #[serde(deserialize_with = "json::integer")]
+    #[prost(fixed64, tag = "4")]
+    pub count: u64,
+    #[prost(double, tag = "5")]
+    pub sum: f64,
+    #[prost(message, repeated, tag = "6")]
+    pub quantile_values: Vec<ValueAtQuantile>,
+}
+
+#[derive(Clone, PartialEq, Deserialize, prost::Message)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ValueAtQuantile {
+    #[prost(double, tag = "1")]
+    pub quantile: f64,
+    #[prost(double, tag = "2")]
+    pub value: f64,
+}
+
+/// Deserializers for the fields the OTLP/JSON mapping doesn't write as their
+/// natural JSON type.
+mod json {
+    use serde::{de, Deserialize, Deserializer};
+    use std::{fmt::Display, str::FromStr};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Integer<T> {
+        Number(T),
+        String(String),
+    }
+
+    impl<T> Integer<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        fn parse<E: de::Error>(self) -> Result<T, E> {
+            match self {
+                Integer::Number(n) => Ok(n),
+                Integer::String(s) => s.parse().map_err(E::custom),
+            }
+        }
+    }
+
+    /// 64-bit integers are written as strings, but may be numbers.
+    pub fn integer<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de> + FromStr,
+        T::Err: Display,
+    {
+        Integer::<T>::deserialize(deserializer)?.parse()
+    }
+
+    pub fn integers<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de> + FromStr,
+        T::Err: Display,
+    {
+        Vec::<Integer<T>>::deserialize(deserializer)?
+            .into_iter()
+            .map(Integer::parse)
+            .collect()
+    }
+
+    /// Trace and span ids are hex strings.
+    pub fn hex<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        super::super::decode_hex(&s).ok_or_else(|| de::Error::custom("invalid hex string"))
+    }
+
+    /// Other bytes are base64 strings.
+    pub fn base64<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        base64::decode(&s).map_err(de::Error::custom)
+    }
+}
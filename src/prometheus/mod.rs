@@ -0,0 +1,11 @@
+//! The remote write protocol of Prometheus, which pushes samples as
+//! snappy compressed Protobuf `WriteRequest`s.
+
+pub mod proto;
+
+/// The label holding the name of the metric of a series.
+pub const METRIC_NAME_LABEL: &str = "__name__";
+
+/// The version of the protocol sent in the `X-Prometheus-Remote-Write-Version`
+/// header.
+pub const REMOTE_WRITE_VERSION: &str = "0.1.0";
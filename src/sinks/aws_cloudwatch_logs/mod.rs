@@ -7,7 +7,7 @@ use crate::{
     sinks::util::{
         encoding::{EncodingConfig, EncodingConfiguration},
         retries::{FixedRetryPolicy, RetryLogic},
-        rusoto, BatchConfig, BatchSettings, Compression, Length, PartitionBatchSink,
+        rusoto, BatchConfig, BatchSettings, Compression, EncodeSink, Length, PartitionBatchSink,
         PartitionBuffer, PartitionInnerBuffer, TowerRequestConfig, TowerRequestSettings,
         VecBuffer2,
     },
@@ -17,7 +17,7 @@ use crate::{
 use bytes::Bytes;
 use chrono::{Duration, Utc};
 use futures::future::{FutureExt, TryFutureExt};
-use futures01::{stream::iter_ok, sync::oneshot, Async, Future, Poll, Sink};
+use futures01::{sync::oneshot, Async, Future, Poll, Sink};
 use lazy_static::lazy_static;
 use rusoto_core::{request::BufferedHttpResponse, RusotoError};
 use rusoto_logs::{
@@ -182,11 +182,11 @@ impl SinkConfig for CloudwatchLogsSinkConfig {
         let encoding = self.encoding.clone();
         let sink = {
             let buffer = PartitionBuffer::new(VecBuffer2::new(batch.size));
-            let svc_sink = PartitionBatchSink::new(svc, buffer, batch.timeout, cx.acker())
-                .sink_map_err(|e| error!("Fatal cloudwatchlogs sink error: {}", e))
-                .with_flat_map(move |event| {
-                    iter_ok(partition_encode(event, &encoding, &log_group, &log_stream))
-                });
+            let svc_sink = PartitionBatchSink::new(svc, buffer, batch.timeout, cx.acker());
+            let svc_sink = EncodeSink::new(svc_sink, move |event| {
+                partition_encode(event, &encoding, &log_group, &log_stream)
+            })
+            .sink_map_err(|e| error!("Fatal cloudwatchlogs sink error: {}", e));
             Box::new(svc_sink)
         };
 
@@ -1202,7 +1202,7 @@ mod integration_tests {
             })
             .collect::<Vec<_>>();
 
-        let pump = sink.send_all(iter_ok(events));
+        let pump = sink.send_all(stream::iter_ok(events));
         let (sink, _) = rt.block_on(pump).unwrap();
         let sink = rt.block_on(sink.flush()).unwrap();
         // drop the sink so it closes all its connections
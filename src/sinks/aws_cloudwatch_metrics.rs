@@ -1,10 +1,14 @@
 use crate::{
     dns::Resolver,
     event::metric::{Metric, MetricKind, MetricValue},
+    event::Event,
     region::RegionOrEndpoint,
     sinks::util::{
-        retries2::RetryLogic, rusoto, service2::TowerRequestConfig, BatchConfig, BatchSettings,
-        Compression, MetricBuffer,
+        retries2::RetryLogic,
+        rusoto,
+        service2::{ServiceBuilderExt, TowerCompat, TowerRequestConfig},
+        BatchConfig, BatchSettings, Compression, EncodeSink, MetricBuffer, PartitionBatchSink,
+        PartitionBuffer, PartitionInnerBuffer,
     },
     topology::config::{DataType, SinkConfig, SinkContext, SinkDescription},
 };
@@ -14,13 +18,14 @@ use futures01::Sink;
 use lazy_static::lazy_static;
 use rusoto_cloudwatch::{
     CloudWatch, CloudWatchClient, Dimension, MetricDatum, PutMetricDataError, PutMetricDataInput,
+    StatisticSet,
 };
 use rusoto_core::{Region, RusotoError};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::convert::TryInto;
 use std::task::{Context, Poll};
-use tower03::Service;
+use tower03::{Service, ServiceBuilder};
 
 #[derive(Clone)]
 pub struct CloudWatchMetricsSvc {
@@ -124,17 +129,21 @@ impl CloudWatchMetricsSvc {
             .get_settings_or_default(BatchSettings::default().events(20).timeout(1));
         let request = config.request.unwrap_with(&REQUEST_DEFAULTS);
 
+        let default_namespace = config.namespace.clone();
         let cloudwatch_metrics = CloudWatchMetricsSvc { client, config };
 
-        let sink = request
-            .batch_sink(
-                CloudWatchMetricsRetryLogic,
-                cloudwatch_metrics,
-                MetricBuffer::new(batch.size),
-                batch.timeout,
-                cx.acker(),
-            )
-            .sink_map_err(|e| error!("CloudwatchMetrics sink error: {}", e));
+        let svc = ServiceBuilder::new()
+            .settings(request, CloudWatchMetricsRetryLogic)
+            .service(cloudwatch_metrics);
+
+        let buffer = PartitionBuffer::new(MetricBuffer::new(batch.size));
+
+        let sink =
+            PartitionBatchSink::new(TowerCompat::new(svc), buffer, batch.timeout, cx.acker());
+        let sink = EncodeSink::new(sink, move |event| {
+            Some(partition_encode(event, &default_namespace))
+        })
+        .sink_map_err(|e| error!("CloudwatchMetrics sink error: {}", e));
 
         Ok(Box::new(sink))
     }
@@ -173,6 +182,18 @@ impl CloudWatchMetricsSvc {
                             dimensions,
                             ..Default::default()
                         }),
+                        MetricValue::Sketch(sketch) => Some(MetricDatum {
+                            metric_name,
+                            statistic_values: Some(StatisticSet {
+                                maximum: sketch.max()?,
+                                minimum: sketch.min()?,
+                                sample_count: sketch.count() as f64,
+                                sum: sketch.sum(),
+                            }),
+                            timestamp,
+                            dimensions,
+                            ..Default::default()
+                        }),
                         _ => None,
                     },
                     MetricKind::Absolute => match event.value {
@@ -198,7 +219,7 @@ impl CloudWatchMetricsSvc {
     }
 }
 
-impl Service<Vec<Metric>> for CloudWatchMetricsSvc {
+impl Service<PartitionInnerBuffer<Vec<Metric>, String>> for CloudWatchMetricsSvc {
     type Response = ();
     type Error = RusotoError<PutMetricDataError>;
     type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
@@ -207,18 +228,20 @@ impl Service<Vec<Metric>> for CloudWatchMetricsSvc {
         Poll::Ready(Ok(()))
     }
 
-    fn call(&mut self, items: Vec<Metric>) -> Self::Future {
+    fn call(&mut self, items: PartitionInnerBuffer<Vec<Metric>, String>) -> Self::Future {
+        let (items, namespace) = items.into_parts();
+        let input = PutMetricDataInput {
+            namespace,
+            ..self.encode_events(items)
+        };
+
+        if input.metric_data.is_empty() {
+            return Box::pin(futures::future::ok(()));
+        }
+
+        debug!(message = "sending data.", ?input);
         let client = self.client.clone();
-        let input = self.encode_events(items);
-
-        Box::pin(async move {
-            if input.metric_data.is_empty() {
-                Ok(())
-            } else {
-                debug!(message = "sending data.", ?input);
-                client.put_metric_data(input).await
-            }
-        })
+        Box::pin(async move { client.put_metric_data(input).await })
     }
 }
 
@@ -244,6 +267,18 @@ impl RetryLogic for CloudWatchMetricsRetryLogic {
     }
 }
 
+/// Batches metrics by their namespace, falling back to the configured one, as
+/// each request only carries a single namespace.
+fn partition_encode(event: Event, default_namespace: &str) -> PartitionInnerBuffer<Event, String> {
+    let namespace = event
+        .as_metric()
+        .namespace
+        .as_deref()
+        .unwrap_or(default_namespace)
+        .to_string();
+    PartitionInnerBuffer::new(event, namespace)
+}
+
 fn timestamp_to_string(timestamp: DateTime<Utc>) -> String {
     timestamp.to_rfc3339_opts(SecondsFormat::Millis, true)
 }
@@ -263,7 +298,10 @@ fn tags_to_dimensions(tags: BTreeMap<String, String>) -> Vec<Dimension> {
 mod tests {
     use super::*;
     use crate::dns::Resolver;
-    use crate::event::metric::{Metric, MetricKind, MetricValue};
+    use crate::event::{
+        metric::{Metric, MetricKind, MetricValue},
+        sketch::DDSketch,
+    };
     use chrono::offset::TimeZone;
     use pretty_assertions::assert_eq;
     use rusoto_cloudwatch::PutMetricDataInput;
@@ -288,6 +326,7 @@ mod tests {
         let events = vec![
             Metric {
                 name: "exception_total".into(),
+                namespace: None,
                 timestamp: None,
                 tags: None,
                 kind: MetricKind::Incremental,
@@ -295,6 +334,7 @@ mod tests {
             },
             Metric {
                 name: "bytes_out".into(),
+                namespace: None,
                 timestamp: Some(Utc.ymd(2018, 11, 14).and_hms_nano(8, 9, 10, 123456789)),
                 tags: None,
                 kind: MetricKind::Incremental,
@@ -302,6 +342,7 @@ mod tests {
             },
             Metric {
                 name: "healthcheck".into(),
+                namespace: None,
                 timestamp: Some(Utc.ymd(2018, 11, 14).and_hms_nano(8, 9, 10, 123456789)),
                 tags: Some(
                     vec![("region".to_owned(), "local".to_owned())]
@@ -348,6 +389,7 @@ mod tests {
     fn encode_events_absolute_gauge() {
         let events = vec![Metric {
             name: "temperature".into(),
+            namespace: None,
             timestamp: None,
             tags: None,
             kind: MetricKind::Absolute,
@@ -371,6 +413,7 @@ mod tests {
     fn encode_events_distribution() {
         let events = vec![Metric {
             name: "latency".into(),
+            namespace: None,
             timestamp: None,
             tags: None,
             kind: MetricKind::Incremental,
@@ -394,10 +437,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn encode_events_sketch() {
+        let events = vec![Metric {
+            name: "latency".into(),
+            namespace: None,
+            timestamp: None,
+            tags: None,
+            kind: MetricKind::Incremental,
+            value: MetricValue::Sketch(DDSketch::from_distribution(
+                &[11.0, 12.0],
+                &[100, 50],
+                0.01,
+            )),
+        }];
+
+        assert_eq!(
+            svc().encode_events(events),
+            PutMetricDataInput {
+                namespace: "vector".into(),
+                metric_data: vec![MetricDatum {
+                    metric_name: "latency".into(),
+                    statistic_values: Some(StatisticSet {
+                        maximum: 12.0,
+                        minimum: 11.0,
+                        sample_count: 150.0,
+                        sum: 1700.0,
+                    }),
+                    ..Default::default()
+                }],
+            }
+        );
+    }
+
     #[test]
     fn encode_events_set() {
         let events = vec![Metric {
             name: "users".into(),
+            namespace: None,
             timestamp: None,
             tags: None,
             kind: MetricKind::Incremental,
@@ -418,6 +495,41 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn partitions_events_by_namespace() {
+        let metric = |name: &str, namespace: Option<&str>| Metric {
+            name: name.into(),
+            namespace: namespace.map(Into::into),
+            timestamp: None,
+            tags: None,
+            kind: MetricKind::Incremental,
+            value: MetricValue::Counter { value: 1.0 },
+        };
+        let events = vec![
+            metric("a", None),
+            metric("b", Some("app")),
+            metric("c", Some("vector")),
+        ];
+
+        let partitions = events
+            .into_iter()
+            .map(|metric| {
+                let (event, namespace) =
+                    partition_encode(Event::Metric(metric), "vector").into_parts();
+                (event.into_metric().name, namespace)
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            partitions,
+            vec![
+                ("a".to_string(), "vector".to_string()),
+                ("b".to_string(), "app".to_string()),
+                ("c".to_string(), "vector".to_string()),
+            ]
+        );
+    }
 }
 
 #[cfg(feature = "aws-cloudwatch-metrics-integration-tests")]
@@ -460,6 +572,7 @@ mod integration_tests {
         for i in 0..100 {
             let event = Event::Metric(Metric {
                 name: format!("counter-{}", 0),
+                namespace: None,
                 timestamp: None,
                 tags: Some(
                     vec![
@@ -480,6 +593,7 @@ mod integration_tests {
         for i in 0..10 {
             let event = Event::Metric(Metric {
                 name: format!("gauge-{}", gauge_name),
+                namespace: None,
                 timestamp: None,
                 tags: None,
                 kind: MetricKind::Absolute,
@@ -492,6 +606,7 @@ mod integration_tests {
         for i in 0..10 {
             let event = Event::Metric(Metric {
                 name: format!("distribution-{}", distribution_name),
+                namespace: None,
                 timestamp: Some(Utc.ymd(2018, 11, 14).and_hms_nano(8, 9, 10, 123456789)),
                 tags: None,
                 kind: MetricKind::Incremental,
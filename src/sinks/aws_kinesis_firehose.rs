@@ -8,13 +8,13 @@ use crate::{
         rusoto,
         service2::TowerRequestConfig,
         sink::Response,
-        BatchConfig, BatchSettings, Compression, VecBuffer,
+        BatchConfig, BatchSettings, Compression, EncodeSink, VecBuffer,
     },
     topology::config::{DataType, SinkConfig, SinkContext, SinkDescription},
 };
 use bytes05::Bytes;
 use futures::{future::BoxFuture, FutureExt, TryFutureExt};
-use futures01::{stream::iter_ok, Sink};
+use futures01::Sink;
 use lazy_static::lazy_static;
 use rusoto_core::RusotoError;
 use rusoto_firehose::{
@@ -139,16 +139,15 @@ impl KinesisFirehoseService {
 
         let kinesis = KinesisFirehoseService { client, config };
 
-        let sink = request
-            .batch_sink(
-                KinesisFirehoseRetryLogic,
-                kinesis,
-                VecBuffer::new(batch.size),
-                batch.timeout,
-                cx.acker(),
-            )
-            .sink_map_err(|e| error!("Fatal kinesis firehose sink error: {}", e))
-            .with_flat_map(move |e| iter_ok(encode_event(e, &encoding)));
+        let sink = request.batch_sink(
+            KinesisFirehoseRetryLogic,
+            kinesis,
+            VecBuffer::new(batch.size),
+            batch.timeout,
+            cx.acker(),
+        );
+        let sink = EncodeSink::new(sink, move |e| encode_event(e, &encoding))
+            .sink_map_err(|e| error!("Fatal kinesis firehose sink error: {}", e));
 
         Ok(sink)
     }
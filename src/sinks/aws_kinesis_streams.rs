@@ -9,13 +9,13 @@ use crate::{
         rusoto,
         service2::TowerRequestConfig,
         sink::Response,
-        BatchConfig, BatchSettings, Compression, VecBuffer,
+        BatchConfig, BatchSettings, Compression, EncodeSink, VecBuffer,
     },
     topology::config::{DataType, SinkConfig, SinkContext, SinkDescription},
 };
 use bytes05::Bytes;
 use futures::{future::BoxFuture, FutureExt, TryFutureExt};
-use futures01::{stream::iter_ok, Sink};
+use futures01::Sink;
 use lazy_static::lazy_static;
 use rand::random;
 use rusoto_core::RusotoError;
@@ -147,16 +147,17 @@ impl KinesisService {
 
         let kinesis = KinesisService { client, config };
 
-        let sink = request
-            .batch_sink(
-                KinesisRetryLogic,
-                kinesis,
-                VecBuffer::new(batch.size),
-                batch.timeout,
-                cx.acker(),
-            )
-            .sink_map_err(|e| error!("Fatal kinesis streams sink error: {}", e))
-            .with_flat_map(move |e| iter_ok(encode_event(e, &partition_key_field, &encoding)));
+        let sink = request.batch_sink(
+            KinesisRetryLogic,
+            kinesis,
+            VecBuffer::new(batch.size),
+            batch.timeout,
+            cx.acker(),
+        );
+        let sink = EncodeSink::new(sink, move |e| {
+            encode_event(e, &partition_key_field, &encoding)
+        })
+        .sink_map_err(|e| error!("Fatal kinesis streams sink error: {}", e));
 
         Ok(sink)
     }
@@ -9,8 +9,8 @@ use crate::{
         rusoto,
         service2::{ServiceBuilderExt, TowerCompat, TowerRequestConfig},
         sink::Response,
-        BatchConfig, BatchSettings, Buffer, Compression, PartitionBatchSink, PartitionBuffer,
-        PartitionInnerBuffer,
+        BatchConfig, BatchSettings, Buffer, Compression, EncodeSink, PartitionBatchSink,
+        PartitionBuffer, PartitionInnerBuffer,
     },
     template::Template,
     topology::config::{DataType, SinkConfig, SinkContext, SinkDescription},
@@ -18,7 +18,7 @@ use crate::{
 use bytes05::Bytes;
 use chrono::Utc;
 use futures::{future::BoxFuture, FutureExt, TryFutureExt};
-use futures01::{stream::iter_ok, Sink};
+use futures01::Sink;
 use http::StatusCode;
 use lazy_static::lazy_static;
 use rusoto_core::RusotoError;
@@ -209,9 +209,9 @@ impl S3SinkConfig {
         let buffer = PartitionBuffer::new(Buffer::new(batch.size, self.compression));
 
         let sink =
-            PartitionBatchSink::new(TowerCompat::new(svc), buffer, batch.timeout, cx.acker())
-                .with_flat_map(move |e| iter_ok(encode_event(e, &key_prefix, &encoding)))
-                .sink_map_err(|error| error!("Sink failed to flush: {}", error));
+            PartitionBatchSink::new(TowerCompat::new(svc), buffer, batch.timeout, cx.acker());
+        let sink = EncodeSink::new(sink, move |e| encode_event(e, &key_prefix, &encoding))
+            .sink_map_err(|error| error!("Sink failed to flush: {}", error));
 
         Ok(Box::new(sink))
     }
@@ -77,6 +77,7 @@ impl SinkConfig for ClickhouseConfig {
             client.clone(),
             cx.acker(),
         )
+        .dead_letter(cx.dead_letter())
         .sink_map_err(|e| error!("Fatal clickhouse sink error: {}", e));
 
         let healthcheck = healthcheck(client, self.clone()).boxed().compat();
@@ -91,6 +92,10 @@ impl SinkConfig for ClickhouseConfig {
     fn sink_type(&self) -> &'static str {
         "clickhouse"
     }
+
+    fn supports_dead_letter(&self) -> bool {
+        true
+    }
 }
 
 #[async_trait::async_trait]
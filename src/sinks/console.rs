@@ -1,9 +1,7 @@
 use crate::{
-    event::{self, Event},
-    sinks::util::{
-        encoding::{EncodingConfig, EncodingConfiguration},
-        StreamSink,
-    },
+    buffers::Acker,
+    event::{self, BatchStatus, Event},
+    sinks::util::encoding::{EncodingConfig, EncodingConfiguration},
     topology::config::{DataType, SinkConfig, SinkContext, SinkDescription},
 };
 use async_trait::async_trait;
@@ -57,9 +55,12 @@ impl SinkConfig for ConsoleSinkConfig {
             Target::Stderr => Box::new(io::stderr()),
         };
 
-        let sink = WriterSink { output, encoding };
+        let sink = WriterSink {
+            output,
+            encoding,
+            acker: cx.acker(),
+        };
         let sink = streaming_sink::compat::adapt_to_topology(sink);
-        let sink = StreamSink::new(sink, cx.acker());
 
         Ok((Box::new(sink), Box::new(future::ok(()))))
     }
@@ -102,12 +103,15 @@ async fn write_event_to_output(
         encode_event(event, encoding).map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
     buf.push('\n');
     output.write_all(buf.as_bytes()).await?;
-    Ok(())
+    // Writes complete in the background, flushing waits for them so the event
+    // is only acked once written.
+    output.flush().await
 }
 
 struct WriterSink {
     output: Box<dyn io::AsyncWrite + Send + Sync + Unpin>,
     encoding: EncodingConfig<Encoding>,
+    acker: Acker,
 }
 
 #[async_trait]
@@ -120,7 +124,11 @@ impl StreamingSink for WriterSink {
         pin_mut!(output);
         pin_mut!(input);
         while let Some(event) = input.next().await {
-            write_event_to_output(&mut output, event, &self.encoding).await?
+            if let Err(error) = write_event_to_output(&mut output, event, &self.encoding).await {
+                self.acker.ack_with_status(1, BatchStatus::Failed);
+                return Err(error.into());
+            }
+            self.acker.ack(1);
         }
         Ok(())
     }
@@ -159,6 +167,7 @@ mod test {
     fn encodes_counter() {
         let event = Event::Metric(Metric {
             name: "foos".into(),
+            namespace: None,
             timestamp: Some(Utc.ymd(2018, 11, 14).and_hms_nano(8, 9, 10, 11)),
             tags: Some(
                 vec![
@@ -182,6 +191,7 @@ mod test {
     fn encodes_set() {
         let event = Event::Metric(Metric {
             name: "users".into(),
+            namespace: None,
             timestamp: None,
             tags: None,
             kind: MetricKind::Incremental,
@@ -199,6 +209,7 @@ mod test {
     fn encodes_histogram_without_timestamp() {
         let event = Event::Metric(Metric {
             name: "glork".into(),
+            namespace: None,
             timestamp: None,
             tags: None,
             kind: MetricKind::Incremental,
@@ -4,13 +4,12 @@ use crate::{
         self,
         encoding::{EncodingConfig, EncodingConfiguration},
         tcp::TcpSink,
-        Encoding, UriSerde,
+        EncodeSink, Encoding, StreamSink, UriSerde,
     },
     tls::{MaybeTlsSettings, TlsConfig},
     topology::config::{DataType, SinkConfig, SinkContext, SinkDescription},
 };
 use bytes::Bytes;
-use futures01::{stream::iter_ok, Sink};
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -56,8 +55,8 @@ impl SinkConfig for DatadogLogsConfig {
         let encoding = self.encoding.clone();
         let api_key = Bytes::from(format!("{} ", self.api_key));
 
-        let sink =
-            sink.with_flat_map(move |e| iter_ok(encode_event(e, api_key.clone(), &encoding)));
+        let sink = StreamSink::new(sink, cx.acker());
+        let sink = EncodeSink::new(sink, move |e| encode_event(e, api_key.clone(), &encoding));
 
         Ok((Box::new(sink), Box::new(healthcheck)))
     }
@@ -1,6 +1,7 @@
 use crate::{
     event::{
         metric::{Metric, MetricKind, MetricValue},
+        sketch::DDSketch,
         Event,
     },
     sinks::util::{
@@ -133,6 +134,7 @@ impl SinkConfig for DatadogConfig {
             client,
             cx.acker(),
         )
+        .dead_letter(cx.dead_letter())
         .sink_map_err(|e| error!("Fatal datadog error: {}", e));
 
         Ok((Box::new(sink), Box::new(healthcheck)))
@@ -145,6 +147,10 @@ impl SinkConfig for DatadogConfig {
     fn sink_type(&self) -> &'static str {
         "datadog_metrics"
     }
+
+    fn supports_dead_letter(&self) -> bool {
+        true
+    }
 }
 
 #[async_trait::async_trait]
@@ -275,11 +281,83 @@ fn stats(values: &[f64], counts: &[u32]) -> Option<DatadogStats> {
     })
 }
 
+/// Expands the statistics of a distribution into one series per statistic.
+fn encode_stats(
+    fullname: &str,
+    s: DatadogStats,
+    ts: i64,
+    interval: i64,
+    tags: Option<Vec<String>>,
+) -> Vec<DatadogMetric> {
+    // https://docs.datadoghq.com/developers/metrics/metrics_type/?tab=histogram#metric-type-definition
+    let mut result = vec![
+        DatadogMetric {
+            metric: format!("{}.min", &fullname),
+            r#type: DatadogMetricType::Gauge,
+            interval: Some(interval),
+            points: vec![DatadogPoint(ts, s.min)],
+            tags: tags.clone(),
+        },
+        DatadogMetric {
+            metric: format!("{}.avg", &fullname),
+            r#type: DatadogMetricType::Gauge,
+            interval: Some(interval),
+            points: vec![DatadogPoint(ts, s.avg)],
+            tags: tags.clone(),
+        },
+        DatadogMetric {
+            metric: format!("{}.count", &fullname),
+            r#type: DatadogMetricType::Rate,
+            interval: Some(interval),
+            points: vec![DatadogPoint(ts, s.count)],
+            tags: tags.clone(),
+        },
+        DatadogMetric {
+            metric: format!("{}.median", &fullname),
+            r#type: DatadogMetricType::Gauge,
+            interval: Some(interval),
+            points: vec![DatadogPoint(ts, s.median)],
+            tags: tags.clone(),
+        },
+        DatadogMetric {
+            metric: format!("{}.max", &fullname),
+            r#type: DatadogMetricType::Gauge,
+            interval: Some(interval),
+            points: vec![DatadogPoint(ts, s.max)],
+            tags: tags.clone(),
+        },
+    ];
+    for (q, v) in s.quantiles {
+        result.push(DatadogMetric {
+            metric: format!("{}.{}percentile", &fullname, (q * 100.0) as u32),
+            r#type: DatadogMetricType::Gauge,
+            interval: Some(interval),
+            points: vec![DatadogPoint(ts, v)],
+            tags: tags.clone(),
+        })
+    }
+    result
+}
+
+fn sketch_stats(sketch: &DDSketch) -> Option<DatadogStats> {
+    let count = sketch.count() as f64;
+    Some(DatadogStats {
+        min: sketch.min()?,
+        max: sketch.max()?,
+        median: sketch.quantile(0.5)?,
+        avg: sketch.sum() / count,
+        sum: sketch.sum(),
+        count,
+        quantiles: vec![(0.95, sketch.quantile(0.95)?)],
+    })
+}
+
 fn encode_events(events: Vec<Metric>, interval: i64, namespace: &str) -> DatadogRequest {
     let series = events
         .into_iter()
         .filter_map(|event| {
-            let fullname = encode_namespace(namespace, &event.name);
+            let fullname =
+                encode_namespace(event.namespace.as_deref().unwrap_or(namespace), &event.name);
             let ts = encode_timestamp(event.timestamp);
             let tags = event.tags.clone().map(encode_tags);
             match event.kind {
@@ -294,64 +372,10 @@ fn encode_events(events: Vec<Metric>, interval: i64, namespace: &str) -> Datadog
                     MetricValue::Distribution {
                         values,
                         sample_rates,
-                    } => {
-                        // https://docs.datadoghq.com/developers/metrics/metrics_type/?tab=histogram#metric-type-definition
-                        if let Some(s) = stats(&values, &sample_rates) {
-                            let mut result = vec![
-                                DatadogMetric {
-                                    metric: format!("{}.min", &fullname),
-                                    r#type: DatadogMetricType::Gauge,
-                                    interval: Some(interval),
-                                    points: vec![DatadogPoint(ts, s.min)],
-                                    tags: tags.clone(),
-                                },
-                                DatadogMetric {
-                                    metric: format!("{}.avg", &fullname),
-                                    r#type: DatadogMetricType::Gauge,
-                                    interval: Some(interval),
-                                    points: vec![DatadogPoint(ts, s.avg)],
-                                    tags: tags.clone(),
-                                },
-                                DatadogMetric {
-                                    metric: format!("{}.count", &fullname),
-                                    r#type: DatadogMetricType::Rate,
-                                    interval: Some(interval),
-                                    points: vec![DatadogPoint(ts, s.count)],
-                                    tags: tags.clone(),
-                                },
-                                DatadogMetric {
-                                    metric: format!("{}.median", &fullname),
-                                    r#type: DatadogMetricType::Gauge,
-                                    interval: Some(interval),
-                                    points: vec![DatadogPoint(ts, s.median)],
-                                    tags: tags.clone(),
-                                },
-                                DatadogMetric {
-                                    metric: format!("{}.max", &fullname),
-                                    r#type: DatadogMetricType::Gauge,
-                                    interval: Some(interval),
-                                    points: vec![DatadogPoint(ts, s.max)],
-                                    tags: tags.clone(),
-                                },
-                            ];
-                            for (q, v) in s.quantiles {
-                                result.push(DatadogMetric {
-                                    metric: format!(
-                                        "{}.{}percentile",
-                                        &fullname,
-                                        (q * 100.0) as u32
-                                    ),
-                                    r#type: DatadogMetricType::Gauge,
-                                    interval: Some(interval),
-                                    points: vec![DatadogPoint(ts, v)],
-                                    tags: tags.clone(),
-                                })
-                            }
-                            Some(result)
-                        } else {
-                            None
-                        }
-                    }
+                    } => stats(&values, &sample_rates)
+                        .map(|s| encode_stats(&fullname, s, ts, interval, tags)),
+                    MetricValue::Sketch(sketch) => sketch_stats(&sketch)
+                        .map(|s| encode_stats(&fullname, s, ts, interval, tags)),
                     MetricValue::Set { values } => Some(vec![DatadogMetric {
                         metric: fullname,
                         r#type: DatadogMetricType::Gauge,
@@ -425,6 +449,7 @@ mod tests {
         let events = vec![
             Metric {
                 name: "total".into(),
+                namespace: None,
                 timestamp: None,
                 tags: None,
                 kind: MetricKind::Incremental,
@@ -432,6 +457,7 @@ mod tests {
             },
             Metric {
                 name: "check".into(),
+                namespace: None,
                 timestamp: Some(ts()),
                 tags: Some(tags()),
                 kind: MetricKind::Incremental,
@@ -439,6 +465,7 @@ mod tests {
             },
             Metric {
                 name: "unsupported".into(),
+                namespace: None,
                 timestamp: Some(ts()),
                 tags: Some(tags()),
                 kind: MetricKind::Absolute,
@@ -479,6 +506,7 @@ mod tests {
         let events = vec![
             Metric {
                 name: "total".into(),
+                namespace: None,
                 timestamp: None,
                 tags: None,
                 kind: MetricKind::Incremental,
@@ -486,6 +514,7 @@ mod tests {
             },
             Metric {
                 name: "check".into(),
+                namespace: None,
                 timestamp: Some(ts()),
                 tags: Some(tags()),
                 kind: MetricKind::Incremental,
@@ -493,6 +522,7 @@ mod tests {
             },
             Metric {
                 name: "unsupported".into(),
+                namespace: None,
                 timestamp: Some(ts()),
                 tags: Some(tags()),
                 kind: MetricKind::Absolute,
@@ -513,6 +543,7 @@ mod tests {
         let events = vec![
             Metric {
                 name: "unsupported".into(),
+                namespace: None,
                 timestamp: Some(ts()),
                 tags: None,
                 kind: MetricKind::Incremental,
@@ -520,6 +551,7 @@ mod tests {
             },
             Metric {
                 name: "volume".into(),
+                namespace: None,
                 timestamp: Some(ts()),
                 tags: None,
                 kind: MetricKind::Absolute,
@@ -539,6 +571,7 @@ mod tests {
     fn encode_set() {
         let events = vec![Metric {
             name: "users".into(),
+            namespace: None,
             timestamp: Some(ts()),
             tags: None,
             kind: MetricKind::Incremental,
@@ -645,6 +678,7 @@ mod tests {
         // https://docs.datadoghq.com/developers/metrics/metrics_type/?tab=histogram#metric-type-definition
         let events = vec![Metric {
             name: "requests".into(),
+            namespace: None,
             timestamp: Some(ts()),
             tags: None,
             kind: MetricKind::Incremental,
@@ -661,4 +695,20 @@ mod tests {
             r#"{"series":[{"metric":"requests.min","type":"gauge","interval":60,"points":[[1542182950,1.0]],"tags":null},{"metric":"requests.avg","type":"gauge","interval":60,"points":[[1542182950,1.875]],"tags":null},{"metric":"requests.count","type":"rate","interval":60,"points":[[1542182950,8.0]],"tags":null},{"metric":"requests.median","type":"gauge","interval":60,"points":[[1542182950,2.0]],"tags":null},{"metric":"requests.max","type":"gauge","interval":60,"points":[[1542182950,3.0]],"tags":null},{"metric":"requests.95percentile","type":"gauge","interval":60,"points":[[1542182950,3.0]],"tags":null}]}"#
         );
     }
+
+    #[test]
+    fn sketch_stats_match_distribution() {
+        let sketch = DDSketch::from_distribution(&[1.0, 2.0, 3.0], &[3, 3, 2], 0.01);
+        let s = sketch_stats(&sketch).unwrap();
+
+        assert_eq!(s.min, 1.0);
+        assert_eq!(s.max, 3.0);
+        assert_eq!(s.sum, 15.0);
+        assert_eq!(s.count, 8.0);
+        assert_eq!(s.avg, 1.875);
+        assert!((s.median - 2.0).abs() <= 0.02);
+        assert!((s.quantiles[0].1 - 3.0).abs() <= 0.03);
+
+        assert_eq!(sketch_stats(&DDSketch::new(0.01)), None);
+    }
 }
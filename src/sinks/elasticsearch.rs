@@ -128,6 +128,7 @@ impl SinkConfig for ElasticSearchConfig {
             client,
             cx.acker(),
         )
+        .dead_letter(cx.dead_letter())
         .sink_map_err(|e| error!("Fatal elasticsearch sink error: {}", e));
 
         Ok((Box::new(sink), Box::new(healthcheck)))
@@ -140,6 +141,10 @@ impl SinkConfig for ElasticSearchConfig {
     fn sink_type(&self) -> &'static str {
         "elasticsearch"
     }
+
+    fn supports_dead_letter(&self) -> bool {
+        true
+    }
 }
 
 #[derive(Debug)]
@@ -1,10 +1,9 @@
 use crate::expiring_hash_map::ExpiringHashMap;
 use crate::{
-    event::{self, Event},
-    sinks::util::{
-        encoding::{EncodingConfigWithDefault, EncodingConfiguration},
-        StreamSink,
-    },
+    buffers::Acker,
+    codecs::{self, SchemaConfig, SchemaEncoder, SchemaFormat},
+    event::{self, BatchStatus, Event},
+    sinks::util::encoding::{EncodingConfigWithDefault, EncodingConfiguration},
     template::Template,
     topology::config::{DataType, SinkConfig, SinkContext, SinkDescription},
 };
@@ -34,6 +33,7 @@ pub struct FileSinkConfig {
         skip_serializing_if = "crate::serde::skip_serializing_if_default"
     )]
     pub encoding: EncodingConfigWithDefault<Encoding>,
+    pub schema: Option<SchemaConfig>,
 }
 
 inventory::submit! {
@@ -45,6 +45,18 @@ inventory::submit! {
 pub enum Encoding {
     Text,
     Ndjson,
+    Avro,
+    Protobuf,
+}
+
+impl Encoding {
+    fn schema_format(&self) -> Option<SchemaFormat> {
+        match self {
+            Encoding::Text | Encoding::Ndjson => None,
+            Encoding::Avro => Some(SchemaFormat::Avro),
+            Encoding::Protobuf => Some(SchemaFormat::Protobuf),
+        }
+    }
 }
 
 impl Default for Encoding {
@@ -53,12 +65,29 @@ impl Default for Encoding {
     }
 }
 
+#[async_trait::async_trait]
 #[typetag::serde(name = "file")]
 impl SinkConfig for FileSinkConfig {
     fn build(&self, cx: SinkContext) -> crate::Result<(super::RouterSink, super::Healthcheck)> {
-        let sink = FileSink::new(&self);
+        let encoder = match self.required_schema()? {
+            Some((format, schema)) => Some(schema.build_local_encoder(format)?),
+            None => None,
+        };
+        let sink = FileSink::new(&self, encoder, cx.acker());
+        let sink = streaming_sink::compat::adapt_to_topology(sink);
+        Ok((Box::new(sink), Box::new(futures01::future::ok(()))))
+    }
+
+    async fn build_async(
+        &self,
+        cx: SinkContext,
+    ) -> crate::Result<(super::RouterSink, super::Healthcheck)> {
+        let encoder = match self.required_schema()? {
+            Some((format, schema)) => Some(schema.build_encoder(format).await?),
+            None => None,
+        };
+        let sink = FileSink::new(&self, encoder, cx.acker());
         let sink = streaming_sink::compat::adapt_to_topology(sink);
-        let sink = StreamSink::new(sink, cx.acker());
         Ok((Box::new(sink), Box::new(futures01::future::ok(()))))
     }
 
@@ -71,21 +100,31 @@ impl SinkConfig for FileSinkConfig {
     }
 }
 
+impl FileSinkConfig {
+    fn required_schema(&self) -> crate::Result<Option<(SchemaFormat, &SchemaConfig)>> {
+        codecs::required_schema(self.encoding.codec().schema_format(), &self.schema)
+    }
+}
+
 #[derive(Debug)]
 pub struct FileSink {
     path: Template,
     encoding: EncodingConfigWithDefault<Encoding>,
+    encoder: Option<SchemaEncoder>,
     idle_timeout: Duration,
     files: ExpiringHashMap<Bytes, File>,
+    acker: Acker,
 }
 
 impl FileSink {
-    pub fn new(config: &FileSinkConfig) -> Self {
+    pub fn new(config: &FileSinkConfig, encoder: Option<SchemaEncoder>, acker: Acker) -> Self {
         Self {
             path: config.path.clone(),
             encoding: config.encoding.clone(),
+            encoder,
             idle_timeout: Duration::from_secs(config.idle_timeout_secs.unwrap_or(30)),
             files: ExpiringHashMap::default(),
+            acker,
         }
     }
 
@@ -123,7 +162,12 @@ impl FileSink {
                             debug!(message = "Receiver exausted, terminating the processing loop.");
                             break;
                         }
-                        Some(event) => self.process_event(event).await,
+                        Some(event) => {
+                            // Events are acked once written, so their
+                            // sources only learn they were delivered then.
+                            let status = self.process_event(event).await;
+                            self.acker.ack_with_status(1, status);
+                        }
                     }
                 }
                 result = self.files.next_expired(), if !self.files.is_empty() => {
@@ -150,7 +194,8 @@ impl FileSink {
         Ok(())
     }
 
-    async fn process_event(&mut self, event: Event) {
+    /// Writes `event` to its file, returning whether it was written.
+    async fn process_event(&mut self, event: Event) -> BatchStatus {
         let path = match self.partition_event(&event) {
             Some(path) => path,
             None => {
@@ -158,7 +203,15 @@ impl FileSink {
                 // file.
                 // This is already logged at `partition_event`, so
                 // here we just skip the event.
-                return;
+                return BatchStatus::Failed;
+            }
+        };
+
+        let buf = match encode_event(&self.encoding, self.encoder.as_ref(), event) {
+            Ok(buf) => buf,
+            Err(error) => {
+                error!(message = "Failed to encode event; dropping event.", %error, rate_limit_secs = 30);
+                return BatchStatus::Failed;
             }
         };
 
@@ -177,7 +230,7 @@ impl FileSink {
                     // Maybe other events will work though! Just log
                     // the error and skip this event.
                     error!(message = "Unable to open the file.", ?path, %error);
-                    return;
+                    return BatchStatus::Failed;
                 }
             };
             self.files.insert_at(path.clone(), file, next_deadline);
@@ -185,8 +238,18 @@ impl FileSink {
         };
 
         trace!(message = "Writing an event to file.", ?path);
-        if let Err(error) = write_event_to_file(file, event, &self.encoding).await {
-            error!(message = "Failed to write file.", ?path, %error);
+        // Writes to the file complete in the background, flushing waits for
+        // them so that failures are reported for this event.
+        let result = match file.write_all(&buf).await {
+            Ok(()) => file.flush().await,
+            Err(error) => Err(error),
+        };
+        match result {
+            Ok(()) => BatchStatus::Delivered,
+            Err(error) => {
+                error!(message = "Failed to write file.", ?path, %error);
+                BatchStatus::Failed
+            }
         }
     }
 }
@@ -207,26 +270,30 @@ async fn open_file(path: impl AsRef<std::path::Path>) -> std::io::Result<File> {
         .await
 }
 
-pub fn encode_event(encoding: &EncodingConfigWithDefault<Encoding>, mut event: Event) -> Vec<u8> {
+/// Encodes an event as a line, or as a length-delimited record for schema
+/// codecs.
+pub fn encode_event(
+    encoding: &EncodingConfigWithDefault<Encoding>,
+    encoder: Option<&SchemaEncoder>,
+    mut event: Event,
+) -> crate::Result<Vec<u8>> {
     encoding.apply_rules(&mut event);
     let log = event.into_log();
-    match encoding.codec() {
-        Encoding::Ndjson => serde_json::to_vec(&log).expect("Unable to encode event as JSON."),
-        Encoding::Text => log
+    let mut buf = match (encoding.codec(), encoder) {
+        (Encoding::Ndjson, _) => serde_json::to_vec(&log).expect("Unable to encode event as JSON."),
+        (Encoding::Text, _) => log
             .get(&event::log_schema().message_key())
             .map(|v| v.to_string_lossy().into_bytes())
             .unwrap_or_default(),
-    }
-}
-
-async fn write_event_to_file(
-    file: &mut File,
-    event: Event,
-    encoding: &EncodingConfigWithDefault<Encoding>,
-) -> Result<(), std::io::Error> {
-    let mut buf = encode_event(encoding, event);
+        (Encoding::Avro, Some(encoder)) | (Encoding::Protobuf, Some(encoder)) => {
+            return Ok(codecs::length_delimited(&encoder.encode(log)?));
+        }
+        (Encoding::Avro, None) | (Encoding::Protobuf, None) => {
+            unreachable!("schema codecs are built with an encoder")
+        }
+    };
     buf.push(b'\n');
-    file.write_all(&buf[..]).await
+    Ok(buf)
 }
 
 #[async_trait]
@@ -262,9 +329,10 @@ mod tests {
             path: template.clone().try_into().unwrap(),
             idle_timeout_secs: None,
             encoding: Encoding::Text.into(),
+            schema: None,
         };
 
-        let mut sink = FileSink::new(&config);
+        let mut sink = FileSink::new(&config, None, Acker::Null);
         let (input, _) = random_lines_with_stream(100, 64);
 
         let events = stream::iter(input.clone().into_iter().map(Event::from));
@@ -295,9 +363,10 @@ mod tests {
             path: template.try_into().unwrap(),
             idle_timeout_secs: None,
             encoding: Encoding::Text.into(),
+            schema: None,
         };
 
-        let mut sink = FileSink::new(&config);
+        let mut sink = FileSink::new(&config, None, Acker::Null);
 
         let (mut input, _) = random_events_with_stream(32, 8);
         input[0].as_mut_log().insert("date", "2019-26-07");
@@ -378,9 +447,10 @@ mod tests {
             path: template.clone().try_into().unwrap(),
             idle_timeout_secs: Some(1),
             encoding: Encoding::Text.into(),
+            schema: None,
         };
 
-        let mut sink = FileSink::new(&config);
+        let mut sink = FileSink::new(&config, None, Acker::Null);
         let (mut input, _) = random_lines_with_stream(10, 64);
 
         let (mut tx, rx) = tokio::sync::mpsc::channel(1);
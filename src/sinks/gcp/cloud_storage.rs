@@ -8,8 +8,8 @@ use crate::{
             http::{HttpClient, HttpClientFuture},
             retries2::{RetryAction, RetryLogic},
             service2::{ServiceBuilderExt, TowerCompat, TowerRequestConfig},
-            BatchConfig, BatchSettings, Buffer, Compression, PartitionBatchSink, PartitionBuffer,
-            PartitionInnerBuffer,
+            BatchConfig, BatchSettings, Buffer, Compression, EncodeSink, PartitionBatchSink,
+            PartitionBuffer, PartitionInnerBuffer,
         },
         Healthcheck, RouterSink,
     },
@@ -20,7 +20,7 @@ use crate::{
 use bytes::Bytes;
 use chrono::Utc;
 use futures::{FutureExt, TryFutureExt};
-use futures01::{stream::iter_ok, Sink};
+use futures01::Sink;
 use http::{StatusCode, Uri};
 use hyper::{
     header::{HeaderName, HeaderValue},
@@ -226,9 +226,9 @@ impl GcsSink {
         let buffer = PartitionBuffer::new(Buffer::new(batch.size, config.compression));
 
         let sink =
-            PartitionBatchSink::new(TowerCompat::new(svc), buffer, batch.timeout, cx.acker())
-                .sink_map_err(|e| error!("Fatal gcs sink error: {}", e))
-                .with_flat_map(move |e| iter_ok(encode_event(e, &key_prefix, &encoding)));
+            PartitionBatchSink::new(TowerCompat::new(svc), buffer, batch.timeout, cx.acker());
+        let sink = EncodeSink::new(sink, move |e| encode_event(e, &key_prefix, &encoding))
+            .sink_map_err(|e| error!("Fatal gcs sink error: {}", e));
 
         Ok(Box::new(sink))
     }
@@ -92,6 +92,7 @@ impl SinkConfig for PubsubConfig {
             client,
             cx.acker(),
         )
+        .dead_letter(cx.dead_letter())
         .sink_map_err(|e| error!("Fatal gcp pubsub sink error: {}", e));
 
         Ok((Box::new(sink), Box::new(healthcheck)))
@@ -104,6 +105,10 @@ impl SinkConfig for PubsubConfig {
     fn sink_type(&self) -> &'static str {
         "gcp_pubsub"
     }
+
+    fn supports_dead_letter(&self) -> bool {
+        true
+    }
 }
 
 struct PubsubSink {
@@ -145,6 +145,7 @@ impl SinkConfig for StackdriverConfig {
             client,
             cx.acker(),
         )
+        .dead_letter(cx.dead_letter())
         .sink_map_err(|e| error!("Fatal stackdriver sink error: {}", e));
 
         Ok((Box::new(sink), Box::new(healthcheck)))
@@ -157,6 +158,10 @@ impl SinkConfig for StackdriverConfig {
     fn sink_type(&self) -> &'static str {
         "gcp_stackdriver_logs"
     }
+
+    fn supports_dead_letter(&self) -> bool {
+        true
+    }
 }
 
 #[async_trait::async_trait]
@@ -56,6 +56,7 @@ impl SinkConfig for HoneycombConfig {
             client.clone(),
             cx.acker(),
         )
+        .dead_letter(cx.dead_letter())
         .sink_map_err(|e| error!("Fatal honeycomb sink error: {}", e));
 
         let healthcheck = Box::new(Box::pin(healthcheck(self.clone(), client)).compat());
@@ -70,6 +71,10 @@ impl SinkConfig for HoneycombConfig {
     fn sink_type(&self) -> &'static str {
         "honeycomb"
     }
+
+    fn supports_dead_letter(&self) -> bool {
+        true
+    }
 }
 
 #[async_trait::async_trait]
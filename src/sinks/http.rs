@@ -1,4 +1,5 @@
 use crate::{
+    codecs::{self, SchemaConfig, SchemaEncoder, SchemaFormat},
     event::{self, Event},
     sinks::util::{
         encoding::{EncodingConfig, EncodingConfiguration},
@@ -20,6 +21,7 @@ use indexmap::IndexMap;
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, Snafu};
+use std::sync::Arc;
 
 #[derive(Debug, Snafu)]
 enum BuildError {
@@ -46,6 +48,7 @@ pub struct HttpSinkConfig {
     #[serde(default)]
     pub compression: Compression,
     pub encoding: EncodingConfig<Encoding>,
+    pub schema: Option<SchemaConfig>,
     #[serde(default)]
     pub batch: BatchConfig,
     #[serde(default)]
@@ -53,6 +56,14 @@ pub struct HttpSinkConfig {
     pub tls: Option<TlsOptions>,
 }
 
+/// The configuration of a running sink, along with the encoder built from
+/// its schema.
+#[derive(Clone, Debug)]
+struct HttpSinkWithSchema {
+    config: HttpSinkConfig,
+    encoder: Option<Arc<SchemaEncoder>>,
+}
+
 #[cfg(test)]
 fn default_config(e: Encoding) -> HttpSinkConfig {
     HttpSinkConfig {
@@ -64,6 +75,7 @@ fn default_config(e: Encoding) -> HttpSinkConfig {
         compression: Default::default(),
         batch: Default::default(),
         encoding: e.into(),
+        schema: Default::default(),
         request: Default::default(),
         tls: Default::default(),
     }
@@ -93,15 +105,69 @@ pub enum Encoding {
     Text,
     Ndjson,
     Json,
+    Avro,
+    Protobuf,
+}
+
+impl Encoding {
+    fn schema_format(&self) -> Option<SchemaFormat> {
+        match self {
+            Encoding::Text | Encoding::Ndjson | Encoding::Json => None,
+            Encoding::Avro => Some(SchemaFormat::Avro),
+            Encoding::Protobuf => Some(SchemaFormat::Protobuf),
+        }
+    }
 }
 
 inventory::submit! {
     SinkDescription::new_without_default::<HttpSinkConfig>("http")
 }
 
+#[async_trait::async_trait]
 #[typetag::serde(name = "http")]
 impl SinkConfig for HttpSinkConfig {
     fn build(&self, cx: SinkContext) -> crate::Result<(super::RouterSink, super::Healthcheck)> {
+        let encoder = match self.required_schema()? {
+            Some((format, schema)) => Some(schema.build_local_encoder(format)?),
+            None => None,
+        };
+        self.build_with_encoder(cx, encoder)
+    }
+
+    async fn build_async(
+        &self,
+        cx: SinkContext,
+    ) -> crate::Result<(super::RouterSink, super::Healthcheck)> {
+        let encoder = match self.required_schema()? {
+            Some((format, schema)) => Some(schema.build_encoder(format).await?),
+            None => None,
+        };
+        self.build_with_encoder(cx, encoder)
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "http"
+    }
+
+    fn supports_dead_letter(&self) -> bool {
+        true
+    }
+}
+
+impl HttpSinkConfig {
+    fn required_schema(&self) -> crate::Result<Option<(SchemaFormat, &SchemaConfig)>> {
+        codecs::required_schema(self.encoding.codec().schema_format(), &self.schema)
+    }
+
+    fn build_with_encoder(
+        &self,
+        cx: SinkContext,
+        encoder: Option<SchemaEncoder>,
+    ) -> crate::Result<(super::RouterSink, super::Healthcheck)> {
         validate_headers(&self.headers, &self.auth)?;
         let tls = TlsSettings::from_options(&self.tls)?;
         let client = HttpClient::new(cx.resolver(), tls)?;
@@ -118,13 +184,17 @@ impl SinkConfig for HttpSinkConfig {
         let request = config.request.unwrap_with(&REQUEST_DEFAULTS);
 
         let sink = BatchedHttpSink::new(
-            config,
+            HttpSinkWithSchema {
+                config,
+                encoder: encoder.map(Arc::new),
+            },
             Buffer::new(batch.size, compression),
             request,
             batch.timeout,
             client.clone(),
             cx.acker(),
         )
+        .dead_letter(cx.dead_letter())
         .sink_map_err(|e| error!("Fatal http sink error: {}", e));
 
         let sink = Box::new(sink);
@@ -139,26 +209,18 @@ impl SinkConfig for HttpSinkConfig {
             None => Ok((sink, Box::new(future::ok(())))),
         }
     }
-
-    fn input_type(&self) -> DataType {
-        DataType::Log
-    }
-
-    fn sink_type(&self) -> &'static str {
-        "http"
-    }
 }
 
 #[async_trait::async_trait]
-impl HttpSink for HttpSinkConfig {
+impl HttpSink for HttpSinkWithSchema {
     type Input = Vec<u8>;
     type Output = Vec<u8>;
 
     fn encode_event(&self, mut event: Event) -> Option<Self::Input> {
-        self.encoding.apply_rules(&mut event);
+        self.config.encoding.apply_rules(&mut event);
         let event = event.into_log();
 
-        let body = match &self.encoding.codec() {
+        let body = match &self.config.encoding.codec() {
             Encoding::Text => {
                 if let Some(v) = event.get(&event::log_schema().message_key()) {
                     let mut b = v.to_string_lossy().into_bytes();
@@ -188,19 +250,37 @@ impl HttpSink for HttpSinkConfig {
                 b.push(b',');
                 b
             }
+
+            Encoding::Avro | Encoding::Protobuf => {
+                let encoder = self
+                    .encoder
+                    .as_ref()
+                    .expect("schema codecs have an encoder");
+                match encoder.encode(event) {
+                    Ok(record) => codecs::length_delimited(&record),
+                    Err(error) => {
+                        warn!(
+                            message = "Failed to encode event; dropping event.",
+                            %error,
+                            rate_limit_secs = 30,
+                        );
+                        return None;
+                    }
+                }
+            }
         };
 
         Some(body)
     }
 
     async fn build_request(&self, mut body: Self::Output) -> crate::Result<http::Request<Vec<u8>>> {
-        let method = match &self.method.clone().unwrap_or(HttpMethod::Post) {
+        let method = match &self.config.method.clone().unwrap_or(HttpMethod::Post) {
             HttpMethod::Post => Method::POST,
             HttpMethod::Put => Method::PUT,
         };
-        let uri: Uri = self.uri.clone().into();
+        let uri: Uri = self.config.uri.clone().into();
 
-        let ct = match self.encoding.codec() {
+        let ct = match self.config.encoding.codec() {
             Encoding::Text => "text/plain",
             Encoding::Ndjson => "application/x-ndjson",
             Encoding::Json => {
@@ -209,6 +289,8 @@ impl HttpSink for HttpSinkConfig {
                 body.push(b']');
                 "application/json"
             }
+            Encoding::Avro => "avro/binary",
+            Encoding::Protobuf => "application/x-protobuf",
         };
 
         let mut builder = Request::builder()
@@ -216,11 +298,11 @@ impl HttpSink for HttpSinkConfig {
             .uri(uri)
             .header("Content-Type", ct);
 
-        if let Some(ce) = self.compression.content_encoding() {
+        if let Some(ce) = self.config.compression.content_encoding() {
             builder = builder.header("Content-Encoding", ce);
         }
 
-        if let Some(headers) = &self.headers {
+        if let Some(headers) = &self.config.headers {
             for (header, value) in headers.iter() {
                 builder = builder.header(header.as_str(), value.as_str());
             }
@@ -228,7 +310,7 @@ impl HttpSink for HttpSinkConfig {
 
         let mut request = builder.body(body).unwrap();
 
-        if let Some(auth) = &self.auth {
+        if let Some(auth) = &self.config.auth {
             auth.apply(&mut request);
         }
 
@@ -311,7 +393,11 @@ mod tests {
 
         let mut config = default_config(Encoding::Text);
         config.encoding = encoding;
-        let bytes = config.encode_event(event).unwrap();
+        let sink = HttpSinkWithSchema {
+            config,
+            encoder: None,
+        };
+        let bytes = sink.encode_event(event).unwrap();
 
         assert_eq!(bytes, Vec::from(&"hello world\n"[..]));
     }
@@ -323,7 +409,11 @@ mod tests {
 
         let mut config = default_config(Encoding::Json);
         config.encoding = encoding;
-        let bytes = config.encode_event(event).unwrap();
+        let sink = HttpSinkWithSchema {
+            config,
+            encoder: None,
+        };
+        let bytes = sink.encode_event(event).unwrap();
 
         #[derive(Deserialize, Debug)]
         #[serde(deny_unknown_fields)]
@@ -116,6 +116,7 @@ impl SinkConfig for InfluxDBLogsConfig {
             client,
             cx.acker(),
         )
+        .dead_letter(cx.dead_letter())
         .sink_map_err(|e| error!("Fatal influxdb_logs sink error: {}", e));
 
         Ok((Box::new(sink), Box::new(healthcheck)))
@@ -128,6 +129,10 @@ impl SinkConfig for InfluxDBLogsConfig {
     fn sink_type(&self) -> &'static str {
         "influxdb_logs"
     }
+
+    fn supports_dead_letter(&self) -> bool {
+        true
+    }
 }
 
 #[async_trait::async_trait]
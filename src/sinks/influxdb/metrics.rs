@@ -1,5 +1,8 @@
 use crate::{
-    event::metric::{Metric, MetricValue},
+    event::{
+        metric::{Metric, MetricValue},
+        sketch::DDSketch,
+    },
     sinks::influxdb::{
         encode_namespace, encode_timestamp, healthcheck, influx_line_protocol, influxdb_settings,
         Field, InfluxDB1Settings, InfluxDB2Settings, ProtocolVersion,
@@ -169,7 +172,8 @@ fn encode_events(
 ) -> String {
     let mut output = String::new();
     for event in events.into_iter() {
-        let fullname = encode_namespace(namespace, &event.name);
+        let fullname =
+            encode_namespace(event.namespace.as_deref().unwrap_or(namespace), &event.name);
         let ts = encode_timestamp(event.timestamp);
         let tags = event.tags.clone();
         match event.value {
@@ -266,6 +270,19 @@ fn encode_events(
             } => {
                 let fields = encode_distribution(&values, &sample_rates);
 
+                influx_line_protocol(
+                    protocol_version,
+                    fullname,
+                    "distribution",
+                    tags,
+                    fields,
+                    ts,
+                    &mut output,
+                );
+            }
+            MetricValue::Sketch(sketch) => {
+                let fields = encode_sketch(&sketch);
+
                 influx_line_protocol(
                     protocol_version,
                     fullname,
@@ -344,6 +361,28 @@ fn encode_distribution(values: &[f64], counts: &[u32]) -> Option<HashMap<String,
     Some(fields)
 }
 
+/// Encodes the same fields as a distribution, with quantiles estimated
+/// from the sketch.
+fn encode_sketch(sketch: &DDSketch) -> Option<HashMap<String, Field>> {
+    let count = sketch.count() as f64;
+    let fields: HashMap<String, Field> = vec![
+        ("min".to_owned(), Field::Float(sketch.min()?)),
+        ("max".to_owned(), Field::Float(sketch.max()?)),
+        ("median".to_owned(), Field::Float(sketch.quantile(0.5)?)),
+        ("avg".to_owned(), Field::Float(sketch.sum() / count)),
+        ("sum".to_owned(), Field::Float(sketch.sum())),
+        ("count".to_owned(), Field::Float(count)),
+        (
+            "quantile_0.95".to_owned(),
+            Field::Float(sketch.quantile(0.95)?),
+        ),
+    ]
+    .into_iter()
+    .collect();
+
+    Some(fields)
+}
+
 fn to_fields(value: f64) -> HashMap<String, Field> {
     let fields: HashMap<String, Field> = vec![("value".to_owned(), Field::Float(value))]
         .into_iter()
@@ -363,6 +402,7 @@ mod tests {
         let events = vec![
             Metric {
                 name: "total".into(),
+                namespace: None,
                 timestamp: Some(ts()),
                 tags: None,
                 kind: MetricKind::Incremental,
@@ -370,6 +410,7 @@ mod tests {
             },
             Metric {
                 name: "check".into(),
+                namespace: None,
                 timestamp: Some(ts()),
                 tags: Some(tags()),
                 kind: MetricKind::Incremental,
@@ -389,6 +430,7 @@ mod tests {
     fn test_encode_gauge() {
         let events = vec![Metric {
             name: "meter".to_owned(),
+            namespace: None,
             timestamp: Some(ts()),
             tags: Some(tags()),
             kind: MetricKind::Incremental,
@@ -406,6 +448,7 @@ mod tests {
     fn test_encode_set() {
         let events = vec![Metric {
             name: "users".into(),
+            namespace: None,
             timestamp: Some(ts()),
             tags: Some(tags()),
             kind: MetricKind::Incremental,
@@ -425,6 +468,7 @@ mod tests {
     fn test_encode_histogram_v1() {
         let events = vec![Metric {
             name: "requests".to_owned(),
+            namespace: None,
             timestamp: Some(ts()),
             tags: Some(tags()),
             kind: MetricKind::Absolute,
@@ -464,6 +508,7 @@ mod tests {
     fn test_encode_histogram() {
         let events = vec![Metric {
             name: "requests".to_owned(),
+            namespace: None,
             timestamp: Some(ts()),
             tags: Some(tags()),
             kind: MetricKind::Absolute,
@@ -503,6 +548,7 @@ mod tests {
     fn test_encode_summary_v1() {
         let events = vec![Metric {
             name: "requests_sum".to_owned(),
+            namespace: None,
             timestamp: Some(ts()),
             tags: Some(tags()),
             kind: MetricKind::Absolute,
@@ -542,6 +588,7 @@ mod tests {
     fn test_encode_summary() {
         let events = vec![Metric {
             name: "requests_sum".to_owned(),
+            namespace: None,
             timestamp: Some(ts()),
             tags: Some(tags()),
             kind: MetricKind::Absolute,
@@ -582,6 +629,7 @@ mod tests {
         let events = vec![
             Metric {
                 name: "requests".into(),
+                namespace: None,
                 timestamp: Some(ts()),
                 tags: Some(tags()),
                 kind: MetricKind::Incremental,
@@ -592,6 +640,7 @@ mod tests {
             },
             Metric {
                 name: "dense_stats".into(),
+                namespace: None,
                 timestamp: Some(ts()),
                 tags: None,
                 kind: MetricKind::Incremental,
@@ -602,6 +651,7 @@ mod tests {
             },
             Metric {
                 name: "sparse_stats".into(),
+                namespace: None,
                 timestamp: Some(ts()),
                 tags: None,
                 kind: MetricKind::Incremental,
@@ -678,6 +728,7 @@ mod tests {
     fn test_encode_distribution_empty_stats() {
         let events = vec![Metric {
             name: "requests".into(),
+            namespace: None,
             timestamp: Some(ts()),
             tags: Some(tags()),
             kind: MetricKind::Incremental,
@@ -695,6 +746,7 @@ mod tests {
     fn test_encode_distribution_zero_counts_stats() {
         let events = vec![Metric {
             name: "requests".into(),
+            namespace: None,
             timestamp: Some(ts()),
             tags: Some(tags()),
             kind: MetricKind::Incremental,
@@ -712,6 +764,7 @@ mod tests {
     fn test_encode_distribution_unequal_stats() {
         let events = vec![Metric {
             name: "requests".into(),
+            namespace: None,
             timestamp: Some(ts()),
             tags: Some(tags()),
             kind: MetricKind::Incremental,
@@ -724,6 +777,52 @@ mod tests {
         let line_protocols = encode_events(ProtocolVersion::V2, events, "ns");
         assert_eq!(line_protocols.len(), 0);
     }
+
+    #[test]
+    fn test_encode_sketch() {
+        let events = vec![
+            Metric {
+                name: "requests".into(),
+                namespace: None,
+                timestamp: Some(ts()),
+                tags: Some(tags()),
+                kind: MetricKind::Incremental,
+                value: MetricValue::Sketch(DDSketch::from_distribution(&[1.0], &[4], 0.01)),
+            },
+            Metric {
+                name: "empty".into(),
+                namespace: None,
+                timestamp: Some(ts()),
+                tags: Some(tags()),
+                kind: MetricKind::Incremental,
+                value: MetricValue::Sketch(DDSketch::new(0.01)),
+            },
+        ];
+
+        let line_protocols = encode_events(ProtocolVersion::V2, events, "ns");
+        let line_protocols: Vec<&str> = line_protocols.split('\n').collect();
+        assert_eq!(line_protocols.len(), 1);
+
+        let line_protocol = split_line_protocol(line_protocols[0]);
+        assert_eq!("ns.requests", line_protocol.0);
+        assert_eq!(
+            "metric_type=distribution,normal_tag=value,true_tag=true",
+            line_protocol.1
+        );
+        assert_fields(
+            line_protocol.2.to_string(),
+            [
+                "avg=1",
+                "count=4",
+                "max=1",
+                "median=1",
+                "min=1",
+                "quantile_0.95=1",
+                "sum=4",
+            ]
+            .to_vec(),
+        );
+    }
 }
 
 #[cfg(feature = "influxdb-integration-tests")]
@@ -787,6 +886,7 @@ mod integration_tests {
         for i in 0..10 {
             let event = Event::Metric(Metric {
                 name: metric.to_string(),
+                namespace: None,
                 timestamp: None,
                 tags: Some(
                     vec![
@@ -1,28 +1,34 @@
 use crate::{
     buffers::Acker,
-    event::{self, Event, Value},
+    codecs::{self, SchemaConfig, SchemaEncoder, SchemaFormat},
+    event::{self, BatchStatus, Event, Value},
     kafka::{KafkaAuthConfig, KafkaCompression},
     serde::to_string,
     sinks::util::encoding::{EncodingConfig, EncodingConfigWithDefault, EncodingConfiguration},
     template::{Template, TemplateError},
     topology::config::{DataType, SinkConfig, SinkContext, SinkDescription},
 };
+use bytes::Bytes;
 use futures::compat::Compat;
 use futures01::{
     future, stream::FuturesUnordered, Async, AsyncSink, Future, Poll, Sink, StartSend, Stream,
 };
+use indexmap::IndexMap;
 use rdkafka::{
     consumer::{BaseConsumer, Consumer},
+    message::OwnedHeaders,
     producer::{DeliveryFuture, FutureProducer, FutureRecord},
 };
 use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, Snafu};
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::time::Duration;
 use string_cache::DefaultAtom as Atom;
 
-type MetadataFuture<F, M> = future::Join<F, future::FutureResult<M, <F as Future>::Error>>;
+/// Resolves to the status of a message once its delivery completes, along
+/// with its sequence number.
+type DeliveryStatus = Box<dyn Future<Item = (BatchStatus, usize), Error = ()> + Send>;
 
 #[derive(Debug, Snafu)]
 enum BuildError {
@@ -30,6 +36,16 @@ enum BuildError {
     KafkaCreateFailed { source: rdkafka::error::KafkaError },
     #[snafu(display("invalid topic template: {}", source))]
     TopicTemplate { source: TemplateError },
+    #[snafu(display("invalid template for header {:?}: {}", name, source))]
+    HeaderTemplate { name: String, source: TemplateError },
+    #[snafu(display("invalid partitioning template: {}", source))]
+    PartitionTemplate { source: TemplateError },
+    #[snafu(display(
+        "the hash partitioning strategy requires between 1 and {} partitions, got {}",
+        i32::max_value(),
+        partitions
+    ))]
+    InvalidPartitions { partitions: u32 },
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -37,7 +53,12 @@ pub struct KafkaSinkConfig {
     bootstrap_servers: String,
     topic: String,
     key_field: Option<Atom>,
+    headers_field: Option<Atom>,
+    #[serde(default)]
+    headers: IndexMap<String, String>,
+    partitioning: Option<PartitioningConfig>,
     encoding: EncodingConfigWithDefault<Encoding>,
+    schema: Option<SchemaConfig>,
     #[serde(default)]
     compression: KafkaCompression,
     #[serde(flatten)]
@@ -64,29 +85,77 @@ pub enum Encoding {
     #[derivative(Default)]
     Text,
     Json,
+    Avro,
+    Protobuf,
+}
+
+impl Encoding {
+    fn schema_format(self) -> Option<SchemaFormat> {
+        match self {
+            Encoding::Text | Encoding::Json => None,
+            Encoding::Avro => Some(SchemaFormat::Avro),
+            Encoding::Protobuf => Some(SchemaFormat::Protobuf),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "strategy", rename_all = "snake_case")]
+pub enum PartitioningConfig {
+    /// Read the partition number from a field of the event.
+    Field { field: Atom },
+    /// Hash the rendered template onto one of `partitions` partitions.
+    Hash { template: String, partitions: u32 },
+}
+
+enum Partitioning {
+    Field(Atom),
+    Hash { template: Template, partitions: u32 },
 }
 
 pub struct KafkaSink {
     producer: FutureProducer,
     topic: Template,
     key_field: Option<Atom>,
+    headers_field: Option<Atom>,
+    headers: Vec<(String, Template)>,
+    partitioning: Option<Partitioning>,
     encoding: EncodingConfig<Encoding>,
-    in_flight: FuturesUnordered<MetadataFuture<Compat<DeliveryFuture>, usize>>,
+    encoder: Option<SchemaEncoder>,
+    in_flight: FuturesUnordered<DeliveryStatus>,
 
     acker: Acker,
     seq_head: usize,
     seq_tail: usize,
-    pending_acks: HashSet<usize>,
+    pending_acks: HashMap<usize, BatchStatus>,
 }
 
 inventory::submit! {
     SinkDescription::new_without_default::<KafkaSinkConfig>("kafka")
 }
 
+#[async_trait::async_trait]
 #[typetag::serde(name = "kafka")]
 impl SinkConfig for KafkaSinkConfig {
     fn build(&self, cx: SinkContext) -> crate::Result<(super::RouterSink, super::Healthcheck)> {
-        let sink = KafkaSink::new(self.clone(), cx.acker())?;
+        let encoder = match self.required_schema()? {
+            Some((format, schema)) => Some(schema.build_local_encoder(format)?),
+            None => None,
+        };
+        let sink = KafkaSink::new(self.clone(), cx.acker(), encoder)?;
+        let hc = healthcheck(self.clone())?;
+        Ok((Box::new(sink), hc))
+    }
+
+    async fn build_async(
+        &self,
+        cx: SinkContext,
+    ) -> crate::Result<(super::RouterSink, super::Healthcheck)> {
+        let encoder = match self.required_schema()? {
+            Some((format, schema)) => Some(schema.build_encoder(format).await?),
+            None => None,
+        };
+        let sink = KafkaSink::new(self.clone(), cx.acker(), encoder)?;
         let hc = healthcheck(self.clone())?;
         Ok((Box::new(sink), hc))
     }
@@ -101,6 +170,10 @@ impl SinkConfig for KafkaSinkConfig {
 }
 
 impl KafkaSinkConfig {
+    fn required_schema(&self) -> crate::Result<Option<(SchemaFormat, &SchemaConfig)>> {
+        codecs::required_schema(self.encoding.codec().schema_format(), &self.schema)
+    }
+
     fn to_rdkafka(&self) -> crate::Result<rdkafka::ClientConfig> {
         let mut client_config = rdkafka::ClientConfig::new();
         client_config
@@ -122,20 +195,65 @@ impl KafkaSinkConfig {
 }
 
 impl KafkaSink {
-    fn new(config: KafkaSinkConfig, acker: Acker) -> crate::Result<Self> {
+    fn new(
+        config: KafkaSinkConfig,
+        acker: Acker,
+        encoder: Option<SchemaEncoder>,
+    ) -> crate::Result<Self> {
+        let partitioning = match config.partitioning.clone() {
+            None => None,
+            Some(PartitioningConfig::Field { field }) => Some(Partitioning::Field(field)),
+            Some(PartitioningConfig::Hash {
+                template,
+                partitions,
+            }) => {
+                // Partitions are numbered by an `i32` on the wire.
+                if partitions == 0 || i32::try_from(partitions).is_err() {
+                    return Err(BuildError::InvalidPartitions { partitions }.into());
+                }
+                Some(Partitioning::Hash {
+                    template: Template::try_from(template).context(PartitionTemplate)?,
+                    partitions,
+                })
+            }
+        };
         let producer = config.to_rdkafka()?.create().context(KafkaCreateFailed)?;
+        let headers = config
+            .headers
+            .into_iter()
+            .map(|(name, template)| {
+                let template =
+                    Template::try_from(template).context(HeaderTemplate { name: name.clone() })?;
+                Ok((name, template))
+            })
+            .collect::<Result<Vec<_>, BuildError>>()?;
         Ok(KafkaSink {
             producer,
             topic: Template::try_from(config.topic).context(TopicTemplate)?,
             key_field: config.key_field,
+            headers_field: config.headers_field,
+            headers,
+            partitioning,
             encoding: config.encoding.into(),
+            encoder,
             in_flight: FuturesUnordered::new(),
             acker,
             seq_head: 0,
             seq_tail: 0,
-            pending_acks: HashSet::new(),
+            pending_acks: HashMap::new(),
         })
     }
+
+    /// Acknowledges the events up to the oldest one still in flight, with
+    /// whether each was delivered.
+    fn ack_completed(&mut self, seqno: usize, status: BatchStatus) {
+        self.pending_acks.insert(seqno, status);
+
+        while let Some(status) = self.pending_acks.remove(&self.seq_tail) {
+            self.acker.ack_with_status(1, status);
+            self.seq_tail += 1
+        }
+    }
 }
 
 impl Sink for KafkaSink {
@@ -147,10 +265,42 @@ impl Sink for KafkaSink {
             error!(message = "Missing keys for topic", ?missing_keys);
         })?;
 
-        let (key, body) = encode_event(item.clone(), &self.key_field, &self.encoding);
+        let (key, body) = match encode_event(
+            item.clone(),
+            &self.key_field,
+            &self.encoding,
+            self.encoder.as_ref(),
+        ) {
+            Ok(encoded) => encoded,
+            Err(error) => {
+                error!(message = "Failed to encode event; dropping event.", %error, rate_limit_secs = 30);
+                let seqno = self.seq_head;
+                self.seq_head += 1;
+                self.ack_completed(seqno, BatchStatus::Failed);
+                return Ok(AsyncSink::Ready);
+            }
+        };
+
+        let headers = render_headers(&item, &self.headers_field, &self.headers);
+        let partition = self
+            .partitioning
+            .as_ref()
+            .and_then(|partitioning| select_partition(&item, partitioning));
 
         let mut record = FutureRecord::to(&topic).key(&key).payload(&body[..]);
 
+        if !headers.is_empty() {
+            let headers = headers.iter().fold(
+                OwnedHeaders::new_with_capacity(headers.len()),
+                |owned, (name, value)| owned.add(name, &value[..]),
+            );
+            record = record.headers(headers);
+        }
+
+        if let Some(partition) = partition {
+            record = record.partition(partition);
+        }
+
         if let Some(Value::Timestamp(timestamp)) =
             item.as_log().get(&event::log_schema().timestamp_key())
         {
@@ -179,8 +329,29 @@ impl Sink for KafkaSink {
         let seqno = self.seq_head;
         self.seq_head += 1;
 
-        self.in_flight
-            .push(Compat::new(future).join(future::ok(seqno)));
+        let status = Compat::new(future).then(move |result| {
+            let status = match result {
+                Ok(Ok((partition, offset))) => {
+                    trace!(
+                        "produced message to partition {} at offset {}",
+                        partition,
+                        offset
+                    );
+                    BatchStatus::Delivered
+                }
+                Ok(Err((e, _msg))) => {
+                    error!("kafka error: {}", e);
+                    BatchStatus::Failed
+                }
+                // The producer was dropped before the message was delivered.
+                Err(e) => {
+                    error!("delivery future canceled: {}", e);
+                    BatchStatus::Failed
+                }
+            };
+            Ok((status, seqno))
+        });
+        self.in_flight.push(Box::new(status));
         Ok(AsyncSink::Ready)
     }
 
@@ -193,29 +364,10 @@ impl Sink for KafkaSink {
                 // nothing in flight
                 Ok(Async::Ready(None)) => return Ok(Async::Ready(())),
 
-                // request finished, check for success
-                Ok(Async::Ready(Some((result, seqno)))) => {
-                    match result {
-                        Ok((partition, offset)) => trace!(
-                            "produced message to partition {} at offset {}",
-                            partition,
-                            offset
-                        ),
-                        Err((e, _msg)) => error!("kafka error: {}", e),
-                    };
-
-                    self.pending_acks.insert(seqno);
-
-                    let mut num_to_ack = 0;
-                    while self.pending_acks.remove(&self.seq_tail) {
-                        num_to_ack += 1;
-                        self.seq_tail += 1
-                    }
-                    self.acker.ack(num_to_ack);
-                }
+                // request finished
+                Ok(Async::Ready(Some((status, seqno)))) => self.ack_completed(seqno, status),
 
-                // request got canceled (according to docs)
-                Err(e) => error!("delivery future canceled: {}", e),
+                Err(()) => unreachable!("delivery statuses never fail"),
             }
         }
     }
@@ -256,7 +408,8 @@ fn encode_event(
     mut event: Event,
     key_field: &Option<Atom>,
     encoding: &EncodingConfig<Encoding>,
-) -> (Vec<u8>, Vec<u8>) {
+    encoder: Option<&SchemaEncoder>,
+) -> crate::Result<(Vec<u8>, Vec<u8>)> {
     encoding.apply_rules(&mut event);
     let key = key_field
         .as_ref()
@@ -264,16 +417,102 @@ fn encode_event(
         .map(|v| v.as_bytes().to_vec())
         .unwrap_or_default();
 
-    let body = match encoding.codec() {
-        Encoding::Json => serde_json::to_vec(&event.as_log()).unwrap(),
-        Encoding::Text => event
+    let body = match (encoding.codec(), encoder) {
+        (Encoding::Json, _) => serde_json::to_vec(&event.as_log()).unwrap(),
+        (Encoding::Text, _) => event
             .as_log()
             .get(&event::log_schema().message_key())
             .map(|v| v.as_bytes().to_vec())
             .unwrap_or_default(),
+        (Encoding::Avro, Some(encoder)) | (Encoding::Protobuf, Some(encoder)) => {
+            encoder.encode(event.into_log())?
+        }
+        (Encoding::Avro, None) | (Encoding::Protobuf, None) => {
+            unreachable!("schema codecs are built with an encoder")
+        }
     };
 
-    (key, body)
+    Ok((key, body))
+}
+
+/// Collects the headers of the record, first from the entries of
+/// `headers_field` and then from the header templates. Templates with
+/// missing fields are skipped.
+fn render_headers(
+    event: &Event,
+    headers_field: &Option<Atom>,
+    templates: &[(String, Template)],
+) -> Vec<(String, Bytes)> {
+    let mut headers = Vec::new();
+
+    if let Some(field) = headers_field {
+        match event.as_log().get(field) {
+            Some(Value::Map(map)) => headers.extend(
+                map.iter()
+                    .map(|(name, value)| (name.clone(), value.as_bytes())),
+            ),
+            Some(_) => warn!(
+                message = "Headers field is not a map; skipping.",
+                %field,
+                rate_limit_secs = 30
+            ),
+            None => (),
+        }
+    }
+
+    for (name, template) in templates {
+        match template.render(event) {
+            Ok(value) => headers.push((name.clone(), value)),
+            Err(missing_keys) => warn!(
+                message = "Missing keys for header; skipping.",
+                header = %name,
+                ?missing_keys,
+                rate_limit_secs = 30
+            ),
+        }
+    }
+
+    headers
+}
+
+/// Picks the partition of the record, or `None` to leave it to the
+/// partitioner configured in librdkafka.
+fn select_partition(event: &Event, partitioning: &Partitioning) -> Option<i32> {
+    match partitioning {
+        Partitioning::Field(field) => {
+            let value = event.as_log().get(field)?;
+            let partition = match value {
+                Value::Integer(partition) => i32::try_from(*partition).ok(),
+                Value::Bytes(bytes) => std::str::from_utf8(bytes)
+                    .ok()
+                    .and_then(|s| s.parse::<i32>().ok()),
+                _ => None,
+            }
+            .filter(|partition| *partition >= 0);
+            if partition.is_none() {
+                warn!(
+                    message = "Partition field is not a valid partition.",
+                    %field,
+                    rate_limit_secs = 30
+                );
+            }
+            partition
+        }
+        Partitioning::Hash {
+            template,
+            partitions,
+        } => match template.render(event) {
+            Ok(value) => Some((crc32fast::hash(&value) % partitions) as i32),
+            Err(missing_keys) => {
+                warn!(
+                    message = "Missing keys for partitioning template.",
+                    ?missing_keys,
+                    rate_limit_secs = 30
+                );
+                None
+            }
+        },
+    }
 }
 
 #[cfg(test)]
@@ -290,7 +529,9 @@ mod tests {
             message.clone().into(),
             &None,
             &EncodingConfig::from(Encoding::Text),
-        );
+            None,
+        )
+        .unwrap();
 
         assert_eq!(&key_bytes[..], key.as_bytes());
         assert_eq!(&bytes[..], message.as_bytes());
@@ -307,7 +548,9 @@ mod tests {
             event,
             &Some("key".into()),
             &EncodingConfig::from(Encoding::Json),
-        );
+            None,
+        )
+        .unwrap();
 
         let map: BTreeMap<String, String> = serde_json::from_slice(&bytes[..]).unwrap();
 
@@ -316,6 +559,106 @@ mod tests {
         assert_eq!(map["key"], "value".to_string());
         assert_eq!(map["foo"], "bar".to_string());
     }
+
+    #[test]
+    fn kafka_render_headers() {
+        let mut event = Event::from("hello world");
+        event.as_mut_log().insert("tenant", "acme");
+        event.as_mut_log().insert("headers.source", "edge");
+        let templates = vec![
+            (
+                "tenant".to_string(),
+                Template::try_from("{{ tenant }}").unwrap(),
+            ),
+            (
+                "region".to_string(),
+                Template::try_from("{{ region }}").unwrap(),
+            ),
+            ("static".to_string(), Template::try_from("vector").unwrap()),
+        ];
+
+        let headers = render_headers(&event, &Some("headers".into()), &templates);
+
+        assert_eq!(
+            headers,
+            vec![
+                ("source".to_string(), Bytes::from("edge")),
+                ("tenant".to_string(), Bytes::from("acme")),
+                ("static".to_string(), Bytes::from("vector")),
+            ]
+        );
+    }
+
+    #[test]
+    fn kafka_select_partition_from_field() {
+        let partitioning = Partitioning::Field("partition".into());
+        let mut event = Event::from("hello world");
+        assert_eq!(select_partition(&event, &partitioning), None);
+
+        event.as_mut_log().insert("partition", 3);
+        assert_eq!(select_partition(&event, &partitioning), Some(3));
+
+        event.as_mut_log().insert("partition", "5");
+        assert_eq!(select_partition(&event, &partitioning), Some(5));
+
+        event.as_mut_log().insert("partition", -1);
+        assert_eq!(select_partition(&event, &partitioning), None);
+    }
+
+    #[test]
+    fn kafka_select_partition_from_hash() {
+        let partitioning = Partitioning::Hash {
+            template: Template::try_from("{{ tenant }}").unwrap(),
+            partitions: 4,
+        };
+        let mut event = Event::from("hello world");
+        assert_eq!(select_partition(&event, &partitioning), None);
+
+        event.as_mut_log().insert("tenant", "acme");
+        let partition = select_partition(&event, &partitioning).unwrap();
+        assert_eq!(partition, (crc32fast::hash(b"acme") % 4) as i32);
+
+        let mut other = Event::from("goodbye");
+        other.as_mut_log().insert("tenant", "acme");
+        assert_eq!(select_partition(&other, &partitioning), Some(partition));
+    }
+
+    #[test]
+    fn kafka_parse_partitioning() {
+        let config: KafkaSinkConfig = toml::from_str(
+            r#"
+            bootstrap_servers = "localhost:9092"
+            topic = "logs"
+            headers.tenant = "{{ tenant }}"
+            partitioning.strategy = "hash"
+            partitioning.template = "{{ tenant }}"
+            partitioning.partitions = 12
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.headers["tenant"], "{{ tenant }}");
+        match config.partitioning {
+            Some(PartitioningConfig::Hash { partitions, .. }) => assert_eq!(partitions, 12),
+            other => panic!("unexpected partitioning: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn kafka_rejects_invalid_partition_counts() {
+        for partitions in &[0, i32::max_value() as u32 + 1, u32::max_value()] {
+            let config = KafkaSinkConfig {
+                bootstrap_servers: "localhost:9092".into(),
+                topic: "logs".into(),
+                partitioning: Some(PartitioningConfig::Hash {
+                    template: "{{ tenant }}".into(),
+                    partitions: *partitions,
+                }),
+                ..Default::default()
+            };
+            assert!(KafkaSink::new(config, Acker::Null, None).is_err());
+        }
+    }
 }
 
 #[cfg(feature = "kafka-integration-tests")]
@@ -458,7 +801,7 @@ mod integration_test {
         };
         let topic = format!("{}-{}", topic, chrono::Utc::now().format("%Y%m%d"));
         let (acker, ack_counter) = Acker::new_for_testing();
-        let sink = KafkaSink::new(config, acker).unwrap();
+        let sink = KafkaSink::new(config, acker, None).unwrap();
 
         let num_events = 1000;
         let (input, events) = random_lines_with_stream(100, num_events);
@@ -77,6 +77,7 @@ impl SinkConfig for LogdnaConfig {
             client.clone(),
             cx.acker(),
         )
+        .dead_letter(cx.dead_letter())
         .sink_map_err(|e| error!("Fatal logdna sink error: {}", e));
 
         let healthcheck = healthcheck(self.clone(), client).boxed().compat();
@@ -91,6 +92,10 @@ impl SinkConfig for LogdnaConfig {
     fn sink_type(&self) -> &'static str {
         "logdna"
     }
+
+    fn supports_dead_letter(&self) -> bool {
+        true
+    }
 }
 
 #[async_trait::async_trait]
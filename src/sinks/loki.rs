@@ -95,6 +95,7 @@ impl SinkConfig for LokiConfig {
             client.clone(),
             cx.acker(),
         )
+        .dead_letter(cx.dead_letter())
         .sink_map_err(|e| error!("Fatal loki sink error: {}", e));
 
         let healthcheck = healthcheck(self.clone(), client).boxed_compat();
@@ -109,6 +110,10 @@ impl SinkConfig for LokiConfig {
     fn sink_type(&self) -> &'static str {
         "loki"
     }
+
+    fn supports_dead_letter(&self) -> bool {
+        true
+    }
 }
 
 #[async_trait::async_trait]
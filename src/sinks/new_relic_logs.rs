@@ -131,6 +131,7 @@ impl NewRelicLogsConfig {
             headers: Some(headers),
             compression: self.compression,
             encoding: self.encoding.clone().without_default(),
+            schema: None,
 
             batch,
             request,
@@ -0,0 +1,315 @@
+use crate::{
+    event::Event,
+    opentelemetry,
+    sinks::util::{
+        http::{Auth, BatchedHttpSink, HttpClient, HttpSink},
+        service2::TowerRequestConfig,
+        Batch, BatchConfig, BatchSettings, Buffer, Compression, PartitionInnerBuffer, PushResult,
+        UriSerde,
+    },
+    tls::{TlsOptions, TlsSettings},
+    topology::config::{DataType, SinkConfig, SinkContext, SinkDescription},
+};
+use futures01::{future, Sink};
+use http::{Request, Uri};
+use indexmap::IndexMap;
+use lazy_static::lazy_static;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct OpenTelemetrySinkConfig {
+    /// The base URI of the collector, to which the path of each signal is
+    /// appended.
+    pub endpoint: UriSerde,
+    pub auth: Option<Auth>,
+    pub headers: Option<IndexMap<String, String>>,
+    #[serde(default)]
+    pub compression: Compression,
+    #[serde(default)]
+    pub batch: BatchConfig,
+    #[serde(default)]
+    pub request: TowerRequestConfig,
+    pub tls: Option<TlsOptions>,
+}
+
+lazy_static! {
+    static ref REQUEST_DEFAULTS: TowerRequestConfig = TowerRequestConfig {
+        in_flight_limit: Some(10),
+        timeout_secs: Some(30),
+        rate_limit_num: Some(10),
+        ..Default::default()
+    };
+}
+
+inventory::submit! {
+    SinkDescription::new_without_default::<OpenTelemetrySinkConfig>("opentelemetry")
+}
+
+#[typetag::serde(name = "opentelemetry")]
+impl SinkConfig for OpenTelemetrySinkConfig {
+    fn build(&self, cx: SinkContext) -> crate::Result<(super::RouterSink, super::Healthcheck)> {
+        let tls = TlsSettings::from_options(&self.tls)?;
+        let client = HttpClient::new(cx.resolver(), tls)?;
+
+        let batch = self.batch.use_size_as_bytes()?.get_settings_or_default(
+            BatchSettings::default()
+                .bytes(bytesize::mib(4u64))
+                .timeout(1),
+        );
+        let request = self.request.unwrap_with(&REQUEST_DEFAULTS);
+
+        let sink = BatchedHttpSink::new(
+            self.clone(),
+            SignalBuffer::new(Buffer::new(batch.size, self.compression)),
+            request,
+            batch.timeout,
+            client,
+            cx.acker(),
+        )
+        .dead_letter(cx.dead_letter())
+        .sink_map_err(|e| error!("Fatal opentelemetry sink error: {}", e));
+
+        Ok((Box::new(sink), Box::new(future::ok(()))))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Any
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "opentelemetry"
+    }
+
+    fn supports_dead_letter(&self) -> bool {
+        true
+    }
+}
+
+/// The kinds of telemetry data, each exported to its own path.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Signal {
+    Logs,
+    Metrics,
+}
+
+impl Signal {
+    fn path(self) -> &'static str {
+        match self {
+            Signal::Logs => "v1/logs",
+            Signal::Metrics => "v1/metrics",
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl HttpSink for OpenTelemetrySinkConfig {
+    type Input = PartitionInnerBuffer<Vec<u8>, Signal>;
+    type Output = PartitionInnerBuffer<Vec<u8>, Signal>;
+
+    /// Each event is encoded as an export request of its own. Concatenated
+    /// Protobuf messages merge their repeated fields, so a batch of them is
+    /// a request holding every event.
+    fn encode_event(&self, event: Event) -> Option<Self::Input> {
+        let (request, signal) = match event {
+            Event::Log(log) => (encode(opentelemetry::log_into_request(log)), Signal::Logs),
+            Event::Metric(metric) => (
+                encode(opentelemetry::metric_into_request(metric)?),
+                Signal::Metrics,
+            ),
+        };
+        Some(PartitionInnerBuffer::new(request, signal))
+    }
+
+    async fn build_request(&self, output: Self::Output) -> crate::Result<http::Request<Vec<u8>>> {
+        let (body, signal) = output.into_parts();
+        let uri: Uri = format!(
+            "{}/{}",
+            self.endpoint.to_string().trim_end_matches('/'),
+            signal.path()
+        )
+        .parse()?;
+
+        let mut builder = Request::post(uri).header("Content-Type", "application/x-protobuf");
+        if let Some(ce) = self.compression.content_encoding() {
+            builder = builder.header("Content-Encoding", ce);
+        }
+        if let Some(headers) = &self.headers {
+            for (header, value) in headers.iter() {
+                builder = builder.header(header.as_str(), value.as_str());
+            }
+        }
+
+        let mut request = builder.body(body)?;
+        if let Some(auth) = &self.auth {
+            auth.apply(&mut request);
+        }
+        Ok(request)
+    }
+}
+
+fn encode(message: impl Message) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(message.encoded_len());
+    message
+        .encode(&mut buf)
+        .expect("a Vec grows to fit the message");
+    buf
+}
+
+/// Buffers the requests of a single signal, so a batch is flushed whenever
+/// the signal of the events changes.
+struct SignalBuffer {
+    inner: Buffer,
+    signal: Option<Signal>,
+}
+
+impl SignalBuffer {
+    fn new(inner: Buffer) -> Self {
+        Self {
+            inner,
+            signal: None,
+        }
+    }
+}
+
+impl Batch for SignalBuffer {
+    type Input = PartitionInnerBuffer<Vec<u8>, Signal>;
+    type Output = PartitionInnerBuffer<Vec<u8>, Signal>;
+
+    fn push(&mut self, item: Self::Input) -> PushResult<Self::Input> {
+        let (request, signal) = item.into_parts();
+        if !self.inner.is_empty() && self.signal != Some(signal) {
+            return PushResult::Overflow(PartitionInnerBuffer::new(request, signal));
+        }
+        match Batch::push(&mut self.inner, request) {
+            PushResult::Ok(full) => {
+                self.signal = Some(signal);
+                PushResult::Ok(full)
+            }
+            PushResult::Overflow(request) => {
+                PushResult::Overflow(PartitionInnerBuffer::new(request, signal))
+            }
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    fn fresh(&self) -> Self {
+        Self::new(self.inner.fresh())
+    }
+
+    fn finish(self) -> Self::Output {
+        let signal = self.signal.unwrap_or(Signal::Logs);
+        PartitionInnerBuffer::new(self.inner.finish(), signal)
+    }
+
+    fn num_items(&self) -> usize {
+        self.inner.num_items()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::{
+            metric::{MetricKind, MetricValue},
+            Metric,
+        },
+        opentelemetry::proto::{ExportLogsServiceRequest, ExportMetricsServiceRequest},
+        sinks::util::{test::build_test_server, BatchSize},
+        test_util::{next_addr, runtime, shutdown_on_idle},
+    };
+    use futures01::Stream;
+
+    fn metric() -> Event {
+        Event::Metric(Metric {
+            name: "requests".into(),
+            namespace: None,
+            timestamp: None,
+            tags: None,
+            kind: MetricKind::Incremental,
+            value: MetricValue::Counter { value: 1.0 },
+        })
+    }
+
+    #[test]
+    fn signal_buffer_splits_signals() {
+        let size = BatchSize {
+            bytes: 1000,
+            events: 10,
+        };
+        let mut buffer = SignalBuffer::new(Buffer::new(size, Compression::None));
+        let item = |signal| PartitionInnerBuffer::new(vec![0], signal);
+
+        assert!(matches!(
+            buffer.push(item(Signal::Logs)),
+            PushResult::Ok(false)
+        ));
+        assert!(matches!(
+            buffer.push(item(Signal::Logs)),
+            PushResult::Ok(false)
+        ));
+        assert!(matches!(
+            buffer.push(item(Signal::Metrics)),
+            PushResult::Overflow(_)
+        ));
+
+        let mut buffer = buffer.fresh();
+        assert!(matches!(
+            buffer.push(item(Signal::Metrics)),
+            PushResult::Ok(false)
+        ));
+        let (body, signal) = buffer.finish().into_parts();
+        assert_eq!(body, vec![0]);
+        assert_eq!(signal, Signal::Metrics);
+    }
+
+    #[test]
+    fn sends_logs_and_metrics_to_their_paths() {
+        let in_addr = next_addr();
+        let config = format!(
+            r#"
+            endpoint = "http://{}/otlp/"
+            "#,
+            in_addr
+        );
+        let config: OpenTelemetrySinkConfig = toml::from_str(&config).unwrap();
+
+        let mut rt = runtime();
+        let cx = SinkContext::new_test();
+        let (sink, _) = config.build(cx).unwrap();
+        let (rx, trigger, server) = build_test_server(in_addr, &mut rt);
+
+        let events = vec![
+            Event::from("one"),
+            Event::from("two"),
+            metric(),
+            Event::from("three"),
+        ];
+        let pump = sink.send_all(futures01::stream::iter_ok(events));
+        rt.spawn(server);
+        let _ = rt.block_on(pump).unwrap();
+        drop(trigger);
+
+        let requests = rx.wait().map(Result::unwrap).collect::<Vec<_>>();
+        shutdown_on_idle(rt);
+
+        let paths: Vec<_> = requests
+            .iter()
+            .map(|(parts, _)| parts.uri.path().to_owned())
+            .collect();
+        assert_eq!(
+            paths,
+            vec!["/otlp/v1/logs", "/otlp/v1/metrics", "/otlp/v1/logs"]
+        );
+
+        let logs = ExportLogsServiceRequest::decode(requests[0].1.clone()).unwrap();
+        assert_eq!(logs.resource_logs.len(), 2);
+        let metrics = ExportMetricsServiceRequest::decode(requests[1].1.clone()).unwrap();
+        assert_eq!(metrics.resource_metrics.len(), 1);
+    }
+}
@@ -3,13 +3,12 @@ use crate::{
     sinks::util::{
         encoding::{EncodingConfig, EncodingConfiguration},
         tcp::TcpSink,
-        Encoding, UriSerde,
+        EncodeSink, Encoding, StreamSink, UriSerde,
     },
     tls::{MaybeTlsSettings, TlsSettings},
     topology::config::{DataType, SinkConfig, SinkContext, SinkDescription},
 };
 use bytes::Bytes;
-use futures01::{stream::iter_ok, Sink};
 use serde::{Deserialize, Serialize};
 use syslog::{Facility, Formatter3164, LogFormat, Severity};
 
@@ -49,7 +48,8 @@ impl SinkConfig for PapertrailConfig {
 
         let encoding = self.encoding.clone();
 
-        let sink = sink.with_flat_map(move |e| iter_ok(encode_event(e, pid, &encoding)));
+        let sink = StreamSink::new(sink, cx.acker());
+        let sink = EncodeSink::new(sink, move |e| encode_event(e, pid, &encoding));
 
         Ok((Box::new(sink), Box::new(healthcheck)))
     }
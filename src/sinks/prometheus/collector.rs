@@ -0,0 +1,143 @@
+use crate::event::metric::{Metric, MetricValue};
+
+/// One of the series a metric is exposed as: the suffix of its name, the
+/// label telling it apart from the other series of the metric, and its value.
+#[derive(Debug, PartialEq)]
+pub(super) struct Series {
+    pub suffix: &'static str,
+    pub label: Option<(&'static str, String)>,
+    pub value: f64,
+}
+
+impl Series {
+    fn new(suffix: &'static str, value: f64) -> Self {
+        Self {
+            suffix,
+            label: None,
+            value,
+        }
+    }
+
+    fn labeled(suffix: &'static str, label: &'static str, label_value: String, value: f64) -> Self {
+        Self {
+            suffix,
+            label: Some((label, label_value)),
+            value,
+        }
+    }
+}
+
+pub(super) fn encode_namespace(namespace: &str, name: &str) -> String {
+    if !namespace.is_empty() {
+        format!("{}_{}", namespace, name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// The exposed name of `metric`, whose own namespace takes precedence over
+/// the configured one.
+pub(super) fn encode_metric_name(namespace: &str, metric: &Metric) -> String {
+    encode_namespace(
+        metric.namespace.as_deref().unwrap_or(namespace),
+        &metric.name,
+    )
+}
+
+/// Counts the samples of a distribution into histogram `buckets`.
+pub(super) fn distribution_to_histogram(
+    buckets: &[f64],
+    values: &[f64],
+    sample_rates: &[u32],
+) -> MetricValue {
+    let mut counts = vec![0; buckets.len()];
+    let mut sum = 0.0;
+    let mut count = 0;
+    for (v, c) in values.iter().zip(sample_rates.iter()) {
+        buckets
+            .iter()
+            .enumerate()
+            .skip_while(|&(_, b)| b < v)
+            .for_each(|(i, _)| {
+                counts[i] += c;
+            });
+
+        sum += v * (*c as f64);
+        count += c;
+    }
+
+    MetricValue::AggregatedHistogram {
+        buckets: buckets.to_vec(),
+        counts,
+        count,
+        sum,
+    }
+}
+
+/// The series of an absolute metric. Distributions are counted into
+/// `buckets`, and sketches are exposed at `quantiles`.
+pub(super) fn metric_series(buckets: &[f64], quantiles: &[f64], metric: &Metric) -> Vec<Series> {
+    match &metric.value {
+        MetricValue::Counter { value } => vec![Series::new("", *value)],
+        MetricValue::Gauge { value } => vec![Series::new("", *value)],
+        MetricValue::Set { values } => vec![Series::new("", values.len() as f64)],
+        MetricValue::Distribution {
+            values,
+            sample_rates,
+        } => {
+            let metric = Metric {
+                value: distribution_to_histogram(buckets, values, sample_rates),
+                ..metric.clone()
+            };
+            metric_series(buckets, quantiles, &metric)
+        }
+        MetricValue::AggregatedHistogram {
+            buckets,
+            counts,
+            count,
+            sum,
+        } => {
+            let mut series: Vec<_> = buckets
+                .iter()
+                .zip(counts.iter())
+                .map(|(b, c)| Series::labeled("_bucket", "le", b.to_string(), *c as f64))
+                .collect();
+            series.push(Series::labeled(
+                "_bucket",
+                "le",
+                "+Inf".to_string(),
+                *count as f64,
+            ));
+            series.push(Series::new("_sum", *sum));
+            series.push(Series::new("_count", *count as f64));
+            series
+        }
+        MetricValue::AggregatedSummary {
+            quantiles,
+            values,
+            count,
+            sum,
+        } => {
+            let mut series: Vec<_> = quantiles
+                .iter()
+                .zip(values.iter())
+                .map(|(q, v)| Series::labeled("", "quantile", q.to_string(), *v))
+                .collect();
+            series.push(Series::new("_sum", *sum));
+            series.push(Series::new("_count", *count as f64));
+            series
+        }
+        MetricValue::Sketch(sketch) => {
+            let mut series: Vec<_> = quantiles
+                .iter()
+                .filter_map(|q| {
+                    let v = sketch.quantile(*q)?;
+                    Some(Series::labeled("", "quantile", q.to_string(), v))
+                })
+                .collect();
+            series.push(Series::new("_sum", sketch.sum()));
+            series.push(Series::new("_count", sketch.count() as f64));
+            series
+        }
+    }
+}
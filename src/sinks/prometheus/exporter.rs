@@ -0,0 +1,946 @@
+use super::collector::{distribution_to_histogram, encode_metric_name, metric_series};
+use crate::{
+    buffers::Acker,
+    event::{
+        metric::{Metric, MetricValue},
+        sketch::{is_valid_relative_accuracy, MIN_RELATIVE_ACCURACY},
+    },
+    sinks::util::MetricEntry,
+    tls::{MaybeTlsSettings, TlsConfig},
+    topology::config::{DataType, SinkConfig, SinkContext, SinkDescription},
+    Event,
+};
+use flate2::{write::GzEncoder, Compression};
+use futures::{
+    compat::{AsyncRead01CompatExt, Future01CompatExt, Stream01CompatExt},
+    future::FutureExt,
+    TryFutureExt, TryStreamExt,
+};
+use futures01::{future, Async, AsyncSink, Future, Sink};
+use hyper::{
+    header::{self, HeaderValue},
+    server::accept,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use snafu::Snafu;
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    net::SocketAddr,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+use stream_cancel::{Trigger, Tripwire};
+use tokio_util::compat::FuturesAsyncReadCompatExt;
+use tracing::field;
+
+const MIN_FLUSH_PERIOD_SECS: u64 = 1;
+
+const TEXT_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+const OPENMETRICS_CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("Flush period for sets must be greater or equal to {} secs", min))]
+    FlushPeriodTooShort { min: u64 },
+    #[snafu(display(
+        "Sketch relative accuracy must be within [{}, 1), got {}",
+        MIN_RELATIVE_ACCURACY,
+        accuracy
+    ))]
+    InvalidSketchAccuracy { accuracy: f64 },
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct PrometheusSinkConfig {
+    pub namespace: String,
+    #[serde(default = "default_address")]
+    pub address: SocketAddr,
+    #[serde(default = "default_histogram_buckets")]
+    pub buckets: Vec<f64>,
+    /// Series that are not updated within this period are expired, and the
+    /// values of sets are reset every period.
+    #[serde(default = "default_flush_period_secs")]
+    pub flush_period_secs: u64,
+    /// Quantiles exposed for sketches, which are rendered as summaries.
+    #[serde(default = "default_summary_quantiles")]
+    pub quantiles: Vec<f64>,
+    /// When set, distributions are aggregated into sketches of this relative
+    /// accuracy instead of histograms.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sketch_relative_accuracy: Option<f64>,
+    /// The `# HELP` text of metrics, keyed by their exposed name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub help: BTreeMap<String, String>,
+    pub tls: Option<TlsConfig>,
+}
+
+pub fn default_histogram_buckets() -> Vec<f64> {
+    vec![
+        0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+    ]
+}
+
+pub fn default_summary_quantiles() -> Vec<f64> {
+    vec![0.5, 0.75, 0.9, 0.95, 0.99]
+}
+
+pub fn default_address() -> SocketAddr {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), 9598)
+}
+
+pub fn default_flush_period_secs() -> u64 {
+    60
+}
+
+inventory::submit! {
+    SinkDescription::new_without_default::<PrometheusSinkConfig>("prometheus")
+}
+
+#[typetag::serde(name = "prometheus")]
+impl SinkConfig for PrometheusSinkConfig {
+    fn build(&self, cx: SinkContext) -> crate::Result<(super::RouterSink, super::Healthcheck)> {
+        if self.flush_period_secs < MIN_FLUSH_PERIOD_SECS {
+            return Err(Box::new(BuildError::FlushPeriodTooShort {
+                min: MIN_FLUSH_PERIOD_SECS,
+            }));
+        }
+        if let Some(accuracy) = self.sketch_relative_accuracy {
+            if !is_valid_relative_accuracy(accuracy) {
+                return Err(Box::new(BuildError::InvalidSketchAccuracy { accuracy }));
+            }
+        }
+        let tls = MaybeTlsSettings::from_config(&self.tls, true)?;
+
+        let sink = Box::new(PrometheusSink::new(self.clone(), tls, cx.acker()));
+        let healthcheck = Box::new(future::ok(()));
+
+        Ok((sink, healthcheck))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Metric
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "prometheus"
+    }
+}
+
+/// When a series was last updated, and when its values were last reset if
+/// it is a set.
+#[derive(Clone, Copy, Debug)]
+struct Freshness {
+    updated: Instant,
+    reset: Instant,
+}
+
+impl Freshness {
+    fn new(now: Instant) -> Self {
+        Self {
+            updated: now,
+            reset: now,
+        }
+    }
+
+    fn is_expired(&self, now: Instant, ttl: Duration) -> bool {
+        now.duration_since(self.updated) > ttl
+    }
+}
+
+type Series = IndexMap<MetricEntry, Freshness>;
+
+struct PrometheusSink {
+    server_shutdown_trigger: Option<Trigger>,
+    config: Arc<PrometheusSinkConfig>,
+    tls: MaybeTlsSettings,
+    metrics: Arc<RwLock<Series>>,
+    last_sweep: Instant,
+    acker: Acker,
+}
+
+/// The exposition formats the sink negotiates from the `Accept` header.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    Text,
+    OpenMetrics,
+}
+
+impl Format {
+    fn negotiate(req: &Request<Body>) -> Self {
+        if header_contains(req, header::ACCEPT, "application/openmetrics-text") {
+            Format::OpenMetrics
+        } else {
+            Format::Text
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Format::Text => TEXT_CONTENT_TYPE,
+            Format::OpenMetrics => OPENMETRICS_CONTENT_TYPE,
+        }
+    }
+}
+
+fn header_contains(req: &Request<Body>, name: header::HeaderName, value: &str) -> bool {
+    req.headers()
+        .get_all(name)
+        .iter()
+        .filter_map(|header| header.to_str().ok())
+        .any(|header| header.contains(value))
+}
+
+fn encode_tags(tags: &Option<BTreeMap<String, String>>) -> String {
+    if let Some(tags) = tags {
+        let mut parts: Vec<_> = tags
+            .iter()
+            .map(|(name, value)| format!("{}=\"{}\"", name, value))
+            .collect();
+
+        parts.sort();
+        format!("{{{}}}", parts.join(","))
+    } else {
+        String::from("")
+    }
+}
+
+fn encode_tags_with_extra(
+    tags: &Option<BTreeMap<String, String>>,
+    tag: String,
+    value: String,
+) -> String {
+    let mut parts: Vec<_> = if let Some(tags) = tags {
+        tags.iter()
+            .chain(vec![(&tag, &value)])
+            .map(|(name, value)| format!("{}=\"{}\"", name, value))
+            .collect()
+    } else {
+        vec![format!("{}=\"{}\"", tag, value)]
+    };
+
+    parts.sort();
+    format!("{{{}}}", parts.join(","))
+}
+
+/// The name of the family `metric` belongs to. OpenMetrics names counter
+/// families without the `_total` suffix of their samples.
+fn encode_family_name(namespace: &str, format: Format, metric: &Metric) -> String {
+    let name = encode_metric_name(namespace, metric);
+    match (format, &metric.value) {
+        (Format::OpenMetrics, MetricValue::Counter { .. }) if name.ends_with("_total") => {
+            name[..name.len() - "_total".len()].to_string()
+        }
+        _ => name,
+    }
+}
+
+fn escape_help(help: &str) -> String {
+    help.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn encode_metric_header(
+    namespace: &str,
+    help: &BTreeMap<String, String>,
+    format: Format,
+    metric: &Metric,
+) -> String {
+    let mut s = String::new();
+    let fullname = encode_metric_name(namespace, metric);
+    let family = encode_family_name(namespace, format, metric);
+    let help = help
+        .get(&fullname)
+        .map(|help| escape_help(help))
+        .unwrap_or_else(|| metric.name.clone());
+
+    let r#type = match &metric.value {
+        MetricValue::Counter { .. } => "counter",
+        MetricValue::Gauge { .. } => "gauge",
+        MetricValue::Distribution { .. } => "histogram",
+        MetricValue::Set { .. } => "gauge",
+        MetricValue::AggregatedHistogram { .. } => "histogram",
+        MetricValue::AggregatedSummary { .. } => "summary",
+        MetricValue::Sketch(_) => "summary",
+    };
+
+    s.push_str(&format!("# HELP {} {}\n", family, help));
+    s.push_str(&format!("# TYPE {} {}\n", family, r#type));
+    s
+}
+
+fn encode_metric_datum(
+    namespace: &str,
+    buckets: &[f64],
+    quantiles: &[f64],
+    format: Format,
+    metric: &Metric,
+) -> String {
+    let mut s = String::new();
+    let fullname = match (format, &metric.value) {
+        (Format::OpenMetrics, MetricValue::Counter { .. }) => {
+            format!("{}_total", encode_family_name(namespace, format, metric))
+        }
+        _ => encode_metric_name(namespace, metric),
+    };
+
+    if metric.kind.is_absolute() {
+        let tags = &metric.tags;
+
+        for series in metric_series(buckets, quantiles, metric) {
+            let tags = match series.label {
+                Some((label, value)) => encode_tags_with_extra(tags, label.to_string(), value),
+                None => encode_tags(tags),
+            };
+            s.push_str(&format!(
+                "{}{}{} {}\n",
+                fullname, series.suffix, tags, series.value
+            ));
+        }
+    }
+
+    s
+}
+
+/// Renders the series that haven't expired, with the series of every family
+/// next to each other as the exposition formats require.
+fn encode_metrics(
+    config: &PrometheusSinkConfig,
+    format: Format,
+    metrics: &Series,
+    now: Instant,
+) -> String {
+    let ttl = Duration::from_secs(config.flush_period_secs);
+    let mut families = BTreeMap::<_, Vec<_>>::new();
+    for (MetricEntry(metric), freshness) in metrics {
+        if !freshness.is_expired(now, ttl) {
+            families
+                .entry(encode_family_name(&config.namespace, format, metric))
+                .or_default()
+                .push(metric);
+        }
+    }
+
+    let mut s = String::new();
+    for family in families.values() {
+        s.push_str(&encode_metric_header(
+            &config.namespace,
+            &config.help,
+            format,
+            family[0],
+        ));
+        for metric in family {
+            s.push_str(&encode_metric_datum(
+                &config.namespace,
+                &config.buckets,
+                &config.quantiles,
+                format,
+                metric,
+            ));
+        }
+    }
+    if format == Format::OpenMetrics {
+        s.push_str("# EOF\n");
+    }
+    s
+}
+
+fn gzip(body: String) -> std::io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(body.as_bytes())?;
+    encoder.finish()
+}
+
+fn handle(
+    req: Request<Body>,
+    config: &PrometheusSinkConfig,
+    metrics: &Series,
+) -> Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send> {
+    let mut response = Response::new(Body::empty());
+
+    match (req.method(), req.uri().path()) {
+        (&Method::GET, "/metrics") => {
+            let format = Format::negotiate(&req);
+            let body = encode_metrics(config, format, metrics, Instant::now());
+
+            if header_contains(&req, header::ACCEPT_ENCODING, "gzip") {
+                match gzip(body) {
+                    Ok(body) => {
+                        *response.body_mut() = body.into();
+                        response
+                            .headers_mut()
+                            .insert(header::CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+                    }
+                    Err(error) => {
+                        error!(message = "failed to compress metrics", %error);
+                        *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+                    }
+                }
+            } else {
+                *response.body_mut() = body.into();
+            }
+
+            response.headers_mut().insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static(format.content_type()),
+            );
+        }
+        _ => {
+            *response.status_mut() = StatusCode::NOT_FOUND;
+        }
+    }
+
+    info!(
+        message = "request complete",
+        response_code = field::debug(response.status())
+    );
+    Box::new(future::ok(response))
+}
+
+/// Removes the series that expired.
+fn expire(metrics: &mut Series, ttl: Duration, now: Instant) {
+    metrics.retain(|_, freshness| !freshness.is_expired(now, ttl));
+}
+
+/// Merges `metric` into its series. Incremental metrics are added to their
+/// series unless it expired, while absolute ones replace it.
+fn record(metrics: &mut Series, metric: Metric, ttl: Duration, now: Instant) {
+    let new = MetricEntry(metric.to_absolute());
+    let (entry, freshness) = match metrics.swap_remove_full(&new) {
+        Some((_, MetricEntry(mut series), mut freshness))
+            if !metric.kind.is_absolute() && !freshness.is_expired(now, ttl) =>
+        {
+            // sets need to be reset from time to time
+            // because otherwise they could grow infinitely
+            if series.value.is_set() && now.duration_since(freshness.reset) > ttl {
+                series.reset();
+                freshness.reset = now;
+            }
+            series.add(&metric);
+            series.timestamp = metric.timestamp;
+            freshness.updated = now;
+            (MetricEntry(series), freshness)
+        }
+        _ => (new, Freshness::new(now)),
+    };
+    metrics.insert(entry, freshness);
+}
+
+impl PrometheusSink {
+    fn new(config: PrometheusSinkConfig, tls: MaybeTlsSettings, acker: Acker) -> Self {
+        Self {
+            server_shutdown_trigger: None,
+            config: Arc::new(config),
+            tls,
+            metrics: Arc::new(RwLock::new(IndexMap::new())),
+            last_sweep: Instant::now(),
+            acker,
+        }
+    }
+
+    /// Distributions are aggregated as they arrive, so that their series
+    /// don't keep every sample they were ever sent.
+    fn normalize(&self, mut metric: Metric) -> Metric {
+        if let Some(accuracy) = self.config.sketch_relative_accuracy {
+            metric.value = metric.value.into_sketch(accuracy);
+        } else if let MetricValue::Distribution {
+            values,
+            sample_rates,
+        } = &metric.value
+        {
+            metric.value = distribution_to_histogram(&self.config.buckets, values, sample_rates);
+        }
+        metric
+    }
+
+    fn start_server_if_needed(&mut self) {
+        if self.server_shutdown_trigger.is_some() {
+            return;
+        }
+
+        let (trigger, tripwire) = Tripwire::new();
+        self.server_shutdown_trigger = Some(trigger);
+
+        let listener = match self.tls.bind(&self.config.address) {
+            Ok(listener) => listener,
+            Err(error) => {
+                error!(
+                    message = "failed to bind the prometheus server",
+                    address = %self.config.address,
+                    %error
+                );
+                return;
+            }
+        };
+
+        let metrics = Arc::clone(&self.metrics);
+        let config = Arc::clone(&self.config);
+
+        let new_service = make_service_fn(move |_| {
+            let metrics = Arc::clone(&metrics);
+            let config = Arc::clone(&config);
+
+            async move {
+                Ok::<_, crate::Error>(service_fn(move |req| {
+                    let metrics = metrics.read().unwrap();
+                    info_span!(
+                        "prometheus_server",
+                        method = field::debug(req.method()),
+                        path = field::debug(req.uri().path()),
+                    )
+                    .in_scope(|| handle(req, &config, &metrics))
+                    .compat()
+                }))
+            }
+        });
+
+        let incoming = listener
+            .incoming()
+            .compat()
+            .map_ok(|stream| stream.compat().compat());
+        let server = Server::builder(accept::from_stream(incoming))
+            .serve(new_service)
+            .with_graceful_shutdown(tripwire.compat().map(|_| ()))
+            .map_err(|e| eprintln!("server error: {}", e));
+
+        tokio::spawn(server);
+    }
+}
+
+impl Sink for PrometheusSink {
+    type SinkItem = Event;
+    type SinkError = ();
+
+    fn start_send(
+        &mut self,
+        event: Self::SinkItem,
+    ) -> Result<AsyncSink<Self::SinkItem>, Self::SinkError> {
+        self.start_server_if_needed();
+
+        let metric = self.normalize(event.into_metric());
+        let ttl = Duration::from_secs(self.config.flush_period_secs);
+        let now = Instant::now();
+        let mut metrics = self.metrics.write().unwrap();
+        // Scrapes skip expired series already, so they are only swept once a
+        // period to keep their number bounded.
+        if now.duration_since(self.last_sweep) >= ttl {
+            expire(&mut metrics, ttl, now);
+            self.last_sweep = now;
+        }
+        record(&mut metrics, metric, ttl, now);
+        drop(metrics);
+
+        self.acker.ack(1);
+
+        Ok(AsyncSink::Ready)
+    }
+
+    fn poll_complete(&mut self) -> Result<Async<()>, Self::SinkError> {
+        self.start_server_if_needed();
+
+        Ok(Async::Ready(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::metric::{Metric, MetricKind, MetricValue};
+    use chrono::Utc;
+    use pretty_assertions::assert_eq;
+
+    fn tags() -> BTreeMap<String, String> {
+        vec![("code".to_owned(), "200".to_owned())]
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn test_encode_counter() {
+        let metric = Metric {
+            name: "hits".to_owned(),
+            namespace: None,
+            timestamp: None,
+            tags: Some(tags()),
+            kind: MetricKind::Absolute,
+            value: MetricValue::Counter { value: 10.0 },
+        };
+
+        let header = encode_metric_header("vector", &BTreeMap::new(), Format::Text, &metric);
+        let frame = encode_metric_datum("vector", &[], &[], Format::Text, &metric);
+
+        assert_eq!(
+            header,
+            "# HELP vector_hits hits\n# TYPE vector_hits counter\n".to_owned()
+        );
+        assert_eq!(frame, "vector_hits{code=\"200\"} 10\n".to_owned());
+    }
+
+    #[test]
+    fn test_encode_metric_namespace() {
+        let metric = Metric {
+            name: "hits".to_owned(),
+            namespace: Some("app".to_owned()),
+            timestamp: None,
+            tags: None,
+            kind: MetricKind::Absolute,
+            value: MetricValue::Counter { value: 10.0 },
+        };
+
+        let header = encode_metric_header("vector", &BTreeMap::new(), Format::Text, &metric);
+        let frame = encode_metric_datum("vector", &[], &[], Format::Text, &metric);
+
+        assert_eq!(
+            header,
+            "# HELP app_hits hits\n# TYPE app_hits counter\n".to_owned()
+        );
+        assert_eq!(frame, "app_hits 10\n".to_owned());
+    }
+
+    #[test]
+    fn test_encode_gauge() {
+        let metric = Metric {
+            name: "temperature".to_owned(),
+            namespace: None,
+            timestamp: None,
+            tags: Some(tags()),
+            kind: MetricKind::Absolute,
+            value: MetricValue::Gauge { value: -1.1 },
+        };
+
+        let header = encode_metric_header("vector", &BTreeMap::new(), Format::Text, &metric);
+        let frame = encode_metric_datum("vector", &[], &[], Format::Text, &metric);
+
+        assert_eq!(
+            header,
+            "# HELP vector_temperature temperature\n# TYPE vector_temperature gauge\n".to_owned()
+        );
+        assert_eq!(frame, "vector_temperature{code=\"200\"} -1.1\n".to_owned());
+    }
+
+    #[test]
+    fn test_encode_set() {
+        let metric = Metric {
+            name: "users".to_owned(),
+            namespace: None,
+            timestamp: None,
+            tags: None,
+            kind: MetricKind::Absolute,
+            value: MetricValue::Set {
+                values: vec!["foo".into()].into_iter().collect(),
+            },
+        };
+
+        let header = encode_metric_header("", &BTreeMap::new(), Format::Text, &metric);
+        let frame = encode_metric_datum("", &[], &[], Format::Text, &metric);
+
+        assert_eq!(
+            header,
+            "# HELP users users\n# TYPE users gauge\n".to_owned()
+        );
+        assert_eq!(frame, "users 1\n".to_owned());
+    }
+
+    #[test]
+    fn test_encode_distribution() {
+        let metric = Metric {
+            name: "requests".to_owned(),
+            namespace: None,
+            timestamp: None,
+            tags: None,
+            kind: MetricKind::Absolute,
+            value: MetricValue::Distribution {
+                values: vec![1.0, 2.0, 3.0],
+                sample_rates: vec![3, 3, 2],
+            },
+        };
+
+        let header = encode_metric_header("", &BTreeMap::new(), Format::Text, &metric);
+        let frame = encode_metric_datum("", &[0.0, 2.5, 5.0], &[], Format::Text, &metric);
+
+        assert_eq!(
+            header,
+            "# HELP requests requests\n# TYPE requests histogram\n".to_owned()
+        );
+        assert_eq!(frame, "requests_bucket{le=\"0\"} 0\nrequests_bucket{le=\"2.5\"} 6\nrequests_bucket{le=\"5\"} 8\nrequests_bucket{le=\"+Inf\"} 8\nrequests_sum 15\nrequests_count 8\n".to_owned());
+    }
+
+    #[test]
+    fn test_encode_histogram() {
+        let metric = Metric {
+            name: "requests".to_owned(),
+            namespace: None,
+            timestamp: None,
+            tags: None,
+            kind: MetricKind::Absolute,
+            value: MetricValue::AggregatedHistogram {
+                buckets: vec![1.0, 2.1, 3.0],
+                counts: vec![1, 2, 3],
+                count: 6,
+                sum: 12.5,
+            },
+        };
+
+        let header = encode_metric_header("", &BTreeMap::new(), Format::Text, &metric);
+        let frame = encode_metric_datum("", &[], &[], Format::Text, &metric);
+
+        assert_eq!(
+            header,
+            "# HELP requests requests\n# TYPE requests histogram\n".to_owned()
+        );
+        assert_eq!(frame, "requests_bucket{le=\"1\"} 1\nrequests_bucket{le=\"2.1\"} 2\nrequests_bucket{le=\"3\"} 3\nrequests_bucket{le=\"+Inf\"} 6\nrequests_sum 12.5\nrequests_count 6\n".to_owned());
+    }
+
+    #[test]
+    fn test_encode_summary() {
+        let metric = Metric {
+            name: "requests".to_owned(),
+            namespace: None,
+            timestamp: None,
+            tags: Some(tags()),
+            kind: MetricKind::Absolute,
+            value: MetricValue::AggregatedSummary {
+                quantiles: vec![0.01, 0.5, 0.99],
+                values: vec![1.5, 2.0, 3.0],
+                count: 6,
+                sum: 12.0,
+            },
+        };
+
+        let header = encode_metric_header("", &BTreeMap::new(), Format::Text, &metric);
+        let frame = encode_metric_datum("", &[], &[], Format::Text, &metric);
+
+        assert_eq!(
+            header,
+            "# HELP requests requests\n# TYPE requests summary\n".to_owned()
+        );
+        assert_eq!(frame, "requests{code=\"200\",quantile=\"0.01\"} 1.5\nrequests{code=\"200\",quantile=\"0.5\"} 2\nrequests{code=\"200\",quantile=\"0.99\"} 3\nrequests_sum{code=\"200\"} 12\nrequests_count{code=\"200\"} 6\n".to_owned());
+    }
+
+    #[test]
+    fn test_encode_sketch() {
+        let metric = Metric {
+            name: "requests".to_owned(),
+            namespace: None,
+            timestamp: None,
+            tags: None,
+            kind: MetricKind::Absolute,
+            value: MetricValue::Distribution {
+                values: vec![1.0, 2.0, 3.0],
+                sample_rates: vec![3, 3, 2],
+            }
+            .into_sketch(0.01),
+        };
+
+        let header = encode_metric_header("", &BTreeMap::new(), Format::Text, &metric);
+        let frame = encode_metric_datum("", &[], &[0.0, 1.0], Format::Text, &metric);
+
+        assert_eq!(
+            header,
+            "# HELP requests requests\n# TYPE requests summary\n".to_owned()
+        );
+        assert_eq!(frame, "requests{quantile=\"0\"} 1\nrequests{quantile=\"1\"} 3\nrequests_sum 15\nrequests_count 8\n".to_owned());
+    }
+
+    #[test]
+    fn test_encode_help() {
+        let metric = Metric {
+            name: "hits".to_owned(),
+            namespace: None,
+            timestamp: None,
+            tags: None,
+            kind: MetricKind::Absolute,
+            value: MetricValue::Counter { value: 10.0 },
+        };
+        let help = vec![("vector_hits".to_owned(), "Hits on\nthe \\ page".to_owned())]
+            .into_iter()
+            .collect();
+
+        let header = encode_metric_header("vector", &help, Format::Text, &metric);
+
+        assert_eq!(
+            header,
+            "# HELP vector_hits Hits on\\nthe \\\\ page\n# TYPE vector_hits counter\n".to_owned()
+        );
+    }
+
+    #[test]
+    fn test_encode_openmetrics_counter() {
+        let metric = |name: &str| Metric {
+            name: name.to_owned(),
+            namespace: None,
+            timestamp: None,
+            tags: Some(tags()),
+            kind: MetricKind::Absolute,
+            value: MetricValue::Counter { value: 10.0 },
+        };
+
+        for name in &["hits", "hits_total"] {
+            let header = encode_metric_header(
+                "vector",
+                &BTreeMap::new(),
+                Format::OpenMetrics,
+                &metric(name),
+            );
+            let frame = encode_metric_datum("vector", &[], &[], Format::OpenMetrics, &metric(name));
+
+            assert_eq!(
+                header,
+                format!("# HELP vector_hits {}\n# TYPE vector_hits counter\n", name)
+            );
+            assert_eq!(frame, "vector_hits_total{code=\"200\"} 10\n".to_owned());
+        }
+    }
+
+    fn config() -> PrometheusSinkConfig {
+        toml::from_str(
+            r#"
+            namespace = "vector"
+            flush_period_secs = 60
+            "#,
+        )
+        .unwrap()
+    }
+
+    fn incremental(name: &str, code: &str, value: MetricValue) -> Metric {
+        Metric {
+            name: name.to_owned(),
+            namespace: None,
+            timestamp: Some(Utc::now()),
+            tags: Some(
+                vec![("code".to_owned(), code.to_owned())]
+                    .into_iter()
+                    .collect(),
+            ),
+            kind: MetricKind::Incremental,
+            value,
+        }
+    }
+
+    #[test]
+    fn test_record_groups_families() {
+        let config = config();
+        let ttl = Duration::from_secs(config.flush_period_secs);
+        let now = Instant::now();
+        let mut metrics = Series::new();
+
+        for (name, code) in &[
+            ("hits", "200"),
+            ("users", "200"),
+            ("hits", "404"),
+            ("hits", "200"),
+        ] {
+            let metric = incremental(name, code, MetricValue::Counter { value: 1.0 });
+            record(&mut metrics, metric, ttl, now);
+        }
+
+        assert_eq!(
+            encode_metrics(&config, Format::Text, &metrics, now),
+            "# HELP vector_hits hits\n# TYPE vector_hits counter\nvector_hits{code=\"404\"} 1\nvector_hits{code=\"200\"} 2\n\
+             # HELP vector_users users\n# TYPE vector_users counter\nvector_users{code=\"200\"} 1\n"
+        );
+    }
+
+    #[test]
+    fn test_record_expires_series() {
+        let config = config();
+        let ttl = Duration::from_secs(config.flush_period_secs);
+        let start = Instant::now();
+        let later = start + Duration::from_secs(45);
+        let expired = start + Duration::from_secs(90);
+        let mut metrics = Series::new();
+
+        let counter = |code| incremental("hits", code, MetricValue::Counter { value: 1.0 });
+        record(&mut metrics, counter("200"), ttl, start);
+        record(&mut metrics, counter("404"), ttl, start);
+        record(&mut metrics, counter("404"), ttl, later);
+
+        assert_eq!(
+            encode_metrics(&config, Format::OpenMetrics, &metrics, expired),
+            "# HELP vector_hits hits\n# TYPE vector_hits counter\nvector_hits_total{code=\"404\"} 2\n# EOF\n"
+        );
+
+        record(&mut metrics, counter("200"), ttl, expired);
+        let (MetricEntry(metric), _) = metrics.get_index(1).unwrap();
+        assert_eq!(metric.value, MetricValue::Counter { value: 1.0 });
+
+        expire(&mut metrics, ttl, expired + Duration::from_secs(60));
+        assert_eq!(metrics.len(), 1);
+    }
+
+    #[test]
+    fn test_record_resets_sets() {
+        let ttl = Duration::from_secs(60);
+        let start = Instant::now();
+        let mut metrics = Series::new();
+
+        let set = |value: &str| {
+            incremental(
+                "users",
+                "200",
+                MetricValue::Set {
+                    values: vec![value.to_owned()].into_iter().collect(),
+                },
+            )
+        };
+        record(&mut metrics, set("foo"), ttl, start);
+        record(
+            &mut metrics,
+            set("bar"),
+            ttl,
+            start + Duration::from_secs(30),
+        );
+        record(
+            &mut metrics,
+            set("baz"),
+            ttl,
+            start + Duration::from_secs(90),
+        );
+
+        let (MetricEntry(metric), _) = metrics.get_index(0).unwrap();
+        assert_eq!(
+            metric.value,
+            MetricValue::Set {
+                values: vec!["baz".to_owned()].into_iter().collect()
+            }
+        );
+    }
+
+    #[test]
+    fn test_normalize_aggregates_distributions() {
+        let mut config = config();
+        config.buckets = vec![1.0, 2.0];
+        let sink = PrometheusSink::new(config, MaybeTlsSettings::Raw(()), Acker::Null);
+        let ttl = Duration::from_secs(60);
+        let now = Instant::now();
+        let mut metrics = Series::new();
+
+        for value in &[0.5, 1.5, 3.0] {
+            let metric = incremental(
+                "requests",
+                "200",
+                MetricValue::Distribution {
+                    values: vec![*value],
+                    sample_rates: vec![1],
+                },
+            );
+            record(&mut metrics, sink.normalize(metric), ttl, now);
+        }
+
+        let (MetricEntry(metric), _) = metrics.get_index(0).unwrap();
+        assert_eq!(
+            metric.value,
+            MetricValue::AggregatedHistogram {
+                buckets: vec![1.0, 2.0],
+                counts: vec![1, 2],
+                count: 3,
+                sum: 5.0,
+            }
+        );
+    }
+}
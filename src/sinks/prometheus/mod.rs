@@ -0,0 +1,11 @@
+mod collector;
+mod exporter;
+mod remote_write;
+
+pub(self) use super::{Healthcheck, RouterSink};
+
+pub use self::exporter::{
+    default_address, default_flush_period_secs, default_histogram_buckets,
+    default_summary_quantiles, PrometheusSinkConfig,
+};
+pub use self::remote_write::PrometheusRemoteWriteConfig;
@@ -0,0 +1,392 @@
+use super::collector::{distribution_to_histogram, encode_metric_name, metric_series};
+use super::exporter::{default_histogram_buckets, default_summary_quantiles};
+use crate::{
+    event::metric::{Metric, MetricValue},
+    prometheus::{
+        proto::{Label, MetricMetadata, MetricType, Sample, TimeSeries, WriteRequest},
+        METRIC_NAME_LABEL, REMOTE_WRITE_VERSION,
+    },
+    sinks::util::{
+        http::{Auth, BatchedHttpSink, HttpClient, HttpSink},
+        service2::TowerRequestConfig,
+        BatchConfig, BatchSettings, MetricEntry, UriSerde, VecBuffer,
+    },
+    tls::{TlsOptions, TlsSettings},
+    topology::config::{DataType, SinkConfig, SinkContext, SinkDescription},
+    Event,
+};
+use chrono::Utc;
+use futures01::{future, Sink};
+use http::{Request, Uri};
+use lazy_static::lazy_static;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, sync::Mutex};
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct PrometheusRemoteWriteConfig {
+    pub endpoint: UriSerde,
+    #[serde(default)]
+    pub namespace: String,
+    #[serde(default = "default_histogram_buckets")]
+    pub buckets: Vec<f64>,
+    #[serde(default = "default_summary_quantiles")]
+    pub quantiles: Vec<f64>,
+    pub auth: Option<Auth>,
+    #[serde(default)]
+    pub batch: BatchConfig,
+    #[serde(default)]
+    pub request: TowerRequestConfig,
+    pub tls: Option<TlsOptions>,
+}
+
+lazy_static! {
+    static ref REQUEST_DEFAULTS: TowerRequestConfig = TowerRequestConfig {
+        in_flight_limit: Some(5),
+        timeout_secs: Some(30),
+        rate_limit_num: Some(5),
+        ..Default::default()
+    };
+}
+
+inventory::submit! {
+    SinkDescription::new_without_default::<PrometheusRemoteWriteConfig>("prometheus_remote_write")
+}
+
+#[typetag::serde(name = "prometheus_remote_write")]
+impl SinkConfig for PrometheusRemoteWriteConfig {
+    fn build(&self, cx: SinkContext) -> crate::Result<(super::RouterSink, super::Healthcheck)> {
+        let tls = TlsSettings::from_options(&self.tls)?;
+        let client = HttpClient::new(cx.resolver(), tls)?;
+
+        let batch = self
+            .batch
+            .disallow_max_bytes()?
+            .use_size_as_events()?
+            .get_settings_or_default(BatchSettings::default().events(1_000).timeout(1));
+        let request = self.request.unwrap_with(&REQUEST_DEFAULTS);
+
+        let sink = RemoteWriteSink {
+            config: self.clone(),
+            totals: Mutex::new(HashSet::new()),
+        };
+
+        let sink = BatchedHttpSink::new(
+            sink,
+            VecBuffer::new(batch.size),
+            request,
+            batch.timeout,
+            client,
+            cx.acker(),
+        )
+        .dead_letter(cx.dead_letter())
+        .sink_map_err(|e| error!("Fatal prometheus_remote_write sink error: {}", e));
+
+        Ok((Box::new(sink), Box::new(future::ok(()))))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Metric
+    }
+
+    fn sink_type(&self) -> &'static str {
+        "prometheus_remote_write"
+    }
+
+    fn supports_dead_letter(&self) -> bool {
+        true
+    }
+}
+
+struct RemoteWriteSink {
+    config: PrometheusRemoteWriteConfig,
+    /// The running totals of incremental metrics.
+    totals: Mutex<HashSet<MetricEntry>>,
+}
+
+impl RemoteWriteSink {
+    /// Remote write stores the running totals of counters and histograms, so
+    /// incremental metrics are added to the last value of their series.
+    /// Distributions are counted into histograms first, keeping the totals
+    /// bounded, while sets only count their own values.
+    fn normalize(&self, mut metric: Metric) -> Metric {
+        if let MetricValue::Distribution {
+            values,
+            sample_rates,
+        } = &metric.value
+        {
+            metric.value = distribution_to_histogram(&self.config.buckets, values, sample_rates);
+        }
+        if metric.kind.is_absolute() || metric.value.is_set() {
+            return metric;
+        }
+
+        let mut totals = self.totals.lock().unwrap();
+        let total = match totals.take(&MetricEntry(metric.to_absolute())) {
+            Some(MetricEntry(mut total)) => {
+                total.add(&metric);
+                total.timestamp = metric.timestamp;
+                total
+            }
+            None => metric.to_absolute(),
+        };
+        totals.insert(MetricEntry(total.clone()));
+        total
+    }
+}
+
+#[async_trait::async_trait]
+impl HttpSink for RemoteWriteSink {
+    type Input = WriteRequest;
+    type Output = Vec<WriteRequest>;
+
+    fn encode_event(&self, event: Event) -> Option<Self::Input> {
+        let metric = self.normalize(event.into_metric());
+        let name = encode_metric_name(&self.config.namespace, &metric);
+        let timestamp = metric.timestamp.unwrap_or_else(Utc::now).timestamp_millis();
+
+        let timeseries = metric_series(&self.config.buckets, &self.config.quantiles, &metric)
+            .into_iter()
+            .map(|series| {
+                let mut labels = metric.tags.clone().unwrap_or_default();
+                if let Some((label, value)) = series.label {
+                    labels.insert(label.to_string(), value);
+                }
+                labels.insert(
+                    METRIC_NAME_LABEL.to_string(),
+                    format!("{}{}", name, series.suffix),
+                );
+
+                TimeSeries {
+                    labels: labels
+                        .into_iter()
+                        .map(|(name, value)| Label { name, value })
+                        .collect(),
+                    samples: vec![Sample {
+                        value: series.value,
+                        timestamp,
+                    }],
+                }
+            })
+            .collect();
+
+        let metadata = MetricMetadata {
+            r#type: metric_type(&metric.value) as i32,
+            metric_family_name: name,
+            ..Default::default()
+        };
+
+        Some(WriteRequest {
+            timeseries,
+            metadata: vec![metadata],
+        })
+    }
+
+    async fn build_request(&self, events: Self::Output) -> crate::Result<Request<Vec<u8>>> {
+        let mut request = WriteRequest::default();
+        let mut families = HashSet::new();
+        for event in events {
+            request.timeseries.extend(event.timeseries);
+            request.metadata.extend(
+                event
+                    .metadata
+                    .into_iter()
+                    .filter(|metadata| families.insert(metadata.metric_family_name.clone())),
+            );
+        }
+
+        let mut body = Vec::with_capacity(request.encoded_len());
+        request.encode(&mut body)?;
+
+        let uri: Uri = self.config.endpoint.clone().into();
+        let mut request = Request::post(uri)
+            .header("Content-Type", "application/x-protobuf")
+            .header("Content-Encoding", "snappy")
+            .header("X-Prometheus-Remote-Write-Version", REMOTE_WRITE_VERSION)
+            .body(snap::raw::Encoder::new().compress_vec(&body)?)?;
+        if let Some(auth) = &self.config.auth {
+            auth.apply(&mut request);
+        }
+        Ok(request)
+    }
+}
+
+fn metric_type(value: &MetricValue) -> MetricType {
+    match value {
+        MetricValue::Counter { .. } => MetricType::Counter,
+        MetricValue::Gauge { .. } => MetricType::Gauge,
+        MetricValue::Set { .. } => MetricType::Gauge,
+        MetricValue::Distribution { .. } => MetricType::Histogram,
+        MetricValue::AggregatedHistogram { .. } => MetricType::Histogram,
+        MetricValue::AggregatedSummary { .. } => MetricType::Summary,
+        MetricValue::Sketch(_) => MetricType::Summary,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::metric::MetricKind,
+        sinks::util::test::build_test_server,
+        test_util::{next_addr, runtime, shutdown_on_idle},
+    };
+    use futures01::Stream;
+    use pretty_assertions::assert_eq;
+
+    fn sink() -> RemoteWriteSink {
+        let config: PrometheusRemoteWriteConfig = toml::from_str(
+            r#"
+            endpoint = "http://localhost:9090/api/v1/write"
+            namespace = "vector"
+            buckets = [1.0, 2.0]
+            "#,
+        )
+        .unwrap();
+        RemoteWriteSink {
+            config,
+            totals: Mutex::new(HashSet::new()),
+        }
+    }
+
+    fn metric(kind: MetricKind, value: MetricValue) -> Event {
+        Event::Metric(Metric {
+            name: "requests".into(),
+            namespace: None,
+            timestamp: Some(Utc::now()),
+            tags: Some(
+                vec![("code".to_owned(), "200".to_owned())]
+                    .into_iter()
+                    .collect(),
+            ),
+            kind,
+            value,
+        })
+    }
+
+    fn labels(series: &TimeSeries) -> Vec<(&str, &str)> {
+        series
+            .labels
+            .iter()
+            .map(|label| (label.name.as_str(), label.value.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn encodes_running_totals_of_counters() {
+        let sink = sink();
+        let counter = |value| metric(MetricKind::Incremental, MetricValue::Counter { value });
+
+        let first = sink.encode_event(counter(1.0)).unwrap();
+        let second = sink.encode_event(counter(2.0)).unwrap();
+
+        assert_eq!(first.timeseries[0].samples[0].value, 1.0);
+        assert_eq!(second.timeseries[0].samples[0].value, 3.0);
+        assert_eq!(
+            labels(&second.timeseries[0]),
+            vec![("__name__", "vector_requests"), ("code", "200")]
+        );
+        assert_eq!(second.metadata[0].metric_family_name, "vector_requests");
+        assert_eq!(second.metadata[0].r#type, MetricType::Counter as i32);
+    }
+
+    #[test]
+    fn encodes_distributions_as_histograms() {
+        let sink = sink();
+        let distribution = metric(
+            MetricKind::Incremental,
+            MetricValue::Distribution {
+                values: vec![1.0, 3.0],
+                sample_rates: vec![2, 1],
+            },
+        );
+
+        let request = sink.encode_event(distribution).unwrap();
+        let series: Vec<_> = request
+            .timeseries
+            .iter()
+            .map(|series| (labels(series), series.samples[0].value))
+            .collect();
+
+        assert_eq!(
+            series,
+            vec![
+                (
+                    vec![
+                        ("__name__", "vector_requests_bucket"),
+                        ("code", "200"),
+                        ("le", "1")
+                    ],
+                    2.0
+                ),
+                (
+                    vec![
+                        ("__name__", "vector_requests_bucket"),
+                        ("code", "200"),
+                        ("le", "2")
+                    ],
+                    2.0
+                ),
+                (
+                    vec![
+                        ("__name__", "vector_requests_bucket"),
+                        ("code", "200"),
+                        ("le", "+Inf")
+                    ],
+                    3.0
+                ),
+                (
+                    vec![("__name__", "vector_requests_sum"), ("code", "200")],
+                    5.0
+                ),
+                (
+                    vec![("__name__", "vector_requests_count"), ("code", "200")],
+                    3.0
+                ),
+            ]
+        );
+        assert_eq!(request.metadata[0].r#type, MetricType::Histogram as i32);
+    }
+
+    #[test]
+    fn sends_snappy_compressed_requests() {
+        let in_addr = next_addr();
+        let config = format!(
+            r#"
+            endpoint = "http://{}/api/v1/write"
+            "#,
+            in_addr
+        );
+        let config: PrometheusRemoteWriteConfig = toml::from_str(&config).unwrap();
+
+        let mut rt = runtime();
+        let cx = SinkContext::new_test();
+        let (sink, _) = config.build(cx).unwrap();
+        let (rx, trigger, server) = build_test_server(in_addr, &mut rt);
+
+        let events = vec![
+            metric(MetricKind::Absolute, MetricValue::Gauge { value: 1.0 }),
+            metric(MetricKind::Absolute, MetricValue::Gauge { value: 2.0 }),
+        ];
+        let pump = sink.send_all(futures01::stream::iter_ok(events));
+        rt.spawn(server);
+        let _ = rt.block_on(pump).unwrap();
+        drop(trigger);
+
+        let requests = rx.wait().map(Result::unwrap).collect::<Vec<_>>();
+        shutdown_on_idle(rt);
+
+        assert_eq!(requests.len(), 1);
+        let (parts, body) = &requests[0];
+        assert_eq!(parts.uri.path(), "/api/v1/write");
+        assert_eq!(parts.headers["Content-Encoding"], "snappy");
+        assert_eq!(parts.headers["X-Prometheus-Remote-Write-Version"], "0.1.0");
+
+        let body = snap::raw::Decoder::new().decompress_vec(&body[..]).unwrap();
+        let request = WriteRequest::decode(&body[..]).unwrap();
+        assert_eq!(request.timeseries.len(), 2);
+        assert_eq!(request.timeseries[1].samples[0].value, 2.0);
+        assert_eq!(request.metadata.len(), 1);
+    }
+}
@@ -1,12 +1,12 @@
 use crate::{
     buffers::Acker,
-    event::{self, Event},
+    event::{self, BatchStatus, Event},
     sinks::util::encoding::{EncodingConfig, EncodingConfigWithDefault, EncodingConfiguration},
     topology::config::{DataType, SinkConfig, SinkContext, SinkDescription},
 };
 use futures::{lock::Mutex, FutureExt, TryFutureExt};
 use futures01::{
-    future, stream::FuturesUnordered, Async, AsyncSink, Future, Poll, Sink, StartSend, Stream,
+    stream::FuturesUnordered, Async, AsyncSink, Future, Poll, Sink, StartSend, Stream,
 };
 use pulsar::{
     proto::CommandSendReceipt, Authentication, Error as PulsarError, Producer, Pulsar,
@@ -14,9 +14,7 @@ use pulsar::{
 };
 use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, Snafu};
-use std::{collections::HashSet, sync::Arc};
-
-type MetadataFuture<F, M> = future::Join<F, future::FutureResult<M, <F as Future>::Error>>;
+use std::{collections::HashMap, sync::Arc};
 
 #[derive(Debug, Snafu)]
 enum BuildError {
@@ -50,15 +48,16 @@ pub enum Encoding {
 struct PulsarSink {
     encoding: EncodingConfig<Encoding>,
     producer: Arc<Mutex<Producer<TokioExecutor>>>,
-    in_flight: FuturesUnordered<MetadataFuture<SendFuture, usize>>,
+    in_flight: FuturesUnordered<SendStatus>,
     // ack
     seq_head: usize,
     seq_tail: usize,
-    pending_acks: HashSet<usize>,
+    pending_acks: HashMap<usize, BatchStatus>,
     acker: Acker,
 }
 
 type SendFuture = Box<dyn Future<Item = CommandSendReceipt, Error = PulsarError> + 'static + Send>;
+type SendStatus = Box<dyn Future<Item = (BatchStatus, usize), Error = ()> + 'static + Send>;
 
 inventory::submit! {
     SinkDescription::new_without_default::<PulsarSinkConfig>("pulsar")
@@ -122,7 +121,7 @@ impl PulsarSinkConfig {
             in_flight: FuturesUnordered::new(),
             seq_head: 0,
             seq_tail: 0,
-            pending_acks: HashSet::new(),
+            pending_acks: HashMap::new(),
             acker,
         })
     }
@@ -150,8 +149,25 @@ impl Sink for PulsarSink {
 
         let seqno = self.seq_head;
         self.seq_head += 1;
-        self.in_flight
-            .push((Box::new(fut.boxed().compat()) as SendFuture).join(future::ok(seqno)));
+        let status = (Box::new(fut.boxed().compat()) as SendFuture).then(move |result| {
+            let status = match result {
+                Ok(receipt) => {
+                    trace!(
+                        "Pulsar sink produced message {:?} from {} at sequence id {}",
+                        receipt.message_id,
+                        receipt.producer_id,
+                        receipt.sequence_id
+                    );
+                    BatchStatus::Delivered
+                }
+                Err(e) => {
+                    error!("Pulsar sink generated an error: {}", e);
+                    BatchStatus::Failed
+                }
+            };
+            Ok((status, seqno))
+        });
+        self.in_flight.push(Box::new(status));
         Ok(AsyncSink::Ready)
     }
 
@@ -164,22 +180,14 @@ impl Sink for PulsarSink {
                 Ok(Async::Ready(None)) => {
                     return Ok(Async::Ready(()));
                 }
-                Ok(Async::Ready(Some((result, seqno)))) => {
-                    trace!(
-                        "Pulsar sink produced message {:?} from {} at sequence id {}",
-                        result.message_id,
-                        result.producer_id,
-                        result.sequence_id
-                    );
-                    self.pending_acks.insert(seqno);
-                    let mut num_to_ack = 0;
-                    while self.pending_acks.remove(&self.seq_tail) {
-                        num_to_ack += 1;
+                Ok(Async::Ready(Some((status, seqno)))) => {
+                    self.pending_acks.insert(seqno, status);
+                    while let Some(status) = self.pending_acks.remove(&self.seq_tail) {
+                        self.acker.ack_with_status(1, status);
                         self.seq_tail += 1;
                     }
-                    self.acker.ack(num_to_ack);
                 }
-                Err(e) => error!("Pulsar sink generated an error: {}", e),
+                Err(()) => unreachable!("send statuses never fail"),
             }
         }
     }
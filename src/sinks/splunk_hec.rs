@@ -98,6 +98,7 @@ impl SinkConfig for HecSinkConfig {
             client.clone(),
             cx.acker(),
         )
+        .dead_letter(cx.dead_letter())
         .sink_map_err(|e| error!("Fatal splunk_hec sink error: {}", e));
 
         let healthcheck = healthcheck(self.clone(), client).boxed().compat();
@@ -112,6 +113,10 @@ impl SinkConfig for HecSinkConfig {
     fn sink_type(&self) -> &'static str {
         "splunk_hec"
     }
+
+    fn supports_dead_letter(&self) -> bool {
+        true
+    }
 }
 
 #[async_trait::async_trait]
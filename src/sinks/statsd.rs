@@ -4,14 +4,16 @@ use crate::{
     event::Event,
     sinks::util::{
         service2::TowerCompat, BatchConfig, BatchSettings, BatchSink, Buffer, Compression,
+        EncodeSink,
     },
     topology::config::{DataType, SinkConfig, SinkContext, SinkDescription},
 };
 use futures::{future, FutureExt, TryFutureExt};
-use futures01::{stream::iter_ok, Sink};
+use futures01::Sink;
 use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, Snafu};
 use std::collections::BTreeMap;
+use std::fmt::Display;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
 use std::task::{Context, Poll};
 use tower03::{Service, ServiceBuilder};
@@ -20,6 +22,8 @@ use tower03::{Service, ServiceBuilder};
 enum BuildError {
     #[snafu(display("failed to bind to udp listener socket, error = {:?}", source))]
     SocketBindError { source: std::io::Error },
+    #[snafu(display("aggregated_strategy = \"tags\" requires tags, but tag_format is \"none\""))]
+    AggregatedTagsWithoutTags,
 }
 
 pub struct StatsdSvc {
@@ -54,6 +58,73 @@ pub struct StatsdSinkConfig {
     pub address: SocketAddr,
     #[serde(default)]
     pub batch: BatchConfig,
+    #[serde(default)]
+    pub aggregated_strategy: AggregatedStrategy,
+    #[serde(default)]
+    pub tag_format: TagFormat,
+    #[serde(default)]
+    pub distribution_type: DistributionType,
+}
+
+/// How aggregated histograms and summaries, which have no statsd
+/// equivalent, are expanded into statsd series.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AggregatedStrategy {
+    /// Drop aggregated metrics.
+    Drop,
+    /// Emit `<name>_bucket` series tagged with `le` and `<name>` series
+    /// tagged with `quantile`, alongside `<name>_count` and `<name>_sum`.
+    Tags,
+    /// Like `tags`, but with the bucket bound or quantile appended to the
+    /// series name, for backends that do not support tags.
+    Names,
+}
+
+impl Default for AggregatedStrategy {
+    fn default() -> Self {
+        AggregatedStrategy::Tags
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TagFormat {
+    /// Append tags as `|#name:value,...`.
+    Dogstatsd,
+    /// Do not send tags.
+    None,
+}
+
+impl Default for TagFormat {
+    fn default() -> Self {
+        TagFormat::Dogstatsd
+    }
+}
+
+/// The statsd type distributions are sent as.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DistributionType {
+    Histogram,
+    Timer,
+    Distribution,
+}
+
+impl Default for DistributionType {
+    fn default() -> Self {
+        DistributionType::Histogram
+    }
+}
+
+impl DistributionType {
+    fn as_str(self) -> &'static str {
+        match self {
+            DistributionType::Histogram => "h",
+            DistributionType::Timer => "ms",
+            DistributionType::Distribution => "d",
+        }
+    }
 }
 
 pub fn default_address() -> SocketAddr {
@@ -92,7 +163,7 @@ impl StatsdSvc {
             .batch
             .use_size_as_bytes()?
             .get_settings_or_default(BatchSettings::default().bytes(1300).events(1000).timeout(1));
-        let namespace = config.namespace.clone();
+        let encoder = Encoder::new(&config)?;
 
         let client = Client::new(config.address)?;
         let service = StatsdSvc { client };
@@ -104,9 +175,9 @@ impl StatsdSvc {
             Buffer::new(batch.size, Compression::None),
             batch.timeout,
             acker,
-        )
-        .sink_map_err(|e| error!("Fatal statsd sink error: {}", e))
-        .with_flat_map(move |event| iter_ok(encode_event(event, &namespace)));
+        );
+        let sink = EncodeSink::new(sink, move |event| encoder.encode_event(event))
+            .sink_map_err(|e| error!("Fatal statsd sink error: {}", e));
 
         Ok(Box::new(sink))
     }
@@ -121,9 +192,9 @@ fn encode_tags(tags: &BTreeMap<String, String>) -> String {
         .iter()
         .map(|(name, value)| {
             if value == "true" {
-                name.to_string()
+                sanitize_tag(name)
             } else {
-                format!("{}:{}", name, value)
+                format!("{}:{}", sanitize_tag(name), sanitize_tag(value))
             }
         })
         .collect();
@@ -131,72 +202,191 @@ fn encode_tags(tags: &BTreeMap<String, String>) -> String {
     parts.join(",")
 }
 
-fn encode_event(event: Event, namespace: &str) -> Option<Vec<u8>> {
-    let mut buf = Vec::new();
-
-    let metric = event.as_metric();
-    match metric.kind {
-        MetricKind::Incremental => match &metric.value {
-            MetricValue::Counter { value } => {
-                buf.push(format!("{}:{}", metric.name, value));
-                buf.push("c".to_string());
-                if let Some(t) = &metric.tags {
-                    buf.push(format!("#{}", encode_tags(t)));
-                };
+/// Replaces the characters delimiting tags and line fields.
+fn sanitize_tag(tag: &str) -> String {
+    tag.replace(|c| c == ',' || c == '|' || c == '\n', "_")
+}
+
+/// Formats a bucket bound or quantile for use in a series name.
+fn encode_bound(bound: f64) -> String {
+    bound.to_string().replace('.', "_").replace('-', "neg")
+}
+
+struct Encoder {
+    namespace: String,
+    aggregated_strategy: AggregatedStrategy,
+    tag_format: TagFormat,
+    distribution_type: DistributionType,
+}
+
+impl Encoder {
+    fn new(config: &StatsdSinkConfig) -> crate::Result<Self> {
+        if config.aggregated_strategy == AggregatedStrategy::Tags
+            && config.tag_format == TagFormat::None
+        {
+            return Err(BuildError::AggregatedTagsWithoutTags.into());
+        }
+
+        Ok(Self {
+            namespace: config.namespace.clone(),
+            aggregated_strategy: config.aggregated_strategy,
+            tag_format: config.tag_format,
+            distribution_type: config.distribution_type,
+        })
+    }
+
+    fn encode_event(&self, event: Event) -> Option<Vec<u8>> {
+        let metric = event.as_metric();
+        let tags = metric.tags.as_ref();
+        let mut lines = Vec::new();
+
+        match (&metric.kind, &metric.value) {
+            (MetricKind::Incremental, MetricValue::Counter { value }) => {
+                lines.push(self.line(&metric.name, value, "c", 1, tags));
             }
-            MetricValue::Gauge { value } => {
-                buf.push(format!("{}:{:+}", metric.name, value));
-                buf.push("g".to_string());
-                if let Some(t) = &metric.tags {
-                    buf.push(format!("#{}", encode_tags(t)));
-                };
+            (MetricKind::Incremental, MetricValue::Gauge { value }) => {
+                lines.push(self.line(&metric.name, format!("{:+}", value), "g", 1, tags));
+            }
+            (MetricKind::Absolute, MetricValue::Gauge { value }) => {
+                lines.push(self.line(&metric.name, value, "g", 1, tags));
+            }
+            (
+                MetricKind::Incremental,
+                MetricValue::Distribution {
+                    values,
+                    sample_rates,
+                },
+            ) => {
+                let metric_type = self.distribution_type.as_str();
+                for (value, sample_rate) in values.iter().zip(sample_rates.iter()) {
+                    // A zero rate carries no samples.
+                    if *sample_rate != 0 {
+                        lines.push(self.line(&metric.name, value, metric_type, *sample_rate, tags));
+                    }
+                }
             }
-            MetricValue::Distribution {
-                values,
-                sample_rates,
-            } => {
-                for (val, sample_rate) in values.iter().zip(sample_rates.iter()) {
-                    buf.push(format!("{}:{}", metric.name, val));
-                    buf.push("h".to_string());
-                    if *sample_rate != 1 {
-                        buf.push(format!("@{}", 1.0 / f64::from(*sample_rate)));
-                    };
-                    if let Some(t) = &metric.tags {
-                        buf.push(format!("#{}", encode_tags(t)));
-                    };
+            (MetricKind::Incremental, MetricValue::Set { values }) => {
+                for value in values {
+                    lines.push(self.line(&metric.name, value, "s", 1, tags));
                 }
             }
-            MetricValue::Set { values } => {
-                for val in values {
-                    buf.push(format!("{}:{}", metric.name, val));
-                    buf.push("s".to_string());
-                    if let Some(t) = &metric.tags {
-                        buf.push(format!("#{}", encode_tags(t)));
-                    };
+            (
+                kind,
+                MetricValue::AggregatedHistogram {
+                    buckets,
+                    counts,
+                    count,
+                    sum,
+                },
+            ) if self.aggregated_strategy != AggregatedStrategy::Drop => {
+                let metric_type = aggregated_type(kind);
+                for (bucket, bucket_count) in buckets.iter().zip(counts.iter()) {
+                    let (name, tags) =
+                        self.expand(format!("{}_bucket", metric.name), "le", *bucket, tags);
+                    lines.push(self.line(&name, bucket_count, metric_type, 1, tags.as_ref()));
                 }
+                self.push_count_and_sum(&mut lines, &metric.name, *count, *sum, kind, tags);
+            }
+            (
+                kind,
+                MetricValue::AggregatedSummary {
+                    quantiles,
+                    values,
+                    count,
+                    sum,
+                },
+            ) if self.aggregated_strategy != AggregatedStrategy::Drop => {
+                for (quantile, value) in quantiles.iter().zip(values.iter()) {
+                    let (name, tags) =
+                        self.expand(metric.name.clone(), "quantile", *quantile, tags);
+                    lines.push(self.line(&name, value, "g", 1, tags.as_ref()));
+                }
+                self.push_count_and_sum(&mut lines, &metric.name, *count, *sum, kind, tags);
             }
             _ => {}
-        },
-        MetricKind::Absolute => {
-            if let MetricValue::Gauge { value } = &metric.value {
-                buf.push(format!("{}:{}", metric.name, value));
-                buf.push("g".to_string());
-                if let Some(t) = &metric.tags {
-                    buf.push(format!("#{}", encode_tags(t)));
-                };
-            };
         }
+
+        if lines.is_empty() {
+            return None;
+        }
+
+        let namespace = metric.namespace.as_deref().unwrap_or(&self.namespace);
+        let mut body = Vec::new();
+        for line in lines {
+            if !namespace.is_empty() {
+                body.extend_from_slice(namespace.as_bytes());
+                body.push(b'.');
+            }
+            body.extend_from_slice(line.as_bytes());
+            body.push(b'\n');
+        }
+
+        Some(body)
     }
 
-    let mut message: String = buf.join("|");
-    if !namespace.is_empty() {
-        message = format!("{}.{}", namespace, message);
-    };
+    /// Formats a single `name:value|type[|@rate][|#tags]` line.
+    fn line(
+        &self,
+        name: &str,
+        value: impl Display,
+        metric_type: &str,
+        sample_rate: u32,
+        tags: Option<&BTreeMap<String, String>>,
+    ) -> String {
+        let mut parts = vec![format!("{}:{}", name, value), metric_type.to_string()];
+        if sample_rate != 1 {
+            parts.push(format!("@{}", 1.0 / f64::from(sample_rate)));
+        }
+        if let (TagFormat::Dogstatsd, Some(tags)) = (self.tag_format, tags) {
+            parts.push(format!("#{}", encode_tags(tags)));
+        }
+        parts.join("|")
+    }
 
-    let mut body: Vec<u8> = message.into_bytes();
-    body.push(b'\n');
+    /// Attaches a histogram bucket bound or summary quantile to a series,
+    /// either as the `tag` tag or appended to its name as `_<tag>_<bound>`.
+    fn expand(
+        &self,
+        name: String,
+        tag: &str,
+        bound: f64,
+        tags: Option<&BTreeMap<String, String>>,
+    ) -> (String, Option<BTreeMap<String, String>>) {
+        match self.aggregated_strategy {
+            AggregatedStrategy::Names => (
+                format!("{}_{}_{}", name, tag, encode_bound(bound)),
+                tags.cloned(),
+            ),
+            _ => {
+                let mut tags = tags.cloned().unwrap_or_default();
+                tags.insert(tag.to_string(), bound.to_string());
+                (name, Some(tags))
+            }
+        }
+    }
 
-    Some(body)
+    fn push_count_and_sum(
+        &self,
+        lines: &mut Vec<String>,
+        name: &str,
+        count: u32,
+        sum: f64,
+        kind: &MetricKind,
+        tags: Option<&BTreeMap<String, String>>,
+    ) {
+        let metric_type = aggregated_type(kind);
+        lines.push(self.line(&format!("{}_count", name), count, metric_type, 1, tags));
+        lines.push(self.line(&format!("{}_sum", name), sum, metric_type, 1, tags));
+    }
+}
+
+/// Absolute counts are cumulative and would be summed up again by statsd if
+/// sent as counters, so they are sent as gauges instead.
+fn aggregated_type(kind: &MetricKind) -> &'static str {
+    match kind {
+        MetricKind::Incremental => "c",
+        MetricKind::Absolute => "g",
+    }
 }
 
 impl Service<Vec<u8>> for StatsdSvc {
@@ -228,7 +418,12 @@ mod test {
         Event,
     };
     use bytes::Bytes;
-    use futures01::{future, stream::Stream, sync::mpsc, Future, Sink};
+    use futures01::{
+        future,
+        stream::{iter_ok, Stream},
+        sync::mpsc,
+        Future, Sink,
+    };
     use std::time::{Duration, Instant};
     use tokio01::{
         self,
@@ -248,6 +443,39 @@ mod test {
         .collect()
     }
 
+    fn config(namespace: &str) -> StatsdSinkConfig {
+        StatsdSinkConfig {
+            namespace: namespace.into(),
+            address: default_address(),
+            batch: BatchConfig::default(),
+            aggregated_strategy: AggregatedStrategy::default(),
+            tag_format: TagFormat::default(),
+            distribution_type: DistributionType::default(),
+        }
+    }
+
+    fn encoder(config: StatsdSinkConfig) -> Encoder {
+        Encoder::new(&config).unwrap()
+    }
+
+    fn encode(encoder: &Encoder, kind: MetricKind, value: MetricValue) -> Option<String> {
+        let event = Event::Metric(Metric {
+            name: "requests".to_owned(),
+            namespace: None,
+            timestamp: None,
+            tags: Some(
+                vec![("host".to_owned(), "a".to_owned())]
+                    .into_iter()
+                    .collect(),
+            ),
+            kind,
+            value,
+        });
+        encoder
+            .encode_event(event)
+            .map(|body| String::from_utf8(body).unwrap())
+    }
+
     #[test]
     fn test_encode_tags() {
         assert_eq!(
@@ -261,13 +489,14 @@ mod test {
     fn test_encode_counter() {
         let metric1 = Metric {
             name: "counter".to_owned(),
+            namespace: None,
             timestamp: None,
             tags: Some(tags()),
             kind: MetricKind::Incremental,
             value: MetricValue::Counter { value: 1.5 },
         };
         let event = Event::Metric(metric1.clone());
-        let frame = &encode_event(event, "").unwrap();
+        let frame = &encoder(config("")).encode_event(event).unwrap();
         let metric2 = parse(from_utf8(&frame).unwrap().trim()).unwrap();
         assert_eq!(metric1, metric2);
     }
@@ -277,13 +506,14 @@ mod test {
     fn test_encode_gauge() {
         let metric1 = Metric {
             name: "gauge".to_owned(),
+            namespace: None,
             timestamp: None,
             tags: Some(tags()),
             kind: MetricKind::Incremental,
             value: MetricValue::Gauge { value: -1.5 },
         };
         let event = Event::Metric(metric1.clone());
-        let frame = &encode_event(event, "").unwrap();
+        let frame = &encoder(config("")).encode_event(event).unwrap();
         let metric2 = parse(from_utf8(&frame).unwrap().trim()).unwrap();
         assert_eq!(metric1, metric2);
     }
@@ -293,6 +523,7 @@ mod test {
     fn test_encode_distribution() {
         let metric1 = Metric {
             name: "distribution".to_owned(),
+            namespace: None,
             timestamp: None,
             tags: Some(tags()),
             kind: MetricKind::Incremental,
@@ -302,7 +533,7 @@ mod test {
             },
         };
         let event = Event::Metric(metric1.clone());
-        let frame = &encode_event(event, "").unwrap();
+        let frame = &encoder(config("")).encode_event(event).unwrap();
         let metric2 = parse(from_utf8(&frame).unwrap().trim()).unwrap();
         assert_eq!(metric1, metric2);
     }
@@ -312,6 +543,7 @@ mod test {
     fn test_encode_set() {
         let metric1 = Metric {
             name: "set".to_owned(),
+            namespace: None,
             timestamp: None,
             tags: Some(tags()),
             kind: MetricKind::Incremental,
@@ -320,11 +552,96 @@ mod test {
             },
         };
         let event = Event::Metric(metric1.clone());
-        let frame = &encode_event(event, "").unwrap();
+        let frame = &encoder(config("")).encode_event(event).unwrap();
         let metric2 = parse(from_utf8(&frame).unwrap().trim()).unwrap();
         assert_eq!(metric1, metric2);
     }
 
+    #[test]
+    fn test_encode_tags_sanitized() {
+        let tags = vec![("a|b".to_owned(), "c,d".to_owned())]
+            .into_iter()
+            .collect();
+        assert_eq!(&encode_tags(&tags), "a_b:c_d");
+    }
+
+    #[test]
+    fn test_encode_aggregated_histogram_tags() {
+        let histogram = MetricValue::AggregatedHistogram {
+            buckets: vec![0.5, 1.0],
+            counts: vec![1, 3],
+            count: 3,
+            sum: 2.5,
+        };
+        let encoded = encode(&encoder(config("ns")), MetricKind::Absolute, histogram);
+        assert_eq!(
+            encoded.unwrap(),
+            "ns.requests_bucket:1|g|#host:a,le:0.5\n\
+             ns.requests_bucket:3|g|#host:a,le:1\n\
+             ns.requests_count:3|g|#host:a\n\
+             ns.requests_sum:2.5|g|#host:a\n"
+        );
+    }
+
+    #[test]
+    fn test_encode_aggregated_summary_names() {
+        let mut config = config("");
+        config.aggregated_strategy = AggregatedStrategy::Names;
+        config.tag_format = TagFormat::None;
+        let summary = MetricValue::AggregatedSummary {
+            quantiles: vec![0.5, 0.99],
+            values: vec![1.5, 4.0],
+            count: 10,
+            sum: 20.0,
+        };
+        let encoded = encode(&encoder(config), MetricKind::Incremental, summary);
+        assert_eq!(
+            encoded.unwrap(),
+            "requests_quantile_0_5:1.5|g\n\
+             requests_quantile_0_99:4|g\n\
+             requests_count:10|c\n\
+             requests_sum:20|c\n"
+        );
+    }
+
+    #[test]
+    fn test_encode_aggregated_drop() {
+        let mut config = config("");
+        config.aggregated_strategy = AggregatedStrategy::Drop;
+        let summary = MetricValue::AggregatedSummary {
+            quantiles: vec![0.5],
+            values: vec![1.5],
+            count: 10,
+            sum: 20.0,
+        };
+        assert_eq!(
+            encode(&encoder(config), MetricKind::Absolute, summary),
+            None
+        );
+    }
+
+    #[test]
+    fn test_encode_distribution_sample_rates() {
+        let mut config = config("");
+        config.distribution_type = DistributionType::Distribution;
+        let distribution = MetricValue::Distribution {
+            values: vec![1.0, 2.0, 3.0],
+            sample_rates: vec![1, 4, 0],
+        };
+        let encoded = encode(&encoder(config), MetricKind::Incremental, distribution);
+        assert_eq!(
+            encoded.unwrap(),
+            "requests:1|d|#host:a\nrequests:2|d|@0.25|#host:a\n"
+        );
+    }
+
+    #[test]
+    fn test_aggregated_tags_require_tag_format() {
+        let mut config = config("");
+        config.tag_format = TagFormat::None;
+        assert!(Encoder::new(&config).is_err());
+    }
+
     #[test]
     fn test_send_to_statsd() {
         crate::test_util::trace_init();
@@ -337,6 +654,9 @@ mod test {
                 timeout_secs: Some(1),
                 ..Default::default()
             },
+            aggregated_strategy: AggregatedStrategy::default(),
+            tag_format: TagFormat::default(),
+            distribution_type: DistributionType::default(),
         };
 
         let mut rt = runtime();
@@ -345,6 +665,7 @@ mod test {
         let mut events = Vec::new();
         let event = Event::Metric(Metric {
             name: "counter".to_owned(),
+            namespace: None,
             timestamp: None,
             tags: Some(tags()),
             kind: MetricKind::Incremental,
@@ -354,6 +675,7 @@ mod test {
 
         let event = Event::Metric(Metric {
             name: "histogram".to_owned(),
+            namespace: None,
             timestamp: None,
             tags: None,
             kind: MetricKind::Incremental,
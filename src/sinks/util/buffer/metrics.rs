@@ -4,6 +4,7 @@ use crate::sinks::util::{Batch, BatchSize, PushResult};
 use std::cmp::Ordering;
 use std::collections::{hash_map::DefaultHasher, HashSet};
 use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
 
 #[derive(Clone, Debug)]
 pub struct MetricEntry(pub Metric);
@@ -15,6 +16,7 @@ impl Hash for MetricEntry {
         let metric = &self.0;
         std::mem::discriminant(&metric.value).hash(state);
         metric.name.hash(state);
+        metric.namespace.hash(state);
         metric.kind.hash(state);
 
         if let Some(tags) = &metric.tags {
@@ -55,9 +57,12 @@ impl PartialEq for MetricEntry {
     }
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone)]
 pub struct MetricBuffer {
-    state: HashSet<MetricEntry>,
+    /// The last known values of absolute counters and gauges, shared with
+    /// every buffer created by `fresh` so the batches of partitioned sinks
+    /// see it as well.
+    state: Arc<Mutex<HashSet<MetricEntry>>>,
     metrics: HashSet<MetricEntry>,
     max_events: usize,
 }
@@ -88,6 +93,7 @@ impl MetricBuffer {
     //   Absolute Counter             => Counter
     //   Gauge                        => Absolute Gauge
     //   Distribution                 => Distribution
+    //   Sketch                       => Sketch
     //   Set                          => Set
     //
     // Deduplicated metrics
@@ -96,12 +102,13 @@ impl MetricBuffer {
     //   AggregatedSummary            => AggregatedSummary
     //   Absolute AggregatedHistogram => Absolute AggregatedHistogram
     //   Absolute AggregatedSummary   => Absolute AggregatedSummary
+    //   Absolute Sketch              => Absolute Sketch
     //
     pub fn new(settings: BatchSize) -> Self {
-        Self::new_with_state(settings.events, HashSet::new())
+        Self::new_with_state(settings.events, Arc::new(Mutex::new(HashSet::new())))
     }
 
-    fn new_with_state(max_events: usize, state: HashSet<MetricEntry>) -> Self {
+    fn new_with_state(max_events: usize, state: Arc<Mutex<HashSet<MetricEntry>>>) -> Self {
         Self {
             state,
             metrics: HashSet::with_capacity(max_events),
@@ -119,6 +126,7 @@ impl Batch for MetricBuffer {
             PushResult::Overflow(item)
         } else {
             let item = item.into_metric();
+            let mut state = self.state.lock().unwrap();
 
             match &item.value {
                 MetricValue::Counter { value } if item.kind.is_absolute() => {
@@ -126,12 +134,13 @@ impl Batch for MetricBuffer {
                     if let Some(MetricEntry(Metric {
                         value: MetricValue::Counter { value: value0, .. },
                         ..
-                    })) = self.state.get(&new)
+                    })) = state.get(&new)
                     {
                         // Counters are disaggregated. We take the previoud value from the state
                         // and emit the difference between previous and current as a Counter
                         let delta = MetricEntry(Metric {
                             name: item.name.to_string(),
+                            namespace: item.namespace.clone(),
                             timestamp: item.timestamp,
                             tags: item.tags.clone(),
                             kind: MetricKind::Incremental,
@@ -147,26 +156,27 @@ impl Batch for MetricBuffer {
                         } else {
                             self.metrics.insert(delta);
                         }
-                        self.state.replace(new);
+                        state.replace(new);
                     } else {
-                        self.state.insert(new);
+                        state.insert(new);
                     }
                 }
                 MetricValue::Gauge { .. } if item.kind.is_incremental() => {
                     let new = MetricEntry(item.clone().to_absolute());
-                    if let Some(MetricEntry(mut existing)) = self.metrics.take(&new) {
+                    let gauge = if let Some(MetricEntry(mut existing)) = self.metrics.take(&new) {
                         existing.add(&item);
-                        self.metrics.insert(MetricEntry(existing));
+                        existing
                     } else {
                         // If the metric is not present in active batch,
                         // then we look it up in permanent state, where we keep track
                         // of its values throughout the entire application uptime
-                        let mut initial = if let Some(default) = self.state.get(&new) {
+                        let mut initial = if let Some(default) = state.get(&new) {
                             default.0.clone()
                         } else {
                             // Otherwise we start from zero value
                             Metric {
                                 name: item.name.to_string(),
+                                namespace: item.namespace.clone(),
                                 timestamp: item.timestamp,
                                 tags: item.tags.clone(),
                                 kind: MetricKind::Absolute,
@@ -174,11 +184,16 @@ impl Batch for MetricBuffer {
                             }
                         };
                         initial.add(&item);
-                        self.metrics.insert(MetricEntry(initial));
-                    }
+                        initial
+                    };
+                    state.replace(MetricEntry(gauge.clone()));
+                    self.metrics.insert(MetricEntry(gauge));
                 }
                 _metric if item.kind.is_absolute() => {
                     let new = MetricEntry(item);
+                    if new.0.value.is_gauge() {
+                        state.replace(new.clone());
+                    }
                     self.metrics.replace(new);
                 }
                 _ => {
@@ -200,16 +215,7 @@ impl Batch for MetricBuffer {
     }
 
     fn fresh(&self) -> Self {
-        let mut state = self.state.clone();
-        for entry in self.metrics.iter() {
-            if (entry.0.value.is_gauge() || entry.0.value.is_counter())
-                && entry.0.kind.is_absolute()
-            {
-                state.replace(entry.clone());
-            }
-        }
-
-        Self::new_with_state(self.max_events, state)
+        Self::new_with_state(self.max_events, Arc::clone(&self.state))
     }
 
     fn finish(self) -> Self::Output {
@@ -273,7 +279,10 @@ mod test {
     use crate::sinks::util::{BatchSink, BatchSize};
     use crate::{
         buffers::Acker,
-        event::metric::{Metric, MetricValue},
+        event::{
+            metric::{Metric, MetricValue},
+            sketch::DDSketch,
+        },
         runtime::Runtime,
         test_util::runtime,
         Event,
@@ -340,6 +349,7 @@ mod test {
         for i in 0..4 {
             let event = Event::Metric(Metric {
                 name: "counter-0".into(),
+                namespace: None,
                 timestamp: None,
                 tags: Some(tag("production")),
                 kind: MetricKind::Incremental,
@@ -351,6 +361,7 @@ mod test {
         for i in 0..4 {
             let event = Event::Metric(Metric {
                 name: format!("counter-{}", i),
+                namespace: None,
                 timestamp: None,
                 tags: Some(tag("staging")),
                 kind: MetricKind::Incremental,
@@ -362,6 +373,7 @@ mod test {
         for i in 0..4 {
             let event = Event::Metric(Metric {
                 name: format!("counter-{}", i),
+                namespace: None,
                 timestamp: None,
                 tags: Some(tag("production")),
                 kind: MetricKind::Incremental,
@@ -389,6 +401,7 @@ mod test {
             [
                 Metric {
                     name: "counter-0".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("production")),
                     kind: MetricKind::Incremental,
@@ -396,6 +409,7 @@ mod test {
                 },
                 Metric {
                     name: "counter-0".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("staging")),
                     kind: MetricKind::Incremental,
@@ -403,6 +417,7 @@ mod test {
                 },
                 Metric {
                     name: "counter-1".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("production")),
                     kind: MetricKind::Incremental,
@@ -410,6 +425,7 @@ mod test {
                 },
                 Metric {
                     name: "counter-1".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("staging")),
                     kind: MetricKind::Incremental,
@@ -417,6 +433,7 @@ mod test {
                 },
                 Metric {
                     name: "counter-2".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("staging")),
                     kind: MetricKind::Incremental,
@@ -424,6 +441,7 @@ mod test {
                 },
                 Metric {
                     name: "counter-3".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("staging")),
                     kind: MetricKind::Incremental,
@@ -437,6 +455,7 @@ mod test {
             [
                 Metric {
                     name: "counter-2".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("production")),
                     kind: MetricKind::Incremental,
@@ -444,6 +463,7 @@ mod test {
                 },
                 Metric {
                     name: "counter-3".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("production")),
                     kind: MetricKind::Incremental,
@@ -461,6 +481,7 @@ mod test {
         for i in 0..4 {
             let event = Event::Metric(Metric {
                 name: format!("counter-{}", i),
+                namespace: None,
                 timestamp: None,
                 tags: Some(tag("production")),
                 kind: MetricKind::Absolute,
@@ -472,6 +493,7 @@ mod test {
         for i in 0..4 {
             let event = Event::Metric(Metric {
                 name: format!("counter-{}", i),
+                namespace: None,
                 timestamp: None,
                 tags: Some(tag("production")),
                 kind: MetricKind::Absolute,
@@ -500,6 +522,7 @@ mod test {
             [
                 Metric {
                     name: "counter-0".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("production")),
                     kind: MetricKind::Incremental,
@@ -507,6 +530,7 @@ mod test {
                 },
                 Metric {
                     name: "counter-1".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("production")),
                     kind: MetricKind::Incremental,
@@ -514,6 +538,7 @@ mod test {
                 },
                 Metric {
                     name: "counter-2".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("production")),
                     kind: MetricKind::Incremental,
@@ -521,6 +546,7 @@ mod test {
                 },
                 Metric {
                     name: "counter-3".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("production")),
                     kind: MetricKind::Incremental,
@@ -538,6 +564,7 @@ mod test {
         for i in 1..5 {
             let event = Event::Metric(Metric {
                 name: format!("gauge-{}", i),
+                namespace: None,
                 timestamp: None,
                 tags: Some(tag("staging")),
                 kind: MetricKind::Incremental,
@@ -549,6 +576,7 @@ mod test {
         for i in 1..5 {
             let event = Event::Metric(Metric {
                 name: format!("gauge-{}", i),
+                namespace: None,
                 timestamp: None,
                 tags: Some(tag("staging")),
                 kind: MetricKind::Incremental,
@@ -575,6 +603,7 @@ mod test {
             [
                 Metric {
                     name: "gauge-1".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("staging")),
                     kind: MetricKind::Absolute,
@@ -582,6 +611,7 @@ mod test {
                 },
                 Metric {
                     name: "gauge-2".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("staging")),
                     kind: MetricKind::Absolute,
@@ -589,6 +619,7 @@ mod test {
                 },
                 Metric {
                     name: "gauge-3".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("staging")),
                     kind: MetricKind::Absolute,
@@ -596,6 +627,7 @@ mod test {
                 },
                 Metric {
                     name: "gauge-4".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("staging")),
                     kind: MetricKind::Absolute,
@@ -613,6 +645,7 @@ mod test {
         for i in 3..6 {
             let event = Event::Metric(Metric {
                 name: format!("gauge-{}", i),
+                namespace: None,
                 timestamp: None,
                 tags: Some(tag("staging")),
                 kind: MetricKind::Absolute,
@@ -626,6 +659,7 @@ mod test {
         for i in 1..4 {
             let event = Event::Metric(Metric {
                 name: format!("gauge-{}", i),
+                namespace: None,
                 timestamp: None,
                 tags: Some(tag("staging")),
                 kind: MetricKind::Incremental,
@@ -637,6 +671,7 @@ mod test {
         for i in 2..5 {
             let event = Event::Metric(Metric {
                 name: format!("gauge-{}", i),
+                namespace: None,
                 timestamp: None,
                 tags: Some(tag("staging")),
                 kind: MetricKind::Absolute,
@@ -665,6 +700,7 @@ mod test {
             [
                 Metric {
                     name: "gauge-1".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("staging")),
                     kind: MetricKind::Absolute,
@@ -672,6 +708,7 @@ mod test {
                 },
                 Metric {
                     name: "gauge-2".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("staging")),
                     kind: MetricKind::Absolute,
@@ -679,6 +716,7 @@ mod test {
                 },
                 Metric {
                     name: "gauge-3".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("staging")),
                     kind: MetricKind::Absolute,
@@ -686,6 +724,7 @@ mod test {
                 },
                 Metric {
                     name: "gauge-4".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("staging")),
                     kind: MetricKind::Absolute,
@@ -693,6 +732,7 @@ mod test {
                 },
                 Metric {
                     name: "gauge-5".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("staging")),
                     kind: MetricKind::Absolute,
@@ -710,6 +750,7 @@ mod test {
         for i in 0..4 {
             let event = Event::Metric(Metric {
                 name: "set-0".into(),
+                namespace: None,
                 timestamp: None,
                 tags: Some(tag("production")),
                 kind: MetricKind::Incremental,
@@ -723,6 +764,7 @@ mod test {
         for i in 0..4 {
             let event = Event::Metric(Metric {
                 name: "set-0".into(),
+                namespace: None,
                 timestamp: None,
                 tags: Some(tag("production")),
                 kind: MetricKind::Incremental,
@@ -749,6 +791,7 @@ mod test {
             sorted(&buffer[0].clone()),
             [Metric {
                 name: "set-0".into(),
+                namespace: None,
                 timestamp: None,
                 tags: Some(tag("production")),
                 kind: MetricKind::Incremental,
@@ -769,6 +812,7 @@ mod test {
         for _ in 2..6 {
             let event = Event::Metric(Metric {
                 name: "dist-2".into(),
+                namespace: None,
                 timestamp: None,
                 tags: Some(tag("production")),
                 kind: MetricKind::Incremental,
@@ -783,6 +827,7 @@ mod test {
         for i in 2..6 {
             let event = Event::Metric(Metric {
                 name: format!("dist-{}", i),
+                namespace: None,
                 timestamp: None,
                 tags: Some(tag("production")),
                 kind: MetricKind::Incremental,
@@ -811,6 +856,7 @@ mod test {
             [
                 Metric {
                     name: "dist-2".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("production")),
                     kind: MetricKind::Incremental,
@@ -821,6 +867,7 @@ mod test {
                 },
                 Metric {
                     name: "dist-3".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("production")),
                     kind: MetricKind::Incremental,
@@ -831,6 +878,7 @@ mod test {
                 },
                 Metric {
                     name: "dist-4".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("production")),
                     kind: MetricKind::Incremental,
@@ -841,6 +889,7 @@ mod test {
                 },
                 Metric {
                     name: "dist-5".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("production")),
                     kind: MetricKind::Incremental,
@@ -853,6 +902,186 @@ mod test {
         );
     }
 
+    #[test]
+    fn metric_buffer_keeps_namespaces_apart() {
+        let (sink, _rt, mut clock, sent_batches) = sink();
+
+        let metric = |namespace: &str, kind, value| {
+            Event::Metric(Metric {
+                name: "metric".into(),
+                namespace: Some(namespace.into()),
+                timestamp: None,
+                tags: None,
+                kind,
+                value,
+            })
+        };
+        let events = vec![
+            metric(
+                "a",
+                MetricKind::Absolute,
+                MetricValue::Counter { value: 1.0 },
+            ),
+            metric(
+                "b",
+                MetricKind::Absolute,
+                MetricValue::Counter { value: 10.0 },
+            ),
+            metric(
+                "a",
+                MetricKind::Absolute,
+                MetricValue::Counter { value: 3.0 },
+            ),
+            metric(
+                "b",
+                MetricKind::Absolute,
+                MetricValue::Counter { value: 15.0 },
+            ),
+            metric(
+                "a",
+                MetricKind::Incremental,
+                MetricValue::Gauge { value: 1.0 },
+            ),
+            metric(
+                "b",
+                MetricKind::Incremental,
+                MetricValue::Gauge { value: 2.0 },
+            ),
+        ];
+
+        let (sink, _) = clock.enter(|_| {
+            sink.sink_map_err(drop)
+                .send_all(futures01::stream::iter_ok(events.into_iter()))
+                .wait()
+                .unwrap()
+        });
+        drop(sink);
+
+        let buffer = Arc::try_unwrap(sent_batches).unwrap().into_inner().unwrap();
+
+        assert_eq!(buffer.len(), 1);
+        assert_eq!(
+            sorted(&buffer[0]),
+            sorted(&[
+                Metric {
+                    name: "metric".into(),
+                    namespace: Some("a".into()),
+                    timestamp: None,
+                    tags: None,
+                    kind: MetricKind::Incremental,
+                    value: MetricValue::Counter { value: 2.0 },
+                },
+                Metric {
+                    name: "metric".into(),
+                    namespace: Some("b".into()),
+                    timestamp: None,
+                    tags: None,
+                    kind: MetricKind::Incremental,
+                    value: MetricValue::Counter { value: 5.0 },
+                },
+                Metric {
+                    name: "metric".into(),
+                    namespace: Some("a".into()),
+                    timestamp: None,
+                    tags: None,
+                    kind: MetricKind::Absolute,
+                    value: MetricValue::Gauge { value: 1.0 },
+                },
+                Metric {
+                    name: "metric".into(),
+                    namespace: Some("b".into()),
+                    timestamp: None,
+                    tags: None,
+                    kind: MetricKind::Absolute,
+                    value: MetricValue::Gauge { value: 2.0 },
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn metric_buffer_fresh_shares_state() {
+        let counter = |value| {
+            Event::Metric(Metric {
+                name: "counter".into(),
+                namespace: None,
+                timestamp: None,
+                tags: None,
+                kind: MetricKind::Absolute,
+                value: MetricValue::Counter { value },
+            })
+        };
+
+        // Partitioned sinks start every batch from the buffer they were
+        // built with rather than from the previous batch.
+        let template = MetricBuffer::new(BatchSize {
+            bytes: 9999,
+            events: 6,
+        });
+        let mut first = template.fresh();
+        let mut second = template.fresh();
+
+        assert!(matches!(first.push(counter(1.0)), PushResult::Ok(false)));
+        assert!(first.finish().is_empty());
+        assert!(matches!(second.push(counter(3.0)), PushResult::Ok(false)));
+        assert_eq!(
+            second.finish(),
+            vec![Metric {
+                name: "counter".into(),
+                namespace: None,
+                timestamp: None,
+                tags: None,
+                kind: MetricKind::Incremental,
+                value: MetricValue::Counter { value: 2.0 },
+            }]
+        );
+    }
+
+    #[test]
+    fn metric_buffer_sketches() {
+        let (sink, _rt, mut clock, sent_batches) = sink();
+
+        let mut expected = DDSketch::new(0.01);
+        let mut events = Vec::new();
+        for i in 1..5 {
+            let mut sketch = DDSketch::new(0.01);
+            sketch.insert(i as f64);
+            expected.insert(i as f64);
+
+            events.push(Event::Metric(Metric {
+                name: "sketch".into(),
+                namespace: None,
+                timestamp: None,
+                tags: Some(tag("production")),
+                kind: MetricKind::Incremental,
+                value: MetricValue::Sketch(sketch),
+            }));
+        }
+
+        let (sink, _) = clock.enter(|_| {
+            sink.sink_map_err(drop)
+                .send_all(futures01::stream::iter_ok(events.into_iter()))
+                .wait()
+                .unwrap()
+        });
+        drop(sink);
+
+        let buffer = Arc::try_unwrap(sent_batches).unwrap().into_inner().unwrap();
+
+        assert_eq!(buffer.len(), 1);
+        assert_eq!(
+            buffer[0],
+            [Metric {
+                name: "sketch".into(),
+                namespace: None,
+                timestamp: None,
+                tags: Some(tag("production")),
+                kind: MetricKind::Incremental,
+                value: MetricValue::Sketch(expected),
+            }]
+        );
+    }
+
     #[test]
     fn metric_buffer_compress_distribution() {
         let values = vec![2.0, 2.0, 3.0, 1.0, 2.0, 2.0, 3.0];
@@ -872,6 +1101,7 @@ mod test {
         for _ in 2..5 {
             let event = Event::Metric(Metric {
                 name: "buckets-2".into(),
+                namespace: None,
                 timestamp: None,
                 tags: Some(tag("production")),
                 kind: MetricKind::Absolute,
@@ -888,6 +1118,7 @@ mod test {
         for i in 2..5 {
             let event = Event::Metric(Metric {
                 name: format!("buckets-{}", i),
+                namespace: None,
                 timestamp: None,
                 tags: Some(tag("production")),
                 kind: MetricKind::Absolute,
@@ -918,6 +1149,7 @@ mod test {
             [
                 Metric {
                     name: "buckets-2".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("production")),
                     kind: MetricKind::Absolute,
@@ -930,6 +1162,7 @@ mod test {
                 },
                 Metric {
                     name: "buckets-3".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("production")),
                     kind: MetricKind::Absolute,
@@ -942,6 +1175,7 @@ mod test {
                 },
                 Metric {
                     name: "buckets-4".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("production")),
                     kind: MetricKind::Absolute,
@@ -964,6 +1198,7 @@ mod test {
         for _ in 0..3 {
             let event = Event::Metric(Metric {
                 name: "buckets-2".into(),
+                namespace: None,
                 timestamp: None,
                 tags: Some(tag("production")),
                 kind: MetricKind::Incremental,
@@ -980,6 +1215,7 @@ mod test {
         for i in 1..4 {
             let event = Event::Metric(Metric {
                 name: "buckets-2".into(),
+                namespace: None,
                 timestamp: None,
                 tags: Some(tag("production")),
                 kind: MetricKind::Incremental,
@@ -1010,6 +1246,7 @@ mod test {
             [
                 Metric {
                     name: "buckets-2".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("production")),
                     kind: MetricKind::Incremental,
@@ -1022,6 +1259,7 @@ mod test {
                 },
                 Metric {
                     name: "buckets-2".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("production")),
                     kind: MetricKind::Incremental,
@@ -1045,6 +1283,7 @@ mod test {
             for i in 2..5 {
                 let event = Event::Metric(Metric {
                     name: format!("quantiles-{}", i),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("production")),
                     kind: MetricKind::Absolute,
@@ -1076,6 +1315,7 @@ mod test {
             [
                 Metric {
                     name: "quantiles-2".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("production")),
                     kind: MetricKind::Absolute,
@@ -1088,6 +1328,7 @@ mod test {
                 },
                 Metric {
                     name: "quantiles-3".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("production")),
                     kind: MetricKind::Absolute,
@@ -1100,6 +1341,7 @@ mod test {
                 },
                 Metric {
                     name: "quantiles-4".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(tag("production")),
                     kind: MetricKind::Absolute,
@@ -0,0 +1,114 @@
+//! Dead-letter output for events a sink permanently failed to deliver.
+//!
+//! A sink with `dead_letter = true` exposes an extra output named
+//! `<sink>.dead_letter` that other components may list in their `inputs`.
+//! Batches whose request failed for good, either because retries were
+//! exhausted or because the response was not retriable, are sent there with
+//! the failure attached under the `dead_letter` field (or as
+//! `dead_letter_*` tags for metrics).
+
+use crate::{event::Event, internal_events::DeadLetterEventsSent};
+use futures01::sync::mpsc;
+use std::sync::Arc;
+
+pub const OUTPUT_SUFFIX: &str = "dead_letter";
+
+/// The name other components use to consume the dead-letter output of `sink`.
+pub fn output_name(sink: &str) -> String {
+    format!("{}.{}", sink, OUTPUT_SUFFIX)
+}
+
+/// Returns the sink owning `output` if it names a dead-letter output.
+pub fn sink_name(output: &str) -> Option<&str> {
+    if !output.ends_with(OUTPUT_SUFFIX) {
+        return None;
+    }
+    let sink = &output[..output.len() - OUTPUT_SUFFIX.len()];
+    match sink.len() {
+        0 | 1 => None,
+        _ if sink.ends_with('.') => Some(&sink[..sink.len() - 1]),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DeadLetter {
+    sink: Arc<str>,
+    tx: mpsc::UnboundedSender<Event>,
+}
+
+impl DeadLetter {
+    /// The channel is unbounded since failed batches are reported from
+    /// request futures which cannot wait on downstream components. Its size
+    /// is limited by the sink's in-flight requests.
+    pub fn new(sink: &str) -> (Self, mpsc::UnboundedReceiver<Event>) {
+        let (tx, rx) = mpsc::unbounded();
+        let dead_letter = Self {
+            sink: sink.into(),
+            tx,
+        };
+        (dead_letter, rx)
+    }
+
+    pub fn send(&self, events: Vec<Event>, reason: &str) {
+        if events.is_empty() {
+            return;
+        }
+
+        emit!(DeadLetterEventsSent {
+            count: events.len(),
+            reason,
+        });
+
+        for mut event in events {
+            match &mut event {
+                Event::Log(log) => {
+                    log.insert("dead_letter.sink", self.sink.as_ref());
+                    log.insert("dead_letter.reason", reason);
+                }
+                Event::Metric(metric) => {
+                    let tags = metric.tags.get_or_insert_with(Default::default);
+                    tags.insert("dead_letter_sink".into(), self.sink.as_ref().into());
+                    tags.insert("dead_letter_reason".into(), reason.into());
+                }
+            }
+
+            // The receiving end is only dropped while the sink is shut down.
+            let _ = self.tx.unbounded_send(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures01::{Future, Stream};
+
+    #[test]
+    fn dead_letter_output_names() {
+        assert_eq!(output_name("es"), "es.dead_letter");
+        assert_eq!(sink_name("es.dead_letter"), Some("es"));
+        assert_eq!(sink_name("my.es.dead_letter"), Some("my.es"));
+        assert_eq!(sink_name(".dead_letter"), None);
+        assert_eq!(sink_name("esdead_letter"), None);
+        assert_eq!(sink_name("es"), None);
+    }
+
+    #[test]
+    fn dead_letter_adds_failure_metadata() {
+        let (dead_letter, rx) = DeadLetter::new("es");
+        dead_letter.send(vec![Event::from("rejected")], "response status: 400");
+        drop(dead_letter);
+
+        let events = rx.collect().wait().unwrap();
+        assert_eq!(events.len(), 1);
+
+        let log = events[0].as_log();
+        assert_eq!(log[&"message".into()], "rejected".into());
+        assert_eq!(log[&"dead_letter.sink".into()], "es".into());
+        assert_eq!(
+            log[&"dead_letter.reason".into()],
+            "response status: 400".into()
+        );
+    }
+}
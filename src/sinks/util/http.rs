@@ -1,7 +1,8 @@
 use super::{
     retries2::{RetryAction, RetryLogic},
     service2::{TowerBatchedSink, TowerRequestSettings},
-    sink, Batch,
+    sink::{self, SkipItem},
+    Batch, DeadLetter,
 };
 use crate::{
     buffers::Acker,
@@ -70,7 +71,9 @@ where
     // An empty slot is needed to buffer an item where we encoded it but
     // the inner sink is applying back pressure. This trick is used in the `WithFlatMap`
     // sink combinator. https://docs.rs/futures/0.1.29/src/futures/sink/with_flat_map.rs.html#20
-    slot: Option<B::Input>,
+    // The original event is kept alongside when a dead-letter output is set.
+    slot: Option<(B::Input, Option<Event>)>,
+    retain_events: bool,
 }
 
 impl<T, B> BatchedHttpSink<T, B, HttpRetryLogic>
@@ -131,8 +134,16 @@ where
             sink,
             inner,
             slot: None,
+            retain_events: false,
         }
     }
+
+    /// Sends the events of batches that fail permanently to `dead_letter`.
+    pub fn dead_letter(mut self, dead_letter: Option<DeadLetter>) -> Self {
+        self.retain_events = dead_letter.is_some();
+        self.inner = self.inner.dead_letter(dead_letter);
+        self
+    }
 }
 
 impl<T, B, L> Sink for BatchedHttpSink<T, B, L>
@@ -151,17 +162,26 @@ where
         }
         assert!(self.slot.is_none(), "poll_complete did not clear slot");
 
-        if let Some(item) = self.sink.encode_event(item) {
-            self.slot = Some(item);
-            self.poll_complete()?;
+        let event = if self.retain_events {
+            Some(item.clone())
+        } else {
+            None
+        };
+
+        match self.sink.encode_event(item) {
+            Some(item) => {
+                self.slot = Some((item, event));
+                self.poll_complete()?;
+            }
+            None => self.inner.skip_item(),
         }
 
         Ok(AsyncSink::Ready)
     }
 
     fn poll_complete(&mut self) -> Poll01<(), Self::SinkError> {
-        if let Some(item) = self.slot.take() {
-            if let AsyncSink::NotReady(item) = self.inner.start_send(item)? {
+        if let Some((item, event)) = self.slot.take() {
+            if let AsyncSink::NotReady(item) = self.inner.start_send_with_event(item, event)? {
                 self.slot = Some(item);
                 return Ok(Async::NotReady);
             }
@@ -341,6 +361,10 @@ impl<T: fmt::Debug> sink::Response for http::Response<T> {
     fn is_successful(&self) -> bool {
         self.status().is_success()
     }
+
+    fn failure_reason(&self) -> String {
+        format!("response status: {}", self.status())
+    }
 }
 
 #[derive(Clone)]
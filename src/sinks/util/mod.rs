@@ -1,5 +1,6 @@
 pub mod batch;
 pub mod buffer;
+pub mod dead_letter;
 pub mod encoding;
 pub mod http;
 pub mod retries;
@@ -30,8 +31,9 @@ pub use buffer::partition::Partition;
 pub use buffer::vec::VecBuffer;
 pub use buffer::vec2::{Length, VecBuffer2};
 pub use buffer::{Buffer, Compression, PartitionBuffer, PartitionInnerBuffer};
+pub use dead_letter::DeadLetter;
 pub use service::{ServiceBuilderExt, TowerRequestConfig, TowerRequestLayer, TowerRequestSettings};
-pub use sink::{BatchSink, PartitionBatchSink, StreamSink};
+pub use sink::{BatchSink, EncodeSink, PartitionBatchSink, SkipItem, StreamSink};
 pub use uri::UriSerde;
 
 #[derive(Debug, Snafu)]
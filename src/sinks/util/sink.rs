@@ -19,6 +19,10 @@
 //! For more advanced use cases like http based sinks, one should use the
 //! `BatchedHttpSink` type, which is a wrapper for `BatchSink` and `HttpSink`.
 //!
+//! Sinks that encode events before handing them to one of these types should
+//! do so through `EncodeSink`, so that the events that fail to encode are
+//! still acked in order.
+//!
 //! # Driving to completetion
 //!
 //! Each sink utility provided here strictly follows the patterns described in
@@ -34,7 +38,11 @@
 
 use super::batch::{Batch, PushResult, StatefulBatch};
 use super::buffer::partition::Partition;
-use crate::buffers::Acker;
+use super::dead_letter::DeadLetter;
+use crate::{
+    buffers::Acker,
+    event::{BatchStatus, Event},
+};
 use futures01::{
     future::Either,
     stream::FuturesUnordered,
@@ -46,6 +54,7 @@ use std::{
     fmt,
     hash::Hash,
     marker::PhantomData,
+    mem,
     time::{Duration, Instant},
 };
 use tokio01::{
@@ -70,6 +79,8 @@ pub struct StreamSink<T> {
     inner: T,
     acker: Acker,
     pending: usize,
+    /// Runs of the pending items with the same status, in order.
+    statuses: Vec<(usize, BatchStatus)>,
 }
 
 impl<T> StreamSink<T> {
@@ -78,10 +89,25 @@ impl<T> StreamSink<T> {
             inner,
             acker,
             pending: 0,
+            statuses: Vec::new(),
+        }
+    }
+
+    fn push_pending(&mut self, status: BatchStatus) {
+        self.pending += 1;
+        match self.statuses.last_mut() {
+            Some((len, last)) if *last == status => *len += 1,
+            _ => self.statuses.push((1, status)),
         }
     }
 }
 
+impl<T> SkipItem for StreamSink<T> {
+    fn skip_item(&mut self) {
+        self.push_pending(BatchStatus::Failed);
+    }
+}
+
 impl<T: Sink> Sink for StreamSink<T> {
     type SinkItem = T::SinkItem;
     type SinkError = T::SinkError;
@@ -90,7 +116,7 @@ impl<T: Sink> Sink for StreamSink<T> {
         trace!("sending item.");
         match self.inner.start_send(item)? {
             AsyncSink::Ready => {
-                self.pending += 1;
+                self.push_pending(BatchStatus::Delivered);
                 trace!(message = "submit successful.", pending_acks = self.pending);
 
                 if self.pending >= STREAM_SINK_MAX {
@@ -111,7 +137,9 @@ impl<T: Sink> Sink for StreamSink<T> {
         try_ready!(self.inner.poll_complete());
 
         trace!(message = "Acking events.", acking_num = self.pending);
-        self.acker.ack(self.pending);
+        for (len, status) in self.statuses.drain(..) {
+            self.acker.ack_with_status(len, status);
+        }
         self.pending = 0;
 
         Ok(().into())
@@ -134,9 +162,22 @@ impl<T: Sink> Sink for StreamSink<T> {
 /// batches have been acked. This means if sequential requests r1, r2,
 /// and r3 are dispatched and r2 and r3 complete, all events contained
 /// in all requests will not be acked until r1 has completed.
+///
+/// # Dead letters
+///
+/// When a `DeadLetter` is set, the original events of a batch that fails
+/// permanently are sent to it. Callers provide them through
+/// `start_send_with_event` since the batch only holds encoded items.
+///
+/// # Acknowledgements
+///
+/// Events are acked with the status of the request of their batch, which
+/// the acker reports to the sources awaiting them.
 pub struct BatchSink<S, B, Request, E = DefaultExecutor> {
     service: ServiceSink<S, Request>,
     batch: StatefulBatch<B>,
+    items: ItemRuns,
+    events: Vec<Event>,
     timeout: Duration,
     linger: Option<Delay>,
     closing: bool,
@@ -172,6 +213,8 @@ where
         Self {
             service,
             batch: batch.into(),
+            items: Vec::new(),
+            events: Vec::new(),
             timeout,
             linger: None,
             closing: false,
@@ -180,31 +223,18 @@ where
         }
     }
 
-    fn should_send(&mut self) -> bool {
-        self.closing || self.batch.was_full() || self.linger_elapsed()
-    }
-
-    fn linger_elapsed(&mut self) -> bool {
-        match &mut self.linger {
-            Some(delay) => delay.poll().expect("timer error").is_ready(),
-            None => false,
-        }
+    pub fn dead_letter(mut self, dead_letter: Option<DeadLetter>) -> Self {
+        self.service.dead_letter = dead_letter;
+        self
     }
-}
 
-impl<S, B, Request, E> Sink for BatchSink<S, B, Request, E>
-where
-    S: Service<Request>,
-    S::Future: Send + 'static,
-    S::Error: Into<crate::Error> + Send + 'static,
-    S::Response: Response,
-    B: Batch<Output = Request>,
-    E: Executor,
-{
-    type SinkItem = B::Input;
-    type SinkError = crate::Error;
-
-    fn start_send(&mut self, item: Self::SinkItem) -> StartSend<Self::SinkItem, Self::SinkError> {
+    /// Like `start_send`, additionally keeping the original `event` of
+    /// `item` to hand to the dead-letter output should its batch fail.
+    pub fn start_send_with_event(
+        &mut self,
+        item: B::Input,
+        event: Option<Event>,
+    ) -> StartSend<(B::Input, Option<Event>), crate::Error> {
         if self.batch.was_full() {
             trace!("batch full.");
             self.poll_complete()?;
@@ -214,7 +244,7 @@ where
                     message = "Batch buffer full; applying back pressure.",
                     rate_limit_secs = 10
                 );
-                return Ok(AsyncSink::NotReady(item));
+                return Ok(AsyncSink::NotReady((item, event)));
             }
         }
 
@@ -227,15 +257,49 @@ where
         }
 
         match self.batch.push(item) {
-            PushResult::Ok(false) => Ok(AsyncSink::Ready),
-            PushResult::Ok(true) => {
-                self.poll_complete()?;
+            PushResult::Ok(full) => {
+                push_item(&mut self.items, self.service.next_items(1));
+                if let Some(event) = event {
+                    self.events.push(event);
+                }
+                if full {
+                    self.poll_complete()?;
+                }
                 Ok(AsyncSink::Ready)
             }
-            PushResult::Overflow(item) => self.start_send(item),
+            PushResult::Overflow(item) => self.start_send_with_event(item, event),
         }
     }
 
+    fn should_send(&mut self) -> bool {
+        self.closing || self.batch.was_full() || self.linger_elapsed()
+    }
+
+    fn linger_elapsed(&mut self) -> bool {
+        match &mut self.linger {
+            Some(delay) => delay.poll().expect("timer error").is_ready(),
+            None => false,
+        }
+    }
+}
+
+impl<S, B, Request, E> Sink for BatchSink<S, B, Request, E>
+where
+    S: Service<Request>,
+    S::Future: Send + 'static,
+    S::Error: Into<crate::Error> + Send + 'static,
+    S::Response: Response,
+    B: Batch<Output = Request>,
+    E: Executor,
+{
+    type SinkItem = B::Input;
+    type SinkError = crate::Error;
+
+    fn start_send(&mut self, item: Self::SinkItem) -> StartSend<Self::SinkItem, Self::SinkError> {
+        self.start_send_with_event(item, None)
+            .map(|result| result.map(|(item, _)| item))
+    }
+
     fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
         loop {
             if self.batch.is_empty() {
@@ -251,10 +315,11 @@ where
                     trace!("Service ready; Sending batch.");
                     let batch = self.batch.fresh_replace();
 
-                    let batch_size = batch.num_items();
                     let request = batch.finish();
+                    let items = mem::replace(&mut self.items, Vec::new());
+                    let events = mem::replace(&mut self.events, Vec::new());
 
-                    let fut = self.service.call(request, batch_size);
+                    let fut = self.service.call(request, items, events);
 
                     self.exec.spawn(fut).expect("Spawn service future");
 
@@ -285,6 +350,18 @@ where
     }
 }
 
+impl<S, B, Request, E> SkipItem for BatchSink<S, B, Request, E>
+where
+    S: Service<Request>,
+    S::Future: Send + 'static,
+    S::Error: Into<crate::Error> + Send + 'static,
+    S::Response: Response,
+{
+    fn skip_item(&mut self) {
+        self.service.skip_item();
+    }
+}
+
 impl<S, B, Request> fmt::Debug for BatchSink<S, B, Request>
 where
     S: fmt::Debug,
@@ -313,18 +390,19 @@ type LingerDelay<K> = Box<dyn Future<Item = LingerState<K>, Error = ()> + Send +
 ///
 /// # Acking
 ///
-/// Service based acking will only ack events when all prior request
-/// batches have been acked. This means if sequential requests r1, r2,
-/// and r3 are dispatched and r2 and r3 complete, all events contained
-/// in all requests will not be acked until r1 has completed.
+/// Events are only acked once all the events that came before them in the
+/// input stream have been acked. Since batches of different partitions mix
+/// events from across the stream, this means the events of a request are
+/// acked once the requests of all the partitions holding earlier events have
+/// completed.
 pub struct PartitionBatchSink<B, S, K, Request, E = DefaultExecutor> {
     batch: StatefulBatch<B>,
     service: ServiceSink<S, Request>,
     exec: E,
-    partitions: HashMap<K, StatefulBatch<B>>,
+    partitions: HashMap<K, (StatefulBatch<B>, ItemRuns)>,
     timeout: Duration,
     closing: bool,
-    sending: VecDeque<B>,
+    sending: VecDeque<(B, ItemRuns)>,
     lingers: FuturesUnordered<LingerDelay<K>>,
     linger_handles: HashMap<K, oneshot::Sender<K>>,
 }
@@ -405,14 +483,13 @@ where
         self.lingers.push(Box::new(fut));
     }
 
-    fn poll_send(&mut self, batch: B) -> Poll<(), crate::Error> {
+    fn poll_send(&mut self, batch: B, items: ItemRuns) -> Poll<(), crate::Error> {
         if let Async::NotReady = self.service.poll_ready()? {
-            self.sending.push_front(batch);
+            self.sending.push_front((batch, items));
             Ok(Async::NotReady)
         } else {
-            let batch_size = batch.num_items();
             let batch = batch.finish();
-            let fut = self.service.call(batch, batch_size);
+            let fut = self.service.call(batch, items, Vec::new());
 
             self.exec.spawn(fut).expect("Spawn service future");
 
@@ -427,7 +504,7 @@ where
         }
 
         match self.partitions.get_mut(partition) {
-            Some(batch) => {
+            Some((batch, items)) => {
                 if !batch.is_empty() {
                     debug!(
                         message = "Send buffer full; applying back pressure.",
@@ -437,6 +514,7 @@ where
                 } else {
                     match batch.push(item) {
                         PushResult::Ok(full) => {
+                            push_item(items, self.service.next_items(1));
                             if full {
                                 if let Err(error) = self.poll_complete() {
                                     return FullBatchResult::Result(Err(error));
@@ -496,7 +574,7 @@ where
         let partition = item.partition();
 
         let item = match self.partitions.get_mut(&partition) {
-            Some(batch) => {
+            Some((batch, items)) => {
                 if batch.was_full() {
                     match self.handle_full_batch(item, &partition) {
                         FullBatchResult::Result(result) => return result,
@@ -506,6 +584,7 @@ where
                     trace!("adding event to batch.");
                     match batch.push(item) {
                         PushResult::Ok(full) => {
+                            push_item(items, self.service.next_items(1));
                             if full {
                                 self.poll_complete()?;
                             }
@@ -533,7 +612,8 @@ where
             PushResult::Ok(full) => {
                 self.set_linger(partition.clone());
 
-                self.partitions.insert(partition, batch);
+                let items = vec![(self.service.next_items(1), 1)];
+                self.partitions.insert(partition, (batch, items));
 
                 if full {
                     self.poll_complete()?;
@@ -547,8 +627,8 @@ where
     fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
         self.service.poll_complete()?;
 
-        while let Some(batch) = self.sending.pop_front() {
-            self.poll_send(batch)?;
+        while let Some((batch, items)) = self.sending.pop_front() {
+            self.poll_send(batch, items)?;
         }
 
         let closing = self.closing;
@@ -570,7 +650,7 @@ where
         let ready = self
             .partitions
             .iter()
-            .filter(|(_, b)| closing || b.was_full())
+            .filter(|(_, (b, _))| closing || b.was_full())
             .map(|(p, _)| p.clone())
             .collect::<Vec<_>>();
 
@@ -587,8 +667,8 @@ where
             }
         }
 
-        for batch in ready_batches.into_iter().chain(partitions) {
-            self.poll_send(batch.into_inner())?;
+        for (batch, items) in ready_batches.into_iter().chain(partitions) {
+            self.poll_send(batch.into_inner(), items)?;
         }
 
         // If we still have an inflight partition then
@@ -613,6 +693,18 @@ where
     }
 }
 
+impl<B, S, K, Request, E> SkipItem for PartitionBatchSink<B, S, K, Request, E>
+where
+    S: Service<Request>,
+    S::Future: Send + 'static,
+    S::Error: Into<crate::Error> + Send + 'static,
+    S::Response: Response,
+{
+    fn skip_item(&mut self) {
+        self.service.skip_item();
+    }
+}
+
 impl<B, S, K, Request> fmt::Debug for PartitionBatchSink<B, S, K, Request>
 where
     B: fmt::Debug,
@@ -627,16 +719,113 @@ where
     }
 }
 
+// === EncodeSink ===
+
+/// Sinks that ack their input in order, and can account for input items
+/// that are dropped instead of being sent.
+pub trait SkipItem {
+    /// Acks the next input item as failed once all the items before it are
+    /// acked.
+    fn skip_item(&mut self);
+}
+
+/// Encodes events with `encode` before sending them to `inner`. Events that
+/// fail to encode are dropped, and acked as failed in order with the others.
+///
+/// Like the `WithFlatMap` combinator, the encoded item is kept in a slot
+/// while `inner` applies back pressure.
+pub struct EncodeSink<S: Sink, F> {
+    inner: S,
+    encode: F,
+    slot: Option<S::SinkItem>,
+}
+
+impl<S, F> EncodeSink<S, F>
+where
+    S: Sink + SkipItem,
+    F: FnMut(Event) -> Option<S::SinkItem>,
+{
+    pub fn new(inner: S, encode: F) -> Self {
+        Self {
+            inner,
+            encode,
+            slot: None,
+        }
+    }
+
+    fn try_empty_slot(&mut self) -> Poll<(), S::SinkError> {
+        if let Some(item) = self.slot.take() {
+            if let AsyncSink::NotReady(item) = self.inner.start_send(item)? {
+                self.slot = Some(item);
+                return Ok(Async::NotReady);
+            }
+        }
+        Ok(Async::Ready(()))
+    }
+}
+
+impl<S, F> Sink for EncodeSink<S, F>
+where
+    S: Sink + SkipItem,
+    F: FnMut(Event) -> Option<S::SinkItem>,
+{
+    type SinkItem = Event;
+    type SinkError = S::SinkError;
+
+    fn start_send(&mut self, event: Self::SinkItem) -> StartSend<Self::SinkItem, Self::SinkError> {
+        if self.try_empty_slot()?.is_not_ready() {
+            return Ok(AsyncSink::NotReady(event));
+        }
+
+        match (self.encode)(event) {
+            Some(item) => {
+                self.slot = Some(item);
+                self.try_empty_slot()?;
+            }
+            None => self.inner.skip_item(),
+        }
+        Ok(AsyncSink::Ready)
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
+        try_ready!(self.try_empty_slot());
+        self.inner.poll_complete()
+    }
+
+    fn close(&mut self) -> Poll<(), Self::SinkError> {
+        try_ready!(self.try_empty_slot());
+        self.inner.close()
+    }
+}
+
 // === ServiceSink ===
 
+/// Runs of consecutive items of the input stream, as their position and
+/// length.
+type ItemRuns = Vec<(usize, usize)>;
+
+/// Adds the item at `position` to `items`, extending the last run when
+/// they are consecutive.
+fn push_item(items: &mut ItemRuns, position: usize) {
+    match items.last_mut() {
+        Some((start, len)) if *start + *len == position => *len += 1,
+        _ => items.push((position, 1)),
+    }
+}
+
 struct ServiceSink<S, Request> {
     service: S,
-    in_flight: FuturesUnordered<Receiver<(usize, usize)>>,
+    in_flight: FuturesUnordered<Receiver<(ItemRuns, BatchStatus)>>,
     acker: Acker,
-    seq_head: usize,
-    seq_tail: usize,
-    pending_acks: HashMap<usize, usize>,
+    /// The position of the next item read from the input stream.
+    item_head: usize,
+    /// The position of the first item not acked yet.
+    item_tail: usize,
+    /// Completed runs of items waiting for earlier items to complete, by
+    /// position.
+    pending_acks: HashMap<usize, (usize, BatchStatus)>,
     next_request_id: usize,
+    dead_letter: Option<DeadLetter>,
     _pd: PhantomData<Request>,
 }
 
@@ -652,10 +841,11 @@ where
             service,
             in_flight: FuturesUnordered::new(),
             acker,
-            seq_head: 0,
-            seq_tail: 0,
+            item_head: 0,
+            item_tail: 0,
             pending_acks: HashMap::new(),
             next_request_id: 0,
+            dead_letter: None,
             _pd: PhantomData,
         }
     }
@@ -664,14 +854,29 @@ where
         self.service.poll_ready().map_err(Into::into)
     }
 
+    /// Reserves the positions of the next `num` items of the input stream,
+    /// returning the first one.
+    fn next_items(&mut self, num: usize) -> usize {
+        let position = self.item_head;
+        self.item_head += num;
+        position
+    }
+
+    /// Acks the item at the next position as failed once all the items
+    /// before it are acked.
+    fn skip_item(&mut self) {
+        let position = self.next_items(1);
+        self.pending_acks.insert(position, (1, BatchStatus::Failed));
+        self.ack_pending();
+    }
+
+    /// Sends a request holding the `items` reserved with `next_items`.
     fn call(
         &mut self,
         req: Request,
-        batch_size: usize,
+        items: ItemRuns,
+        events: Vec<Event>,
     ) -> Box<dyn Future<Item = (), Error = ()> + Send + 'static> {
-        let seqno = self.seq_head;
-        self.seq_head += 1;
-
         let (tx, rx) = oneshot::channel();
 
         self.in_flight.push(rx);
@@ -679,6 +884,8 @@ where
         let request_id = self.next_request_id;
         self.next_request_id = request_id.wrapping_add(1);
 
+        let dead_letter = self.dead_letter.clone();
+
         trace!(
             message = "submitting service request.",
             in_flight_requests = self.in_flight.len()
@@ -688,25 +895,34 @@ where
             .call(req)
             .map_err(Into::into)
             .then(move |result| {
-                match result {
+                let status = match result {
                     Ok(response) if response.is_successful() => {
                         trace!(message = "Response successful.", ?response);
+                        BatchStatus::Delivered
                     }
                     Ok(response) => {
                         error!(message = "Response wasn't successful.", ?response);
+                        if let Some(dead_letter) = dead_letter {
+                            dead_letter.send(events, &response.failure_reason());
+                        }
+                        BatchStatus::Failed
                     }
                     Err(error) => {
                         error!(
                             message = "Request failed.",
                             %error,
                         );
+                        if let Some(dead_letter) = dead_letter {
+                            dead_letter.send(events, &error.to_string());
+                        }
+                        BatchStatus::Failed
                     }
-                }
+                };
 
                 // If the rx end is dropped we still completed
                 // the request so this is a weird case that we can
                 // ignore for now.
-                let _ = tx.send((seqno, batch_size));
+                let _ = tx.send((items, status));
 
                 Ok::<_, ()>(())
             })
@@ -720,21 +936,25 @@ where
             match self.in_flight.poll() {
                 Ok(Async::NotReady) => return Ok(Async::NotReady),
                 Ok(Async::Ready(None)) => return Ok(Async::Ready(())),
-                Ok(Async::Ready(Some((seqno, batch_size)))) => {
-                    self.pending_acks.insert(seqno, batch_size);
-
-                    let mut num_to_ack = 0;
-                    while let Some(ack_size) = self.pending_acks.remove(&self.seq_tail) {
-                        num_to_ack += ack_size;
-                        self.seq_tail += 1
+                Ok(Async::Ready(Some((items, status)))) => {
+                    for (position, len) in items {
+                        self.pending_acks.insert(position, (len, status));
                     }
-                    trace!(message = "acking events.", acking_num = num_to_ack);
-                    self.acker.ack(num_to_ack);
+                    self.ack_pending();
                 }
                 Err(_) => panic!("ServiceSink service sender dropped"),
             }
         }
     }
+
+    /// Acks the completed items that no earlier item is waited for.
+    fn ack_pending(&mut self) {
+        while let Some((len, status)) = self.pending_acks.remove(&self.item_tail) {
+            trace!(message = "acking events.", acking_num = len, ?status);
+            self.acker.ack_with_status(len, status);
+            self.item_tail += len;
+        }
+    }
 }
 
 impl<S, Request> fmt::Debug for ServiceSink<S, Request>
@@ -745,8 +965,8 @@ where
         f.debug_struct("ServiceSink")
             .field("service", &self.service)
             .field("acker", &self.acker)
-            .field("seq_head", &self.seq_head)
-            .field("seq_tail", &self.seq_tail)
+            .field("item_head", &self.item_head)
+            .field("item_tail", &self.item_tail)
             .field("pending_acks", &self.pending_acks)
             .finish()
     }
@@ -758,6 +978,11 @@ pub trait Response: fmt::Debug {
     fn is_successful(&self) -> bool {
         true
     }
+
+    /// Describes an unsuccessful response for the dead-letter output.
+    fn failure_reason(&self) -> String {
+        format!("{:?}", self)
+    }
 }
 
 impl Response for () {}
@@ -767,6 +992,7 @@ impl<'a> Response for &'a str {}
 mod tests {
     use super::*;
     use crate::buffers::Acker;
+    use crate::event::{BatchNotifier, EventFinalizers};
     use crate::sinks::util::{buffer::partition::Partition, BatchSize, VecBuffer};
     use crate::test_util::runtime;
     use bytes::Bytes;
@@ -903,6 +1129,117 @@ mod tests {
         });
     }
 
+    #[test]
+    fn batch_sink_sends_failed_batches_to_dead_letter() {
+        let rt = runtime();
+        let mut clock = MockClock::new();
+
+        let (acker, ack_counter) = Acker::new_for_testing();
+        let (dead_letter, dead_letter_rx) = DeadLetter::new("out");
+
+        let svc = tower::service_fn(|req: Vec<usize>| {
+            future::result(if req[0] == 0 {
+                Err(std::io::Error::new(std::io::ErrorKind::Other, "rejected"))
+            } else {
+                Ok(())
+            })
+        });
+
+        let batch_size = BatchSize {
+            events: 2,
+            ..BATCH_SIZE
+        };
+
+        let mut sink = BatchSink::with_executor(
+            svc,
+            VecBuffer::new(batch_size),
+            TIMEOUT,
+            acker,
+            rt.executor(),
+        )
+        .dead_letter(Some(dead_letter));
+
+        let _ = clock.enter(|_| {
+            for i in 0..4 {
+                let event = Event::from(format!("event {}", i));
+                assert!(sink
+                    .start_send_with_event(i, Some(event))
+                    .unwrap()
+                    .is_ready());
+            }
+            sink.flush().wait().unwrap()
+        });
+
+        assert_eq!(ack_counter.load(Relaxed), 4);
+
+        let dead_letters = dead_letter_rx.collect().wait().unwrap();
+        assert_eq!(dead_letters.len(), 2);
+        for (i, event) in dead_letters.iter().enumerate() {
+            let log = event.as_log();
+            assert_eq!(log[&"message".into()], format!("event {}", i).into());
+            assert_eq!(log[&"dead_letter.sink".into()], "out".into());
+            assert_eq!(log[&"dead_letter.reason".into()], "rejected".into());
+        }
+    }
+
+    #[test]
+    fn batch_sink_acks_failed_batches_with_failed_status() {
+        let rt = runtime();
+        let mut clock = MockClock::new();
+
+        let (acker, _) = Acker::new_for_testing();
+        let (acker, pending) = acker.finalizing();
+
+        let svc = tower::service_fn(|req: Vec<usize>| {
+            future::result(if req[0] == 0 {
+                Err(std::io::Error::new(std::io::ErrorKind::Other, "rejected"))
+            } else {
+                Ok(())
+            })
+        });
+
+        let batch_size = BatchSize {
+            events: 2,
+            ..BATCH_SIZE
+        };
+
+        let sink = BatchSink::with_executor(
+            svc,
+            VecBuffer::new(batch_size),
+            TIMEOUT,
+            acker,
+            rt.executor(),
+        );
+
+        let mut receivers = Vec::new();
+        for _ in 0..4 {
+            let (notifier, receiver) = BatchNotifier::new_with_receiver();
+            pending.push(EventFinalizers::new(notifier));
+            receivers.push(receiver);
+        }
+
+        let _ = clock.enter(|_| {
+            sink.sink_map_err(drop)
+                .send_all(futures01::stream::iter_ok(0..4))
+                .wait()
+                .unwrap()
+        });
+
+        let statuses = receivers
+            .iter_mut()
+            .map(|receiver| receiver.try_recv())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            vec![
+                Some(BatchStatus::Failed),
+                Some(BatchStatus::Failed),
+                Some(BatchStatus::Delivered),
+                Some(BatchStatus::Delivered),
+            ]
+        );
+    }
+
     #[test]
     fn batch_sink_buffers_messages_until_limit() {
         let rt = runtime();
@@ -1176,6 +1513,51 @@ mod tests {
         assert_eq!(&*output, &vec![vec![1]]);
     }
 
+    #[test]
+    fn partition_batch_sink_acks_in_input_order() {
+        let mut exec = MockExec::default();
+        let mut clock = MockClock::new();
+
+        let (acker, ack_counter) = Acker::new_for_testing();
+
+        let svc = tower::service_fn(|_| future::ok::<_, std::io::Error>(()));
+
+        let batch_size = BatchSize {
+            events: 2,
+            ..BATCH_SIZE
+        };
+
+        let mut sink = PartitionBatchSink::with_executor(
+            svc,
+            VecBuffer::new(batch_size),
+            TIMEOUT,
+            acker,
+            exec.clone(),
+        );
+
+        clock.enter(|handle| {
+            for partition in vec![Partitions::A, Partitions::B, Partitions::B] {
+                assert!(sink.start_send(partition).unwrap().is_ready());
+            }
+
+            sink.poll_complete().unwrap();
+            exec.poll().unwrap();
+            sink.poll_complete().unwrap();
+
+            // The batch of `B` is complete, but the earlier `A` is still
+            // lingering.
+            assert_eq!(ack_counter.load(Relaxed), 0);
+
+            handle.advance(TIMEOUT + Duration::from_secs(1));
+
+            sink.poll_complete().unwrap();
+            exec.poll().unwrap();
+            sink.poll_complete().unwrap();
+
+            assert_eq!(ack_counter.load(Relaxed), 3);
+        });
+    }
+
     #[test]
     fn service_sink_doesnt_propagate_error() {
         // We need a mock executor here because we need to ensure
@@ -5,15 +5,15 @@ use crate::{
         TcpConnectionDisconnected, TcpConnectionEstablished, TcpConnectionFailed,
         TcpConnectionShutdown, TcpEventSent, TcpFlushError,
     },
-    sinks::util::{encode_event, encoding::EncodingConfig, Encoding, SinkBuildError, StreamSink},
+    sinks::util::{
+        encode_event, encoding::EncodingConfig, EncodeSink, Encoding, SinkBuildError, StreamSink,
+    },
     sinks::{Healthcheck, RouterSink},
     tls::{MaybeTlsConnector, MaybeTlsSettings, MaybeTlsStream, TlsConfig},
     topology::config::SinkContext,
 };
 use bytes::Bytes;
-use futures01::{
-    future, stream::iter_ok, try_ready, Async, AsyncSink, Future, Poll, Sink, StartSend,
-};
+use futures01::{future, try_ready, Async, AsyncSink, Future, Poll, Sink, StartSend};
 use serde::{Deserialize, Serialize};
 use snafu::Snafu;
 use std::io::{ErrorKind, Read};
@@ -55,10 +55,10 @@ impl TcpSinkConfig {
         let healthcheck = tcp.healthcheck();
 
         let encoding = self.encoding.clone();
-        let sink = Box::new(
-            StreamSink::new(tcp, cx.acker())
-                .with_flat_map(move |event| iter_ok(encode_event(event, &encoding))),
-        );
+        let sink = StreamSink::new(tcp, cx.acker());
+        let sink = Box::new(EncodeSink::new(sink, move |event| {
+            encode_event(event, &encoding)
+        }));
 
         Ok((sink, healthcheck))
     }
@@ -1,11 +1,13 @@
-use super::{encode_event, encoding::EncodingConfig, Encoding, SinkBuildError, StreamSink};
+use super::{
+    encode_event, encoding::EncodingConfig, EncodeSink, Encoding, SinkBuildError, StreamSink,
+};
 use crate::{
     dns::{Resolver, ResolverFuture},
     sinks::{Healthcheck, RouterSink},
     topology::config::SinkContext,
 };
 use bytes::Bytes;
-use futures01::{future, stream::iter_ok, Async, AsyncSink, Future, Poll, Sink, StartSend};
+use futures01::{future, Async, AsyncSink, Future, Poll, Sink, StartSend};
 use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, Snafu};
 use std::io;
@@ -54,8 +56,8 @@ pub fn raw_udp(
 ) -> Result<RouterSink, UdpBuildError> {
     let sink = UdpSink::new(host, port, cx.resolver())?;
     let sink = StreamSink::new(sink, cx.acker());
-    Ok(Box::new(sink.with_flat_map(move |event| {
-        iter_ok(encode_event(event, &encoding))
+    Ok(Box::new(EncodeSink::new(sink, move |event| {
+        encode_event(event, &encoding)
     })))
 }
 
@@ -3,14 +3,12 @@ use crate::{
         UnixSocketConnectionEstablished, UnixSocketConnectionFailure, UnixSocketError,
         UnixSocketEventSent,
     },
-    sinks::util::{encode_event, encoding::EncodingConfig, Encoding, StreamSink},
+    sinks::util::{encode_event, encoding::EncodingConfig, EncodeSink, Encoding, StreamSink},
     sinks::{Healthcheck, RouterSink},
     topology::config::SinkContext,
 };
 use bytes::Bytes;
-use futures01::{
-    future, stream::iter_ok, try_ready, Async, AsyncSink, Future, Poll, Sink, StartSend,
-};
+use futures01::{future, try_ready, Async, AsyncSink, Future, Poll, Sink, StartSend};
 use serde::{Deserialize, Serialize};
 use snafu::Snafu;
 use std::io;
@@ -39,8 +37,9 @@ impl UnixSinkConfig {
         let unix = UnixSink::new(self.path.clone());
         let sink = StreamSink::new(unix, cx.acker());
 
-        let sink =
-            Box::new(sink.with_flat_map(move |event| iter_ok(encode_event(event, &encoding))));
+        let sink = Box::new(EncodeSink::new(sink, move |event| {
+            encode_event(event, &encoding)
+        }));
         let healthcheck = unix_healthcheck(self.path.clone());
 
         Ok((sink, healthcheck))
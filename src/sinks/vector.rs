@@ -1,14 +1,13 @@
 use crate::{
     event::proto,
     internal_events::VectorEventSent,
-    sinks::util::{tcp::TcpSink, StreamSink},
+    sinks::util::{tcp::TcpSink, EncodeSink, StreamSink},
     tls::{MaybeTlsSettings, TlsConfig},
     topology::config::{DataType, SinkConfig, SinkContext, SinkDescription},
     Event,
 };
 use bytes::Bytes;
 use bytes05::{BufMut, BytesMut};
-use futures01::{stream::iter_ok, Sink};
 use prost::Message;
 use serde::{Deserialize, Serialize};
 use snafu::Snafu;
@@ -50,8 +49,7 @@ impl SinkConfig for VectorSinkConfig {
 
         let sink = TcpSink::new(host, port, cx.resolver(), tls);
         let healthcheck = sink.healthcheck();
-        let sink = StreamSink::new(sink, cx.acker())
-            .with_flat_map(move |event| iter_ok(encode_event(event)));
+        let sink = EncodeSink::new(StreamSink::new(sink, cx.acker()), encode_event);
 
         Ok((Box::new(sink), healthcheck))
     }
@@ -1,5 +1,9 @@
 use crate::{
-    event::{self, Event},
+    codecs::{
+        decoding::{Codec, DecodingConfig},
+        framing::FramingConfig,
+    },
+    event::{self, finalization::OrderedFinalizer, BatchNotifier, BatchStatus, Event},
     internal_events::FileEventReceived,
     shutdown::ShutdownSignal,
     topology::config::{DataType, GlobalOptions, SourceConfig, SourceDescription},
@@ -8,7 +12,7 @@ use crate::{
 use bytes05::Bytes;
 use file_source::{
     paths_provider::glob::{Glob, MatchOptions},
-    FileServer, Fingerprinter,
+    Acknowledgements, Checkpoint, FileServer, Fingerprinter,
 };
 use futures::{
     compat::{Compat01As03Sink, Future01CompatExt},
@@ -71,6 +75,8 @@ enum BuildError {
         condition_pattern: String,
         source: regex::Error,
     },
+    #[snafu(display("files can only be read as newline delimited frames"))]
+    UnsupportedFraming,
 }
 
 #[derive(Deserialize, Serialize, Debug, PartialEq)]
@@ -93,6 +99,8 @@ pub struct FileConfig {
     pub max_read_bytes: usize,
     pub oldest_first: bool,
     pub remove_after: Option<u64>,
+    pub acknowledgements: bool,
+    pub decoding: Option<DecodingConfig>,
 }
 
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
@@ -183,6 +191,8 @@ impl Default for FileConfig {
             max_read_bytes: 2048,
             oldest_first: false,
             remove_after: None,
+            acknowledgements: false,
+            decoding: None,
         }
     }
 }
@@ -214,6 +224,15 @@ impl SourceConfig for FileConfig {
             Regex::new(indicator).with_context(|| InvalidMessageStartIndicator { indicator })?;
         }
 
+        match self
+            .decoding
+            .as_ref()
+            .and_then(|decoding| decoding.framing.as_ref())
+        {
+            None | Some(FramingConfig::NewlineDelimited { .. }) => (),
+            Some(_) => return Err(BuildError::UnsupportedFraming.into()),
+        }
+
         Ok(file_source(self, data_dir, shutdown, out))
     }
 
@@ -240,20 +259,43 @@ pub fn file_source(
     let paths_provider = Glob::new(&config.include, &config.exclude, MatchOptions::default())
         .expect("invalid glob patterns");
 
+    let acknowledgements = if config.acknowledgements {
+        Some(Acknowledgements::new())
+    } else {
+        None
+    };
+
+    // Lines are always newline delimited, possibly with their own length limit.
+    let max_line_bytes = match config
+        .decoding
+        .as_ref()
+        .and_then(|decoding| decoding.framing.as_ref())
+    {
+        Some(FramingConfig::NewlineDelimited {
+            max_length: Some(max_length),
+        }) => *max_length,
+        _ => config.max_line_bytes,
+    };
+
     let file_server = FileServer {
         paths_provider,
         max_read_bytes: config.max_read_bytes,
         start_at_beginning: config.start_at_beginning,
         ignore_before,
-        max_line_bytes: config.max_line_bytes,
+        max_line_bytes,
         data_dir,
         glob_minimum_cooldown,
         fingerprinter: config.fingerprinting.clone().into(),
         oldest_first: config.oldest_first,
         remove_after: config.remove_after.map(Duration::from_secs),
+        acknowledgements: acknowledgements.clone(),
     };
 
     let file_key = config.file_key.clone();
+    let codec = config
+        .decoding
+        .as_ref()
+        .map_or(Codec::Bytes, |decoding| decoding.codec);
     let host_key = config
         .host_key
         .clone()
@@ -271,7 +313,22 @@ pub fn file_source(
         // sizing here is just a guess
         let (tx, rx) = futures01::sync::mpsc::channel(100);
 
-        let messages: Box<dyn Stream<Item = (Bytes, String), Error = ()> + Send> =
+        // Checkpoints only advance once all previous lines are delivered too.
+        let finalizer = acknowledgements.map(|acknowledgements| {
+            OrderedFinalizer::new(shutdown.clone(), move |status, checkpoint: Checkpoint| {
+                if status == BatchStatus::Failed {
+                    error!(
+                        message = "Line was not delivered, the checkpoint of its file won't advance anymore.",
+                        ?checkpoint,
+                    );
+                    acknowledgements.fail(checkpoint);
+                } else {
+                    acknowledgements.acknowledge(checkpoint);
+                }
+            })
+        });
+
+        let messages: Box<dyn Stream<Item = (Bytes, String, Checkpoint), Error = ()> + Send> =
             if let Some(ref multiline_config) = multiline_config {
                 Box::new(LineAgg::new(
                     rx,
@@ -295,14 +352,23 @@ pub fn file_source(
         let span2 = span.clone();
         tokio01::spawn(
             messages
-                .map(move |(msg, file): (Bytes, String)| {
-                    let _enter = span2.enter();
-                    emit!(FileEventReceived {
-                        file: &file,
-                        byte_size: msg.len(),
-                    });
-                    create_event(msg, file, &host_key, &hostname, &file_key)
-                })
+                .filter_map(
+                    move |(msg, file, checkpoint): (Bytes, String, Checkpoint)| {
+                        let _enter = span2.enter();
+                        emit!(FileEventReceived {
+                            file: &file,
+                            byte_size: msg.len(),
+                        });
+                        let mut event =
+                            create_event(codec, msg, file, &host_key, &hostname, &file_key)?;
+                        if let Some(finalizer) = &finalizer {
+                            let (notifier, receiver) = BatchNotifier::new_with_receiver();
+                            event.add_batch_notifier(notifier);
+                            finalizer.add(checkpoint, receiver);
+                        }
+                        Some(event)
+                    },
+                )
                 .forward(out.sink_map_err(|e| error!(%e)))
                 .map(|_| ())
                 .instrument(span),
@@ -324,13 +390,14 @@ pub fn file_source(
 }
 
 fn create_event(
+    codec: Codec,
     line: Bytes,
     file: String,
     host_key: &str,
     hostname: &Option<String>,
     file_key: &Option<String>,
-) -> Event {
-    let mut event = Event::from(line);
+) -> Option<Event> {
+    let mut event = codec.try_decode(bytes::Bytes::from(&line[..]), "file")?;
 
     // Add source type
     event
@@ -345,7 +412,7 @@ fn create_event(
         event.as_mut_log().insert(host_key, hostname.clone());
     }
 
-    event
+    Some(event)
 }
 
 #[cfg(test)]
@@ -469,7 +536,8 @@ mod tests {
         let hostname = Some("Some.Machine".to_string());
         let file_key = Some("file".to_string());
 
-        let event = create_event(line, file, &host_key, &hostname, &file_key);
+        let event =
+            create_event(Codec::Bytes, line, file, &host_key, &hostname, &file_key).unwrap();
         let log = event.into_log();
 
         assert_eq!(log[&"file".into()], "some_file.rs".into());
@@ -860,6 +928,45 @@ mod tests {
         shutdown_on_idle(rt);
     }
 
+    #[test]
+    fn file_acknowledgements_hold_back_checkpoints() {
+        let dir = tempdir().unwrap();
+        let config = file::FileConfig {
+            include: vec![dir.path().join("*")],
+            acknowledgements: true,
+            ..test_default_file_config(&dir)
+        };
+
+        let path = dir.path().join("file");
+        let mut file = File::create(&path).unwrap();
+        writeln!(&mut file, "zeroth line").unwrap();
+        writeln!(&mut file, "first line").unwrap();
+        sleep();
+
+        // Events are still held downstream when the server shuts down, so
+        // their positions are not checkpointed and are read again.
+        for _ in 0..2 {
+            let (trigger_shutdown, shutdown, _) = ShutdownSignal::new_wired();
+
+            let (tx, rx) = futures01::sync::mpsc::channel(10);
+            let source = file::file_source(&config, config.data_dir.clone().unwrap(), shutdown, tx);
+            let mut rt = runtime();
+            rt.spawn(source);
+
+            sleep();
+
+            drop(trigger_shutdown);
+            shutdown_on_idle(rt);
+
+            let received = wait_with_timeout(rx.collect());
+            let lines = received
+                .into_iter()
+                .map(|event| event.as_log()[&event::log_schema().message_key()].to_string_lossy())
+                .collect::<Vec<_>>();
+            assert_eq!(lines, vec!["zeroth line", "first line"]);
+        }
+    }
+
     #[test]
     fn file_start_position_server_restart() {
         let dir = tempdir().unwrap();
@@ -1,5 +1,10 @@
 use crate::{
+    codecs::{
+        decoding::{Codec, DecodingConfig},
+        framing::{self, Framer},
+    },
     event::{self, Event},
+    internal_events::{DecoderDeserializeFailed, DecoderFramingFailed},
     shutdown::ShutdownSignal,
     sources::util::{ErrorMessage, HttpSource},
     tls::TlsConfig,
@@ -23,6 +28,10 @@ pub struct SimpleHttpConfig {
     #[serde(default)]
     headers: Vec<String>,
     tls: Option<TlsConfig>,
+    #[serde(default)]
+    acknowledgements: bool,
+    /// Replaces the `encoding` when set.
+    decoding: Option<DecodingConfig>,
 }
 
 inventory::submit! {
@@ -32,6 +41,7 @@ inventory::submit! {
 #[derive(Clone)]
 struct SimpleHttpSource {
     encoding: Encoding,
+    decoding: Option<(Framer, Codec)>,
     headers: Vec<String>,
 }
 
@@ -46,8 +56,18 @@ pub enum Encoding {
 }
 
 impl HttpSource for SimpleHttpSource {
-    fn build_event(&self, body: Bytes, header_map: HeaderMap) -> Result<Vec<Event>, ErrorMessage> {
-        decode_body(body, self.encoding)
+    fn build_event(
+        &self,
+        body: Bytes,
+        header_map: HeaderMap,
+        _path: &str,
+    ) -> Result<Vec<Event>, ErrorMessage> {
+        let events = match &self.decoding {
+            Some((framer, codec)) => decode_frames(body, framer, *codec),
+            None => decode_body(body, self.encoding),
+        };
+
+        events
             .map(|events| add_headers(events, &self.headers, header_map))
             .map(|mut events| {
                 // Add source type
@@ -69,11 +89,26 @@ impl SourceConfig for SimpleHttpConfig {
         shutdown: ShutdownSignal,
         out: mpsc::Sender<Event>,
     ) -> crate::Result<super::Source> {
+        let decoding = match &self.decoding {
+            Some(decoding) => Some((
+                decoding.framer(framing::DEFAULT_MAX_LENGTH)?,
+                decoding.codec,
+            )),
+            None => None,
+        };
         let source = SimpleHttpSource {
             encoding: self.encoding,
+            decoding,
             headers: self.headers.clone(),
         };
-        source.run(self.address, "", &self.tls, out, shutdown)
+        source.run(
+            self.address,
+            &[""],
+            &self.tls,
+            self.acknowledgements,
+            out,
+            shutdown,
+        )
     }
 
     fn output_type(&self) -> DataType {
@@ -145,6 +180,29 @@ fn decode_body(body: Bytes, enc: Encoding) -> Result<Vec<Event>, ErrorMessage> {
     }
 }
 
+fn decode_frames(body: Bytes, framer: &Framer, codec: Codec) -> Result<Vec<Event>, ErrorMessage> {
+    let frames = framer.frames(&body).map_err(|error| {
+        emit!(DecoderFramingFailed {
+            source_type: "http",
+            error: &error,
+        });
+        ErrorMessage::new(StatusCode::BAD_REQUEST, format!("Bad request: {}", error))
+    })?;
+
+    frames
+        .into_iter()
+        .map(|frame| {
+            codec.decode(frame).map_err(|error| {
+                emit!(DecoderDeserializeFailed {
+                    source_type: "http",
+                    error: &error,
+                });
+                ErrorMessage::new(StatusCode::BAD_REQUEST, format!("Bad request: {}", error))
+            })
+        })
+        .collect()
+}
+
 fn json_parse_object(value: JsonValue) -> Result<Event, ErrorMessage> {
     let mut event = Event::new_empty_log();
     let log = event.as_mut_log();
@@ -201,12 +259,15 @@ mod tests {
 
     use crate::shutdown::ShutdownSignal;
     use crate::{
-        event::{self, Event},
+        event::{self, BatchStatus, Event},
         runtime::Runtime,
         test_util::{self, collect_n, runtime},
         topology::config::{GlobalOptions, SourceConfig},
     };
-    use futures::compat::Future01CompatExt;
+    use futures::{
+        compat::{Future01CompatExt, Stream01CompatExt},
+        StreamExt,
+    };
     use futures01::sync::mpsc;
     use http::HeaderMap;
     use pretty_assertions::assert_eq;
@@ -227,6 +288,8 @@ mod tests {
                 encoding,
                 headers,
                 tls: None,
+                acknowledgements: false,
+                decoding: None,
             }
             .build(
                 "default",
@@ -378,6 +441,59 @@ mod tests {
         });
     }
 
+    #[test]
+    fn http_decoding() {
+        let mut rt = runtime();
+        let (sender, rx) = mpsc::channel(100);
+        let address = test_util::next_addr();
+        rt.spawn(
+            SimpleHttpConfig {
+                address,
+                encoding: Encoding::Text,
+                headers: vec![],
+                tls: None,
+                acknowledgements: false,
+                decoding: Some(
+                    toml::from_str(
+                        r#"
+                        codec = "json"
+                        framing = { method = "character_delimited", delimiter = ";" }
+                        "#,
+                    )
+                    .unwrap(),
+                ),
+            }
+            .build(
+                "default",
+                &GlobalOptions::default(),
+                ShutdownSignal::noop(),
+                sender,
+            )
+            .unwrap(),
+        );
+
+        rt.block_on_std(async move {
+            let body = r#"{"level":"info","status":200};{"level":"warn"}"#;
+            assert_eq!(200, send(address, body).await);
+            assert_eq!(400, send(address, r#"{"level":"error"};{"#).await);
+
+            let mut events = collect_n(rx, 2).compat().await.unwrap();
+            {
+                let event = events.remove(0);
+                let log = event.as_log();
+                assert_eq!(log[&Atom::from("level")], "info".into());
+                assert_eq!(log[&Atom::from("status")], 200.into());
+                assert!(log.get(&event::log_schema().timestamp_key()).is_some());
+                assert_eq!(log[event::log_schema().source_type_key()], "http".into());
+            }
+            {
+                let event = events.remove(0);
+                let log = event.as_log();
+                assert_eq!(log[&Atom::from("level")], "warn".into());
+            }
+        });
+    }
+
     #[test]
     fn http_ndjson() {
         let mut rt = runtime();
@@ -448,4 +564,41 @@ mod tests {
             }
         });
     }
+
+    #[test]
+    fn http_acknowledgements() {
+        let mut rt = runtime();
+        let (sender, rx) = mpsc::channel(100);
+        let address = test_util::next_addr();
+        rt.spawn(
+            SimpleHttpConfig {
+                address,
+                encoding: Encoding::Text,
+                headers: vec![],
+                tls: None,
+                acknowledgements: true,
+                decoding: None,
+            }
+            .build(
+                "default",
+                &GlobalOptions::default(),
+                ShutdownSignal::noop(),
+                sender,
+            )
+            .unwrap(),
+        );
+
+        rt.block_on_std(async move {
+            let mut rx = rx.compat();
+
+            let request = tokio::spawn(send(address, "rejected"));
+            let mut event = rx.next().await.unwrap().unwrap();
+            event.take_finalizers().update_status(BatchStatus::Failed);
+            assert_eq!(500, request.await.unwrap());
+
+            let request = tokio::spawn(send(address, "delivered"));
+            drop(rx.next().await);
+            assert_eq!(200, request.await.unwrap());
+        });
+    }
 }
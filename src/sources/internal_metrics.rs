@@ -14,11 +14,30 @@ use futures01::{sync::mpsc, Future, Sink};
 use metrics_core::Key;
 use metrics_runtime::{Controller, Measurement};
 use serde::{Deserialize, Serialize};
+use snafu::Snafu;
 use std::{collections::BTreeMap, time::Duration};
-use tokio::time::interval;
 
-#[derive(Deserialize, Serialize, Debug, Clone, Default)]
-pub struct InternalMetricsConfig;
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("`scrape_interval_secs` must be greater than zero"))]
+    ZeroScrapeInterval,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields, default)]
+pub struct InternalMetricsConfig {
+    scrape_interval_secs: u64,
+    namespace: Option<String>,
+}
+
+impl Default for InternalMetricsConfig {
+    fn default() -> Self {
+        Self {
+            scrape_interval_secs: 2,
+            namespace: None,
+        }
+    }
+}
 
 inventory::submit! {
     SourceDescription::new::<InternalMetricsConfig>("internal_metrics")
@@ -33,7 +52,15 @@ impl SourceConfig for InternalMetricsConfig {
         shutdown: ShutdownSignal,
         out: mpsc::Sender<Event>,
     ) -> crate::Result<super::Source> {
-        let fut = run(get_controller()?, out, shutdown).boxed().compat();
+        if self.scrape_interval_secs == 0 {
+            return Err(Box::new(BuildError::ZeroScrapeInterval));
+        }
+        let interval = Duration::from_secs(self.scrape_interval_secs);
+        let namespace = self.namespace.clone();
+
+        let fut = run(get_controller()?, interval, namespace, out, shutdown)
+            .boxed()
+            .compat();
         Ok(Box::new(fut))
     }
 
@@ -55,10 +82,12 @@ fn get_controller() -> crate::Result<Controller> {
 
 async fn run(
     controller: Controller,
+    interval: Duration,
+    namespace: Option<String>,
     mut out: mpsc::Sender<Event>,
     mut shutdown: ShutdownSignal,
 ) -> Result<(), ()> {
-    let mut interval = interval(Duration::from_secs(2)).map(|_| ());
+    let mut interval = tokio::time::interval(interval).map(|_| ());
 
     while let Some(()) = interval.next().await {
         // Check for shutdown signal
@@ -66,7 +95,7 @@ async fn run(
             break;
         }
 
-        let metrics = capture_metrics(&controller);
+        let metrics = capture_metrics(&controller, namespace.clone());
 
         let (sink, _) = out
             .send_all(futures01::stream::iter_ok(metrics))
@@ -79,15 +108,18 @@ async fn run(
     Ok(())
 }
 
-fn capture_metrics(controller: &Controller) -> impl Iterator<Item = Event> {
+fn capture_metrics(
+    controller: &Controller,
+    namespace: Option<String>,
+) -> impl Iterator<Item = Event> {
     controller
         .snapshot()
         .into_measurements()
         .into_iter()
-        .map(|(k, m)| into_event(k, m))
+        .map(move |(k, m)| into_event(k, m, namespace.clone()))
 }
 
-fn into_event(key: Key, measurement: Measurement) -> Event {
+fn into_event(key: Key, measurement: Measurement, namespace: Option<String>) -> Event {
     let value = match measurement {
         Measurement::Counter(v) => MetricValue::Counter { value: v as f64 },
         Measurement::Gauge(v) => MetricValue::Gauge { value: v as f64 },
@@ -112,6 +144,7 @@ fn into_event(key: Key, measurement: Measurement) -> Event {
 
     let metric = Metric {
         name: key.name().to_string(),
+        namespace,
         timestamp: Some(Utc::now()),
         tags: if labels.is_empty() {
             None
@@ -127,7 +160,7 @@ fn into_event(key: Key, measurement: Measurement) -> Event {
 
 #[cfg(test)]
 mod tests {
-    use super::{capture_metrics, get_controller};
+    use super::{capture_metrics, get_controller, InternalMetricsConfig};
     use crate::event::metric::{Metric, MetricValue};
     use metrics::{counter, gauge, timing, value};
     use std::collections::BTreeMap;
@@ -153,7 +186,7 @@ mod tests {
         // There *seems* to be a race condition here (CI was flaky), so add a slight delay.
         std::thread::sleep(std::time::Duration::from_millis(300));
 
-        let output = capture_metrics(&controller)
+        let output = capture_metrics(&controller, Some("vector".into()))
             .map(|event| {
                 let m = event.into_metric();
                 (m.name.clone(), m)
@@ -180,5 +213,23 @@ mod tests {
         let mut labels = BTreeMap::new();
         labels.insert(String::from("host"), String::from("foo"));
         assert_eq!(Some(labels), output["quux"].tags);
+        assert_eq!(Some("vector"), output["quux"].namespace.as_deref());
+    }
+
+    #[test]
+    fn config_defaults() {
+        let config: InternalMetricsConfig = toml::from_str("").unwrap();
+        assert_eq!(config.scrape_interval_secs, 2);
+        assert_eq!(config.namespace, None);
+
+        let config: InternalMetricsConfig = toml::from_str(
+            r#"
+            scrape_interval_secs = 10
+            namespace = "vector"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.scrape_interval_secs, 10);
+        assert_eq!(config.namespace.as_deref(), Some("vector"));
     }
 }
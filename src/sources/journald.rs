@@ -1,6 +1,6 @@
 use crate::{
     event,
-    event::{Event, LogEvent, Value},
+    event::{finalization::OrderedFinalizer, BatchNotifier, BatchStatus, Event, LogEvent, Value},
     shutdown::ShutdownSignal,
     topology::config::{DataType, GlobalOptions, SourceConfig, SourceDescription},
 };
@@ -26,6 +26,7 @@ use std::iter::FromIterator;
 use std::path::PathBuf;
 use std::process::{Child, ChildStdout, Command, Stdio};
 use std::str::FromStr;
+use std::sync::Arc;
 use std::time;
 use string_cache::DefaultAtom as Atom;
 use tokio::{task::spawn_blocking, time::delay_for};
@@ -68,6 +69,7 @@ pub struct JournaldConfig {
     pub journalctl_path: Option<PathBuf>,
     #[serde(default)]
     pub remap_priority: bool,
+    pub acknowledgements: bool,
 }
 
 inventory::submit! {
@@ -144,9 +146,8 @@ impl JournaldConfig {
     where
         J: JournalSource + Send + 'static,
     {
-        let out = out
-            .sink_map_err(|_| ())
-            .with(|record: Record| future::ok(create_event(record)));
+        let out = out.sink_map_err(|_| ());
+        let acknowledgements = self.acknowledgements;
 
         // Retrieve the saved checkpoint, and use it to seek forward in the journald log
         let cursor = match checkpointer.get() {
@@ -165,13 +166,38 @@ impl JournaldConfig {
         Ok(Box::new(future::lazy(move || {
             info!(message = "Starting journald server.",);
 
+            let checkpoints = if acknowledgements {
+                // Once a batch fails the checkpoint stays at the last delivered
+                // batch, so the records after it are read again after a restart.
+                let mut failed = false;
+                Checkpoints::Acknowledged(OrderedFinalizer::new(
+                    shutdown.clone(),
+                    move |status, cursor: String| {
+                        if failed {
+                            return;
+                        }
+                        if status == BatchStatus::Failed {
+                            error!(
+                                message = "Journald records were not delivered, the checkpoint won't advance anymore.",
+                                %cursor,
+                            );
+                            failed = true;
+                            return;
+                        }
+                        set_checkpoint(&mut checkpointer, &cursor);
+                    },
+                ))
+            } else {
+                Checkpoints::Immediate(checkpointer)
+            };
+
             let journald_server = JournaldServer {
                 journal,
                 include_units,
                 exclude_units,
                 channel: out,
                 shutdown: shutdown.clone(),
-                checkpointer,
+                checkpoints,
                 batch_size,
                 remap_priority,
             };
@@ -305,13 +331,21 @@ impl Iterator for Journalctl {
     }
 }
 
+/// How the cursor of each batch of records is saved.
+enum Checkpoints {
+    /// Saved as soon as the batch is sent.
+    Immediate(Checkpointer),
+    /// Saved in order once the batch is delivered.
+    Acknowledged(OrderedFinalizer<String>),
+}
+
 struct JournaldServer<J, T> {
     journal: J,
     include_units: HashSet<String>,
     exclude_units: HashSet<String>,
     channel: T,
     shutdown: ShutdownSignal,
-    checkpointer: Checkpointer,
+    checkpoints: Checkpoints,
     batch_size: usize,
     remap_priority: bool,
 }
@@ -319,7 +353,7 @@ struct JournaldServer<J, T> {
 impl<J, T> JournaldServer<J, T>
 where
     J: JournalSource,
-    T: Sink<SinkItem = Record, SinkError = ()>,
+    T: Sink<SinkItem = Event, SinkError = ()>,
 {
     pub fn run(mut self) {
         let timeout = time::Duration::from_millis(500); // arbitrary timeout
@@ -330,6 +364,10 @@ where
             let mut saw_record = false;
             let mut at_end = false;
             let mut cursor: Option<String> = None;
+            let mut batch = match self.checkpoints {
+                Checkpoints::Immediate(_) => None,
+                Checkpoints::Acknowledged(_) => Some(BatchNotifier::new_with_receiver()),
+            };
 
             for _ in 0..self.batch_size {
                 let text = match self.journal.next() {
@@ -365,7 +403,12 @@ where
                     continue;
                 }
 
-                match channel.send(record).wait() {
+                let mut event = create_event(record);
+                if let Some((notifier, _)) = &batch {
+                    event.add_batch_notifier(Arc::clone(notifier));
+                }
+
+                match channel.send(event).wait() {
                     Ok(_) => {}
                     Err(()) => error!(message = "Could not send journald log"),
                 }
@@ -373,11 +416,15 @@ where
 
             if saw_record {
                 if let Some(cursor) = cursor {
-                    if let Err(err) = self.checkpointer.set(&cursor) {
-                        error!(
-                            message = "Could not set journald checkpoint.",
-                            error = field::display(&err)
-                        );
+                    match &mut self.checkpoints {
+                        Checkpoints::Immediate(checkpointer) => {
+                            set_checkpoint(checkpointer, &cursor)
+                        }
+                        Checkpoints::Acknowledged(finalizer) => {
+                            if let Some((_, receiver)) = batch.take() {
+                                finalizer.add(cursor, receiver);
+                            }
+                        }
                     }
                 }
             }
@@ -397,6 +444,15 @@ where
     }
 }
 
+fn set_checkpoint(checkpointer: &mut Checkpointer, cursor: &str) {
+    if let Err(err) = checkpointer.set(cursor) {
+        error!(
+            message = "Could not set journald checkpoint.",
+            error = field::display(&err)
+        );
+    }
+}
+
 fn decode_record(text: &str, remap: bool) -> Result<Record, JsonError> {
     let mut record = serde_json::from_str::<JsonValue>(&text)?;
     // journalctl will output non-ASCII messages using an array
@@ -682,6 +738,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn acknowledged_checkpoint_waits_for_delivery() {
+        let tempdir = tempdir().unwrap();
+        let config = JournaldConfig {
+            acknowledgements: true,
+            ..JournaldConfig::default()
+        };
+
+        let run = |deliver: bool| {
+            let (tx, rx) = futures01::sync::mpsc::channel(10);
+            let (trigger, shutdown, _) = ShutdownSignal::new_wired();
+            let checkpointer = Checkpointer::new(tempdir.path().to_path_buf())
+                .expect("Creating checkpointer failed!");
+            let source = config
+                .source::<FakeJournal>(
+                    tx,
+                    shutdown,
+                    checkpointer,
+                    HashSet::new(),
+                    HashSet::new(),
+                    DEFAULT_BATCH_SIZE,
+                    true,
+                )
+                .expect("Creating journald source failed");
+            let mut rt = runtime();
+            rt.spawn(source);
+
+            // Delivered events are dropped right away, others are held
+            // until the source has shut down.
+            let held = if deliver {
+                rt.spawn(rx.for_each(|_| Ok(())));
+                None
+            } else {
+                Some(rx)
+            };
+
+            std::thread::sleep(Duration::from_millis(100));
+            drop(trigger);
+            shutdown_on_idle(rt);
+            drop(held);
+
+            Checkpointer::new(tempdir.path().to_path_buf())
+                .unwrap()
+                .get()
+                .unwrap()
+        };
+
+        assert_eq!(run(false), None);
+        assert_eq!(run(true), Some("4".into()));
+    }
+
     #[test]
     fn handles_checkpoint() {
         let received = run_journal(&[], &[], Some("1"));
@@ -1,5 +1,14 @@
 use crate::{
-    event::{self, Event},
+    codecs::{
+        self,
+        decoding::{Codec, DecodingConfig},
+        framing::Framer,
+        SchemaConfig, SchemaDecoder, SchemaFormat,
+    },
+    event::{
+        self, finalization::OrderedFinalizer, BatchNotifier, BatchStatus, Event, LogEvent, Value,
+    },
+    internal_events::{DecoderDeserializeFailed, DecoderFramingFailed},
     kafka::KafkaAuthConfig,
     shutdown::ShutdownSignal,
     topology::config::{DataType, GlobalOptions, SourceConfig, SourceDescription},
@@ -14,11 +23,21 @@ use futures01::{sync::mpsc, Sink};
 use rdkafka::{
     config::ClientConfig,
     consumer::{Consumer, StreamConsumer},
-    message::Message,
+    message::{BorrowedMessage, Headers, Message},
+    Offset, TopicPartitionList,
 };
 use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, Snafu};
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+    time::Duration,
+};
+
+/// How many times a rejected message is consumed again before its offset is
+/// stored anyway, so that a message no sink will ever accept doesn't stall
+/// its partition.
+const MAX_REWINDS: usize = 3;
 
 #[derive(Debug, Snafu)]
 enum BuildError {
@@ -26,6 +45,8 @@ enum BuildError {
     KafkaCreateError { source: rdkafka::error::KafkaError },
     #[snafu(display("Could not subscribe to Kafka topics: {}", source))]
     KafkaSubscribeError { source: rdkafka::error::KafkaError },
+    #[snafu(display("The `codec` and `decoding` options are mutually exclusive"))]
+    CodecAndDecoding,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -45,9 +66,18 @@ pub struct KafkaSourceConfig {
     #[serde(default = "default_commit_interval_ms")]
     commit_interval_ms: u64,
     key_field: Option<String>,
+    topic_field: Option<String>,
+    partition_field: Option<String>,
+    offset_field: Option<String>,
+    headers_field: Option<String>,
+    codec: Option<SchemaFormat>,
+    schema: Option<SchemaConfig>,
+    decoding: Option<DecodingConfig>,
     librdkafka_options: Option<HashMap<String, String>>,
     #[serde(flatten)]
     auth: KafkaAuthConfig,
+    #[serde(default)]
+    acknowledgements: bool,
 }
 
 fn default_session_timeout_ms() -> u64 {
@@ -100,16 +130,93 @@ fn kafka_source(
     shutdown: ShutdownSignal,
     out: mpsc::Sender<Event>,
 ) -> crate::Result<super::Source> {
-    let key_field = config.key_field.clone();
+    let fields = Arc::new(MetadataFields {
+        key: config.key_field.clone(),
+        topic: config.topic_field.clone(),
+        partition: config.partition_field.clone(),
+        offset: config.offset_field.clone(),
+        headers: config.headers_field.clone(),
+    });
+    if config.codec.is_some() && config.decoding.is_some() {
+        return Err(BuildError::CodecAndDecoding.into());
+    }
+    let decoder = match codecs::required_schema(config.codec, &config.schema)? {
+        Some((format, schema)) => Some(Arc::new(schema.build_decoder(format)?)),
+        None => None,
+    };
+    let decoding = match &config.decoding {
+        Some(decoding) => Some(Arc::new(PayloadDecoder {
+            framer: match &decoding.framing {
+                Some(framing) => Some(framing.build(codecs::framing::DEFAULT_MAX_LENGTH)?),
+                None => None,
+            },
+            codec: decoding.codec,
+        })),
+        None => None,
+    };
+    let acknowledgements = config.acknowledgements;
+    let seek_timeout = Duration::from_millis(config.socket_timeout_ms);
     let consumer = Arc::new(create_consumer(config)?);
 
     let fut = async move {
+        // Offsets are stored once all previous messages are delivered too.
+        let finalizer = if acknowledgements {
+            let consumer = Arc::clone(&consumer);
+            let mut rewinds = Rewinds::default();
+            Some(Arc::new(OrderedFinalizer::new(
+                shutdown.clone(),
+                move |status, (topic, partition, offset): (String, i32, i64)| {
+                    match rewinds.resolve(status, &topic, partition, offset) {
+                        Resolution::Ignore => return,
+                        Resolution::Rewind => {
+                            error!(
+                                message = "Message was not delivered, consuming its partition again from it.",
+                                %topic,
+                                %partition,
+                                %offset,
+                            );
+                            if let Err(error) = consumer.seek(
+                                &topic,
+                                partition,
+                                Offset::Offset(offset),
+                                seek_timeout,
+                            ) {
+                                error!(
+                                    message = "Cannot seek back to the message, its partition is not committed anymore until it is assigned again.",
+                                    ?error,
+                                );
+                            }
+                            return;
+                        }
+                        Resolution::Skip => error!(
+                            message = "Message was not delivered after being consumed again, skipping it.",
+                            %topic,
+                            %partition,
+                            %offset,
+                        ),
+                        Resolution::Store => {}
+                    }
+
+                    let mut tpl = TopicPartitionList::new();
+                    tpl.add_partition_offset(&topic, partition, Offset::Offset(offset + 1));
+                    if let Err(error) = consumer.store_offsets(&tpl) {
+                        error!(message = "Cannot store offset for the message", ?error);
+                    }
+                },
+            )))
+        } else {
+            None
+        };
+
         Arc::clone(&consumer)
             .start()
             .take_until(shutdown.clone().compat())
             .then(move |message| {
-                let key_field = key_field.clone();
+                let fields = Arc::clone(&fields);
+                let decoder = decoder.clone();
+                let decoding = decoding.clone();
                 let consumer = Arc::clone(&consumer);
+                let finalizer = finalizer.clone();
 
                 async move {
                     match message {
@@ -126,38 +233,79 @@ fn kafka_source(
                                 }
                                 Some(Ok(payload)) => Bytes::from(payload),
                             };
-                            let mut event = Event::new_empty_log();
-                            let log = event.as_mut_log();
-
-                            log.insert(event::log_schema().message_key().clone(), payload);
+                            let logs = match (decoder, decoding) {
+                                (Some(decoder), _) => match decoder.decode(&payload).await {
+                                    Ok(log) => vec![log],
+                                    Err(error) => {
+                                        return Err(error!(
+                                            message = "Cannot decode payload",
+                                            %error,
+                                            rate_limit_secs = 30,
+                                        ))
+                                    }
+                                },
+                                (None, Some(decoding)) => decoding.decode(&payload),
+                                (None, None) => {
+                                    let mut log = LogEvent::default();
+                                    log.insert(event::log_schema().message_key().clone(), payload);
+                                    vec![log]
+                                }
+                            };
+                            if logs.is_empty() {
+                                return Err(());
+                            }
 
-                            // Extract timestamp from kafka message
+                            // Extract timestamp from kafka message, unless
+                            // the decoded record has one.
                             let timestamp = msg
                                 .timestamp()
                                 .to_millis()
                                 .and_then(|millis| Utc.timestamp_millis_opt(millis).latest())
                                 .unwrap_or_else(Utc::now);
-                            log.insert(event::log_schema().timestamp_key().clone(), timestamp);
-
-                            // Add source type
-                            log.insert(event::log_schema().source_type_key(), "kafka");
-
-                            if let Some(key_field) = &key_field {
-                                match msg.key_view::<[u8]>() {
-                                    None => (),
-                                    Some(Err(e)) => {
-                                        return Err(
-                                            error!(message = "Cannot extract key", error = ?e),
-                                        )
+
+                            let key = match msg.key_view::<[u8]>() {
+                                Some(Err(e)) if fields.key.is_some() => {
+                                    return Err(error!(message = "Cannot extract key", error = ?e))
+                                }
+                                Some(Ok(key)) => Some(Bytes::from(key)),
+                                _ => None,
+                            };
+
+                            // All the events of a message are acknowledged together.
+                            let batch = finalizer.as_ref().map(|finalizer| {
+                                let (notifier, receiver) = BatchNotifier::new_with_receiver();
+                                let position =
+                                    (msg.topic().to_owned(), msg.partition(), msg.offset());
+                                finalizer.add(position, receiver);
+                                notifier
+                            });
+
+                            let events = logs
+                                .into_iter()
+                                .map(|mut log| {
+                                    log.try_insert(event::log_schema().timestamp_key(), timestamp);
+
+                                    // Add source type
+                                    log.insert(event::log_schema().source_type_key(), "kafka");
+
+                                    if let (Some(key_field), Some(key)) = (&fields.key, &key) {
+                                        log.insert(key_field.clone(), key.clone());
                                     }
-                                    Some(Ok(key)) => {
-                                        log.insert(key_field.clone(), key);
+
+                                    fields.insert_metadata(&mut log, &msg);
+
+                                    let mut event = Event::from(log);
+                                    if let Some(notifier) = &batch {
+                                        event.add_batch_notifier(Arc::clone(notifier));
                                     }
-                                }
-                            }
+                                    event
+                                })
+                                .collect::<Vec<_>>();
 
-                            consumer.store_offset(&msg).map_err(|e| error!(message = "Cannot store offset for the message", error = ?e))?;
-                            Ok(event)
+                            if finalizer.is_none() {
+                                consumer.store_offset(&msg).map_err(|e| error!(message = "Cannot store offset for the message", error = ?e))?;
+                            }
+                            Ok(events)
                         }
                     }
                 }
@@ -169,11 +317,15 @@ fn kafka_source(
             //         .sink_map_err(|e| error!(message = "Error sending to sink", error = ?e)),
             // )
             .for_each(|item| {
-                let out = out.clone();
+                let mut out = out.clone();
                 async move {
-                    if let Ok(item) = item {
-                        if let Err(e) = out.send(item).compat().await {
-                            error!(message = "Error sending to sink", error = ?e);
+                    for event in item.unwrap_or_default() {
+                        match out.send(event).compat().await {
+                            Ok(sender) => out = sender,
+                            Err(e) => {
+                                error!(message = "Error sending to sink", error = ?e);
+                                break;
+                            }
                         }
                     }
                 }
@@ -185,6 +337,129 @@ fn kafka_source(
     Ok(Box::new(Compat::new(fut.boxed())))
 }
 
+/// Splits the payload of messages into frames when configured, and decodes
+/// each frame into an event.
+#[derive(Debug)]
+struct PayloadDecoder {
+    framer: Option<Framer>,
+    codec: Codec,
+}
+
+impl PayloadDecoder {
+    fn decode(&self, payload: &[u8]) -> Vec<LogEvent> {
+        let frames = match &self.framer {
+            Some(framer) => match framer.frames(payload) {
+                Ok(frames) => frames,
+                Err(error) => {
+                    emit!(DecoderFramingFailed {
+                        source_type: "kafka",
+                        error: &error,
+                    });
+                    return Vec::new();
+                }
+            },
+            None => vec![Bytes::from(payload)],
+        };
+
+        frames
+            .into_iter()
+            .filter_map(|frame| {
+                self.codec
+                    .parse(frame)
+                    .map_err(|error| {
+                        emit!(DecoderDeserializeFailed {
+                            source_type: "kafka",
+                            error: &error,
+                        })
+                    })
+                    .ok()
+            })
+            .collect()
+    }
+}
+
+/// Names of the fields the message metadata is inserted into, if any.
+#[derive(Debug)]
+/// What to do with the offset of a message once its events are finalized.
+#[derive(Debug, PartialEq)]
+enum Resolution {
+    Store,
+    Rewind,
+    Skip,
+    Ignore,
+}
+
+/// The partitions consumed again from a message that was not delivered, by the
+/// offset of that message and how many times it was consumed again. No offset
+/// of such a partition is stored until the message is delivered or skipped.
+#[derive(Default)]
+struct Rewinds(HashMap<(String, i32), (i64, usize)>);
+
+impl Rewinds {
+    fn resolve(
+        &mut self,
+        status: BatchStatus,
+        topic: &str,
+        partition: i32,
+        offset: i64,
+    ) -> Resolution {
+        let key = (topic.to_owned(), partition);
+        let rewinds = match self.0.get(&key) {
+            // Messages read before rewinding are read again after the failed
+            // one.
+            Some((failed, _)) if *failed != offset => return Resolution::Ignore,
+            Some((_, rewinds)) => {
+                let rewinds = *rewinds;
+                self.0.remove(&key);
+                rewinds
+            }
+            None => 0,
+        };
+
+        match status {
+            BatchStatus::Delivered => Resolution::Store,
+            BatchStatus::Failed if rewinds >= MAX_REWINDS => Resolution::Skip,
+            BatchStatus::Failed => {
+                self.0.insert(key, (offset, rewinds + 1));
+                Resolution::Rewind
+            }
+        }
+    }
+}
+
+struct MetadataFields {
+    key: Option<String>,
+    topic: Option<String>,
+    partition: Option<String>,
+    offset: Option<String>,
+    headers: Option<String>,
+}
+
+impl MetadataFields {
+    fn insert_metadata(&self, log: &mut LogEvent, msg: &BorrowedMessage) {
+        if let Some(topic_field) = &self.topic {
+            log.insert(topic_field, msg.topic());
+        }
+        if let Some(partition_field) = &self.partition {
+            log.insert(partition_field, msg.partition());
+        }
+        if let Some(offset_field) = &self.offset {
+            log.insert(offset_field, msg.offset());
+        }
+        if let Some(headers_field) = &self.headers {
+            let mut headers = BTreeMap::new();
+            if let Some(borrowed) = msg.headers() {
+                for i in 0..borrowed.count() {
+                    if let Some((name, value)) = borrowed.get(i) {
+                        headers.insert(name.to_owned(), Value::from(value));
+                    }
+                }
+            }
+            log.insert(headers_field, headers);
+        }
+    }
+}
+
 fn create_consumer(config: &KafkaSourceConfig) -> crate::Result<StreamConsumer> {
     let mut client_config = ClientConfig::new();
     client_config
@@ -220,8 +495,14 @@ fn create_consumer(config: &KafkaSourceConfig) -> crate::Result<StreamConsumer>
 
 #[cfg(test)]
 mod test {
-    use super::{kafka_source, KafkaSourceConfig};
-    use crate::shutdown::ShutdownSignal;
+    use super::{
+        kafka_source, KafkaSourceConfig, PayloadDecoder, Resolution, Rewinds, MAX_REWINDS,
+    };
+    use crate::{
+        codecs::{decoding::Codec, framing::FramingConfig, SchemaFormat},
+        event::BatchStatus,
+        shutdown::ShutdownSignal,
+    };
     use futures01::sync::mpsc;
 
     fn make_config() -> KafkaSourceConfig {
@@ -253,6 +534,74 @@ mod test {
         };
         assert!(kafka_source(&config, ShutdownSignal::noop(), mpsc::channel(1).0).is_err());
     }
+
+    #[test]
+    fn kafka_source_create_codec_and_decoding() {
+        let config = KafkaSourceConfig {
+            codec: Some(SchemaFormat::Avro),
+            schema: Some(Default::default()),
+            decoding: Some(Default::default()),
+            ..make_config()
+        };
+        assert!(kafka_source(&config, ShutdownSignal::noop(), mpsc::channel(1).0).is_err());
+    }
+
+    #[test]
+    fn kafka_payload_decoder_splits_frames() {
+        let decoder = PayloadDecoder {
+            framer: Some(FramingConfig::default().build(1024).unwrap()),
+            codec: Codec::Json,
+        };
+        let logs = decoder.decode(b"{\"a\":1}\nnot json\n{\"a\":2}");
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[1][&"a".into()], 2.into());
+
+        let decoder = PayloadDecoder {
+            framer: None,
+            codec: Codec::Bytes,
+        };
+        assert_eq!(decoder.decode(b"one\ntwo").len(), 1);
+    }
+
+    #[test]
+    fn kafka_rewinds_are_bounded() {
+        let mut rewinds = Rewinds::default();
+
+        for _ in 0..MAX_REWINDS {
+            assert_eq!(
+                rewinds.resolve(BatchStatus::Failed, "topic", 0, 10),
+                Resolution::Rewind
+            );
+            // Messages after the failed one are consumed again.
+            assert_eq!(
+                rewinds.resolve(BatchStatus::Delivered, "topic", 0, 11),
+                Resolution::Ignore
+            );
+            // Other partitions are unaffected.
+            assert_eq!(
+                rewinds.resolve(BatchStatus::Delivered, "topic", 1, 11),
+                Resolution::Store
+            );
+        }
+        assert_eq!(
+            rewinds.resolve(BatchStatus::Failed, "topic", 0, 10),
+            Resolution::Skip
+        );
+        assert_eq!(
+            rewinds.resolve(BatchStatus::Delivered, "topic", 0, 11),
+            Resolution::Store
+        );
+
+        // A later failure is rewound again.
+        assert_eq!(
+            rewinds.resolve(BatchStatus::Failed, "topic", 0, 12),
+            Resolution::Rewind
+        );
+        assert_eq!(
+            rewinds.resolve(BatchStatus::Delivered, "topic", 0, 12),
+            Resolution::Store
+        );
+    }
 }
 
 #[cfg(feature = "kafka-integration-tests")]
@@ -268,6 +617,7 @@ mod integration_test {
     use futures01::sync::mpsc;
     use rdkafka::{
         config::ClientConfig,
+        message::OwnedHeaders,
         producer::{FutureProducer, FutureRecord},
         util::Timeout,
     };
@@ -286,6 +636,7 @@ mod integration_test {
         let record = FutureRecord::to(&topic)
             .payload(text)
             .key(key)
+            .headers(OwnedHeaders::new().add("header-key", "header-value"))
             .timestamp(timestamp);
 
         if let Err(err) = producer.send(record, Timeout::Never).await {
@@ -309,6 +660,10 @@ mod integration_test {
             session_timeout_ms: 6000,
             commit_interval_ms: 5000,
             key_field: Some("message_key".to_string()),
+            topic_field: Some("topic".to_string()),
+            partition_field: Some("partition".to_string()),
+            offset_field: Some("offset".to_string()),
+            headers_field: Some("headers".to_string()),
             socket_timeout_ms: 60000,
             fetch_wait_max_ms: 100,
             ..Default::default()
@@ -342,5 +697,12 @@ mod integration_test {
             events[0].as_log()[event::log_schema().timestamp_key()],
             now.into()
         );
+        assert_eq!(events[0].as_log()[&Atom::from("topic")], topic.into());
+        assert_eq!(events[0].as_log()[&Atom::from("partition")], 0.into());
+        assert_eq!(events[0].as_log()[&Atom::from("offset")], 0.into());
+        assert_eq!(
+            events[0].as_log()[&Atom::from("headers.header-key")],
+            "header-value".into()
+        );
     }
 }
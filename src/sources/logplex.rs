@@ -26,7 +26,12 @@ pub struct LogplexConfig {
 struct LogplexSource {}
 
 impl HttpSource for LogplexSource {
-    fn build_event(&self, body: Bytes, header_map: HeaderMap) -> Result<Vec<Event>, ErrorMessage> {
+    fn build_event(
+        &self,
+        body: Bytes,
+        header_map: HeaderMap,
+        _path: &str,
+    ) -> Result<Vec<Event>, ErrorMessage> {
         decode_message(body, header_map)
     }
 }
@@ -41,7 +46,7 @@ impl SourceConfig for LogplexConfig {
         out: mpsc::Sender<Event>,
     ) -> crate::Result<super::Source> {
         let source = LogplexSource::default();
-        source.run(self.address, "events", &self.tls, out, shutdown)
+        source.run(self.address, &["events"], &self.tls, false, out, shutdown)
     }
 
     fn output_type(&self) -> DataType {
@@ -17,6 +17,8 @@ pub mod journald;
 pub mod kafka;
 #[cfg(feature = "sources-logplex")]
 pub mod logplex;
+#[cfg(feature = "sources-opentelemetry")]
+pub mod opentelemetry;
 #[cfg(feature = "sources-prometheus")]
 pub mod prometheus;
 #[cfg(feature = "sources-socket")]
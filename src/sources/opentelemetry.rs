@@ -0,0 +1,290 @@
+use crate::{
+    event::{self, Event},
+    internal_events::{OpenTelemetryDecodeFailed, OpenTelemetryEventsReceived},
+    opentelemetry::{
+        self,
+        proto::{ExportLogsServiceRequest, ExportMetricsServiceRequest},
+    },
+    shutdown::ShutdownSignal,
+    sources::util::{ErrorMessage, HttpSource},
+    tls::TlsConfig,
+    topology::config::{DataType, GlobalOptions, SourceConfig, SourceDescription},
+};
+use bytes05::Bytes;
+use flate2::read::GzDecoder;
+use futures01::sync::mpsc;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{io::Read, net::SocketAddr};
+use warp::http::{header, HeaderMap, StatusCode};
+
+const LOGS_PATH: &str = "v1/logs";
+const METRICS_PATH: &str = "v1/metrics";
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct OpenTelemetryConfig {
+    address: SocketAddr,
+    tls: Option<TlsConfig>,
+    #[serde(default)]
+    acknowledgements: bool,
+}
+
+inventory::submit! {
+    SourceDescription::new_without_default::<OpenTelemetryConfig>("opentelemetry")
+}
+
+#[typetag::serde(name = "opentelemetry")]
+impl SourceConfig for OpenTelemetryConfig {
+    fn build(
+        &self,
+        _: &str,
+        _: &GlobalOptions,
+        shutdown: ShutdownSignal,
+        out: mpsc::Sender<Event>,
+    ) -> crate::Result<super::Source> {
+        OpenTelemetrySource.run(
+            self.address,
+            &[LOGS_PATH, METRICS_PATH],
+            &self.tls,
+            self.acknowledgements,
+            out,
+            shutdown,
+        )
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Any
+    }
+
+    fn source_type(&self) -> &'static str {
+        "opentelemetry"
+    }
+}
+
+/// Receives OTLP/HTTP export requests, encoded as Protobuf or JSON.
+#[derive(Clone)]
+struct OpenTelemetrySource;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    Protobuf,
+    Json,
+}
+
+impl HttpSource for OpenTelemetrySource {
+    fn build_event(
+        &self,
+        body: Bytes,
+        header_map: HeaderMap,
+        path: &str,
+    ) -> Result<Vec<Event>, ErrorMessage> {
+        let byte_size = body.len();
+        let format = request_format(&header_map)?;
+        let body = decompress(body, &header_map)?;
+
+        let events = if path == LOGS_PATH {
+            decode::<ExportLogsServiceRequest>(&body, format).map(|request| {
+                let mut events = opentelemetry::logs_into_events(request);
+                let key = event::log_schema().source_type_key();
+                for event in events.iter_mut() {
+                    event.as_mut_log().try_insert(key, "opentelemetry");
+                }
+                events
+            })
+        } else {
+            decode::<ExportMetricsServiceRequest>(&body, format)
+                .map(opentelemetry::metrics_into_events)
+        };
+
+        let events = events.map_err(|error| {
+            emit!(OpenTelemetryDecodeFailed { error: &error });
+            ErrorMessage::new(
+                StatusCode::BAD_REQUEST,
+                format!("Invalid OTLP request: {}", error),
+            )
+        })?;
+
+        emit!(OpenTelemetryEventsReceived {
+            count: events.len(),
+            byte_size,
+        });
+        Ok(events)
+    }
+}
+
+fn request_format(header_map: &HeaderMap) -> Result<Format, ErrorMessage> {
+    let content_type = header_map
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("application/x-protobuf");
+    match content_type.split(';').next().unwrap_or("").trim() {
+        "application/x-protobuf" | "application/protobuf" => Ok(Format::Protobuf),
+        "application/json" => Ok(Format::Json),
+        content_type => Err(ErrorMessage::new(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            format!("Unsupported Content-Type: {}", content_type),
+        )),
+    }
+}
+
+fn decompress(body: Bytes, header_map: &HeaderMap) -> Result<Bytes, ErrorMessage> {
+    match header_map
+        .get(header::CONTENT_ENCODING)
+        .and_then(|value| value.to_str().ok())
+    {
+        None | Some("identity") => Ok(body),
+        Some("gzip") => {
+            let mut decoded = Vec::new();
+            GzDecoder::new(&body[..])
+                .read_to_end(&mut decoded)
+                .map_err(|error| {
+                    ErrorMessage::new(
+                        StatusCode::BAD_REQUEST,
+                        format!("Failed decompressing payload with gzip decoder: {}", error),
+                    )
+                })?;
+            Ok(decoded.into())
+        }
+        Some(encoding) => Err(ErrorMessage::new(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            format!("Unsupported Content-Encoding: {}", encoding),
+        )),
+    }
+}
+
+fn decode<T>(body: &[u8], format: Format) -> crate::Result<T>
+where
+    T: prost::Message + Default + DeserializeOwned,
+{
+    Ok(match format {
+        Format::Protobuf => T::decode(body)?,
+        Format::Json => serde_json::from_slice(body)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::metric::{MetricKind, MetricValue},
+        shutdown::ShutdownSignal,
+        test_util::{self, collect_n, runtime},
+    };
+    use futures::compat::Future01CompatExt;
+    use prost::Message;
+
+    fn source(rt: &mut crate::runtime::Runtime) -> (mpsc::Receiver<Event>, SocketAddr) {
+        test_util::trace_init();
+        let (sender, recv) = mpsc::channel(100);
+        let address = test_util::next_addr();
+        rt.spawn(
+            OpenTelemetryConfig {
+                address,
+                tls: None,
+                acknowledgements: false,
+            }
+            .build(
+                "default",
+                &GlobalOptions::default(),
+                ShutdownSignal::noop(),
+                sender,
+            )
+            .unwrap(),
+        );
+        (recv, address)
+    }
+
+    async fn send(address: SocketAddr, path: &str, content_type: &str, body: Vec<u8>) -> u16 {
+        reqwest::Client::new()
+            .post(&format!("http://{}/{}", address, path))
+            .header("Content-Type", content_type)
+            .body(body)
+            .send()
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    }
+
+    #[test]
+    fn receives_json_logs() {
+        let body = r#"{"resourceLogs": [{"scopeLogs": [{"logRecords": [
+            {"timeUnixNano": "1600000000000000000", "body": {"stringValue": "hello"}}
+        ]}]}]}"#;
+
+        let mut rt = runtime();
+        let (rx, addr) = source(&mut rt);
+
+        rt.block_on_std(async move {
+            assert_eq!(
+                200,
+                send(addr, "v1/logs", "application/json", body.into()).await
+            );
+
+            let events = collect_n(rx, 1).compat().await.unwrap();
+            let log = events[0].as_log();
+            assert_eq!(log[&event::log_schema().message_key()], "hello".into());
+            assert_eq!(
+                log[event::log_schema().source_type_key()],
+                "opentelemetry".into()
+            );
+        });
+    }
+
+    #[test]
+    fn receives_protobuf_metrics() {
+        let metric = crate::event::Metric {
+            name: "requests".into(),
+            namespace: None,
+            timestamp: None,
+            tags: Some(
+                vec![("host".to_owned(), "a".to_owned())]
+                    .into_iter()
+                    .collect(),
+            ),
+            kind: MetricKind::Incremental,
+            value: MetricValue::Counter { value: 2.0 },
+        };
+        let request = opentelemetry::metric_into_request(metric).unwrap();
+        let mut body = Vec::new();
+        request.encode(&mut body).unwrap();
+
+        let mut rt = runtime();
+        let (rx, addr) = source(&mut rt);
+
+        rt.block_on_std(async move {
+            assert_eq!(
+                200,
+                send(addr, "v1/metrics", "application/x-protobuf", body).await
+            );
+
+            let events = collect_n(rx, 1).compat().await.unwrap();
+            let metric = events[0].as_metric();
+            assert_eq!(metric.name, "requests");
+            assert_eq!(metric.kind, MetricKind::Incremental);
+            assert_eq!(metric.value, MetricValue::Counter { value: 2.0 });
+            assert_eq!(metric.tags.as_ref().unwrap()["host"], "a");
+        });
+    }
+
+    #[test]
+    fn rejects_invalid_requests() {
+        let mut rt = runtime();
+        let (_rx, addr) = source(&mut rt);
+
+        rt.block_on_std(async move {
+            assert_eq!(
+                400,
+                send(addr, "v1/logs", "application/json", b"{".to_vec()).await
+            );
+            assert_eq!(
+                415,
+                send(addr, "v1/logs", "text/plain", b"hello".to_vec()).await
+            );
+            assert_eq!(
+                404,
+                send(addr, "v1/traces", "application/json", b"{}".to_vec()).await
+            );
+        });
+    }
+}
@@ -1,33 +1,93 @@
 use crate::{
+    dns::Resolver,
+    event::metric::{Metric, MetricKind, MetricValue},
     hyper::body_to_bytes,
     internal_events::{PrometheusHttpError, PrometheusParseError, PrometheusRequestCompleted},
     shutdown::ShutdownSignal,
+    sinks::util::http::{Auth, HttpClient},
     stream::StreamExt01,
+    tls::{TlsOptions, TlsSettings},
     topology::config::GlobalOptions,
     Event,
 };
+use bytes::Bytes;
+use chrono::Utc;
 use futures::{FutureExt, TryFutureExt};
 use futures01::{sync::mpsc, Future, Sink, Stream as Stream01};
-use hyper::{Body, Client, Request};
-use hyper_openssl::HttpsConnector;
+use http::{StatusCode, Uri};
+use hyper::{Body, Request};
 use serde::{Deserialize, Serialize};
-use snafu::ResultExt;
-use std::time::{Duration, Instant};
-use tokio01::timer::Interval;
+use snafu::{ResultExt, Snafu};
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+use tokio01::timer::{Interval, Timeout};
 
 pub mod parser;
+mod remote_write;
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("Cannot use both `hosts` and `endpoints`"))]
+    BothHostsAndEndpoints,
+    #[snafu(display(
+        "Scrape timeout of {} secs must not exceed the scrape interval of {} secs",
+        timeout,
+        interval
+    ))]
+    TimeoutTooLong { timeout: u64, interval: u64 },
+}
+
+#[derive(Debug, Snafu)]
+enum ScrapeError {
+    #[snafu(display("Unexpected status {}", status))]
+    UnexpectedStatus { status: StatusCode },
+    #[snafu(display("Timed out after {:?}", timeout))]
+    TimedOut { timeout: Duration },
+}
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
 struct PrometheusConfig {
+    /// The full URLs of the endpoints to scrape.
+    #[serde(default)]
+    endpoints: Vec<String>,
+    /// Deprecated. Use `endpoints`, these get `metrics` appended.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     hosts: Vec<String>,
     #[serde(default = "default_scrape_interval_secs")]
     scrape_interval_secs: u64,
+    #[serde(default = "default_scrape_timeout_secs")]
+    scrape_timeout_secs: u64,
+    /// Use the first `_` separated segment of metric names as their namespace.
+    #[serde(default)]
+    split_namespace: bool,
+    /// The tag holding the `host:port` of the target of a metric.
+    #[serde(default = "default_instance_tag")]
+    instance_tag: String,
+    /// Tags added to the metrics of every target, such as a `job`.
+    #[serde(default)]
+    tags: BTreeMap<String, String>,
+    /// Keep the scraped labels that conflict with the tags of the target,
+    /// instead of renaming them with an `exported_` prefix.
+    #[serde(default)]
+    honor_labels: bool,
+    auth: Option<Auth>,
+    tls: Option<TlsOptions>,
 }
 
 pub fn default_scrape_interval_secs() -> u64 {
     15
 }
 
+pub fn default_scrape_timeout_secs() -> u64 {
+    5
+}
+
+fn default_instance_tag() -> String {
+    "instance".into()
+}
+
 #[typetag::serde(name = "prometheus")]
 impl crate::topology::config::SourceConfig for PrometheusConfig {
     fn build(
@@ -37,12 +97,50 @@ impl crate::topology::config::SourceConfig for PrometheusConfig {
         shutdown: ShutdownSignal,
         out: mpsc::Sender<Event>,
     ) -> crate::Result<super::Source> {
-        let mut urls = Vec::new();
-        for host in self.hosts.iter() {
-            let base_uri = host.parse::<http::Uri>().context(super::UriParseError)?;
-            urls.push(format!("{}metrics", base_uri));
+        if self.scrape_timeout_secs > self.scrape_interval_secs {
+            return Err(BuildError::TimeoutTooLong {
+                timeout: self.scrape_timeout_secs,
+                interval: self.scrape_interval_secs,
+            }
+            .into());
+        }
+
+        let endpoints = match (!self.hosts.is_empty(), !self.endpoints.is_empty()) {
+            (true, true) => return Err(BuildError::BothHostsAndEndpoints.into()),
+            (true, false) => {
+                warn!("The `hosts` setting is deprecated, use `endpoints` instead");
+                let mut endpoints = Vec::new();
+                for host in self.hosts.iter() {
+                    let base_uri = host.parse::<Uri>().context(super::UriParseError)?;
+                    endpoints.push(format!("{}metrics", base_uri));
+                }
+                endpoints
+            }
+            (false, _) => self.endpoints.clone(),
+        };
+
+        let mut targets = Vec::new();
+        for endpoint in endpoints {
+            let uri = endpoint.parse::<Uri>().context(super::UriParseError)?;
+            targets.push(Target::new(uri, &self.instance_tag, &self.tags));
         }
-        Ok(prometheus(urls, self.scrape_interval_secs, shutdown, out))
+
+        let tls = TlsSettings::from_options(&self.tls)?;
+        let scraper = Scraper {
+            client: HttpClient::new(Resolver, tls)?,
+            auth: self.auth.clone(),
+            timeout: Duration::from_secs(self.scrape_timeout_secs),
+            honor_labels: self.honor_labels,
+            split_namespace: self.split_namespace,
+        };
+
+        Ok(prometheus(
+            targets,
+            self.scrape_interval_secs,
+            scraper,
+            shutdown,
+            out,
+        ))
     }
 
     fn output_type(&self) -> crate::topology::config::DataType {
@@ -54,49 +152,155 @@ impl crate::topology::config::SourceConfig for PrometheusConfig {
     }
 }
 
-fn prometheus(
-    urls: Vec<String>,
-    interval: u64,
-    shutdown: ShutdownSignal,
-    out: mpsc::Sender<Event>,
-) -> super::Source {
-    let out = out.sink_map_err(|e| error!("error sending metric: {:?}", e));
+/// An endpoint to scrape, along with the tags of its metrics.
+#[derive(Clone, Debug)]
+struct Target {
+    uri: Uri,
+    tags: BTreeMap<String, String>,
+}
 
-    let task = Interval::new(Instant::now(), Duration::from_secs(interval))
-        .map_err(|e| error!("timer error: {:?}", e))
-        .take_until(shutdown)
-        .map(move |_| futures01::stream::iter_ok(urls.clone()))
-        .flatten()
-        .map(move |url| {
-            let https = HttpsConnector::new().expect("TLS initialization failed");
-            let client = Client::builder().build(https);
+impl Target {
+    fn new(uri: Uri, instance_tag: &str, tags: &BTreeMap<String, String>) -> Self {
+        let mut tags = tags.clone();
+        let port = uri.port_u16().unwrap_or_else(|| {
+            if uri.scheme_str() == Some("https") {
+                443
+            } else {
+                80
+            }
+        });
+        let instance = format!("{}:{}", uri.host().unwrap_or(""), port);
+        tags.insert(instance_tag.to_owned(), instance);
+        Self { uri, tags }
+    }
 
-            let request = Request::get(&url)
-                .body(Body::empty())
-                .expect("error creating request");
+    /// Adds the tags of the target to a scraped metric. Conflicting scraped
+    /// labels are either kept, or renamed with an `exported_` prefix like
+    /// Prometheus does.
+    fn tag(&self, mut metric: Metric, honor_labels: bool) -> Metric {
+        let tags = metric.tags.get_or_insert_with(BTreeMap::new);
+        for (name, value) in self.tags.iter() {
+            if honor_labels {
+                tags.entry(name.clone()).or_insert_with(|| value.clone());
+            } else if let Some(exported) = tags.insert(name.clone(), value.clone()) {
+                tags.insert(format!("exported_{}", name), exported);
+            }
+        }
+        metric
+    }
 
-            client
-                .request(request)
-                .and_then(|response| body_to_bytes(response.into_body()).boxed())
-                .compat()
-                .map(|body| {
+    /// A gauge describing the scrape of the target.
+    fn synthetic(&self, name: &str, value: f64) -> Event {
+        Event::Metric(Metric {
+            name: name.to_owned(),
+            namespace: None,
+            timestamp: Some(Utc::now()),
+            tags: Some(self.tags.clone()),
+            kind: MetricKind::Absolute,
+            value: MetricValue::Gauge { value },
+        })
+    }
+}
+
+#[derive(Clone)]
+struct Scraper {
+    client: HttpClient,
+    auth: Option<Auth>,
+    timeout: Duration,
+    honor_labels: bool,
+    split_namespace: bool,
+}
+
+impl Scraper {
+    /// Scrapes the metrics of `target`, followed by the synthetic `up` and
+    /// `scrape_duration_seconds` metrics of the scrape.
+    fn scrape(&self, target: Target) -> impl Future<Item = Vec<Event>, Error = ()> {
+        let start = Instant::now();
+
+        let mut request = Request::get(target.uri.clone())
+            .body(Body::empty())
+            .expect("error creating request");
+        if let Some(auth) = &self.auth {
+            auth.apply(&mut request);
+        }
+
+        let mut client = self.client.clone();
+        let fetch = async move {
+            let response = client.send(request).await?;
+            let status = response.status();
+            if !status.is_success() {
+                return Err(ScrapeError::UnexpectedStatus { status }.into());
+            }
+            Ok::<Bytes, crate::Error>(body_to_bytes(response.into_body()).await?)
+        };
+
+        let scraper = self.clone();
+        Timeout::new(fetch.boxed().compat(), self.timeout).then(move |result| {
+            let metrics = match result {
+                Ok(body) => {
                     emit!(PrometheusRequestCompleted);
 
                     let packet = String::from_utf8_lossy(&body);
-                    let metrics = parser::parse(&packet)
+                    parser::parse(&packet)
                         .map_err(|error| {
                             emit!(PrometheusParseError { error });
                         })
-                        .unwrap_or_default()
-                        .into_iter()
-                        .map(Event::Metric);
+                        .ok()
+                }
+                Err(error) => {
+                    let error = error.into_inner().unwrap_or_else(|| {
+                        ScrapeError::TimedOut {
+                            timeout: scraper.timeout,
+                        }
+                        .into()
+                    });
+                    emit!(PrometheusHttpError {
+                        url: &target.uri.to_string(),
+                        error,
+                    });
+                    None
+                }
+            };
+            let up = if metrics.is_some() { 1.0 } else { 0.0 };
 
-                    futures01::stream::iter_ok(metrics)
+            let mut events: Vec<_> = metrics
+                .unwrap_or_default()
+                .into_iter()
+                .map(|metric| {
+                    let metric = if scraper.split_namespace {
+                        metric.split_namespace('_')
+                    } else {
+                        metric
+                    };
+                    Event::Metric(target.tag(metric, scraper.honor_labels))
                 })
+                .collect();
+            events.push(target.synthetic("up", up));
+            events.push(target.synthetic("scrape_duration_seconds", start.elapsed().as_secs_f64()));
+            Ok(events)
+        })
+    }
+}
+
+fn prometheus(
+    targets: Vec<Target>,
+    interval: u64,
+    scraper: Scraper,
+    shutdown: ShutdownSignal,
+    out: mpsc::Sender<Event>,
+) -> super::Source {
+    let out = out.sink_map_err(|e| error!("error sending metric: {:?}", e));
+
+    let task = Interval::new(Instant::now(), Duration::from_secs(interval))
+        .map_err(|e| error!("timer error: {:?}", e))
+        .take_until(shutdown)
+        .map(move |_| futures01::stream::iter_ok(targets.clone()))
+        .flatten()
+        .map(move |target| {
+            scraper
+                .scrape(target)
+                .map(futures01::stream::iter_ok)
                 .flatten_stream()
-                .map_err(|error| {
-                    emit!(PrometheusHttpError { error });
-                })
         })
         .flatten()
         .forward(out)
@@ -111,9 +315,9 @@ mod test {
     use super::*;
     use crate::{
         hyper::body_to_bytes,
-        sinks::prometheus::PrometheusSinkConfig,
-        test_util::{block_on, next_addr, runtime},
-        topology::{self, config},
+        sinks::prometheus::{default_summary_quantiles, PrometheusSinkConfig},
+        test_util::{block_on, collect_n, next_addr, runtime},
+        topology::{self, config, config::SourceConfig},
         Error,
     };
     use futures::TryFutureExt;
@@ -173,8 +377,16 @@ mod test {
         config.add_source(
             "in",
             PrometheusConfig {
-                hosts: vec![format!("http://{}", in_addr)],
+                endpoints: vec![format!("http://{}/metrics", in_addr)],
+                hosts: vec![],
                 scrape_interval_secs: 1,
+                scrape_timeout_secs: 1,
+                split_namespace: false,
+                instance_tag: default_instance_tag(),
+                tags: BTreeMap::new(),
+                honor_labels: false,
+                auth: None,
+                tls: None,
             },
         );
         config.add_sink(
@@ -184,7 +396,11 @@ mod test {
                 address: out_addr,
                 namespace: "vector".into(),
                 buckets: vec![1.0, 2.0, 4.0],
-                flush_period_secs: 1,
+                flush_period_secs: 10,
+                quantiles: default_summary_quantiles(),
+                sketch_relative_accuracy: None,
+                help: Default::default(),
+                tls: None,
             },
         );
 
@@ -204,38 +420,161 @@ mod test {
         let lines = std::str::from_utf8(&body)
             .unwrap()
             .lines()
+            .filter(|line| !line.contains("scrape_duration_seconds"))
             .collect::<Vec<_>>();
 
-        assert_eq!(lines, vec![
+        let instance = format!("instance=\"{}\"", in_addr);
+        let expected = vec![
+            "# HELP vector_http_request_duration_seconds http_request_duration_seconds",
+            "# TYPE vector_http_request_duration_seconds histogram",
+            "vector_http_request_duration_seconds_bucket{INSTANCE,le=\"0.05\"} 24054",
+            "vector_http_request_duration_seconds_bucket{INSTANCE,le=\"0.1\"} 33444",
+            "vector_http_request_duration_seconds_bucket{INSTANCE,le=\"0.2\"} 100392",
+            "vector_http_request_duration_seconds_bucket{INSTANCE,le=\"0.5\"} 129389",
+            "vector_http_request_duration_seconds_bucket{INSTANCE,le=\"1\"} 133988",
+            "vector_http_request_duration_seconds_bucket{INSTANCE,le=\"+Inf\"} 144320",
+            "vector_http_request_duration_seconds_sum{INSTANCE} 53423",
+            "vector_http_request_duration_seconds_count{INSTANCE} 144320",
             "# HELP vector_promhttp_metric_handler_requests_total promhttp_metric_handler_requests_total",
             "# TYPE vector_promhttp_metric_handler_requests_total counter",
-            "vector_promhttp_metric_handler_requests_total{code=\"200\"} 100",
-            "vector_promhttp_metric_handler_requests_total{code=\"404\"} 7",
+            "vector_promhttp_metric_handler_requests_total{code=\"200\",INSTANCE} 100",
+            "vector_promhttp_metric_handler_requests_total{code=\"404\",INSTANCE} 7",
             "# HELP vector_prometheus_remote_storage_samples_in_total prometheus_remote_storage_samples_in_total",
             "# TYPE vector_prometheus_remote_storage_samples_in_total gauge",
-            "vector_prometheus_remote_storage_samples_in_total 57011636",
-            "# HELP vector_http_request_duration_seconds http_request_duration_seconds",
-            "# TYPE vector_http_request_duration_seconds histogram",
-            "vector_http_request_duration_seconds_bucket{le=\"0.05\"} 24054",
-            "vector_http_request_duration_seconds_bucket{le=\"0.1\"} 33444",
-            "vector_http_request_duration_seconds_bucket{le=\"0.2\"} 100392",
-            "vector_http_request_duration_seconds_bucket{le=\"0.5\"} 129389",
-            "vector_http_request_duration_seconds_bucket{le=\"1\"} 133988",
-            "vector_http_request_duration_seconds_bucket{le=\"+Inf\"} 144320",
-            "vector_http_request_duration_seconds_sum 53423",
-            "vector_http_request_duration_seconds_count 144320",
+            "vector_prometheus_remote_storage_samples_in_total{INSTANCE} 57011636",
             "# HELP vector_rpc_duration_seconds rpc_duration_seconds",
             "# TYPE vector_rpc_duration_seconds summary",
-            "vector_rpc_duration_seconds{code=\"200\",quantile=\"0.01\"} 3102",
-            "vector_rpc_duration_seconds{code=\"200\",quantile=\"0.05\"} 3272",
-            "vector_rpc_duration_seconds{code=\"200\",quantile=\"0.5\"} 4773",
-            "vector_rpc_duration_seconds{code=\"200\",quantile=\"0.9\"} 9001",
-            "vector_rpc_duration_seconds{code=\"200\",quantile=\"0.99\"} 76656",
-            "vector_rpc_duration_seconds_sum{code=\"200\"} 17560473",
-            "vector_rpc_duration_seconds_count{code=\"200\"} 2693",
-            ],
-        );
+            "vector_rpc_duration_seconds{code=\"200\",INSTANCE,quantile=\"0.01\"} 3102",
+            "vector_rpc_duration_seconds{code=\"200\",INSTANCE,quantile=\"0.05\"} 3272",
+            "vector_rpc_duration_seconds{code=\"200\",INSTANCE,quantile=\"0.5\"} 4773",
+            "vector_rpc_duration_seconds{code=\"200\",INSTANCE,quantile=\"0.9\"} 9001",
+            "vector_rpc_duration_seconds{code=\"200\",INSTANCE,quantile=\"0.99\"} 76656",
+            "vector_rpc_duration_seconds_sum{code=\"200\",INSTANCE} 17560473",
+            "vector_rpc_duration_seconds_count{code=\"200\",INSTANCE} 2693",
+            "# HELP vector_up up",
+            "# TYPE vector_up gauge",
+            "vector_up{INSTANCE} 1",
+        ];
+        let expected = expected
+            .into_iter()
+            .map(|line| line.replace("INSTANCE", &instance))
+            .collect::<Vec<_>>();
+        assert_eq!(lines, expected);
 
         block_on(topology.stop()).unwrap();
     }
+
+    #[test]
+    fn test_prometheus_target_tags_and_auth() {
+        let mut rt = runtime();
+        let in_addr = next_addr();
+
+        let make_svc = make_service_fn(|_| async {
+            Ok::<_, Error>(service_fn(|req: Request<Body>| async move {
+                let authorized = req
+                    .headers()
+                    .get("Authorization")
+                    .map(|value| value == "Basic dXNlcjpwYXNz")
+                    .unwrap_or(false);
+                let response = if authorized && req.uri().path() == "/custom/metrics" {
+                    Response::new(Body::from("requests_total{job=\"app\"} 10\n"))
+                } else {
+                    Response::builder()
+                        .status(StatusCode::NOT_FOUND)
+                        .body(Body::empty())
+                        .unwrap()
+                };
+                Ok::<_, Error>(response)
+            }))
+        });
+
+        rt.spawn_std(async move {
+            if let Err(e) = Server::bind(&in_addr).serve(make_svc).await {
+                error!("server error: {:?}", e);
+            }
+        });
+
+        let config: PrometheusConfig = toml::from_str(&format!(
+            r#"
+            endpoints = ["http://{0}/custom/metrics", "http://{0}/missing"]
+            tags = {{ job = "node" }}
+
+            [auth]
+            strategy = "basic"
+            user = "user"
+            password = "pass"
+            "#,
+            in_addr
+        ))
+        .unwrap();
+
+        let (tx, rx) = mpsc::channel(10);
+        let source = config
+            .build(
+                "default",
+                &GlobalOptions::default(),
+                ShutdownSignal::noop(),
+                tx,
+            )
+            .unwrap();
+        rt.spawn(source);
+
+        let events = rt.block_on(collect_n(rx, 5)).unwrap();
+        let metrics: Vec<_> = events.iter().map(Event::as_metric).collect();
+        let tags = |tags: &[(&str, &str)]| {
+            Some(
+                tags.iter()
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect::<BTreeMap<_, _>>(),
+            )
+        };
+        let instance = in_addr.to_string();
+
+        assert_eq!(metrics[0].name, "requests_total");
+        assert_eq!(
+            metrics[0].tags,
+            tags(&[
+                ("exported_job", "app"),
+                ("instance", instance.as_str()),
+                ("job", "node")
+            ])
+        );
+        assert_eq!(metrics[1].name, "up");
+        assert_eq!(metrics[1].value, MetricValue::Gauge { value: 1.0 });
+        assert_eq!(
+            metrics[1].tags,
+            tags(&[("instance", instance.as_str()), ("job", "node")])
+        );
+        assert_eq!(metrics[2].name, "scrape_duration_seconds");
+        assert_eq!(metrics[3].name, "up");
+        assert_eq!(metrics[3].value, MetricValue::Gauge { value: 0.0 });
+    }
+
+    #[test]
+    fn test_prometheus_honor_labels() {
+        let target = Target::new(
+            "http://localhost/metrics".parse().unwrap(),
+            "instance",
+            &vec![("job".to_owned(), "node".to_owned())]
+                .into_iter()
+                .collect(),
+        );
+        let metric = Metric {
+            name: "requests_total".into(),
+            namespace: None,
+            timestamp: None,
+            tags: Some(
+                vec![("job".to_owned(), "app".to_owned())]
+                    .into_iter()
+                    .collect(),
+            ),
+            kind: MetricKind::Absolute,
+            value: MetricValue::Counter { value: 10.0 },
+        };
+
+        let tags = target.tag(metric, true).tags.unwrap();
+        assert_eq!(tags["job"], "app");
+        assert_eq!(tags["instance"], "localhost:80");
+        assert!(!tags.contains_key("exported_job"));
+    }
 }
@@ -287,6 +287,7 @@ pub fn parse(packet: &str) -> Result<Vec<Metric>, ParserError> {
 
                     let counter = Metric {
                         name: metric.name,
+                        namespace: None,
                         timestamp: None,
                         tags,
                         kind: MetricKind::Absolute,
@@ -309,6 +310,7 @@ pub fn parse(packet: &str) -> Result<Vec<Metric>, ParserError> {
 
                     let gauge = Metric {
                         name: metric.name,
+                        namespace: None,
                         timestamp: None,
                         tags,
                         kind: MetricKind::Absolute,
@@ -386,6 +388,7 @@ pub fn parse(packet: &str) -> Result<Vec<Metric>, ParserError> {
 
                     let hist = Metric {
                         name: aggregate.name,
+                        namespace: None,
                         timestamp: None,
                         tags,
                         kind: MetricKind::Absolute,
@@ -463,6 +466,7 @@ pub fn parse(packet: &str) -> Result<Vec<Metric>, ParserError> {
 
                     let summary = Metric {
                         name: aggregate.name,
+                        namespace: None,
                         timestamp: None,
                         tags,
                         kind: MetricKind::Absolute,
@@ -516,6 +520,7 @@ mod test {
             parse(exp),
             Ok(vec![Metric {
                 name: "uptime".into(),
+                namespace: None,
                 timestamp: None,
                 tags: None,
                 kind: MetricKind::Absolute,
@@ -568,6 +573,7 @@ mod test {
             Ok(vec![
                 Metric {
                     name: "name".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(
                         vec![
@@ -582,6 +588,7 @@ mod test {
                 },
                 Metric {
                     name: "name2".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(
                         vec![
@@ -598,6 +605,7 @@ mod test {
                 },
                 Metric {
                     name: "name2".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(
                         vec![("labelname".into(), "val1".into()),]
@@ -627,6 +635,7 @@ mod test {
             Ok(vec![
                 Metric {
                     name: "http_requests_total".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(
                         vec![
@@ -641,6 +650,7 @@ mod test {
                 },
                 Metric {
                     name: "http_requests_total".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(
                         vec![
@@ -669,6 +679,7 @@ mod test {
             parse(exp),
             Ok(vec![Metric {
                 name: "latency".into(),
+                namespace: None,
                 timestamp: None,
                 tags: None,
                 kind: MetricKind::Absolute,
@@ -687,6 +698,7 @@ mod test {
             parse(exp),
             Ok(vec![Metric {
                 name: "metric_without_timestamp_and_labels".into(),
+                namespace: None,
                 timestamp: None,
                 tags: None,
                 kind: MetricKind::Absolute,
@@ -705,6 +717,7 @@ mod test {
             parse(exp),
             Ok(vec![Metric {
                 name: "no_labels".into(),
+                namespace: None,
                 timestamp: None,
                 tags: None,
                 kind: MetricKind::Absolute,
@@ -723,6 +736,7 @@ mod test {
             parse(exp),
             Ok(vec![Metric {
                 name: "msdos_file_access_time_seconds".into(),
+                namespace: None,
                 timestamp: None,
                 tags: Some(
                     vec![
@@ -750,6 +764,7 @@ mod test {
             parse(exp),
             Ok(vec![Metric {
                 name: "something_weird".into(),
+                namespace: None,
                 timestamp: None,
                 tags: Some(
                     vec![("problem".into(), "division by zero".into())]
@@ -777,6 +792,7 @@ mod test {
             Ok(vec![
                 Metric {
                     name: "latency".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(
                         vec![("env".into(), "production".into())]
@@ -788,6 +804,7 @@ mod test {
                 },
                 Metric {
                     name: "latency".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(vec![("env".into(), "testing".into())].into_iter().collect()),
                     kind: MetricKind::Absolute,
@@ -813,6 +830,7 @@ mod test {
             Ok(vec![
                 Metric {
                     name: "uptime".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: None,
                     kind: MetricKind::Absolute,
@@ -820,6 +838,7 @@ mod test {
                 },
                 Metric {
                     name: "temperature".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: None,
                     kind: MetricKind::Absolute,
@@ -827,6 +846,7 @@ mod test {
                 },
                 Metric {
                     name: "launch_count".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: None,
                     kind: MetricKind::Absolute,
@@ -872,6 +892,7 @@ mod test {
             Ok(vec![
                 Metric {
                     name: "uptime".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: None,
                     kind: MetricKind::Absolute,
@@ -879,6 +900,7 @@ mod test {
                 },
                 Metric {
                     name: "last_downtime".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: None,
                     kind: MetricKind::Absolute,
@@ -886,6 +908,7 @@ mod test {
                 },
                 Metric {
                     name: "temperature".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: None,
                     kind: MetricKind::Absolute,
@@ -893,6 +916,7 @@ mod test {
                 },
                 Metric {
                     name: "temperature_7_days_average".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: None,
                     kind: MetricKind::Absolute,
@@ -921,6 +945,7 @@ mod test {
             parse(exp),
             Ok(vec![Metric {
                 name: "http_request_duration_seconds".into(),
+                namespace: None,
                 timestamp: None,
                 tags: None,
                 kind: MetricKind::Absolute,
@@ -985,6 +1010,7 @@ mod test {
             Ok(vec![
                 Metric {
                     name: "gitlab_runner_job_duration_seconds".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(vec![("runner".into(), "z".into())].into_iter().collect()),
                     kind: MetricKind::Absolute,
@@ -1000,6 +1026,7 @@ mod test {
                 },
                 Metric {
                     name: "gitlab_runner_job_duration_seconds".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(vec![("runner".into(), "x".into())].into_iter().collect()),
                     kind: MetricKind::Absolute,
@@ -1015,6 +1042,7 @@ mod test {
                 },
                 Metric {
                     name: "gitlab_runner_job_duration_seconds".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(vec![("runner".into(), "y".into())].into_iter().collect()),
                     kind: MetricKind::Absolute,
@@ -1060,6 +1088,7 @@ mod test {
             Ok(vec![
                 Metric {
                     name: "rpc_duration_seconds".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: Some(vec![("service".into(), "a".into())].into_iter().collect()),
                     kind: MetricKind::Absolute,
@@ -1072,6 +1101,7 @@ mod test {
                 },
                 Metric {
                     name: "go_gc_duration_seconds".into(),
+                    namespace: None,
                     timestamp: None,
                     tags: None,
                     kind: MetricKind::Absolute,
@@ -0,0 +1,286 @@
+use crate::{
+    event::{
+        metric::{Metric, MetricKind, MetricValue},
+        Event,
+    },
+    internal_events::{PrometheusRemoteWriteParseError, PrometheusRemoteWriteReceived},
+    prometheus::{
+        proto::{MetricType, WriteRequest},
+        METRIC_NAME_LABEL,
+    },
+    shutdown::ShutdownSignal,
+    sources::util::{ErrorMessage, HttpSource},
+    tls::TlsConfig,
+    topology::config::{DataType, GlobalOptions, SourceConfig, SourceDescription},
+};
+use bytes05::Bytes;
+use chrono::{TimeZone, Utc};
+use futures01::sync::mpsc;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use snafu::Snafu;
+use std::{
+    collections::{BTreeMap, HashSet},
+    net::SocketAddr,
+};
+use warp::http::{HeaderMap, StatusCode};
+
+#[derive(Debug, Snafu)]
+enum ParseError {
+    #[snafu(display("time series without a {} label", METRIC_NAME_LABEL))]
+    MissingName,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+struct PrometheusRemoteWriteConfig {
+    address: SocketAddr,
+    tls: Option<TlsConfig>,
+    #[serde(default)]
+    acknowledgements: bool,
+}
+
+inventory::submit! {
+    SourceDescription::new_without_default::<PrometheusRemoteWriteConfig>("prometheus_remote_write")
+}
+
+#[typetag::serde(name = "prometheus_remote_write")]
+impl SourceConfig for PrometheusRemoteWriteConfig {
+    fn build(
+        &self,
+        _: &str,
+        _: &GlobalOptions,
+        shutdown: ShutdownSignal,
+        out: mpsc::Sender<Event>,
+    ) -> crate::Result<crate::sources::Source> {
+        RemoteWriteSource.run(
+            self.address,
+            &[""],
+            &self.tls,
+            self.acknowledgements,
+            out,
+            shutdown,
+        )
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Metric
+    }
+
+    fn source_type(&self) -> &'static str {
+        "prometheus_remote_write"
+    }
+}
+
+#[derive(Clone)]
+struct RemoteWriteSource;
+
+impl HttpSource for RemoteWriteSource {
+    fn build_event(
+        &self,
+        body: Bytes,
+        _header_map: HeaderMap,
+        _path: &str,
+    ) -> Result<Vec<Event>, ErrorMessage> {
+        let byte_size = body.len();
+        let events = decode_body(&body).map_err(|error| {
+            emit!(PrometheusRemoteWriteParseError { error: &error });
+            ErrorMessage::new(
+                StatusCode::BAD_REQUEST,
+                format!("Invalid remote write request: {}", error),
+            )
+        })?;
+
+        emit!(PrometheusRemoteWriteReceived {
+            count: events.len(),
+            byte_size,
+        });
+        Ok(events)
+    }
+}
+
+/// A snappy block expands to at most 22 times its size, as a back reference
+/// of 3 bytes copies up to 64, so longer declared lengths are rejected before
+/// allocating them.
+const MAX_SNAPPY_EXPANSION: usize = 22;
+
+/// Every sample becomes an absolute gauge, unless the metadata sent along
+/// with the samples declares its metric a counter.
+fn decode_body(body: &[u8]) -> crate::Result<Vec<Event>> {
+    if snap::raw::decompress_len(body)? > body.len().saturating_mul(MAX_SNAPPY_EXPANSION) {
+        return Err("snappy block declares a length larger than it can hold".into());
+    }
+    let body = snap::raw::Decoder::new().decompress_vec(body)?;
+    let request = WriteRequest::decode(&body[..])?;
+
+    let counters: HashSet<_> = request
+        .metadata
+        .iter()
+        .filter(|metadata| metadata.r#type == MetricType::Counter as i32)
+        .map(|metadata| metadata.metric_family_name.as_str())
+        .collect();
+    let is_counter = |name: &str| {
+        // OpenMetrics counter families leave out the suffix of their samples.
+        counters.contains(name)
+            || (name.ends_with("_total") && counters.contains(&name[..name.len() - 6]))
+    };
+
+    let mut events = Vec::new();
+    for series in request.timeseries.iter() {
+        let mut name = None;
+        let mut tags = BTreeMap::new();
+        for label in series.labels.iter() {
+            if label.name == METRIC_NAME_LABEL {
+                name = Some(label.value.clone());
+            } else {
+                tags.insert(label.name.clone(), label.value.clone());
+            }
+        }
+        let name = name.ok_or(ParseError::MissingName)?;
+        let counter = is_counter(&name);
+
+        for sample in series.samples.iter() {
+            let value = if counter {
+                MetricValue::Counter {
+                    value: sample.value,
+                }
+            } else {
+                MetricValue::Gauge {
+                    value: sample.value,
+                }
+            };
+            events.push(Event::Metric(Metric {
+                name: name.clone(),
+                namespace: None,
+                timestamp: Utc.timestamp_millis_opt(sample.timestamp).single(),
+                tags: if tags.is_empty() {
+                    None
+                } else {
+                    Some(tags.clone())
+                },
+                kind: MetricKind::Absolute,
+                value,
+            }));
+        }
+    }
+
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        prometheus::proto::{Label, MetricMetadata, Sample, TimeSeries},
+        test_util::{self, collect_n, runtime},
+    };
+    use futures::compat::Future01CompatExt;
+    use pretty_assertions::assert_eq;
+
+    fn source(rt: &mut crate::runtime::Runtime) -> (mpsc::Receiver<Event>, SocketAddr) {
+        test_util::trace_init();
+        let (sender, recv) = mpsc::channel(100);
+        let address = test_util::next_addr();
+        rt.spawn(
+            PrometheusRemoteWriteConfig {
+                address,
+                tls: None,
+                acknowledgements: false,
+            }
+            .build(
+                "default",
+                &GlobalOptions::default(),
+                ShutdownSignal::noop(),
+                sender,
+            )
+            .unwrap(),
+        );
+        (recv, address)
+    }
+
+    async fn send(address: SocketAddr, body: Vec<u8>) -> u16 {
+        reqwest::Client::new()
+            .post(&format!("http://{}/", address))
+            .header("Content-Type", "application/x-protobuf")
+            .header("Content-Encoding", "snappy")
+            .body(body)
+            .send()
+            .await
+            .unwrap()
+            .status()
+            .as_u16()
+    }
+
+    fn series(name: &str, value: f64) -> TimeSeries {
+        TimeSeries {
+            labels: vec![
+                Label {
+                    name: METRIC_NAME_LABEL.into(),
+                    value: name.into(),
+                },
+                Label {
+                    name: "code".into(),
+                    value: "200".into(),
+                },
+            ],
+            samples: vec![Sample {
+                value,
+                timestamp: 1_588_422_166_658,
+            }],
+        }
+    }
+
+    #[test]
+    fn receives_samples() {
+        let request = WriteRequest {
+            timeseries: vec![series("requests_total", 10.0), series("temperature", 20.5)],
+            metadata: vec![MetricMetadata {
+                r#type: MetricType::Counter as i32,
+                metric_family_name: "requests".into(),
+                ..Default::default()
+            }],
+        };
+        let mut body = Vec::new();
+        request.encode(&mut body).unwrap();
+
+        let mut rt = runtime();
+        let (rx, addr) = source(&mut rt);
+
+        rt.block_on_std(async move {
+            assert_eq!(
+                200,
+                send(addr, snap::raw::Encoder::new().compress_vec(&body).unwrap()).await
+            );
+
+            let events = collect_n(rx, 2).compat().await.unwrap();
+            let tags: BTreeMap<_, _> = vec![("code".to_owned(), "200".to_owned())]
+                .into_iter()
+                .collect();
+            assert_eq!(
+                events[0].as_metric(),
+                &Metric {
+                    name: "requests_total".into(),
+                    namespace: None,
+                    timestamp: Some(Utc.timestamp_millis(1_588_422_166_658)),
+                    tags: Some(tags.clone()),
+                    kind: MetricKind::Absolute,
+                    value: MetricValue::Counter { value: 10.0 },
+                }
+            );
+            assert_eq!(
+                events[1].as_metric().value,
+                MetricValue::Gauge { value: 20.5 }
+            );
+        });
+    }
+
+    #[test]
+    fn rejects_invalid_requests() {
+        let mut rt = runtime();
+        let (_rx, addr) = source(&mut rt);
+
+        rt.block_on_std(async move {
+            assert_eq!(400, send(addr, b"not snappy".to_vec()).await);
+        });
+    }
+}
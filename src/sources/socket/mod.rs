@@ -5,6 +5,7 @@ mod unix;
 
 use super::util::TcpSource;
 use crate::{
+    codecs::framing,
     event::{self, Event},
     shutdown::ShutdownSignal,
     tls::MaybeTlsSettings,
@@ -77,7 +78,10 @@ impl SourceConfig for SocketConfig {
     ) -> crate::Result<super::Source> {
         match self.mode.clone() {
             Mode::Tcp(config) => {
+                let decoding = config.decoding.clone().unwrap_or_default();
                 let tcp = tcp::RawTcpSource {
+                    framer: decoding.framer(config.max_length)?,
+                    codec: decoding.codec,
                     config: config.clone(),
                 };
                 let tls = MaybeTlsSettings::from_config(&config.tls, true)?;
@@ -94,7 +98,15 @@ impl SourceConfig for SocketConfig {
                     .host_key
                     .clone()
                     .unwrap_or_else(|| event::log_schema().host_key().clone());
-                Ok(udp::udp(config.address, host_key, shutdown, out))
+                let decoding = config.decoding.unwrap_or_default();
+                Ok(udp::udp(
+                    config.address,
+                    host_key,
+                    decoding.framer(framing::DEFAULT_MAX_LENGTH)?,
+                    decoding.codec,
+                    shutdown,
+                    out,
+                ))
             }
             #[cfg(unix)]
             Mode::Unix(config) => {
@@ -102,9 +114,11 @@ impl SourceConfig for SocketConfig {
                     .host_key
                     .clone()
                     .unwrap_or_else(|| event::log_schema().host_key().to_string());
+                let decoding = config.decoding.unwrap_or_default();
                 Ok(unix::unix(
                     config.path,
-                    config.max_length,
+                    decoding.framer(config.max_length)?,
+                    decoding.codec,
                     host_key,
                     shutdown,
                     out,
@@ -257,6 +271,47 @@ mod test {
         );
     }
 
+    #[test]
+    fn tcp_decodes_json() {
+        let (tx, rx) = mpsc::channel(10);
+
+        let addr = next_addr();
+
+        let mut config = TcpConfig::new(addr.into());
+        config.decoding = Some(toml::from_str(r#"codec = "json""#).unwrap());
+
+        let server = SocketConfig::from(config)
+            .build(
+                "default",
+                &GlobalOptions::default(),
+                ShutdownSignal::noop(),
+                tx,
+            )
+            .unwrap();
+        let mut rt = runtime();
+        rt.spawn(server);
+        wait_for_tcp(addr);
+
+        let lines = vec![
+            r#"{"foo":"bar"}"#.to_owned(),
+            "not json".to_owned(),
+            r#"{"foo":"baz"}"#.to_owned(),
+        ];
+
+        rt.block_on(send_lines(addr, lines.into_iter())).unwrap();
+
+        let (event, rx) = block_on(rx.into_future()).unwrap();
+        let event = event.unwrap();
+        assert_eq!(event.as_log()[&"foo".into()], "bar".into());
+        assert_eq!(
+            event.as_log()[event::log_schema().source_type_key()],
+            "socket".into()
+        );
+
+        let (event, _rx) = block_on(rx.into_future()).unwrap();
+        assert_eq!(event.unwrap().as_log()[&"foo".into()], "baz".into());
+    }
+
     #[test]
     fn tcp_with_tls() {
         let (tx, rx) = mpsc::channel(10);
@@ -1,11 +1,14 @@
 use crate::{
+    codecs::{
+        decoding::{Codec, DecodingConfig},
+        framing::Framer,
+    },
     event::{self, Event},
     internal_events::TcpEventReceived,
     sources::util::{SocketListenAddr, TcpSource},
     tls::TlsConfig,
 };
 use bytes::Bytes;
-use codec01::BytesDelimitedCodec;
 use serde::{Deserialize, Serialize};
 use string_cache::DefaultAtom as Atom;
 use tracing::field;
@@ -20,6 +23,7 @@ pub struct TcpConfig {
     pub shutdown_timeout_secs: u64,
     pub host_key: Option<Atom>,
     pub tls: Option<TlsConfig>,
+    pub decoding: Option<DecodingConfig>,
 }
 
 fn default_max_length() -> usize {
@@ -38,6 +42,7 @@ impl TcpConfig {
             host_key: None,
             shutdown_timeout_secs: default_shutdown_timeout_secs(),
             tls: Default::default(),
+            decoding: None,
         }
     }
 }
@@ -45,18 +50,20 @@ impl TcpConfig {
 #[derive(Debug, Clone)]
 pub struct RawTcpSource {
     pub config: TcpConfig,
+    pub framer: Framer,
+    pub codec: Codec,
 }
 
 impl TcpSource for RawTcpSource {
-    type Decoder = BytesDelimitedCodec;
+    type Decoder = Framer;
 
     fn decoder(&self) -> Self::Decoder {
-        BytesDelimitedCodec::new_with_max_length(b'\n', self.config.max_length)
+        self.framer.clone()
     }
 
     fn build_event(&self, frame: Bytes, host: Bytes) -> Option<Event> {
         let byte_size = frame.len();
-        let mut event = Event::from(frame);
+        let mut event = self.codec.try_decode(frame, "socket")?;
 
         event
             .as_mut_log()
@@ -1,24 +1,32 @@
 use crate::{
+    codecs::{
+        decoding::{Codec, DecodingConfig},
+        framing::Framer,
+    },
     event::{self, Event},
-    internal_events::{UdpEventReceived, UdpSocketError},
+    internal_events::{DecoderFramingFailed, UdpEventReceived, UdpSocketError},
     shutdown::ShutdownSignal,
     sources::Source,
     stream::StreamExt01,
 };
-use bytes::Bytes;
-use codec01::BytesDelimitedCodec;
-use futures01::{future, sync::mpsc, Future, Sink, Stream};
+use bytes::BytesMut;
+use futures01::{future, stream, sync::mpsc, Future, Sink, Stream};
 use serde::{Deserialize, Serialize};
 use std::{io, net::SocketAddr};
 use string_cache::DefaultAtom as Atom;
-use tokio01::net::udp::{UdpFramed, UdpSocket};
+use tokio01::{
+    codec::BytesCodec,
+    net::udp::{UdpFramed, UdpSocket},
+};
 
-/// UDP processes messages per packet, where messages are separated by newline.
+/// UDP processes messages per packet, where messages are separated by newline
+/// unless the `decoding` sets another framing.
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct UdpConfig {
     pub address: SocketAddr,
     pub host_key: Option<Atom>,
+    pub decoding: Option<DecodingConfig>,
 }
 
 impl UdpConfig {
@@ -26,6 +34,7 @@ impl UdpConfig {
         Self {
             address,
             host_key: None,
+            decoding: None,
         }
     }
 }
@@ -33,6 +42,8 @@ impl UdpConfig {
 pub fn udp(
     address: SocketAddr,
     host_key: Atom,
+    framer: Framer,
+    codec: Codec,
     shutdown: ShutdownSignal,
     out: mpsc::Sender<Event>,
 ) -> Source {
@@ -48,25 +59,38 @@ pub fn udp(
         })
         .and_then(move |socket| {
             let host_key = host_key.clone();
-            // UDP processes messages per packet, where messages are separated by newline.
+            // UDP processes messages per packet, where messages are separated by the framing.
             // And stretch to end of packet.
-            UdpFramed::with_decode(socket, BytesDelimitedCodec::new(b'\n'), true)
+            UdpFramed::new(socket, BytesCodec::new())
                 .take_until(shutdown)
-                .map(move |(line, addr): (Bytes, _)| {
-                    let byte_size = line.len();
-                    let mut event = Event::from(line);
+                .map(move |(packet, addr): (BytesMut, SocketAddr)| {
+                    let frames = framer.frames(&packet).unwrap_or_else(|error| {
+                        emit!(DecoderFramingFailed {
+                            source_type: "socket",
+                            error: &error,
+                        });
+                        Vec::new()
+                    });
+
+                    let host_key = host_key.clone();
+                    let events = frames.into_iter().filter_map(move |frame| {
+                        let byte_size = frame.len();
+                        let mut event = codec.try_decode(frame, "socket")?;
 
-                    event
-                        .as_mut_log()
-                        .insert(event::log_schema().source_type_key(), "socket");
+                        event
+                            .as_mut_log()
+                            .insert(event::log_schema().source_type_key(), "socket");
 
-                    event
-                        .as_mut_log()
-                        .insert(host_key.clone(), addr.to_string());
+                        event
+                            .as_mut_log()
+                            .insert(host_key.clone(), addr.to_string());
 
-                    emit!(UdpEventReceived { byte_size });
-                    event
+                        emit!(UdpEventReceived { byte_size });
+                        Some(event)
+                    });
+                    stream::iter_ok::<_, io::Error>(events)
                 })
+                .flatten()
                 // Error from Decoder or UdpSocket
                 .map_err(|error: io::Error| {
                     emit!(UdpSocketError { error });
@@ -1,4 +1,8 @@
 use crate::{
+    codecs::{
+        decoding::{Codec, DecodingConfig},
+        framing::Framer,
+    },
     event::{self, Event},
     internal_events::UnixSocketEventReceived,
     shutdown::ShutdownSignal,
@@ -8,7 +12,6 @@ use bytes::Bytes;
 use futures01::sync::mpsc;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
-use tokio01::codec::LinesCodec;
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(deny_unknown_fields)]
@@ -17,6 +20,7 @@ pub struct UnixConfig {
     #[serde(default = "default_max_length")]
     pub max_length: usize,
     pub host_key: Option<String>,
+    pub decoding: Option<DecodingConfig>,
 }
 
 fn default_max_length() -> usize {
@@ -29,17 +33,23 @@ impl UnixConfig {
             path,
             max_length: default_max_length(),
             host_key: None,
+            decoding: None,
         }
     }
 }
 
 /**
 * Function to pass to build_unix_source, specific to the basic unix source.
-* Takes a single line of a received message and builds an Event object.
+* Takes a single frame of a received message and builds an Event object.
 **/
-fn build_event(host_key: &str, received_from: Option<Bytes>, line: &str) -> Option<Event> {
-    let byte_size = line.len();
-    let mut event = Event::from(line);
+fn build_event(
+    codec: Codec,
+    host_key: &str,
+    received_from: Option<Bytes>,
+    frame: Bytes,
+) -> Option<Event> {
+    let byte_size = frame.len();
+    let mut event = codec.try_decode(frame, "socket")?;
     event
         .as_mut_log()
         .insert(event::log_schema().source_type_key(), "socket");
@@ -52,17 +62,18 @@ fn build_event(host_key: &str, received_from: Option<Bytes>, line: &str) -> Opti
 
 pub fn unix(
     path: PathBuf,
-    max_length: usize,
+    framer: Framer,
+    codec: Codec,
     host_key: String,
     shutdown: ShutdownSignal,
     out: mpsc::Sender<Event>,
 ) -> Source {
     build_unix_source(
         path,
-        LinesCodec::new_with_max_length(max_length),
+        framer,
         host_key,
         shutdown,
         out,
-        build_event,
+        move |host_key, received_from, frame| build_event(codec, host_key, received_from, frame),
     )
 }
@@ -1,10 +1,18 @@
 use crate::{
-    shutdown::ShutdownSignal, stream::StreamExt01, topology::config::GlobalOptions, Event,
+    event::Metric,
+    shutdown::ShutdownSignal,
+    sources::util::{SocketListenAddr, TcpSource},
+    stream::StreamExt01,
+    tls::{MaybeTlsSettings, TlsConfig},
+    topology::config::GlobalOptions,
+    Event,
 };
 use futures01::{future, sync::mpsc, Future, Sink, Stream};
 use parser::parse;
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize};
 use std::net::SocketAddr;
+#[cfg(unix)]
+use std::path::PathBuf;
 use tokio01::{
     self,
     codec::BytesCodec,
@@ -13,10 +21,92 @@ use tokio01::{
 use tracing::field;
 
 pub mod parser;
+mod tcp;
+#[cfg(unix)]
+mod unix;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct StatsdConfig {
+    #[serde(flatten)]
+    pub mode: Mode,
+    /// The maximum length of lines received over TCP and Unix sockets, and
+    /// of datagrams received over Unix datagram sockets.
+    pub max_length: usize,
+    /// Use the first `.` separated segment of metric names as their namespace.
+    pub split_namespace: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum Mode {
+    Tcp {
+        address: SocketListenAddr,
+        tls: Option<TlsConfig>,
+        #[serde(default = "default_shutdown_timeout_secs")]
+        shutdown_timeout_secs: u64,
+    },
+    Udp {
+        address: SocketAddr,
+    },
+    #[cfg(unix)]
+    Unix {
+        path: PathBuf,
+    },
+    #[cfg(unix)]
+    UnixDatagram {
+        path: PathBuf,
+    },
+}
+
+fn default_max_length() -> usize {
+    bytesize::kib(100u64) as usize
+}
+
+fn default_shutdown_timeout_secs() -> u64 {
+    30
+}
+
+impl StatsdConfig {
+    pub fn new(mode: Mode) -> Self {
+        Self {
+            mode,
+            max_length: default_max_length(),
+            split_namespace: false,
+        }
+    }
+}
 
-#[derive(Deserialize, Serialize, Debug)]
-struct StatsdConfig {
-    address: SocketAddr,
+// TODO: derive this once serde supports defaults for internally tagged enums.
+impl<'de> Deserialize<'de> for StatsdConfig {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct Config {
+            #[serde(flatten)]
+            mode: Mode,
+            #[serde(default = "default_max_length")]
+            max_length: usize,
+            #[serde(default)]
+            split_namespace: bool,
+        }
+
+        // Configs predating `mode` only have an UDP `address`.
+        let mut value = toml::Value::deserialize(deserializer)?;
+        if let toml::Value::Table(table) = &mut value {
+            table
+                .entry("mode".to_string())
+                .or_insert_with(|| "udp".into());
+        }
+
+        let config = Config::deserialize(value).map_err(de::Error::custom)?;
+        Ok(Self {
+            mode: config.mode,
+            max_length: config.max_length,
+            split_namespace: config.split_namespace,
+        })
+    }
 }
 
 #[typetag::serde(name = "statsd")]
@@ -28,7 +118,37 @@ impl crate::topology::config::SourceConfig for StatsdConfig {
         shutdown: ShutdownSignal,
         out: mpsc::Sender<Event>,
     ) -> crate::Result<super::Source> {
-        Ok(statsd(self.address, shutdown, out))
+        match self.mode.clone() {
+            Mode::Tcp {
+                address,
+                tls,
+                shutdown_timeout_secs,
+            } => {
+                let source = tcp::StatsdTcpSource {
+                    max_length: self.max_length,
+                    split_namespace: self.split_namespace,
+                };
+                let tls = MaybeTlsSettings::from_config(&tls, true)?;
+                source.run(address, shutdown_timeout_secs, tls, shutdown, out)
+            }
+            Mode::Udp { address } => Ok(statsd(address, self.split_namespace, shutdown, out)),
+            #[cfg(unix)]
+            Mode::Unix { path } => Ok(unix::statsd_unix(
+                path,
+                self.max_length,
+                self.split_namespace,
+                shutdown,
+                out,
+            )),
+            #[cfg(unix)]
+            Mode::UnixDatagram { path } => Ok(unix::statsd_unix_datagram(
+                path,
+                self.max_length,
+                self.split_namespace,
+                shutdown,
+                out,
+            )),
+        }
     }
 
     fn output_type(&self) -> crate::topology::config::DataType {
@@ -40,7 +160,39 @@ impl crate::topology::config::SourceConfig for StatsdConfig {
     }
 }
 
-fn statsd(addr: SocketAddr, shutdown: ShutdownSignal, out: mpsc::Sender<Event>) -> super::Source {
+/// Parses a single line received on any of the listeners.
+fn parse_event(line: &str, split_namespace: bool) -> Option<Event> {
+    if line.trim().is_empty() {
+        return None;
+    }
+
+    parse(line)
+        .map_err(|error| error!("{}", error))
+        .ok()
+        .map(|metric: Metric| {
+            if split_namespace {
+                metric.split_namespace('.')
+            } else {
+                metric
+            }
+        })
+        .map(Event::Metric)
+}
+
+/// Parses the lines of a single datagram.
+fn parse_packet(packet: &[u8], split_namespace: bool) -> Vec<Event> {
+    String::from_utf8_lossy(packet)
+        .lines()
+        .filter_map(|line| parse_event(line, split_namespace))
+        .collect()
+}
+
+fn statsd(
+    addr: SocketAddr,
+    split_namespace: bool,
+    shutdown: ShutdownSignal,
+    out: mpsc::Sender<Event>,
+) -> super::Source {
     let out = out.sink_map_err(|e| error!("error sending metric: {:?}", e));
 
     Box::new(
@@ -58,14 +210,8 @@ fn statsd(addr: SocketAddr, shutdown: ShutdownSignal, out: mpsc::Sender<Event>)
         .and_then(move |socket| {
             let metrics_in = UdpFramed::new(socket, BytesCodec::new())
                 .take_until(shutdown)
-                .map(|(bytes, _sock)| {
-                    let packet = String::from_utf8_lossy(bytes.as_ref());
-                    let metrics = packet
-                        .lines()
-                        .map(parse)
-                        .filter_map(|res| res.map_err(|e| error!("{}", e)).ok())
-                        .map(Event::Metric)
-                        .collect::<Vec<_>>();
+                .map(move |(bytes, _sock)| {
+                    let metrics = parse_packet(bytes.as_ref(), split_namespace);
                     futures01::stream::iter_ok::<_, std::io::Error>(metrics)
                 })
                 .flatten()
@@ -76,19 +222,22 @@ fn statsd(addr: SocketAddr, shutdown: ShutdownSignal, out: mpsc::Sender<Event>)
     )
 }
 
-#[cfg(feature = "sinks-prometheus")]
 #[cfg(test)]
 mod test {
-    use super::StatsdConfig;
-    use crate::{
-        sinks::prometheus::PrometheusSinkConfig,
-        test_util::{block_on, next_addr, runtime, shutdown_on_idle},
-        topology::{self, config},
+    use super::{Mode, StatsdConfig};
+    #[cfg(feature = "sinks-prometheus")]
+    use {
+        crate::{
+            sinks::prometheus::{default_summary_quantiles, PrometheusSinkConfig},
+            test_util::{block_on, next_addr, runtime, shutdown_on_idle},
+            topology::{self, config},
+        },
+        futures::{TryFutureExt, TryStreamExt},
+        futures01::Stream,
+        std::{thread, time::Duration},
     };
-    use futures::{TryFutureExt, TryStreamExt};
-    use futures01::Stream;
-    use std::{thread, time::Duration};
 
+    #[cfg(feature = "sinks-prometheus")]
     fn parse_count(lines: &[&str], prefix: &str) -> usize {
         lines
             .iter()
@@ -99,13 +248,14 @@ mod test {
             .unwrap()
     }
 
+    #[cfg(feature = "sinks-prometheus")]
     #[test]
     fn test_statsd() {
         let in_addr = next_addr();
         let out_addr = next_addr();
 
         let mut config = config::Config::empty();
-        config.add_source("in", StatsdConfig { address: in_addr });
+        config.add_source("in", StatsdConfig::new(Mode::Udp { address: in_addr }));
         config.add_sink(
             "out",
             &["in"],
@@ -114,6 +264,10 @@ mod test {
                 namespace: "vector".into(),
                 buckets: vec![1.0, 2.0, 4.0],
                 flush_period_secs: 1,
+                quantiles: default_summary_quantiles(),
+                sketch_relative_accuracy: None,
+                help: Default::default(),
+                tls: None,
             },
         );
 
@@ -216,8 +370,8 @@ mod test {
                 .lines()
                 .collect::<Vec<_>>();
 
-            // Check rested
-            assert_eq!(parse_count(&lines, "vector_set"), 0);
+            // Check expired
+            assert!(!lines.iter().any(|line| line.starts_with("vector_set")));
 
             // Recheck that set is also reseted------------
 
@@ -256,4 +410,30 @@ mod test {
         block_on(topology.stop()).unwrap();
         shutdown_on_idle(rt);
     }
+
+    #[test]
+    fn config_defaults_to_udp() {
+        let config: StatsdConfig = toml::from_str(
+            r#"
+            address = "127.0.0.1:8125"
+            split_namespace = true
+            "#,
+        )
+        .unwrap();
+
+        assert!(matches!(config.mode, Mode::Udp { .. }));
+        assert!(config.split_namespace);
+
+        let config: StatsdConfig = toml::from_str(
+            r#"
+            mode = "tcp"
+            address = "127.0.0.1:8125"
+            "#,
+        )
+        .unwrap();
+
+        assert!(matches!(config.mode, Mode::Tcp { .. }));
+        assert!(!config.split_namespace);
+        assert_eq!(config.max_length, 102400);
+    }
 }
@@ -58,6 +58,7 @@ pub fn parse(packet: &str) -> Result<Metric, ParseError> {
             let val: f64 = parts[0].parse()?;
             Metric {
                 name,
+                namespace: None,
                 timestamp: None,
                 tags,
                 kind: MetricKind::Incremental,
@@ -70,6 +71,7 @@ pub fn parse(packet: &str) -> Result<Metric, ParseError> {
             let val: f64 = parts[0].parse()?;
             Metric {
                 name,
+                namespace: None,
                 timestamp: None,
                 tags,
                 kind: MetricKind::Incremental,
@@ -94,6 +96,7 @@ pub fn parse(packet: &str) -> Result<Metric, ParseError> {
             match parse_direction(parts[0])? {
                 None => Metric {
                     name,
+                    namespace: None,
                     timestamp: None,
                     tags,
                     kind: MetricKind::Absolute,
@@ -101,6 +104,7 @@ pub fn parse(packet: &str) -> Result<Metric, ParseError> {
                 },
                 Some(sign) => Metric {
                     name,
+                    namespace: None,
                     timestamp: None,
                     tags,
                     kind: MetricKind::Incremental,
@@ -112,6 +116,7 @@ pub fn parse(packet: &str) -> Result<Metric, ParseError> {
         }
         "s" => Metric {
             name,
+            namespace: None,
             timestamp: None,
             tags,
             kind: MetricKind::Incremental,
@@ -236,6 +241,7 @@ mod test {
             parse("foo:1|c"),
             Ok(Metric {
                 name: "foo".into(),
+                namespace: None,
                 timestamp: None,
                 tags: None,
                 kind: MetricKind::Incremental,
@@ -250,6 +256,7 @@ mod test {
             parse("foo:1|c|#tag1,tag2:value"),
             Ok(Metric {
                 name: "foo".into(),
+                namespace: None,
                 timestamp: None,
                 tags: Some(
                     vec![
@@ -271,6 +278,7 @@ mod test {
             parse("bar:2|c|@0.1"),
             Ok(Metric {
                 name: "bar".into(),
+                namespace: None,
                 timestamp: None,
                 tags: None,
                 kind: MetricKind::Incremental,
@@ -285,6 +293,7 @@ mod test {
             parse("bar:2|c|@0"),
             Ok(Metric {
                 name: "bar".into(),
+                namespace: None,
                 timestamp: None,
                 tags: None,
                 kind: MetricKind::Incremental,
@@ -299,6 +308,7 @@ mod test {
             parse("glork:320|ms|@0.1"),
             Ok(Metric {
                 name: "glork".into(),
+                namespace: None,
                 timestamp: None,
                 tags: None,
                 kind: MetricKind::Incremental,
@@ -316,6 +326,7 @@ mod test {
             parse("glork:320|h|@0.1|#region:us-west1,production,e:"),
             Ok(Metric {
                 name: "glork".into(),
+                namespace: None,
                 timestamp: None,
                 tags: Some(
                     vec![
@@ -341,6 +352,7 @@ mod test {
             parse("gaugor:333|g"),
             Ok(Metric {
                 name: "gaugor".into(),
+                namespace: None,
                 timestamp: None,
                 tags: None,
                 kind: MetricKind::Absolute,
@@ -355,6 +367,7 @@ mod test {
             parse("gaugor:-4|g"),
             Ok(Metric {
                 name: "gaugor".into(),
+                namespace: None,
                 timestamp: None,
                 tags: None,
                 kind: MetricKind::Incremental,
@@ -365,6 +378,7 @@ mod test {
             parse("gaugor:+10|g"),
             Ok(Metric {
                 name: "gaugor".into(),
+                namespace: None,
                 timestamp: None,
                 tags: None,
                 kind: MetricKind::Incremental,
@@ -379,6 +393,7 @@ mod test {
             parse("uniques:765|s"),
             Ok(Metric {
                 name: "uniques".into(),
+                namespace: None,
                 timestamp: None,
                 tags: None,
                 kind: MetricKind::Incremental,
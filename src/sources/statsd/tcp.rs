@@ -0,0 +1,92 @@
+use super::parse_event;
+use crate::{event::Event, sources::util::TcpSource};
+use bytes::Bytes;
+use codec01::BytesDelimitedCodec;
+
+#[derive(Debug, Clone)]
+pub struct StatsdTcpSource {
+    pub max_length: usize,
+    pub split_namespace: bool,
+}
+
+impl TcpSource for StatsdTcpSource {
+    type Decoder = BytesDelimitedCodec;
+
+    fn decoder(&self) -> Self::Decoder {
+        BytesDelimitedCodec::new_with_max_length(b'\n', self.max_length)
+    }
+
+    fn build_event(&self, frame: Bytes, _host: Bytes) -> Option<Event> {
+        let line = String::from_utf8_lossy(&frame);
+        parse_event(&line, self.split_namespace)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Mode, StatsdConfig};
+    use crate::{
+        event::metric::{Metric, MetricKind, MetricValue},
+        shutdown::ShutdownSignal,
+        test_util::{collect_n, next_addr, runtime, send_lines, wait_for_tcp},
+        topology::config::{GlobalOptions, SourceConfig},
+        Event,
+    };
+    use futures01::sync::mpsc;
+
+    #[test]
+    fn tcp_parses_lines() {
+        let (tx, rx) = mpsc::channel(10);
+        let addr = next_addr();
+
+        let mut config = StatsdConfig::new(Mode::Tcp {
+            address: addr.into(),
+            tls: None,
+            shutdown_timeout_secs: 30,
+        });
+        config.max_length = 20;
+
+        let server = config
+            .build(
+                "default",
+                &GlobalOptions::default(),
+                ShutdownSignal::noop(),
+                tx,
+            )
+            .unwrap();
+        let mut rt = runtime();
+        rt.spawn(server);
+        wait_for_tcp(addr);
+
+        let lines = vec![
+            "foo:1|c".to_owned(),
+            "this_line_is_too_long:1|c".to_owned(),
+            "".to_owned(),
+            "bar:2|g".to_owned(),
+        ];
+        rt.block_on(send_lines(addr, lines.into_iter())).unwrap();
+
+        let events = rt.block_on(collect_n(rx, 2)).unwrap();
+        assert_eq!(
+            events,
+            vec![
+                Event::Metric(Metric {
+                    name: "foo".into(),
+                    namespace: None,
+                    timestamp: None,
+                    tags: None,
+                    kind: MetricKind::Incremental,
+                    value: MetricValue::Counter { value: 1.0 },
+                }),
+                Event::Metric(Metric {
+                    name: "bar".into(),
+                    namespace: None,
+                    timestamp: None,
+                    tags: None,
+                    kind: MetricKind::Absolute,
+                    value: MetricValue::Gauge { value: 2.0 },
+                }),
+            ]
+        );
+    }
+}
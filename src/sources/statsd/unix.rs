@@ -0,0 +1,156 @@
+use super::{parse_event, parse_packet};
+use crate::{
+    event::Event,
+    shutdown::ShutdownSignal,
+    sources::{util::build_unix_source, Source},
+    stream::StreamExt01,
+};
+use futures01::{future, stream, sync::mpsc, try_ready, Async, Future, Sink, Stream};
+use std::path::PathBuf;
+use tokio01::codec::LinesCodec;
+use tokio_uds::UnixDatagram;
+
+pub fn statsd_unix(
+    path: PathBuf,
+    max_length: usize,
+    split_namespace: bool,
+    shutdown: ShutdownSignal,
+    out: mpsc::Sender<Event>,
+) -> Source {
+    // Metrics carry no host, so the host key and peer path are unused.
+    build_unix_source(
+        path,
+        LinesCodec::new_with_max_length(max_length),
+        String::new(),
+        shutdown,
+        out,
+        move |_host_key, _received_from, line: String| parse_event(&line, split_namespace),
+    )
+}
+
+/// Receives datagrams of newline delimited metrics, each truncated to
+/// `max_length` bytes.
+pub fn statsd_unix_datagram(
+    path: PathBuf,
+    max_length: usize,
+    split_namespace: bool,
+    shutdown: ShutdownSignal,
+    out: mpsc::Sender<Event>,
+) -> Source {
+    let out = out.sink_map_err(|e| error!("error sending metric: {:?}", e));
+
+    Box::new(
+        future::lazy(move || {
+            let socket = UnixDatagram::bind(&path).expect("failed to bind to unix datagram socket");
+
+            info!(message = "listening.", ?path, r#type = "unix_datagram");
+
+            future::ok(socket)
+        })
+        .and_then(move |socket| {
+            let mut buf = vec![0; max_length];
+            let datagrams = stream::poll_fn(move || {
+                let size = try_ready!(socket.poll_recv(&mut buf));
+                Ok(Async::Ready(Some(parse_packet(
+                    &buf[..size],
+                    split_namespace,
+                ))))
+            });
+
+            datagrams
+                .take_until(shutdown)
+                .map(stream::iter_ok::<_, std::io::Error>)
+                .flatten()
+                .map_err(|e| error!("error reading datagram: {:?}", e))
+                .forward(out)
+                .map(|_| info!("finished sending"))
+        }),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Mode, StatsdConfig};
+    use crate::{
+        event::metric::{Metric, MetricKind, MetricValue},
+        shutdown::ShutdownSignal,
+        test_util::{collect_n, runtime},
+        topology::config::{GlobalOptions, SourceConfig},
+        Event,
+    };
+    use futures01::{sync::mpsc, Future, Sink, Stream};
+    use std::os::unix::net::UnixDatagram;
+    use tokio01::codec::{FramedWrite, LinesCodec};
+    use tokio_uds::UnixStream;
+
+    fn counter(name: &str, value: f64) -> Event {
+        Event::Metric(Metric {
+            name: name.into(),
+            namespace: None,
+            timestamp: None,
+            tags: None,
+            kind: MetricKind::Incremental,
+            value: MetricValue::Counter { value },
+        })
+    }
+
+    #[test]
+    fn unix_parses_lines() {
+        let (tx, rx) = mpsc::channel(10);
+        let path = tempfile::tempdir().unwrap().into_path().join("statsd_test");
+
+        let server = StatsdConfig::new(Mode::Unix { path: path.clone() })
+            .build(
+                "default",
+                &GlobalOptions::default(),
+                ShutdownSignal::noop(),
+                tx,
+            )
+            .unwrap();
+        let mut rt = runtime();
+        rt.spawn(server);
+
+        // Wait for server to accept traffic
+        while std::os::unix::net::UnixStream::connect(&path).is_err() {}
+
+        let lines = futures01::stream::iter_ok::<_, ()>(vec!["foo:1|c".to_owned()]);
+        UnixStream::connect(&path)
+            .map_err(|e| panic!("{:}", e))
+            .and_then(|socket| {
+                let out =
+                    FramedWrite::new(socket, LinesCodec::new()).sink_map_err(|e| panic!("{:?}", e));
+                lines.forward(out).map(|_| ())
+            })
+            .wait()
+            .unwrap();
+
+        let events = rt.block_on(collect_n(rx, 1)).unwrap();
+        assert_eq!(events, vec![counter("foo", 1.0)]);
+    }
+
+    #[test]
+    fn unix_datagram_parses_packets() {
+        let (tx, rx) = mpsc::channel(10);
+        let path = tempfile::tempdir().unwrap().into_path().join("statsd_test");
+
+        let server = StatsdConfig::new(Mode::UnixDatagram { path: path.clone() })
+            .build(
+                "default",
+                &GlobalOptions::default(),
+                ShutdownSignal::noop(),
+                tx,
+            )
+            .unwrap();
+        let mut rt = runtime();
+        rt.spawn(server);
+
+        // Wait for server to bind the socket
+        while !path.exists() {}
+
+        let socket = UnixDatagram::unbound().unwrap();
+        socket.send_to(b"foo:1|c\nbar:2|c", &path).unwrap();
+
+        let events = rt.block_on(collect_n(rx, 2)).unwrap();
+        assert_eq!(events, vec![counter("foo", 1.0), counter("bar", 2.0)]);
+    }
+}
@@ -1,17 +1,23 @@
 use crate::{
+    codecs::{
+        decoding::{Codec, DecodingConfig},
+        framing::Framer,
+    },
     event::{self, Event},
+    internal_events::DecoderFramingFailed,
     shutdown::ShutdownSignal,
     stream::StreamExt01,
     topology::config::{DataType, GlobalOptions, SourceConfig, SourceDescription},
 };
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
 use futures::compat::Compat;
-use futures01::{sync::mpsc, Future, Sink, Stream};
+use futures01::{stream, sync::mpsc, Future, Sink, Stream};
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use snafu::Snafu;
 use std::{io, sync::Mutex, thread};
-use tokio::sync::broadcast::{channel, Sender};
+use tokio::sync::broadcast::{channel, RecvError, Sender};
+use tokio01::codec::Decoder;
 
 #[derive(Debug, Snafu)]
 enum BuildError {
@@ -29,6 +35,7 @@ pub struct StdinConfig {
     #[serde(default = "default_max_length")]
     pub max_length: usize,
     pub host_key: Option<String>,
+    pub decoding: Option<DecodingConfig>,
 }
 
 impl Default for StdinConfig {
@@ -36,6 +43,7 @@ impl Default for StdinConfig {
         StdinConfig {
             max_length: default_max_length(),
             host_key: None,
+            decoding: None,
         }
     }
 }
@@ -70,7 +78,7 @@ impl SourceConfig for StdinConfig {
 }
 
 pub fn stdin_source<R>(
-    stdin: R,
+    mut stdin: R,
     config: StdinConfig,
     shutdown: ShutdownSignal,
     out: mpsc::Sender<Event>,
@@ -79,7 +87,8 @@ where
     R: Send + io::BufRead + 'static,
 {
     // The idea is to have one dedicated future for reading stdin running in the background,
-    // and the sources would recieve the lines thorugh a multi consumer channel.
+    // and the sources would recieve the chunks read thorugh a multi consumer channel, each
+    // splitting them into frames on its own.
     //
     // Implemented solution relies on having a copy of optional sender behind a global mutex,
     // and have stdin sources and background thread synchronize on it.
@@ -108,6 +117,10 @@ where
         .host_key
         .unwrap_or_else(|| event::log_schema().host_key().to_string());
     let hostname = hostname::get_hostname();
+    let decoding = config.decoding.unwrap_or_default();
+    let mut framer = decoding.framer(config.max_length)?;
+    let codec = decoding.codec;
+    let mut buffer = BytesMut::new();
 
     let mut guard = CRITICAL_SECTION
         .lock()
@@ -122,30 +135,33 @@ where
             thread::spawn(move || {
                 info!("Capturing STDIN.");
 
-                for line in stdin.lines() {
-                    match line {
+                loop {
+                    let chunk = match stdin.fill_buf() {
+                        Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
                         Err(e) => {
                             error!(message = "Unable to read from source.", error = %e);
                             break;
                         }
-                        Ok(line) => {
-                            if sender.send(Bytes::from(line)).is_err() {
-                                // There are no active receivers.
-                                // Try to stop.
-                                let mut guard =
-                                    CRITICAL_SECTION.lock().expect("CRITICAL_SECTION poisoned");
-
-                                if sender.receiver_count() == 0 {
-                                    guard.take();
-                                    return;
-                                }
-
-                                // A new receiver has shown up.
-
-                                // It's fine not to resend the line since it came from
-                                // before this new receiver has shown up.
-                            }
+                        // End of file.
+                        Ok(buf) if buf.is_empty() => break,
+                        Ok(buf) => Bytes::from(buf),
+                    };
+                    stdin.consume(chunk.len());
+
+                    if sender.send(chunk).is_err() {
+                        // There are no active receivers.
+                        // Try to stop.
+                        let mut guard = CRITICAL_SECTION.lock().expect("CRITICAL_SECTION poisoned");
+
+                        if sender.receiver_count() == 0 {
+                            guard.take();
+                            return;
                         }
+
+                        // A new receiver has shown up.
+
+                        // It's fine not to resend the chunk since it came from
+                        // before this new receiver has shown up.
                     }
                 }
 
@@ -163,7 +179,21 @@ where
     Ok(Box::new(
         Compat::new(receiver)
             .take_until(shutdown)
-            .map(move |line| create_event(line, &host_key, &hostname))
+            .map(Some)
+            // Flush the last frame at the end of the input.
+            .chain(stream::once(Ok(None)))
+            .map(move |chunk| {
+                let frames = match chunk {
+                    Some(chunk) => {
+                        buffer.extend_from_slice(&chunk);
+                        split_frames(&mut framer, &mut buffer, false)
+                    }
+                    None => split_frames(&mut framer, &mut buffer, true),
+                };
+                stream::iter_ok::<_, RecvError>(frames)
+            })
+            .flatten()
+            .filter_map(move |frame| create_event(codec, frame, &host_key, &hostname))
             .map_err(|e| error!("error reading line: {:?}", e))
             .forward(
                 out.sink_map_err(|e| error!(message = "Unable to send event to out.", error = %e)),
@@ -172,8 +202,39 @@ where
     ))
 }
 
-fn create_event(line: Bytes, host_key: &str, hostname: &Option<String>) -> Event {
-    let mut event = Event::from(line);
+/// Splits the buffered input into frames, including its remainder at the end
+/// of the input.
+fn split_frames(framer: &mut Framer, buffer: &mut BytesMut, eof: bool) -> Vec<Bytes> {
+    let mut frames = Vec::new();
+    loop {
+        let frame = if eof {
+            framer.decode_eof(buffer)
+        } else {
+            framer.decode(buffer)
+        };
+        match frame {
+            Ok(Some(frame)) => frames.push(frame),
+            Ok(None) => break,
+            Err(error) => {
+                emit!(DecoderFramingFailed {
+                    source_type: "stdin",
+                    error: &error,
+                });
+                buffer.clear();
+                break;
+            }
+        }
+    }
+    frames
+}
+
+fn create_event(
+    codec: Codec,
+    frame: Bytes,
+    host_key: &str,
+    hostname: &Option<String>,
+) -> Option<Event> {
+    let mut event = codec.try_decode(frame, "stdin")?;
 
     // Add source type
     event
@@ -184,7 +245,7 @@ fn create_event(line: Bytes, host_key: &str, hostname: &Option<String>) -> Event
         event.as_mut_log().insert(host_key, hostname.clone());
     }
 
-    event
+    Some(event)
 }
 
 #[cfg(test)]
@@ -201,7 +262,7 @@ mod tests {
         let host_key = "host".to_string();
         let hostname = Some("Some.Machine".to_string());
 
-        let event = create_event(line, &host_key, &hostname);
+        let event = create_event(Codec::Bytes, line, &host_key, &hostname).unwrap();
         let log = event.into_log();
 
         assert_eq!(log[&"host".into()], "Some.Machine".into());
@@ -2,6 +2,7 @@ use super::util::{SocketListenAddr, TcpSource};
 #[cfg(unix)]
 use crate::sources::util::build_unix_source;
 use crate::{
+    codecs::decoding,
     event::{self, Event, Value},
     internal_events::{SyslogEventReceived, SyslogUdpReadError},
     shutdown::ShutdownSignal,
@@ -10,7 +11,7 @@ use crate::{
     topology::config::{DataType, GlobalOptions, SourceConfig, SourceDescription},
 };
 use bytes::{Bytes, BytesMut};
-use chrono::{Datelike, Utc};
+use chrono::Utc;
 use derive_is_enum_variant::is_enum_variant;
 use futures01::{future, sync::mpsc, Future, Sink, Stream};
 use serde::{Deserialize, Serialize};
@@ -18,7 +19,7 @@ use std::io;
 use std::net::SocketAddr;
 #[cfg(unix)]
 use std::path::PathBuf;
-use syslog_loose::{self, IncompleteDate, Message, ProcId, Protocol};
+use string_cache::DefaultAtom as Atom;
 use tokio01::{
     self,
     codec::{BytesCodec, Decoder, LinesCodec},
@@ -104,7 +105,9 @@ impl SourceConfig for SyslogConfig {
                 host_key,
                 shutdown,
                 out,
-                event_from_str,
+                |host_key, default_host, line: String| {
+                    event_from_str(host_key, default_host, &line)
+                },
             )),
         }
     }
@@ -284,18 +287,6 @@ pub fn udp(
     )
 }
 
-/// Function used to resolve the year for syslog messages that don't include the year.
-/// If the current month is January, and the syslog message is for December, it will take the previous year.
-/// Otherwise, take the current year.
-fn resolve_year((month, _date, _hour, _min, _sec): IncompleteDate) -> i32 {
-    let now = Utc::now();
-    if now.month() == 1 && month == 12 {
-        now.year() - 1
-    } else {
-        now.year()
-    }
-}
-
 /**
 * Function to pass to build_unix_source, specific to the Unix mode of the syslog source.
 * Handles the logic of parsing and decoding the syslog message format.
@@ -308,32 +299,23 @@ fn event_from_str(host_key: &str, default_host: Option<Bytes>, line: &str) -> Op
         byte_size: line.len()
     });
 
-    let line = line.trim();
-    let parsed = syslog_loose::parse_message_with_year(line, resolve_year);
-    let mut event = Event::from(&parsed.msg[..]);
+    let mut log = decoding::parse_syslog(line);
 
     // Add source type
-    event
-        .as_mut_log()
-        .insert(event::log_schema().source_type_key(), "syslog");
+    log.insert(event::log_schema().source_type_key(), "syslog");
 
     if let Some(default_host) = default_host.clone() {
-        event.as_mut_log().insert("source_ip", default_host);
+        log.insert("source_ip", default_host);
     }
 
-    if let Some(parsed_host) = parsed.hostname.map(Bytes::from).or(default_host) {
-        event.as_mut_log().insert(host_key, parsed_host);
+    let parsed_host = log.get(&Atom::from("hostname")).cloned();
+    if let Some(host) = parsed_host.or_else(|| default_host.map(Value::from)) {
+        log.insert(host_key, host);
     }
 
-    let timestamp = parsed
-        .timestamp
-        .map(|ts| ts.into())
-        .unwrap_or_else(Utc::now);
-    event
-        .as_mut_log()
-        .insert(event::log_schema().timestamp_key().clone(), timestamp);
+    log.try_insert(event::log_schema().timestamp_key(), Utc::now());
 
-    insert_fields_from_syslog(&mut event, parsed);
+    let event = Event::from(log);
 
     trace!(
         message = "processing one event.",
@@ -343,43 +325,6 @@ fn event_from_str(host_key: &str, default_host: Option<Bytes>, line: &str) -> Op
     Some(event)
 }
 
-fn insert_fields_from_syslog(event: &mut Event, parsed: Message<&str>) {
-    let log = event.as_mut_log();
-
-    if let Some(host) = parsed.hostname {
-        log.insert("hostname", host);
-    }
-    if let Some(severity) = parsed.severity {
-        log.insert("severity", severity.as_str());
-    }
-    if let Some(facility) = parsed.facility {
-        log.insert("facility", facility.as_str());
-    }
-    if let Protocol::RFC5424(version) = parsed.protocol {
-        log.insert("version", version as i64);
-    }
-    if let Some(app_name) = parsed.appname {
-        log.insert("appname", app_name);
-    }
-    if let Some(msg_id) = parsed.msgid {
-        log.insert("msgid", msg_id);
-    }
-    if let Some(procid) = parsed.procid {
-        let value: Value = match procid {
-            ProcId::PID(pid) => pid.into(),
-            ProcId::Name(name) => name.into(),
-        };
-        log.insert("procid", value);
-    }
-
-    for element in parsed.structured_data.iter() {
-        for (name, value) in element.params.iter() {
-            let key = format!("{}.{}", element.id, name);
-            log.insert(key, *value);
-        }
-    }
-}
-
 #[cfg(test)]
 mod test {
     use super::{event_from_str, SyslogConfig};
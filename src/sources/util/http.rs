@@ -1,4 +1,4 @@
-use crate::event::Event;
+use crate::event::{BatchNotifier, BatchStatus, Event};
 use crate::{
     shutdown::ShutdownSignal,
     tls::{MaybeTlsSettings, TlsConfig},
@@ -12,9 +12,10 @@ use serde::Serialize;
 use std::error::Error;
 use std::fmt;
 use std::net::SocketAddr;
+use std::sync::Arc;
 use tokio_util::compat::FuturesAsyncReadCompatExt;
 use warp::{
-    filters::BoxedFilter,
+    filters::path::FullPath,
     http::{HeaderMap, StatusCode},
     reject::Rejection,
     Filter,
@@ -50,52 +51,75 @@ impl fmt::Debug for RejectShuttingDown {
 impl warp::reject::Reject for RejectShuttingDown {}
 
 pub trait HttpSource: Clone + Send + Sync + 'static {
+    /// Builds the events of a request to `path`, one of the paths the source
+    /// is run with.
     fn build_event(
         &self,
         body: bytes05::Bytes,
         header_map: HeaderMap,
+        path: &str,
     ) -> Result<Vec<Event>, ErrorMessage>;
 
+    /// With `acknowledgements`, requests are only answered once their events
+    /// are delivered, and with an error if a sink rejected them.
     fn run(
         self,
         address: SocketAddr,
-        path: &'static str,
+        paths: &'static [&'static str],
         tls: &Option<TlsConfig>,
+        acknowledgements: bool,
         out: mpsc::Sender<Event>,
         shutdown: ShutdownSignal,
     ) -> crate::Result<crate::sources::Source> {
-        let mut filter: BoxedFilter<()> = warp::post().boxed();
-        if !path.is_empty() && path != "/" {
-            for s in path.split('/') {
-                filter = filter.and(warp::path(s)).boxed();
-            }
-        }
-        let svc = filter
-            .and(warp::path::end())
+        let svc = warp::post()
+            .and(warp::path::full())
             .and(warp::header::headers_cloned())
             .and(warp::body::bytes())
-            .and_then(move |headers: HeaderMap, body| {
+            .and_then(move |full_path: FullPath, headers: HeaderMap, body| {
                 info!("Handling http request: {:?}", headers);
 
                 let this = self.clone();
                 let out = out.clone();
 
                 async move {
-                    match this.build_event(body, headers) {
-                        Ok(events) => {
-                            out.send_all(futures01::stream::iter_ok(events))
-                                .compat()
-                                .map_err(move |e: mpsc::SendError<Event>| {
-                                    // can only fail if receiving end disconnected, so we are shuting down,
-                                    // probably not gracefully.
-                                    error!("Failed to forward events, downstream is closed");
-                                    error!("Tried to send the following event: {:?}", e);
-                                    warp::reject::custom(RejectShuttingDown)
-                                })
-                                .map_ok(|_| warp::reply())
-                                .await
+                    let path = paths
+                        .iter()
+                        .find(|path| same_path(path, full_path.as_str()))
+                        .ok_or_else(warp::reject::not_found)?;
+
+                    let mut events = this
+                        .build_event(body, headers, path)
+                        .map_err(warp::reject::custom)?;
+
+                    let receiver = if acknowledgements {
+                        let (notifier, receiver) = BatchNotifier::new_with_receiver();
+                        for event in &mut events {
+                            event.add_batch_notifier(Arc::clone(&notifier));
                         }
-                        Err(err) => Err(warp::reject::custom(err)),
+                        Some(receiver)
+                    } else {
+                        None
+                    };
+
+                    out.send_all(futures01::stream::iter_ok(events))
+                        .compat()
+                        .map_err(move |e: mpsc::SendError<Event>| {
+                            // can only fail if receiving end disconnected, so we are shuting down,
+                            // probably not gracefully.
+                            error!("Failed to forward events, downstream is closed");
+                            error!("Tried to send the following event: {:?}", e);
+                            warp::reject::custom(RejectShuttingDown)
+                        })
+                        .await?;
+
+                    match receiver {
+                        Some(receiver) if receiver.await == BatchStatus::Failed => {
+                            Err(warp::reject::custom(ErrorMessage::new(
+                                StatusCode::INTERNAL_SERVER_ERROR,
+                                "Events were not delivered".into(),
+                            )))
+                        }
+                        _ => Ok(warp::reply()),
                     }
                 }
             });
@@ -133,3 +157,9 @@ pub trait HttpSource: Clone + Send + Sync + 'static {
         Ok(Box::new(fut.boxed().compat()))
     }
 }
+
+/// Compares paths ignoring their leading and trailing slashes, so an empty
+/// path is the root.
+fn same_path(a: &str, b: &str) -> bool {
+    a.trim_matches('/') == b.trim_matches('/')
+}
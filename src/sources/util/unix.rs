@@ -18,15 +18,13 @@ use tracing_futures::Instrument;
 * for build_event can allow for different source-specific logic (such as decoding syslog messages
 * in the syslog source).
 **/
-pub fn build_unix_source<
-    D: Decoder<Item = String, Error = std::io::Error> + Clone + Send + 'static,
->(
+pub fn build_unix_source<D: Decoder<Error = std::io::Error> + Clone + Send + 'static>(
     path: PathBuf,
     decoder: D,
     host_key: String,
     shutdown: ShutdownSignal,
     out: mpsc::Sender<Event>,
-    build_event: impl Fn(&str, Option<Bytes>, &str) -> Option<Event>
+    build_event: impl Fn(&str, Option<Bytes>, D::Item) -> Option<Event>
         + std::marker::Send
         + std::marker::Sync
         + std::clone::Clone
@@ -66,8 +64,8 @@ pub fn build_unix_source<
                     path.map(|p| p.to_string_lossy().into_owned().into());
                 let lines_in =
                     FramedRead::new(socket.allow_read_until(shutdown.clone()), decoder.clone())
-                        .filter_map(move |line| {
-                            build_event(&host_key, received_from.clone(), &line)
+                        .filter_map(move |frame| {
+                            build_event(&host_key, received_from.clone(), frame)
                         })
                         .map_err(move |error| {
                             emit!(UnixSocketError {
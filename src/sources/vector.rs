@@ -1,7 +1,7 @@
 use super::util::{SocketListenAddr, TcpSource};
 use crate::{
     event::proto,
-    internal_events::{VectorEventReceived, VectorProtoDecodeError},
+    internal_events::{VectorEventReceived, VectorInvalidEvent, VectorProtoDecodeError},
     shutdown::ShutdownSignal,
     tls::{MaybeTlsSettings, TlsConfig},
     topology::config::{DataType, GlobalOptions, SourceConfig, SourceDescription},
@@ -11,6 +11,7 @@ use bytes::{Bytes, BytesMut};
 use futures01::sync::mpsc;
 use prost::Message;
 use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
 use tokio01::codec::LengthDelimitedCodec;
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -78,13 +79,20 @@ impl TcpSource for VectorSource {
         let frame = bytes05::Bytes::copy_from_slice(&frame);
 
         let byte_size = frame.len();
-        match proto::EventWrapper::decode(frame).map(Event::from) {
+        let proto = match proto::EventWrapper::decode(frame) {
+            Ok(proto) => proto,
+            Err(error) => {
+                emit!(VectorProtoDecodeError { error });
+                return None;
+            }
+        };
+        match Event::try_from(proto) {
             Ok(event) => {
                 emit!(VectorEventReceived { byte_size });
                 Some(event)
             }
             Err(error) => {
-                emit!(VectorProtoDecodeError { error });
+                emit!(VectorInvalidEvent { error });
                 None
             }
         }
@@ -139,6 +147,7 @@ mod test {
             Event::from("source"),
             Event::Metric(Metric {
                 name: String::from("also test a metric"),
+                namespace: None,
                 timestamp: None,
                 tags: None,
                 kind: MetricKind::Absolute,
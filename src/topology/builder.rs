@@ -1,10 +1,16 @@
 use super::{
     config::{DataType, SinkContext, TransformContext},
-    fanout::{self, Fanout},
+    fanout::{self, Fanout, Outputs},
     task::Task,
     ConfigDiff,
 };
-use crate::{buffers, dns::Resolver, event::Event, shutdown::SourceShutdownCoordinator};
+use crate::{
+    buffers,
+    dns::Resolver,
+    event::Event,
+    shutdown::SourceShutdownCoordinator,
+    sinks::util::dead_letter::{self, DeadLetter},
+};
 use futures01::{
     future::{lazy, Either},
     sync::mpsc,
@@ -16,6 +22,8 @@ use tokio01::util::FutureExt;
 pub struct Pieces {
     pub inputs: HashMap<String, (buffers::BufferInputCloner, Vec<String>)>,
     pub outputs: HashMap<String, fanout::ControlChannel>,
+    /// Names of the `outputs` each component has besides its primary one.
+    pub secondary_outputs: HashMap<String, Vec<String>>,
     pub tasks: HashMap<String, Task>,
     pub source_tasks: HashMap<String, Task>,
     pub healthchecks: HashMap<String, Task>,
@@ -45,6 +53,16 @@ pub fn check(config: &super::Config) -> Result<Vec<String>, Vec<String>> {
         errors.push("No sinks defined in the config.".to_owned());
     }
 
+    for (name, sink) in config.sinks.iter() {
+        if sink.dead_letter && !sink.inner.supports_dead_letter() {
+            errors.push(format!(
+                "Sink {:?} of type {:?} doesn't support `dead_letter`.",
+                name,
+                sink.inner.sink_type()
+            ));
+        }
+    }
+
     // Warnings and errors
     let sink_inputs = config
         .sinks
@@ -64,11 +82,19 @@ pub fn check(config: &super::Config) -> Result<Vec<String>, Vec<String>> {
         }
 
         for input in inputs {
-            if !config.sources.contains_key(&input) && !config.transforms.contains_key(&input) {
-                errors.push(format!(
+            if config.output_exists(&input) {
+                continue;
+            }
+
+            match dead_letter::sink_name(&input).filter(|sink| config.sinks.contains_key(*sink)) {
+                Some(sink) => errors.push(format!(
+                    "Input {:?} for {} {:?} doesn't exist; set `dead_letter = true` on sink {:?} to expose it.",
+                    input, output_type, name, sink
+                )),
+                None => errors.push(format!(
                     "Input {:?} for {} {:?} doesn't exist.",
                     input, output_type, name
-                ));
+                )),
             }
         }
     }
@@ -114,6 +140,7 @@ pub async fn build_pieces(
 ) -> Result<Pieces, Vec<String>> {
     let mut inputs = HashMap::new();
     let mut outputs = HashMap::new();
+    let mut secondary_outputs = HashMap::new();
     let mut tasks = HashMap::new();
     let mut source_tasks = HashMap::new();
     let mut healthchecks = HashMap::new();
@@ -177,6 +204,7 @@ pub async fn build_pieces(
         let cx = TransformContext { resolver };
 
         let input_type = transform.inner.input_type();
+        let named_outputs = transform.inner.named_outputs();
         let transform = match transform.inner.build(cx) {
             Err(error) => {
                 errors.push(format!("Transform \"{}\": {}", name, error));
@@ -186,14 +214,40 @@ pub async fn build_pieces(
         };
 
         let (input_tx, input_rx) = futures01::sync::mpsc::channel(100);
-        let input_tx = buffers::BufferInputCloner::Memory(input_tx, buffers::WhenFull::Block);
+        let usage = buffers::BufferUsage::new(&name, "transform", typetag);
+        let input_tx =
+            buffers::BufferInputCloner::Memory(input_tx, buffers::WhenFull::Block, usage.clone());
+        let input_rx = usage.track_output(input_rx);
 
         let (output, control) = Fanout::new();
 
-        let transform = transform
-            .transform_stream(filter_event_type(input_rx, input_type))
-            .forward(output)
-            .map(|_| debug!("Finished"));
+        let input_rx = filter_event_type(input_rx, input_type);
+        let transform = if named_outputs.is_empty() {
+            Either::A(
+                transform
+                    .transform_stream(input_rx)
+                    .forward(output)
+                    .map(|_| debug!("Finished")),
+            )
+        } else {
+            let mut named = HashMap::new();
+            let mut names = Vec::new();
+            for output_name in named_outputs {
+                let (output, control) = Fanout::new();
+                let full_name = format!("{}.{}", name, output_name);
+                outputs.insert(full_name.clone(), control);
+                names.push(full_name);
+                named.insert(output_name, output);
+            }
+            secondary_outputs.insert(name.clone(), names);
+
+            Either::B(
+                transform
+                    .transform_stream_outputs(input_rx)
+                    .forward(Outputs::new(output, named))
+                    .map(|_| debug!("Finished")),
+            )
+        };
         let task = Task::new(&name, &typetag, transform);
 
         inputs.insert(name.clone(), (input_tx, trans_inputs.clone()));
@@ -213,7 +267,7 @@ pub async fn build_pieces(
         let typetag = sink.inner.sink_type();
         let input_type = sink.inner.input_type();
 
-        let buffer = sink.buffer.build(&config.global.data_dir, &name);
+        let buffer = sink.buffer.build(&config.global.data_dir, &name, typetag);
         let (tx, rx, acker) = match buffer {
             Err(error) => {
                 errors.push(format!("Sink \"{}\": {}", name, error));
@@ -221,8 +275,23 @@ pub async fn build_pieces(
             }
             Ok(buffer) => buffer,
         };
+        // Sinks report the status of the events they're done with through
+        // their acks, which are in input order, so the finalizers of each
+        // event are kept aside until the sink acks it.
+        let (acker, pending_finalizers) = acker.finalizing();
+
+        let (dead_letter, dead_letter_rx) = if sink.dead_letter {
+            let (dead_letter, rx) = DeadLetter::new(&name);
+            (Some(dead_letter), Some(rx))
+        } else {
+            (None, None)
+        };
 
-        let cx = SinkContext { resolver, acker };
+        let cx = SinkContext {
+            resolver,
+            acker,
+            dead_letter,
+        };
 
         let (sink, healthcheck) = match sink.inner.build_async(cx).await {
             Err(error) => {
@@ -233,9 +302,24 @@ pub async fn build_pieces(
         };
 
         let sink = filter_event_type(rx, input_type)
+            .map(move |mut event| {
+                pending_finalizers.push(event.take_finalizers());
+                event
+            })
             .forward(sink)
             .map(|_| debug!("Finished"));
-        let task = Task::new(&name, &typetag, sink);
+        let task = match dead_letter_rx {
+            Some(dead_letter_rx) => {
+                let (output, control) = Fanout::new();
+                let pump = dead_letter_rx.forward(output).map(|_| ());
+
+                let output_name = dead_letter::output_name(&name);
+                outputs.insert(output_name.clone(), control);
+                secondary_outputs.insert(name.clone(), vec![output_name]);
+                Task::new(&name, &typetag, sink.join(pump).map(|_| ()))
+            }
+            None => Task::new(&name, &typetag, sink),
+        };
 
         let healthcheck_task = if enable_healthcheck {
             let healthcheck_task = healthcheck
@@ -261,6 +345,7 @@ pub async fn build_pieces(
         let pieces = Pieces {
             inputs,
             outputs,
+            secondary_outputs,
             tasks,
             source_tasks,
             healthchecks,
@@ -0,0 +1,115 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::path::Path;
+
+/// The serialization format of a config file.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Format::Toml
+    }
+}
+
+impl Format {
+    /// Detects the format of a config file from its extension. Returns `None`
+    /// when the extension is missing or not recognized.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Some(Format::Toml),
+            Some("yaml") | Some("yml") => Some(Format::Yaml),
+            Some("json") => Some(Format::Json),
+            _ => None,
+        }
+    }
+
+    /// Resolves the format of a config file, preferring an explicit override
+    /// and falling back to TOML when the extension is not recognized.
+    pub fn resolve<P: AsRef<Path>>(path: P, explicit: Option<Format>) -> Self {
+        explicit
+            .or_else(|| Self::from_path(path))
+            .unwrap_or_default()
+    }
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "toml" => Ok(Format::Toml),
+            "yaml" => Ok(Format::Yaml),
+            "json" => Ok(Format::Json),
+            s => Err(format!(
+                "{} is not a valid option, expected `toml`, `yaml` or `json`",
+                s
+            )),
+        }
+    }
+}
+
+/// Parses a config value from a string in the given format.
+pub fn deserialize<T: DeserializeOwned>(content: &str, format: Format) -> Result<T, Vec<String>> {
+    match format {
+        Format::Toml => toml::from_str(content).map_err(|e| vec![e.to_string()]),
+        Format::Yaml => serde_yaml::from_str(content).map_err(|e| vec![e.to_string()]),
+        Format::Json => serde_json::from_str(content).map_err(|e| vec![e.to_string()]),
+    }
+}
+
+/// Renders a config value as a string in the given format.
+pub fn serialize<T: Serialize>(value: &T, format: Format) -> Result<String, String> {
+    match format {
+        Format::Toml => toml::to_string(value).map_err(|e| e.to_string()),
+        Format::Yaml => serde_yaml::to_string(value).map_err(|e| e.to_string()),
+        Format::Json => serde_json::to_string_pretty(value).map_err(|e| e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{deserialize, Format};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn detects_format_from_extension() {
+        assert_eq!(Format::from_path("vector.toml"), Some(Format::Toml));
+        assert_eq!(Format::from_path("vector.yaml"), Some(Format::Yaml));
+        assert_eq!(Format::from_path("vector.yml"), Some(Format::Yaml));
+        assert_eq!(Format::from_path("vector.json"), Some(Format::Json));
+        assert_eq!(Format::from_path("vector.conf"), None);
+        assert_eq!(Format::from_path("vector"), None);
+    }
+
+    #[test]
+    fn explicit_format_overrides_extension() {
+        assert_eq!(Format::resolve("vector.toml", None), Format::Toml);
+        assert_eq!(Format::resolve("vector.conf", None), Format::Toml);
+        assert_eq!(Format::resolve("vector.json", None), Format::Json);
+        assert_eq!(
+            Format::resolve("vector.toml", Some(Format::Yaml)),
+            Format::Yaml
+        );
+    }
+
+    #[test]
+    fn deserializes_all_formats() {
+        let expected = vec![("foo".to_owned(), "bar".to_owned())]
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+
+        for (content, format) in &[
+            (r#"foo = "bar""#, Format::Toml),
+            ("foo: bar", Format::Yaml),
+            (r#"{"foo": "bar"}"#, Format::Json),
+        ] {
+            let parsed: BTreeMap<String, String> = deserialize(content, *format).unwrap();
+            assert_eq!(parsed, expected);
+        }
+    }
+}
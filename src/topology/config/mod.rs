@@ -4,7 +4,11 @@ use crate::{
     dns::Resolver,
     event::{self, Event, Metric},
     shutdown::ShutdownSignal,
-    sinks, sources, transforms,
+    sinks::{
+        self,
+        util::{dead_letter, DeadLetter},
+    },
+    sources, transforms,
 };
 use component::ComponentDescription;
 use futures01::sync::mpsc;
@@ -15,10 +19,13 @@ use std::fs::DirBuilder;
 use std::{collections::HashMap, path::PathBuf};
 
 pub mod component;
+pub mod format;
 mod validation;
 mod vars;
 pub mod watcher;
 
+pub use format::Format;
+
 #[derive(Deserialize, Serialize, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct Config {
@@ -147,6 +154,10 @@ pub struct SinkOuter {
     pub buffer: crate::buffers::BufferConfig,
     #[serde(default = "healthcheck_default")]
     pub healthcheck: bool,
+    /// Exposes the events this sink permanently failed to deliver as the
+    /// `<name>.dead_letter` output.
+    #[serde(default)]
+    pub dead_letter: bool,
     pub inputs: Vec<String>,
     #[serde(flatten)]
     pub inner: Box<dyn SinkConfig>,
@@ -167,12 +178,19 @@ pub trait SinkConfig: core::fmt::Debug + Send + Sync {
     fn input_type(&self) -> DataType;
 
     fn sink_type(&self) -> &'static str;
+
+    /// Whether the events the sink fails to deliver, including those it gave
+    /// up retrying, are sent to its `dead_letter` output.
+    fn supports_dead_letter(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct SinkContext {
     pub(super) acker: Acker,
     pub(super) resolver: Resolver,
+    pub(super) dead_letter: Option<DeadLetter>,
 }
 
 impl SinkContext {
@@ -181,6 +199,7 @@ impl SinkContext {
         Self {
             acker: Acker::Null,
             resolver: Resolver,
+            dead_letter: None,
         }
     }
 
@@ -188,6 +207,10 @@ impl SinkContext {
         self.acker.clone()
     }
 
+    pub fn dead_letter(&self) -> Option<DeadLetter> {
+        self.dead_letter.clone()
+    }
+
     pub fn resolver(&self) -> Resolver {
         self.resolver
     }
@@ -214,6 +237,13 @@ pub trait TransformConfig: core::fmt::Debug + Send + Sync {
 
     fn transform_type(&self) -> &'static str;
 
+    /// Names of the secondary outputs this transform sends events to besides
+    /// its primary output. Other components consume them as
+    /// `<transform>.<output>`. They carry events of the transform's input type.
+    fn named_outputs(&self) -> Vec<&'static str> {
+        Vec::new()
+    }
+
     /// Allows a transform configuration to expand itself into multiple "child"
     /// transformations to replace it. This allows a transform to act as a macro
     /// for various patterns.
@@ -317,6 +347,7 @@ impl Config {
         let sink = SinkOuter {
             buffer: Default::default(),
             healthcheck: true,
+            dead_letter: false,
             inner: Box::new(sink),
             inputs,
         };
@@ -381,7 +412,14 @@ impl Config {
         }
     }
 
-    pub fn load(mut input: impl std::io::Read) -> Result<Self, Vec<String>> {
+    pub fn load(input: impl std::io::Read) -> Result<Self, Vec<String>> {
+        Self::load_with_format(input, Format::Toml)
+    }
+
+    pub fn load_with_format(
+        mut input: impl std::io::Read,
+        format: Format,
+    ) -> Result<Self, Vec<String>> {
         let mut source_string = String::new();
         input
             .read_to_string(&mut source_string)
@@ -393,9 +431,9 @@ impl Config {
                 vars.insert("HOSTNAME".into(), hostname);
             }
         }
-        let with_vars = vars::interpolate(&source_string, &vars);
+        let with_vars = vars::interpolate(&source_string, &vars)?;
 
-        toml::from_str(&with_vars).map_err(|e| vec![e.to_string()])
+        format::deserialize(&with_vars, format)
     }
 
     pub fn append(&mut self, with: Self) -> Result<(), Vec<String>> {
@@ -480,6 +518,35 @@ impl Config {
     pub fn typecheck(&self) -> Result<(), Vec<String>> {
         validation::typecheck(self)
     }
+
+    /// Full names of the secondary outputs of component `name`, such as
+    /// transform named outputs and sink dead-letter outputs.
+    pub fn secondary_outputs(&self, name: &str) -> Vec<String> {
+        let transform_outputs = self
+            .transforms
+            .get(name)
+            .into_iter()
+            .flat_map(|transform| transform.inner.named_outputs())
+            .map(|output| format!("{}.{}", name, output));
+        let dead_letter = self
+            .sinks
+            .get(name)
+            .filter(|sink| sink.dead_letter)
+            .map(|_| dead_letter::output_name(name));
+
+        transform_outputs.chain(dead_letter).collect()
+    }
+
+    /// Whether `input` names an output other components can consume.
+    pub fn output_exists(&self, input: &str) -> bool {
+        self.sources.contains_key(input)
+            || self.transforms.contains_key(input)
+            || input.match_indices('.').any(|(i, _)| {
+                self.secondary_outputs(&input[..i])
+                    .iter()
+                    .any(|output| output == input)
+            })
+    }
 }
 
 impl Clone for Config {
@@ -505,7 +572,7 @@ fn healthcheck_default() -> bool {
     feature = "transforms-json_parser"
 ))]
 mod test {
-    use super::Config;
+    use super::{Config, Format};
     use std::path::PathBuf;
 
     #[test]
@@ -530,6 +597,48 @@ mod test {
         )
     }
 
+    #[test]
+    fn load_yaml_and_json() {
+        let yaml = Config::load_with_format(
+            r#"
+      data_dir: /foobar
+      sources:
+        in:
+          type: file
+          include: ["/var/log/messages"]
+      sinks:
+        out:
+          type: console
+          inputs: ["in"]
+          encoding: json
+      "#
+            .as_bytes(),
+            Format::Yaml,
+        )
+        .unwrap();
+
+        let json = Config::load_with_format(
+            r#"{
+        "data_dir": "/foobar",
+        "sources": {
+          "in": { "type": "file", "include": ["/var/log/messages"] }
+        },
+        "sinks": {
+          "out": { "type": "console", "inputs": ["in"], "encoding": "json" }
+        }
+      }"#
+            .as_bytes(),
+            Format::Json,
+        )
+        .unwrap();
+
+        for config in &[yaml, json] {
+            assert_eq!(Some(PathBuf::from("/foobar")), config.global.data_dir);
+            assert!(config.sources.contains_key("in"));
+            assert_eq!(config.sinks["out"].inputs, vec!["in".to_owned()]);
+        }
+    }
+
     #[test]
     fn default_schema() {
         let config: Config = toml::from_str(
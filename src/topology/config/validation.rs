@@ -1,4 +1,7 @@
-use crate::topology::{config::DataType, Config};
+use crate::{
+    sinks::util::dead_letter,
+    topology::{config::DataType, Config},
+};
 use std::collections::HashMap;
 
 pub fn typecheck(config: &Config) -> Result<(), Vec<String>> {
@@ -107,6 +110,9 @@ impl Graph {
                             ));
                         }
                     }
+                    // A sink only feeds its own dead-letter output, which
+                    // carries the sink's input type.
+                    (Node::Sink { .. }, Node::Transform { .. }) => {}
                     (Node::Sink { .. }, _) | (_, Node::Source { .. }) => unreachable!(),
                 }
             }
@@ -138,10 +144,23 @@ impl From<&Config> for Graph {
                 config.inner.output_type(),
                 config.inputs.clone(),
             );
+            // Named outputs carry events of the transform's input type.
+            for output in config.inner.named_outputs() {
+                graph.add_transform(
+                    &format!("{}.{}", name, output),
+                    DataType::Any,
+                    config.inner.input_type(),
+                    vec![name],
+                );
+            }
         }
 
         for (name, config) in config.sinks.iter() {
-            graph.add_sink(name, config.inner.input_type(), config.inputs.clone());
+            let ty = config.inner.input_type();
+            graph.add_sink(name, ty, config.inputs.clone());
+            if config.dead_letter {
+                graph.add_transform(&dead_letter::output_name(name), ty, ty, vec![name]);
+            }
         }
 
         graph
@@ -253,6 +272,36 @@ mod test {
         graph.paths().unwrap();
     }
 
+    #[test]
+    fn paths_through_dead_letter_outputs() {
+        let mut graph = Graph::default();
+        graph.add_source("in", DataType::Log);
+        graph.add_sink("es", DataType::Log, vec!["in"]);
+        graph.add_transform("es.dead_letter", DataType::Log, DataType::Log, vec!["es"]);
+        graph.add_sink("replay", DataType::Log, vec!["es.dead_letter"]);
+
+        assert_eq!(Ok(()), graph.typecheck());
+
+        let mut graph = Graph::default();
+        graph.add_source("in", DataType::Log);
+        graph.add_sink("es", DataType::Log, vec!["in", "retry"]);
+        graph.add_transform("es.dead_letter", DataType::Log, DataType::Log, vec!["es"]);
+        graph.add_transform(
+            "retry",
+            DataType::Log,
+            DataType::Log,
+            vec!["es.dead_letter"],
+        );
+
+        assert_eq!(
+            Err(vec![
+                "Cyclic dependency detected in the chain [ es -> es.dead_letter -> retry -> es ]"
+                    .into()
+            ]),
+            graph.paths()
+        );
+    }
+
     #[test]
     fn detects_type_mismatches() {
         let mut graph = Graph::default();
@@ -1,53 +1,147 @@
 use regex::{Captures, Regex};
 use std::collections::HashMap;
 
-pub fn interpolate(input: &str, vars: &HashMap<String, String>) -> String {
-    let re = Regex::new(r"\$\$|\$(\w+)|\$\{(\w+)(?::-([^}]+)?)?\}").unwrap();
-    re.replace_all(input, |caps: &Captures<'_>| {
-        caps.get(1)
-            .or_else(|| caps.get(2))
-            .map(|m| m.as_str())
-            .map(|name| {
-                vars.get(name).map(|val| val.as_str()).unwrap_or_else(|| {
-                    caps.get(3).map(|m| m.as_str()).unwrap_or_else(|| {
+/// Interpolates environment variables and secret files into a raw config.
+///
+/// Supported forms are `$VAR`, `${VAR}`, `${VAR:-default}`, `${VAR:?message}`
+/// and `${file:/path/to/secret}`. A `$$` sequence escapes a literal `$`.
+/// Required variables that are unset or empty and unreadable secret files are
+/// reported as errors, unset optional variables are replaced with an empty
+/// string.
+pub fn interpolate(input: &str, vars: &HashMap<String, String>) -> Result<String, Vec<String>> {
+    let re = Regex::new(r"\$\$|\$\{file:([^}?-][^}]*)\}|\$(\w+)|\$\{(\w+)(?:(:-|:\?)([^}]*))?\}")
+        .unwrap();
+    let mut errors = Vec::new();
+
+    let interpolated = re
+        .replace_all(input, |caps: &Captures<'_>| {
+            if let Some(path) = caps.get(1) {
+                return read_secret(path.as_str()).unwrap_or_else(|error| {
+                    errors.push(error);
+                    String::new()
+                });
+            }
+
+            let name = match caps.get(2).or_else(|| caps.get(3)) {
+                Some(name) => name.as_str(),
+                None => return "$".to_owned(),
+            };
+            let value = vars.get(name).map(|val| val.as_str());
+            let argument = caps.get(5).map(|m| m.as_str()).unwrap_or("");
+
+            match caps.get(4).map(|m| m.as_str()) {
+                Some(":-") => value.unwrap_or(argument).to_owned(),
+                Some(":?") => match value {
+                    Some(value) if !value.is_empty() => value.to_owned(),
+                    _ => {
+                        errors.push(if argument.is_empty() {
+                            format!("required environment variable {:?} is not set", name)
+                        } else {
+                            format!(
+                                "required environment variable {:?} is not set: {}",
+                                name, argument
+                            )
+                        });
+                        String::new()
+                    }
+                },
+                _ => value
+                    .unwrap_or_else(|| {
                         warn!("unknown env var in config: {:?}", name);
                         ""
                     })
-                })
-            })
-            .unwrap_or("$")
-            .to_string()
-    })
-    .into_owned()
+                    .to_owned(),
+            }
+        })
+        .into_owned();
+
+    if errors.is_empty() {
+        Ok(interpolated)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Reads a secret from disk, dropping the trailing newline most editors and
+/// secret stores append.
+fn read_secret(path: &str) -> Result<String, String> {
+    std::fs::read_to_string(path)
+        .map(|secret| {
+            secret
+                .trim_end_matches(|c| c == '\n' || c == '\r')
+                .to_owned()
+        })
+        .map_err(|error| format!("could not read secret file {:?}: {}", path, error))
 }
 
 #[cfg(test)]
 mod test {
     use super::interpolate;
-    #[test]
-    fn interpolation() {
-        let vars = vec![
+    use std::{collections::HashMap, io::Write};
+
+    fn vars() -> HashMap<String, String> {
+        vec![
             ("FOO".into(), "dogs".into()),
             ("FOOBAR".into(), "cats".into()),
+            ("EMPTY".into(), "".into()),
         ]
         .into_iter()
-        .collect();
-
-        assert_eq!("dogs", interpolate("$FOO", &vars));
-        assert_eq!("dogs", interpolate("${FOO}", &vars));
-        assert_eq!("cats", interpolate("${FOOBAR}", &vars));
-        assert_eq!("xcatsy", interpolate("x${FOOBAR}y", &vars));
-        assert_eq!("x", interpolate("x$FOOBARy", &vars));
-        assert_eq!("$ x", interpolate("$ x", &vars));
-        assert_eq!("$FOO", interpolate("$$FOO", &vars));
-        assert_eq!("", interpolate("$NOT_FOO", &vars));
-        assert_eq!("-FOO", interpolate("$NOT-FOO", &vars));
-        assert_eq!("${FOO x", interpolate("${FOO x", &vars));
-        assert_eq!("${}", interpolate("${}", &vars));
-        assert_eq!("dogs", interpolate("${FOO:-cats}", &vars));
-        assert_eq!("dogcats", interpolate("${NOT:-dogcats}", &vars));
-        assert_eq!("dogs and cats", interpolate("${NOT:-dogs and cats}", &vars));
-        assert_eq!("${:-cats}", interpolate("${:-cats}", &vars));
-        assert_eq!("", interpolate("${NOT:-}", &vars));
+        .collect()
+    }
+
+    #[test]
+    fn interpolation() {
+        let vars = vars();
+        let interpolate = |input| interpolate(input, &vars).unwrap();
+
+        assert_eq!("dogs", interpolate("$FOO"));
+        assert_eq!("dogs", interpolate("${FOO}"));
+        assert_eq!("cats", interpolate("${FOOBAR}"));
+        assert_eq!("xcatsy", interpolate("x${FOOBAR}y"));
+        assert_eq!("x", interpolate("x$FOOBARy"));
+        assert_eq!("$ x", interpolate("$ x"));
+        assert_eq!("$FOO", interpolate("$$FOO"));
+        assert_eq!("", interpolate("$NOT_FOO"));
+        assert_eq!("-FOO", interpolate("$NOT-FOO"));
+        assert_eq!("${FOO x", interpolate("${FOO x"));
+        assert_eq!("${}", interpolate("${}"));
+        assert_eq!("dogs", interpolate("${FOO:-cats}"));
+        assert_eq!("dogcats", interpolate("${NOT:-dogcats}"));
+        assert_eq!("dogs and cats", interpolate("${NOT:-dogs and cats}"));
+        assert_eq!("${:-cats}", interpolate("${:-cats}"));
+        assert_eq!("", interpolate("${NOT:-}"));
+        assert_eq!("dogs", interpolate("${FOO:?must be set}"));
+        assert_eq!("$${FOO:?x}", interpolate("$$$${FOO:?x}"));
+        assert_eq!("fallback", interpolate("${file:-fallback}"));
+    }
+
+    #[test]
+    fn required_variables() {
+        let vars = vars();
+
+        assert_eq!(
+            interpolate("a ${NOT:?the endpoint is needed} b ${EMPTY:?}", &vars),
+            Err(vec![
+                r#"required environment variable "NOT" is not set: the endpoint is needed"#
+                    .to_owned(),
+                r#"required environment variable "EMPTY" is not set"#.to_owned(),
+            ])
+        );
+    }
+
+    #[test]
+    fn secret_files() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "hunter2").unwrap();
+        let path = file.path().to_str().unwrap();
+
+        assert_eq!(
+            interpolate(&format!("password = \"${{file:{}}}\"", path), &vars()),
+            Ok("password = \"hunter2\"".to_owned())
+        );
+
+        let errors = interpolate("${file:/does/not/exist}", &vars()).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with(r#"could not read secret file "/does/not/exist""#));
     }
 }
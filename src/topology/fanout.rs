@@ -2,6 +2,7 @@ use crate::sinks::RouterSink;
 use crate::Event;
 use futures01::sync::mpsc;
 use futures01::{future, Async, AsyncSink, Poll, Sink, StartSend, Stream};
+use std::collections::HashMap;
 
 pub struct Fanout {
     sinks: Vec<(String, RouterSink)>,
@@ -140,9 +141,62 @@ impl Sink for Fanout {
     }
 }
 
+/// Routes the events of a transform with named outputs to the fanout of the
+/// output each event was emitted on, `None` being the primary output.
+pub struct Outputs {
+    primary: Fanout,
+    named: HashMap<&'static str, Fanout>,
+}
+
+impl Outputs {
+    pub fn new(primary: Fanout, named: HashMap<&'static str, Fanout>) -> Self {
+        Self { primary, named }
+    }
+}
+
+impl Sink for Outputs {
+    type SinkItem = (Option<&'static str>, Event);
+    type SinkError = ();
+
+    fn start_send(&mut self, item: Self::SinkItem) -> StartSend<Self::SinkItem, Self::SinkError> {
+        let (output, event) = item;
+        let fanout = match output {
+            None => &mut self.primary,
+            Some(name) => match self.named.get_mut(name) {
+                Some(fanout) => fanout,
+                None => {
+                    debug!(
+                        message = "Dropping event sent to undeclared output.",
+                        output = name
+                    );
+                    return Ok(AsyncSink::Ready);
+                }
+            },
+        };
+
+        Ok(match fanout.start_send(event)? {
+            AsyncSink::Ready => AsyncSink::Ready,
+            AsyncSink::NotReady(event) => AsyncSink::NotReady((output, event)),
+        })
+    }
+
+    fn poll_complete(&mut self) -> Poll<(), Self::SinkError> {
+        let mut all_complete = self.primary.poll_complete()?.is_ready();
+        for fanout in self.named.values_mut() {
+            all_complete &= fanout.poll_complete()?.is_ready();
+        }
+
+        if all_complete {
+            Ok(Async::Ready(()))
+        } else {
+            Ok(Async::NotReady)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{ControlMessage, Fanout};
+    use super::{ControlMessage, Fanout, Outputs};
     use crate::test_util::{self, runtime, CollectCurrent};
     use crate::Event;
     use futures01::sync::mpsc;
@@ -451,4 +505,30 @@ mod tests {
         );
         assert_eq!(CollectCurrent::new(rx_a2).wait().unwrap().1, vec![rec3]);
     }
+
+    #[test]
+    fn outputs_route_to_named_fanouts() {
+        let (tx_a, rx_a) = mpsc::unbounded();
+        let tx_a = Box::new(tx_a.sink_map_err(|_| unreachable!()));
+        let (tx_b, rx_b) = mpsc::unbounded();
+        let tx_b = Box::new(tx_b.sink_map_err(|_| unreachable!()));
+
+        let mut primary = Fanout::new().0;
+        primary.add("a".to_string(), tx_a);
+        let mut errors = Fanout::new().0;
+        errors.add("b".to_string(), tx_b);
+
+        let outputs = Outputs::new(primary, vec![("errors", errors)].into_iter().collect());
+
+        let rec1 = Event::from("line 1".to_string());
+        let rec2 = Event::from("line 2".to_string());
+        let rec3 = Event::from("line 3".to_string());
+
+        let outputs = outputs.send((None, rec1.clone())).wait().unwrap();
+        let outputs = outputs.send((Some("errors"), rec2.clone())).wait().unwrap();
+        let _outputs = outputs.send((Some("unknown"), rec3)).wait().unwrap();
+
+        assert_eq!(CollectCurrent::new(rx_a).wait().unwrap().1, vec![rec1]);
+        assert_eq!(CollectCurrent::new(rx_b).wait().unwrap().1, vec![rec2]);
+    }
 }
@@ -382,6 +382,10 @@ impl RunningTopology {
 
             self.remove_inputs(&name);
             self.remove_outputs(&name);
+            self.remove_secondary_outputs(&name);
+        }
+        for name in &diff.transforms.to_change {
+            self.remove_secondary_outputs(&name);
         }
 
         // Sinks
@@ -392,6 +396,10 @@ impl RunningTopology {
             drop(previous); // detach and forget
 
             self.remove_inputs(&name);
+            self.remove_secondary_outputs(&name);
+        }
+        for name in &diff.sinks.to_change {
+            self.remove_secondary_outputs(&name);
         }
     }
 
@@ -407,6 +415,12 @@ impl RunningTopology {
         // it as an input
         for name in diff.transforms.changed_and_added() {
             self.setup_outputs(&name, new_pieces);
+            self.setup_secondary_outputs(&name, new_pieces);
+        }
+
+        // Dead-letter outputs of sinks can feed both transforms and sinks.
+        for name in diff.sinks.changed_and_added() {
+            self.setup_secondary_outputs(&name, new_pieces);
         }
 
         for name in &diff.transforms.to_change {
@@ -505,6 +519,12 @@ impl RunningTopology {
         self.outputs.remove(name);
     }
 
+    fn remove_secondary_outputs(&mut self, name: &str) {
+        for output in self.config.secondary_outputs(name) {
+            self.remove_outputs(&output);
+        }
+    }
+
     fn remove_inputs(&mut self, name: &str) {
         self.inputs.remove(name);
 
@@ -553,6 +573,14 @@ impl RunningTopology {
         self.outputs.insert(name.to_string(), output);
     }
 
+    fn setup_secondary_outputs(&mut self, name: &str, new_pieces: &mut builder::Pieces) {
+        if let Some(outputs) = new_pieces.secondary_outputs.remove(name) {
+            for output in outputs {
+                self.setup_outputs(&output, new_pieces);
+            }
+        }
+    }
+
     fn setup_inputs(&mut self, name: &str, new_pieces: &mut builder::Pieces) {
         let (tx, inputs) = new_pieces.inputs.remove(name).unwrap();
 
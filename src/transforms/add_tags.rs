@@ -90,6 +90,7 @@ mod tests {
     fn add_tags() {
         let event = Event::Metric(Metric {
             name: "bar".into(),
+            namespace: None,
             timestamp: None,
             tags: None,
             kind: MetricKind::Absolute,
@@ -118,6 +119,7 @@ mod tests {
         tags.insert("region".to_string(), "us-east-1".to_string());
         let event = Event::Metric(Metric {
             name: "bar".into(),
+            namespace: None,
             timestamp: None,
             tags: Some(tags),
             kind: MetricKind::Absolute,
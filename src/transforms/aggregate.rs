@@ -0,0 +1,454 @@
+use super::Transform;
+use crate::{
+    event::{
+        metric::{Metric, MetricValue},
+        sketch::DEFAULT_RELATIVE_ACCURACY,
+        Event,
+    },
+    sinks::util::MetricEntry,
+    topology::config::{DataType, TransformConfig, TransformContext, TransformDescription},
+};
+use async_stream::stream;
+use futures::{
+    compat::{Compat, Compat01As03},
+    stream, StreamExt,
+};
+use futures01::Stream as Stream01;
+use serde::{Deserialize, Serialize};
+use snafu::Snafu;
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("`interval_ms` must be greater than zero"))]
+    ZeroInterval,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields, default)]
+pub struct AggregateConfig {
+    /// The length of the windows metrics are aggregated over.
+    pub interval_ms: u64,
+    /// Emit the running totals of incremental counters and gauges as
+    /// absolute metrics, instead of their sums over each window.
+    pub absolute: bool,
+    /// How long the running total of a series is kept without updates.
+    pub expire_after_ms: u64,
+}
+
+impl Default for AggregateConfig {
+    fn default() -> Self {
+        Self {
+            interval_ms: 10_000,
+            absolute: false,
+            expire_after_ms: 300_000,
+        }
+    }
+}
+
+inventory::submit! {
+    TransformDescription::new::<AggregateConfig>("aggregate")
+}
+
+#[typetag::serde(name = "aggregate")]
+impl TransformConfig for AggregateConfig {
+    fn build(&self, _cx: TransformContext) -> crate::Result<Box<dyn Transform>> {
+        if self.interval_ms == 0 {
+            return Err(Box::new(BuildError::ZeroInterval));
+        }
+        Ok(Box::new(Aggregate::new(self)))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Metric
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Metric
+    }
+
+    fn transform_type(&self) -> &'static str {
+        "aggregate"
+    }
+}
+
+/// Combines the metrics of each series over a window, emitting a single
+/// metric per series when the window closes. Incremental metrics are added
+/// up, while the last value of absolute metrics wins.
+pub struct Aggregate {
+    interval: Duration,
+    absolute: bool,
+    expire_after: Duration,
+    /// The series seen during the current window.
+    window: HashSet<MetricEntry>,
+    /// The running totals of incremental counters and gauges, kept across
+    /// windows when emitting absolute metrics, along with when they were
+    /// last updated.
+    totals: HashMap<MetricEntry, Instant>,
+}
+
+impl Aggregate {
+    pub fn new(config: &AggregateConfig) -> Self {
+        Self {
+            interval: Duration::from_millis(config.interval_ms),
+            absolute: config.absolute,
+            expire_after: Duration::from_millis(config.expire_after_ms),
+            window: HashSet::new(),
+            totals: HashMap::new(),
+        }
+    }
+
+    fn record(&mut self, metric: Metric, now: Instant) {
+        let metric = match metric.value {
+            MetricValue::Counter { .. } | MetricValue::Gauge { .. }
+                if self.absolute && metric.kind.is_incremental() =>
+            {
+                let total = match self.totals.remove_entry(&MetricEntry(metric.to_absolute())) {
+                    Some((MetricEntry(mut total), _)) => {
+                        total.add(&metric);
+                        total.timestamp = metric.timestamp;
+                        total
+                    }
+                    None => metric.to_absolute(),
+                };
+                self.totals.insert(MetricEntry(total.clone()), now);
+                total
+            }
+            // Other values have no bounded total, so their changes are
+            // emitted over each window, with samples kept in a sketch.
+            MetricValue::Distribution { .. } if self.absolute => Metric {
+                value: metric.value.into_sketch(DEFAULT_RELATIVE_ACCURACY),
+                ..metric
+            },
+            _ => metric,
+        };
+
+        let entry = MetricEntry(metric);
+        match self.window.take(&entry) {
+            Some(MetricEntry(mut existing)) if entry.0.kind.is_incremental() => {
+                existing.add(&entry.0);
+                existing.timestamp = entry.0.timestamp;
+                self.window.insert(MetricEntry(existing));
+            }
+            _ => {
+                self.window.insert(entry);
+            }
+        }
+    }
+
+    /// Emits the series of the window, and forgets the totals that weren't
+    /// updated for `expire_after`.
+    fn flush_into(&mut self, output: &mut Vec<Event>, now: Instant) {
+        output.extend(
+            self.window
+                .drain()
+                .map(|MetricEntry(metric)| Event::Metric(metric)),
+        );
+
+        let expire_after = self.expire_after;
+        self.totals
+            .retain(|_, updated| now.duration_since(*updated) < expire_after);
+    }
+}
+
+impl Transform for Aggregate {
+    // Only used in tests
+    fn transform(&mut self, event: Event) -> Option<Event> {
+        self.record(event.into_metric(), Instant::now());
+        None
+    }
+
+    fn transform_stream(
+        self: Box<Self>,
+        input_rx: Box<dyn Stream01<Item = Event, Error = ()> + Send>,
+    ) -> Box<dyn Stream01<Item = Event, Error = ()> + Send>
+    where
+        Self: 'static,
+    {
+        let mut me = self;
+
+        let mut flush_stream = tokio::time::interval(me.interval);
+        let mut input_stream = Compat01As03::new(input_rx);
+
+        let stream = stream! {
+          loop {
+            let mut output = Vec::new();
+            let done = tokio::select! {
+                _ = flush_stream.next() => {
+                  me.flush_into(&mut output, Instant::now());
+                  false
+                }
+                maybe_event = input_stream.next() => {
+                  match maybe_event {
+                    None => {
+                      me.flush_into(&mut output, Instant::now());
+                      true
+                    }
+                    Some(Ok(event)) => {
+                      me.record(event.into_metric(), Instant::now());
+                      false
+                    }
+                    Some(Err(())) => panic!("unexpected error reading channel"),
+                  }
+                }
+            };
+            yield stream::iter(output.into_iter());
+            if done { break }
+          }
+        }
+        .flatten();
+
+        // Needed for compat
+        let try_stream = Box::pin(stream.map::<Result<Event, ()>, _>(Ok));
+
+        Box::new(Compat::new(try_stream))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::metric::{MetricKind, MetricValue},
+        test_util::runtime,
+    };
+    use chrono::{offset::TimeZone, DateTime, Utc};
+    use pretty_assertions::assert_eq;
+
+    fn ts(second: u32) -> DateTime<Utc> {
+        Utc.ymd(2020, 6, 1).and_hms(12, 0, second)
+    }
+
+    fn metric(name: &str, second: u32, kind: MetricKind, value: MetricValue) -> Event {
+        Event::Metric(Metric {
+            name: name.into(),
+            namespace: None,
+            timestamp: Some(ts(second)),
+            tags: Some(
+                vec![("host".to_owned(), "localhost".to_owned())]
+                    .into_iter()
+                    .collect(),
+            ),
+            kind,
+            value,
+        })
+    }
+
+    fn counter(name: &str, second: u32, value: f64) -> Event {
+        metric(
+            name,
+            second,
+            MetricKind::Incremental,
+            MetricValue::Counter { value },
+        )
+    }
+
+    fn aggregate(absolute: bool) -> Aggregate {
+        Aggregate::new(&AggregateConfig {
+            interval_ms: 1000,
+            absolute,
+            expire_after_ms: 60_000,
+        })
+    }
+
+    fn flush(aggregate: &mut Aggregate) -> Vec<Metric> {
+        flush_at(aggregate, Instant::now())
+    }
+
+    fn flush_at(aggregate: &mut Aggregate, now: Instant) -> Vec<Metric> {
+        let mut output = Vec::new();
+        aggregate.flush_into(&mut output, now);
+        let mut metrics: Vec<_> = output.into_iter().map(Event::into_metric).collect();
+        metrics.sort_by(|a, b| a.name.cmp(&b.name));
+        metrics
+    }
+
+    #[test]
+    fn aggregate_adds_incremental_metrics() {
+        let mut aggregate = aggregate(false);
+        assert_eq!(aggregate.transform(counter("requests", 1, 1.0)), None);
+        aggregate.transform(counter("requests", 2, 2.0));
+        aggregate.transform(counter("errors", 3, 5.0));
+        aggregate.transform(metric(
+            "latency",
+            1,
+            MetricKind::Incremental,
+            MetricValue::Distribution {
+                values: vec![1.0],
+                sample_rates: vec![1],
+            },
+        ));
+        aggregate.transform(metric(
+            "latency",
+            2,
+            MetricKind::Incremental,
+            MetricValue::Distribution {
+                values: vec![2.0],
+                sample_rates: vec![10],
+            },
+        ));
+
+        let metrics = flush(&mut aggregate);
+        assert_eq!(
+            metrics,
+            vec![
+                counter("errors", 3, 5.0).into_metric(),
+                metric(
+                    "latency",
+                    2,
+                    MetricKind::Incremental,
+                    MetricValue::Distribution {
+                        values: vec![1.0, 2.0],
+                        sample_rates: vec![1, 10],
+                    },
+                )
+                .into_metric(),
+                counter("requests", 2, 3.0).into_metric(),
+            ]
+        );
+        assert!(flush(&mut aggregate).is_empty());
+    }
+
+    #[test]
+    fn aggregate_keeps_last_absolute_value() {
+        let mut aggregate = aggregate(false);
+        let gauge = |second, value| {
+            metric(
+                "memory",
+                second,
+                MetricKind::Absolute,
+                MetricValue::Gauge { value },
+            )
+        };
+        aggregate.transform(gauge(1, 10.0));
+        aggregate.transform(gauge(2, 20.0));
+
+        assert_eq!(flush(&mut aggregate), vec![gauge(2, 20.0).into_metric()]);
+    }
+
+    #[test]
+    fn aggregate_separates_series() {
+        let mut aggregate = aggregate(false);
+        let mut other = counter("requests", 1, 1.0);
+        other
+            .as_mut_metric()
+            .tags
+            .as_mut()
+            .unwrap()
+            .insert("host".into(), "remote".into());
+        aggregate.transform(counter("requests", 1, 1.0));
+        aggregate.transform(other);
+
+        assert_eq!(flush(&mut aggregate).len(), 2);
+    }
+
+    #[test]
+    fn aggregate_running_totals_as_absolute() {
+        let mut aggregate = aggregate(true);
+        let total = |second, value| {
+            metric(
+                "requests",
+                second,
+                MetricKind::Absolute,
+                MetricValue::Counter { value },
+            )
+            .into_metric()
+        };
+
+        aggregate.transform(counter("requests", 1, 1.0));
+        aggregate.transform(counter("requests", 2, 2.0));
+        assert_eq!(flush(&mut aggregate), vec![total(2, 3.0)]);
+
+        aggregate.transform(counter("requests", 3, 4.0));
+        assert_eq!(flush(&mut aggregate), vec![total(3, 7.0)]);
+    }
+
+    #[test]
+    fn aggregate_expires_idle_totals() {
+        let mut aggregate = aggregate(true);
+        let start = Instant::now();
+
+        aggregate.record(counter("requests", 1, 1.0).into_metric(), start);
+        flush_at(&mut aggregate, start + Duration::from_secs(30));
+        assert_eq!(aggregate.totals.len(), 1);
+
+        flush_at(&mut aggregate, start + Duration::from_secs(60));
+        assert!(aggregate.totals.is_empty());
+
+        aggregate.record(
+            counter("requests", 2, 2.0).into_metric(),
+            start + Duration::from_secs(61),
+        );
+        assert_eq!(
+            flush_at(&mut aggregate, start + Duration::from_secs(61)),
+            vec![metric(
+                "requests",
+                2,
+                MetricKind::Absolute,
+                MetricValue::Counter { value: 2.0 }
+            )
+            .into_metric()]
+        );
+    }
+
+    #[test]
+    fn aggregate_emits_other_values_over_windows_as_absolute() {
+        let mut aggregate = aggregate(true);
+        let distribution = |second, value| {
+            metric(
+                "latency",
+                second,
+                MetricKind::Incremental,
+                MetricValue::Distribution {
+                    values: vec![value],
+                    sample_rates: vec![1],
+                },
+            )
+        };
+
+        aggregate.transform(distribution(1, 1.0));
+        aggregate.transform(distribution(2, 2.0));
+        let metrics = flush(&mut aggregate);
+        assert_eq!(metrics.len(), 1);
+        assert_eq!(metrics[0].kind, MetricKind::Incremental);
+        match &metrics[0].value {
+            MetricValue::Sketch(sketch) => assert_eq!(sketch.count(), 2),
+            value => panic!("unexpected value {:?}", value),
+        }
+
+        aggregate.transform(distribution(3, 3.0));
+        match &flush(&mut aggregate)[0].value {
+            MetricValue::Sketch(sketch) => assert_eq!(sketch.count(), 1),
+            value => panic!("unexpected value {:?}", value),
+        }
+        assert!(aggregate.totals.is_empty());
+    }
+
+    #[test]
+    fn aggregate_rejects_zero_interval() {
+        let config = AggregateConfig {
+            interval_ms: 0,
+            ..Default::default()
+        };
+        assert!(config.build(TransformContext::new_test()).is_err());
+    }
+
+    #[test]
+    fn aggregate_flushes_when_input_ends() {
+        let mut rt = runtime();
+        let metrics = rt.block_on_std(async move {
+            let input = vec![counter("requests", 1, 1.0), counter("requests", 2, 2.0)];
+            let aggregate = Box::new(aggregate(false));
+            let output = aggregate.transform_stream(Box::new(futures01::stream::iter_ok(input)));
+            Compat01As03::new(output)
+                .map(|event| event.unwrap().into_metric())
+                .collect::<Vec<_>>()
+                .await
+        });
+
+        assert_eq!(metrics, vec![counter("requests", 2, 3.0).into_metric()]);
+    }
+}
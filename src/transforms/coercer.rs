@@ -1,5 +1,5 @@
-use super::Transform;
-use crate::event::Event;
+use super::{route_error, Transform, ERRORS_OUTPUT};
+use crate::event::{Event, LogEvent, Value};
 use crate::topology::config::{DataType, TransformConfig, TransformContext, TransformDescription};
 use crate::types::{parse_conversion_map, Conversion};
 use serde::{Deserialize, Serialize};
@@ -40,6 +40,10 @@ impl TransformConfig for CoercerConfig {
     fn transform_type(&self) -> &'static str {
         "coercer"
     }
+
+    fn named_outputs(&self) -> Vec<&'static str> {
+        vec![ERRORS_OUTPUT]
+    }
 }
 
 pub struct Coercer {
@@ -47,9 +51,19 @@ pub struct Coercer {
     drop_unspecified: bool,
 }
 
-impl Transform for Coercer {
-    fn transform(&mut self, event: Event) -> Option<Event> {
-        let mut log = event.into_log();
+/// A field that could not be converted, with its original value.
+struct Failed {
+    field: Atom,
+    value: Value,
+    error: String,
+}
+
+impl Coercer {
+    /// Converts the configured fields of `log`, returning the fields that
+    /// failed to convert with their original values.
+    fn coerce(&self, mut log: LogEvent) -> (Event, Vec<Failed>) {
+        let mut failed = Vec::new();
+
         if self.drop_unspecified {
             // This uses a different algorithm from the default path
             // below, as it will be fewer steps to fully recreate the
@@ -59,42 +73,88 @@ impl Transform for Coercer {
             let new_log = new_event.as_mut_log();
             for (field, conv) in &self.types {
                 if let Some(value) = log.remove(field) {
-                    match conv.convert(value) {
-                        Ok(converted) => {
-                            new_log.insert(field, converted);
-                        }
-                        Err(error) => {
-                            warn!(
-                                message = "Could not convert types.",
-                                field = &field[..],
-                                %error,
-                                rate_limit_secs = 10,
-                            );
-                        }
+                    if let Some(converted) = convert(field, conv, value, &mut failed) {
+                        new_log.insert(field, converted);
                     }
                 }
             }
-            return Some(new_event);
+            (new_event, failed)
         } else {
             for (field, conv) in &self.types {
                 if let Some(value) = log.remove(field) {
-                    match conv.convert(value) {
-                        Ok(converted) => {
-                            log.insert(field, converted);
-                        }
-                        Err(error) => {
-                            warn!(
-                                message = "Could not convert types.",
-                                field = &field[..],
-                                %error,
-                                rate_limit_secs = 10,
-                            );
-                        }
+                    if let Some(converted) = convert(field, conv, value, &mut failed) {
+                        log.insert(field, converted);
                     }
                 }
             }
+            (Event::Log(log), failed)
+        }
+    }
+}
+
+fn convert(
+    field: &Atom,
+    conv: &Conversion,
+    value: Value,
+    failed: &mut Vec<Failed>,
+) -> Option<Value> {
+    // The value is only borrowed by the conversion, so the original can be
+    // handed back when it fails without copying every converted value.
+    match conv.convert_ref(&value) {
+        Ok(converted) => Some(converted),
+        Err(error) => {
+            warn!(
+                message = "Could not convert types.",
+                field = &field[..],
+                %error,
+                rate_limit_secs = 10,
+            );
+            failed.push(Failed {
+                field: field.clone(),
+                value,
+                error: error.to_string(),
+            });
+            None
+        }
+    }
+}
+
+impl Transform for Coercer {
+    fn transform(&mut self, event: Event) -> Option<Event> {
+        Some(self.coerce(event.into_log()).0)
+    }
+
+    fn transform_outputs(
+        &mut self,
+        output: &mut Vec<Event>,
+        named: &mut Vec<(&'static str, Event)>,
+        event: Event,
+    ) {
+        let (event, failed) = self.coerce(event.into_log());
+
+        if !failed.is_empty() {
+            // The errors output keeps the original values of the fields
+            // that could not be converted.
+            let mut error_event = event.clone();
+            let log = error_event.as_mut_log();
+            let mut reasons = Vec::with_capacity(failed.len());
+            for Failed {
+                field,
+                value,
+                error,
+            } in failed
+            {
+                reasons.push(format!(
+                    "could not convert field {:?}: {}",
+                    &field[..],
+                    error
+                ));
+                log.insert(field, value);
+            }
+            route_error(named, error_event, reasons.join(", "));
         }
-        Some(Event::Log(log))
+
+        output.push(event);
     }
 }
 
@@ -104,11 +164,12 @@ mod tests {
     use crate::event::{LogEvent, Value};
     use crate::{
         topology::config::{TransformConfig, TransformContext},
+        transforms::Transform,
         Event,
     };
     use pretty_assertions::assert_eq;
 
-    fn parse_it(extra: &str) -> LogEvent {
+    fn build(extra: &str) -> (Event, Box<dyn Transform>) {
         let mut event = Event::from("dummy message");
         for &(key, value) in &[
             ("number", "1234"),
@@ -119,7 +180,7 @@ mod tests {
             event.as_mut_log().insert(key, value);
         }
 
-        let mut coercer = toml::from_str::<CoercerConfig>(&format!(
+        let coercer = toml::from_str::<CoercerConfig>(&format!(
             r#"{}
             [types]
             number = "int"
@@ -131,6 +192,11 @@ mod tests {
         .unwrap()
         .build(TransformContext::new_test())
         .unwrap();
+        (event, coercer)
+    }
+
+    fn parse_it(extra: &str) -> LogEvent {
+        let (event, mut coercer) = build(extra);
         coercer.transform(event).unwrap().into_log()
     }
 
@@ -163,4 +229,26 @@ mod tests {
 
         assert_eq!(log, expected.into_log());
     }
+
+    #[test]
+    fn routes_nonconvertible_fields_to_errors() {
+        let (event, mut coercer) = build("");
+
+        let mut output = Vec::new();
+        let mut named = Vec::new();
+        coercer.transform_outputs(&mut output, &mut named, event);
+
+        assert_eq!(output.len(), 1);
+        assert!(output[0].as_log().get(&"float".into()).is_none());
+
+        assert_eq!(named.len(), 1);
+        let (name, event) = &named[0];
+        assert_eq!(*name, "errors");
+        let log = event.as_log();
+        assert_eq!(log[&"number".into()], Value::Integer(1234));
+        assert_eq!(log[&"float".into()], Value::Bytes("broken".into()));
+        assert!(log[&"error_reason".into()]
+            .to_string_lossy()
+            .starts_with("could not convert field \"float\""));
+    }
 }
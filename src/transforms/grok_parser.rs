@@ -1,6 +1,6 @@
-use super::Transform;
+use super::{route_error, Transform, ERRORS_OUTPUT};
 use crate::{
-    event::{self, Event, PathComponent, PathIter},
+    event::{self, Event, LogEvent, PathComponent, PathIter},
     topology::config::{DataType, TransformConfig, TransformContext, TransformDescription},
     types::{parse_conversion_map_no_atoms, Conversion},
 };
@@ -69,6 +69,10 @@ impl TransformConfig for GrokParserConfig {
     fn transform_type(&self) -> &'static str {
         "grok_parser"
     }
+
+    fn named_outputs(&self) -> Vec<&'static str> {
+        vec![ERRORS_OUTPUT]
+    }
 }
 
 pub struct GrokParser {
@@ -79,53 +83,84 @@ pub struct GrokParser {
     paths: HashMap<String, Vec<PathComponent>>,
 }
 
-impl Transform for GrokParser {
-    fn transform(&mut self, event: Event) -> Option<Event> {
-        let mut event = event.into_log();
-        let value = event.get(&self.field).map(|s| s.to_string_lossy());
-
-        if let Some(value) = value {
-            if let Some(matches) = self.pattern.match_against(&value) {
-                let drop_field = self.drop_field && matches.get(&self.field).is_none();
-                for (name, value) in matches.iter() {
-                    let conv = self.types.get(name).unwrap_or(&Conversion::Bytes);
-                    match conv.convert(value.into()) {
-                        Ok(value) => {
-                            if let Some(path) = self.paths.get(name) {
-                                event.insert_path(path.to_vec(), value);
-                            } else {
-                                let path = PathIter::new(name).collect::<Vec<_>>();
-                                self.paths.insert(name.to_string(), path.clone());
-                                event.insert_path(path, value);
-                            }
-                        }
-                        Err(error) => {
-                            debug!(
-                                message = "Could not convert types.",
-                                %name,
-                                %error,
-                                rate_limit_secs = 30,
-                            );
-                        }
+impl GrokParser {
+    /// Inserts the fields captured by the pattern into `event`, returning why
+    /// nothing could be captured.
+    fn parse(&mut self, event: &mut LogEvent) -> Result<(), String> {
+        let value = match event.get(&self.field).map(|s| s.to_string_lossy()) {
+            Some(value) => value,
+            None => {
+                debug!(
+                    message = "Field does not exist.",
+                    field = self.field.as_ref(),
+                    rate_limit_secs = 30,
+                );
+                return Err(format!("field {:?} does not exist", &self.field[..]));
+            }
+        };
+
+        let matches = match self.pattern.match_against(&value) {
+            Some(matches) => matches,
+            None => {
+                debug!(message = "No fields captured from grok pattern.");
+                return Err("no fields captured from grok pattern".into());
+            }
+        };
+
+        let drop_field = self.drop_field && matches.get(&self.field).is_none();
+        for (name, value) in matches.iter() {
+            let conv = self.types.get(name).unwrap_or(&Conversion::Bytes);
+            match conv.convert(value.into()) {
+                Ok(value) => {
+                    if let Some(path) = self.paths.get(name) {
+                        event.insert_path(path.to_vec(), value);
+                    } else {
+                        let path = PathIter::new(name).collect::<Vec<_>>();
+                        self.paths.insert(name.to_string(), path.clone());
+                        event.insert_path(path, value);
                     }
                 }
-
-                if drop_field {
-                    event.remove(&self.field);
+                Err(error) => {
+                    debug!(
+                        message = "Could not convert types.",
+                        %name,
+                        %error,
+                        rate_limit_secs = 30,
+                    );
                 }
-            } else {
-                debug!(message = "No fields captured from grok pattern.");
             }
-        } else {
-            debug!(
-                message = "Field does not exist.",
-                field = self.field.as_ref(),
-                rate_limit_secs = 30,
-            );
         }
 
+        if drop_field {
+            event.remove(&self.field);
+        }
+
+        Ok(())
+    }
+}
+
+impl Transform for GrokParser {
+    fn transform(&mut self, event: Event) -> Option<Event> {
+        let mut event = event.into_log();
+        let _ = self.parse(&mut event);
         Some(Event::Log(event))
     }
+
+    fn transform_outputs(
+        &mut self,
+        output: &mut Vec<Event>,
+        named: &mut Vec<(&'static str, Event)>,
+        event: Event,
+    ) {
+        let mut event = event.into_log();
+        let result = self.parse(&mut event);
+        let event = Event::Log(event);
+
+        if let Err(reason) = result {
+            route_error(named, event.clone(), reason);
+        }
+        output.push(event);
+    }
 }
 
 #[cfg(test)]
@@ -205,6 +240,29 @@ mod tests {
             .is_empty());
     }
 
+    #[test]
+    fn grok_parser_routes_no_match_to_errors() {
+        let mut parser = GrokParserConfig {
+            pattern: "%{HTTPD_COMMONLOG}".into(),
+            ..Default::default()
+        }
+        .build(TransformContext::new_test())
+        .unwrap();
+
+        let mut output = Vec::new();
+        let mut named = Vec::new();
+        parser.transform_outputs(&mut output, &mut named, Event::from("not a log line"));
+
+        assert_eq!(output.len(), 1);
+        assert_eq!(named.len(), 1);
+        let (name, event) = &named[0];
+        assert_eq!(*name, "errors");
+        assert_eq!(
+            event.as_log()[&"error_reason".into()],
+            "no fields captured from grok pattern".into()
+        );
+    }
+
     #[test]
     fn grok_parser_can_not_drop_parsed_field() {
         let event = parse_log(
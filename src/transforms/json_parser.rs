@@ -1,4 +1,4 @@
-use super::Transform;
+use super::{route_error, Transform, ERRORS_OUTPUT};
 use crate::{
     event::{self, Event},
     internal_events::{JsonEventProcessed, JsonFailedParse},
@@ -41,6 +41,10 @@ impl TransformConfig for JsonParserConfig {
     fn transform_type(&self) -> &'static str {
         "json_parser"
     }
+
+    fn named_outputs(&self) -> Vec<&'static str> {
+        vec![ERRORS_OUTPUT]
+    }
 }
 
 #[derive(Debug)]
@@ -70,62 +74,82 @@ impl From<JsonParserConfig> for JsonParser {
     }
 }
 
-impl Transform for JsonParser {
-    fn transform(&mut self, mut event: Event) -> Option<Event> {
+impl JsonParser {
+    /// Parses the configured field into `event`, returning why it could not
+    /// be parsed.
+    fn parse(&self, event: &mut Event) -> Result<(), String> {
         let log = event.as_mut_log();
         let to_parse = log.get(&self.field).map(|s| s.as_bytes());
 
         emit!(JsonEventProcessed);
 
-        let parsed = to_parse
-            .and_then(|to_parse| {
-                serde_json::from_slice::<Value>(to_parse.as_ref())
-                    .map_err(|error| {
-                        emit!(JsonFailedParse {
-                            field: &self.field,
-                            error
-                        })
-                    })
-                    .ok()
-            })
-            .and_then(|value| {
-                if let Value::Object(object) = value {
-                    Some(object)
-                } else {
-                    None
-                }
+        let to_parse =
+            to_parse.ok_or_else(|| format!("field {:?} does not exist", &self.field[..]))?;
+        let value = serde_json::from_slice::<Value>(to_parse.as_ref()).map_err(|error| {
+            let reason = error.to_string();
+            emit!(JsonFailedParse {
+                field: &self.field,
+                error
             });
+            reason
+        })?;
+        let object = match value {
+            Value::Object(object) => object,
+            _ => return Err("parsed value is not an object".into()),
+        };
 
-        if let Some(object) = parsed {
-            match self.target_field {
-                Some(ref target_field) => {
-                    let contains_target = log.contains(&target_field);
-
-                    if contains_target && !self.overwrite_target {
-                        error!(message = "target field already exists", %target_field);
-                    } else {
-                        if self.drop_field {
-                            log.remove(&self.field);
-                        }
+        match self.target_field {
+            Some(ref target_field) => {
+                let contains_target = log.contains(&target_field);
 
-                        log.insert(&target_field, Value::Object(object));
-                    }
-                }
-                None => {
+                if contains_target && !self.overwrite_target {
+                    error!(message = "target field already exists", %target_field);
+                } else {
                     if self.drop_field {
                         log.remove(&self.field);
                     }
 
-                    for (key, value) in object {
-                        log.insert_flat(key, value);
-                    }
+                    log.insert(&target_field, Value::Object(object));
                 }
             }
-        } else if self.drop_invalid {
-            return None;
+            None => {
+                if self.drop_field {
+                    log.remove(&self.field);
+                }
+
+                for (key, value) in object {
+                    log.insert_flat(key, value);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Transform for JsonParser {
+    fn transform(&mut self, mut event: Event) -> Option<Event> {
+        match self.parse(&mut event) {
+            Err(_) if self.drop_invalid => None,
+            _ => Some(event),
         }
+    }
 
-        Some(event)
+    fn transform_outputs(
+        &mut self,
+        output: &mut Vec<Event>,
+        named: &mut Vec<(&'static str, Event)>,
+        mut event: Event,
+    ) {
+        match self.parse(&mut event) {
+            Ok(()) => output.push(event),
+            Err(reason) => {
+                if !self.drop_invalid {
+                    output.push(event.clone());
+                }
+                route_error(named, event, reason);
+            }
+        }
     }
 }
 
@@ -376,6 +400,48 @@ mod test {
         assert!(parser.transform(event).is_none());
     }
 
+    #[test]
+    fn json_parser_routes_invalid_to_errors() {
+        let mut parser = JsonParser::from(JsonParserConfig {
+            drop_invalid: true,
+            ..Default::default()
+        });
+
+        let mut output = Vec::new();
+        let mut named = Vec::new();
+        parser.transform_outputs(
+            &mut output,
+            &mut named,
+            Event::from(r#"{"greeting": "hello"}"#),
+        );
+        parser.transform_outputs(&mut output, &mut named, Event::from(r#""hello""#));
+
+        assert_eq!(output.len(), 1);
+        assert_eq!(output[0].as_log()[&"greeting".into()], "hello".into());
+
+        assert_eq!(named.len(), 1);
+        let (name, event) = &named[0];
+        assert_eq!(*name, "errors");
+        assert_eq!(
+            event.as_log()[&event::log_schema().message_key()],
+            r#""hello""#.into()
+        );
+        assert_eq!(
+            event.as_log()[&"error_reason".into()],
+            "parsed value is not an object".into()
+        );
+
+        // Without `drop_invalid` failed events are also passed through.
+        let mut parser = JsonParser::from(JsonParserConfig::default());
+        let mut output = Vec::new();
+        let mut named = Vec::new();
+        parser.transform_outputs(&mut output, &mut named, Event::from("{"));
+
+        assert_eq!(output.len(), 1);
+        assert!(output[0].as_log().get(&"error_reason".into()).is_none());
+        assert_eq!(named.len(), 1);
+    }
+
     #[test]
     fn json_parser_chained() {
         let mut parser1 = JsonParser::from(JsonParserConfig {
@@ -1,15 +1,19 @@
 use super::Transform;
 use crate::{
     event::metric::{Metric, MetricKind, MetricValue},
-    event::{self, Value},
-    template::{Template, TemplateError},
+    event::{
+        self,
+        sketch::{DDSketch, DEFAULT_RELATIVE_ACCURACY},
+        Value,
+    },
+    template::Template,
     topology::config::{DataType, TransformConfig, TransformContext, TransformDescription},
     Event,
 };
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
+use snafu::Snafu;
 use std::collections::BTreeMap;
-use std::convert::TryFrom;
 use string_cache::DefaultAtom as Atom;
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -22,34 +26,50 @@ pub struct LogToMetricConfig {
 #[serde(tag = "type", rename_all = "snake_case")]
 pub struct CounterConfig {
     field: Atom,
-    name: Option<Atom>,
+    name: Option<Template>,
+    namespace: Option<Template>,
     #[serde(default = "default_increment_by_value")]
     increment_by_value: bool,
-    tags: Option<IndexMap<Atom, String>>,
+    tags: Option<IndexMap<Atom, Template>>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub struct GaugeConfig {
     field: Atom,
-    name: Option<Atom>,
-    tags: Option<IndexMap<Atom, String>>,
+    name: Option<Template>,
+    namespace: Option<Template>,
+    tags: Option<IndexMap<Atom, Template>>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub struct SetConfig {
     field: Atom,
-    name: Option<Atom>,
-    tags: Option<IndexMap<Atom, String>>,
+    name: Option<Template>,
+    namespace: Option<Template>,
+    tags: Option<IndexMap<Atom, Template>>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub struct HistogramConfig {
     field: Atom,
-    name: Option<Atom>,
-    tags: Option<IndexMap<Atom, String>>,
+    name: Option<Template>,
+    namespace: Option<Template>,
+    tags: Option<IndexMap<Atom, Template>>,
+    /// Count the value into these buckets, in increasing order, instead of
+    /// emitting a distribution.
+    buckets: Option<Vec<f64>>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub struct SummaryConfig {
+    field: Atom,
+    name: Option<Template>,
+    namespace: Option<Template>,
+    tags: Option<IndexMap<Atom, Template>>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -59,6 +79,49 @@ pub enum MetricConfig {
     Histogram(HistogramConfig),
     Gauge(GaugeConfig),
     Set(SetConfig),
+    Summary(SummaryConfig),
+}
+
+impl MetricConfig {
+    fn field(&self) -> &Atom {
+        match self {
+            MetricConfig::Counter(counter) => &counter.field,
+            MetricConfig::Histogram(hist) => &hist.field,
+            MetricConfig::Gauge(gauge) => &gauge.field,
+            MetricConfig::Set(set) => &set.field,
+            MetricConfig::Summary(summary) => &summary.field,
+        }
+    }
+
+    fn name(&self) -> Option<&Template> {
+        match self {
+            MetricConfig::Counter(counter) => counter.name.as_ref(),
+            MetricConfig::Histogram(hist) => hist.name.as_ref(),
+            MetricConfig::Gauge(gauge) => gauge.name.as_ref(),
+            MetricConfig::Set(set) => set.name.as_ref(),
+            MetricConfig::Summary(summary) => summary.name.as_ref(),
+        }
+    }
+
+    fn namespace(&self) -> Option<&Template> {
+        match self {
+            MetricConfig::Counter(counter) => counter.namespace.as_ref(),
+            MetricConfig::Histogram(hist) => hist.namespace.as_ref(),
+            MetricConfig::Gauge(gauge) => gauge.namespace.as_ref(),
+            MetricConfig::Set(set) => set.namespace.as_ref(),
+            MetricConfig::Summary(summary) => summary.namespace.as_ref(),
+        }
+    }
+
+    fn tags(&self) -> Option<&IndexMap<Atom, Template>> {
+        match self {
+            MetricConfig::Counter(counter) => counter.tags.as_ref(),
+            MetricConfig::Histogram(hist) => hist.tags.as_ref(),
+            MetricConfig::Gauge(gauge) => gauge.tags.as_ref(),
+            MetricConfig::Set(set) => set.tags.as_ref(),
+            MetricConfig::Summary(summary) => summary.tags.as_ref(),
+        }
+    }
 }
 
 fn default_increment_by_value() -> bool {
@@ -69,6 +132,12 @@ pub struct LogToMetric {
     config: LogToMetricConfig,
 }
 
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("the buckets of {:?} must be in increasing order", field))]
+    UnsortedBuckets { field: String },
+}
+
 inventory::submit! {
     TransformDescription::new_without_default::<LogToMetricConfig>("log_to_metric")
 }
@@ -76,6 +145,20 @@ inventory::submit! {
 #[typetag::serde(name = "log_to_metric")]
 impl TransformConfig for LogToMetricConfig {
     fn build(&self, _cx: TransformContext) -> crate::Result<Box<dyn Transform>> {
+        for metric in &self.metrics {
+            if let MetricConfig::Histogram(HistogramConfig {
+                field,
+                buckets: Some(buckets),
+                ..
+            }) = metric
+            {
+                if !buckets.windows(2).all(|pair| pair[0] < pair[1]) {
+                    return Err(Box::new(BuildError::UnsortedBuckets {
+                        field: field.to_string(),
+                    }));
+                }
+            }
+        }
         Ok(Box::new(LogToMetric::new(self.clone())))
     }
 
@@ -100,24 +183,23 @@ impl LogToMetric {
 
 enum TransformError {
     FieldNotFound,
-    TemplateError(TemplateError),
     RenderError(String),
     ParseError(&'static str),
 }
 
-fn render_template(s: &str, event: &Event) -> Result<String, TransformError> {
-    let template = Template::try_from(s).map_err(TransformError::TemplateError)?;
-    let name = template.render(&event).map_err(|e| {
+fn render_template(template: &Template, event: &Event) -> Result<String, TransformError> {
+    let rendered = template.render(event).map_err(|e| {
         TransformError::RenderError(format!(
             "Keys ({:?}) do not exist on the event. Dropping event.",
             e
         ))
     })?;
-    Ok(String::from_utf8_lossy(&name.to_vec()).to_string())
+    Ok(String::from_utf8_lossy(&rendered).into_owned())
 }
 
+/// Tags whose template refers to fields missing from the event are left out.
 fn render_tags(
-    tags: &Option<IndexMap<Atom, String>>,
+    tags: Option<&IndexMap<Atom, Template>>,
     event: &Event,
 ) -> Option<BTreeMap<String, String>> {
     match tags {
@@ -138,6 +220,13 @@ fn render_tags(
     }
 }
 
+fn parse_value(value: &Value, name: &'static str) -> Result<f64, TransformError> {
+    value
+        .to_string_lossy()
+        .parse()
+        .map_err(|_| TransformError::ParseError(name))
+}
+
 fn to_metric(config: &MetricConfig, event: &Event) -> Result<Metric, TransformError> {
     let log = event.as_log();
 
@@ -146,96 +235,73 @@ fn to_metric(config: &MetricConfig, event: &Event) -> Result<Metric, TransformEr
         .and_then(Value::as_timestamp)
         .cloned();
 
-    match config {
+    let field = config.field();
+    let value = log.get(field).ok_or(TransformError::FieldNotFound)?;
+
+    let (kind, value) = match config {
         MetricConfig::Counter(counter) => {
-            let value = log
-                .get(&counter.field)
-                .ok_or(TransformError::FieldNotFound)?;
             let value = if counter.increment_by_value {
-                value
-                    .to_string_lossy()
-                    .parse()
-                    .map_err(|_| TransformError::ParseError("counter value"))?
+                parse_value(value, "counter value")?
             } else {
                 1.0
             };
-
-            let name = counter.name.as_ref().unwrap_or(&counter.field);
-            let name = render_template(&name, &event)?;
-
-            let tags = render_tags(&counter.tags, &event);
-
-            Ok(Metric {
-                name,
-                timestamp,
-                tags,
-                kind: MetricKind::Incremental,
-                value: MetricValue::Counter { value },
-            })
+            (MetricKind::Incremental, MetricValue::Counter { value })
         }
         MetricConfig::Histogram(hist) => {
-            let value = log.get(&hist.field).ok_or(TransformError::FieldNotFound)?;
-            let value = value
-                .to_string_lossy()
-                .parse()
-                .map_err(|_| TransformError::ParseError("histogram value"))?;
-
-            let name = hist.name.as_ref().unwrap_or(&hist.field);
-            let name = render_template(&name, &event)?;
-
-            let tags = render_tags(&hist.tags, &event);
-
-            Ok(Metric {
-                name,
-                timestamp,
-                tags,
-                kind: MetricKind::Incremental,
-                value: MetricValue::Distribution {
+            let value = parse_value(value, "histogram value")?;
+            let value = match &hist.buckets {
+                Some(buckets) => MetricValue::AggregatedHistogram {
+                    buckets: buckets.clone(),
+                    counts: buckets.iter().map(|b| (value <= *b) as u32).collect(),
+                    count: 1,
+                    sum: value,
+                },
+                None => MetricValue::Distribution {
                     values: vec![value],
                     sample_rates: vec![1],
                 },
-            })
+            };
+            (MetricKind::Incremental, value)
         }
-        MetricConfig::Gauge(gauge) => {
-            let value = log.get(&gauge.field).ok_or(TransformError::FieldNotFound)?;
-            let value = value
-                .to_string_lossy()
-                .parse()
-                .map_err(|_| TransformError::ParseError("gauge value"))?;
-
-            let name = gauge.name.as_ref().unwrap_or(&gauge.field);
-            let name = render_template(&name, &event)?;
-
-            let tags = render_tags(&gauge.tags, &event);
-
-            Ok(Metric {
-                name,
-                timestamp,
-                tags,
-                kind: MetricKind::Absolute,
-                value: MetricValue::Gauge { value },
-            })
+        MetricConfig::Summary(_) => {
+            let value = parse_value(value, "summary value")?;
+            let mut sketch = DDSketch::new(DEFAULT_RELATIVE_ACCURACY);
+            sketch.insert(value);
+            (MetricKind::Incremental, MetricValue::Sketch(sketch))
+        }
+        MetricConfig::Gauge(_) => {
+            let value = parse_value(value, "gauge value")?;
+            (MetricKind::Absolute, MetricValue::Gauge { value })
         }
-        MetricConfig::Set(set) => {
-            let value = log.get(&set.field).ok_or(TransformError::FieldNotFound)?;
+        MetricConfig::Set(_) => {
             let value = value.to_string_lossy();
-
-            let name = set.name.as_ref().unwrap_or(&set.field);
-            let name = render_template(&name, &event)?;
-
-            let tags = render_tags(&set.tags, &event);
-
-            Ok(Metric {
-                name,
-                timestamp,
-                tags,
-                kind: MetricKind::Incremental,
-                value: MetricValue::Set {
+            (
+                MetricKind::Incremental,
+                MetricValue::Set {
                     values: vec![value].into_iter().collect(),
                 },
-            })
+            )
         }
-    }
+    };
+
+    let name = match config.name() {
+        Some(name) => render_template(name, event)?,
+        None => field.to_string(),
+    };
+    let namespace = match config.namespace() {
+        Some(namespace) => Some(render_template(namespace, event)?),
+        None => None,
+    };
+    let tags = render_tags(config.tags(), event);
+
+    Ok(Metric {
+        name,
+        namespace,
+        timestamp,
+        tags,
+        kind,
+        value,
+    })
 }
 
 impl Transform for LogToMetric {
@@ -259,9 +325,6 @@ impl Transform for LogToMetric {
                 Err(TransformError::RenderError(error)) => {
                     debug!(message = "Unable to render.", %error, rate_limit_secs = 30);
                 }
-                Err(TransformError::TemplateError(error)) => {
-                    debug!(message = "failed to parse.", %error, rate_limit_secs = 30);
-                }
             }
         }
     }
@@ -272,7 +335,8 @@ mod tests {
     use super::{LogToMetric, LogToMetricConfig};
     use crate::{
         event::metric::{Metric, MetricKind, MetricValue},
-        event::{self, Event},
+        event::{self, sketch::DDSketch, Event},
+        topology::config::{TransformConfig, TransformContext},
         transforms::Transform,
     };
     use chrono::{offset::TimeZone, DateTime, Utc};
@@ -311,6 +375,7 @@ mod tests {
             metric.into_metric(),
             Metric {
                 name: "status".into(),
+                namespace: None,
                 timestamp: Some(ts()),
                 tags: None,
                 kind: MetricKind::Incremental,
@@ -342,6 +407,7 @@ mod tests {
             metric.into_metric(),
             Metric {
                 name: "http_requests_total".into(),
+                namespace: None,
                 timestamp: Some(ts()),
                 tags: Some(
                     vec![
@@ -377,6 +443,7 @@ mod tests {
             metric.into_metric(),
             Metric {
                 name: "exception_total".into(),
+                namespace: None,
                 timestamp: Some(ts()),
                 tags: None,
                 kind: MetricKind::Incremental,
@@ -423,6 +490,7 @@ mod tests {
             metric.into_metric(),
             Metric {
                 name: "amount_total".into(),
+                namespace: None,
                 timestamp: Some(ts()),
                 tags: None,
                 kind: MetricKind::Incremental,
@@ -450,6 +518,7 @@ mod tests {
             metric.into_metric(),
             Metric {
                 name: "memory_rss_bytes".into(),
+                namespace: None,
                 timestamp: Some(ts()),
                 tags: None,
                 kind: MetricKind::Absolute,
@@ -524,6 +593,7 @@ mod tests {
             output.pop().unwrap().into_metric(),
             Metric {
                 name: "exception_total".into(),
+                namespace: None,
                 timestamp: Some(ts()),
                 tags: None,
                 kind: MetricKind::Incremental,
@@ -534,6 +604,7 @@ mod tests {
             output.pop().unwrap().into_metric(),
             Metric {
                 name: "status".into(),
+                namespace: None,
                 timestamp: Some(ts()),
                 tags: None,
                 kind: MetricKind::Incremental,
@@ -577,6 +648,7 @@ mod tests {
             output.pop().unwrap().into_metric(),
             Metric {
                 name: "xyz_exception_total".into(),
+                namespace: None,
                 timestamp: Some(ts()),
                 tags: None,
                 kind: MetricKind::Incremental,
@@ -587,6 +659,7 @@ mod tests {
             output.pop().unwrap().into_metric(),
             Metric {
                 name: "local_abc_status_set".into(),
+                namespace: None,
                 timestamp: Some(ts()),
                 tags: None,
                 kind: MetricKind::Incremental,
@@ -616,6 +689,7 @@ mod tests {
             metric.into_metric(),
             Metric {
                 name: "unique_user_ip".into(),
+                namespace: None,
                 timestamp: Some(ts()),
                 tags: None,
                 kind: MetricKind::Incremental,
@@ -644,6 +718,7 @@ mod tests {
             metric.into_metric(),
             Metric {
                 name: "response_time".into(),
+                namespace: None,
                 timestamp: Some(ts()),
                 tags: None,
                 kind: MetricKind::Incremental,
@@ -654,4 +729,132 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn response_time_histogram_buckets() {
+        let config = parse_config(
+            r#"
+            [[metrics]]
+            type = "histogram"
+            field = "response_time"
+            buckets = [1.0, 5.0, 10.0]
+            "#,
+        );
+
+        let event = create_event("response_time", "2.5");
+        let mut transform = LogToMetric::new(config);
+        let metric = transform.transform(event).unwrap();
+
+        assert_eq!(
+            metric.into_metric().value,
+            MetricValue::AggregatedHistogram {
+                buckets: vec![1.0, 5.0, 10.0],
+                counts: vec![0, 1, 1],
+                count: 1,
+                sum: 2.5,
+            }
+        );
+    }
+
+    #[test]
+    fn response_time_summary() {
+        let config = parse_config(
+            r#"
+            [[metrics]]
+            type = "summary"
+            field = "response_time"
+            "#,
+        );
+
+        let event = create_event("response_time", "2.5");
+        let mut transform = LogToMetric::new(config);
+        let metric = transform.transform(event).unwrap();
+
+        assert_eq!(
+            metric.into_metric(),
+            Metric {
+                name: "response_time".into(),
+                namespace: None,
+                timestamp: Some(ts()),
+                tags: None,
+                kind: MetricKind::Incremental,
+                value: MetricValue::Sketch(DDSketch::from_distribution(&[2.5], &[1], 0.01)),
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_unsorted_buckets() {
+        let config = parse_config(
+            r#"
+            [[metrics]]
+            type = "histogram"
+            field = "response_time"
+            buckets = [1.0, 10.0, 5.0]
+            "#,
+        );
+        assert!(config.build(TransformContext::new_test()).is_err());
+    }
+
+    #[test]
+    fn templated_name_and_namespace() {
+        let config = parse_config(
+            r#"
+            [[metrics]]
+            type = "counter"
+            field = "status"
+            name = "{{endpoint}}_requests_total"
+            namespace = "{{service}}"
+            tags = {code = "{{status}}", region = "{{region}}"}
+            "#,
+        );
+
+        let mut event = create_event("status", "200");
+        event.as_mut_log().insert("endpoint", "login");
+        event.as_mut_log().insert("service", "auth");
+        let mut transform = LogToMetric::new(config);
+        let metric = transform.transform(event).unwrap().into_metric();
+
+        assert_eq!(metric.name, "login_requests_total");
+        assert_eq!(metric.namespace, Some("auth".into()));
+        assert_eq!(
+            metric.tags,
+            Some(
+                vec![("code".to_owned(), "200".to_owned())]
+                    .into_iter()
+                    .collect()
+            )
+        );
+    }
+
+    #[test]
+    fn missing_namespace_field() {
+        let config = parse_config(
+            r#"
+            [[metrics]]
+            type = "counter"
+            field = "status"
+            namespace = "{{service}}"
+            "#,
+        );
+
+        let event = create_event("status", "200");
+        let mut transform = LogToMetric::new(config);
+
+        assert!(transform.transform(event).is_none());
+    }
+
+    #[test]
+    fn rejects_invalid_templates() {
+        let config = toml::from_str::<LogToMetricConfig>(
+            r#"
+            [[metrics]]
+            type = "counter"
+            field = "status"
+            name = "requests_%E"
+            "#,
+        );
+
+        assert!(config.is_err());
+    }
 }
@@ -1,5 +1,8 @@
 use super::util::{table_to_set, table_to_timestamp, timestamp_to_table};
-use crate::event::metric::{Metric, MetricKind, MetricValue};
+use crate::event::{
+    metric::{Metric, MetricKind, MetricValue},
+    sketch::{is_valid_relative_accuracy, DDSketch, MIN_RELATIVE_ACCURACY},
+};
 use rlua::prelude::*;
 use std::collections::BTreeMap;
 
@@ -34,6 +37,9 @@ impl<'a> ToLua<'a> for Metric {
         let tbl = ctx.create_table()?;
 
         tbl.set("name", self.name)?;
+        if let Some(namespace) = self.namespace {
+            tbl.set("namespace", namespace)?;
+        }
         if let Some(ts) = self.timestamp {
             tbl.set("timestamp", timestamp_to_table(ctx, ts)?)?;
         }
@@ -93,6 +99,18 @@ impl<'a> ToLua<'a> for Metric {
                 aggregated_summary.set("sum", sum)?;
                 tbl.set("aggregated_summary", aggregated_summary)?;
             }
+            MetricValue::Sketch(sketch) => {
+                let table = ctx.create_table()?;
+                table.set("relative_accuracy", sketch.relative_accuracy())?;
+                table.set("positive", sketch.positive_bins().clone())?;
+                table.set("negative", sketch.negative_bins().clone())?;
+                table.set("zero_count", sketch.zero_count())?;
+                table.set("count", sketch.count())?;
+                table.set("sum", sketch.sum())?;
+                table.set("min", sketch.min())?;
+                table.set("max", sketch.max())?;
+                tbl.set("sketch", table)?;
+            }
         }
 
         Ok(LuaValue::Table(tbl))
@@ -113,6 +131,7 @@ impl<'a> FromLua<'a> for Metric {
         };
 
         let name: String = table.get("name")?;
+        let namespace: Option<String> = table.get("namespace")?;
         let timestamp = table
             .get::<_, Option<LuaTable>>("timestamp")?
             .map(table_to_timestamp)
@@ -159,16 +178,50 @@ impl<'a> FromLua<'a> for Metric {
                 count: aggregated_summary.get("count")?,
                 sum: aggregated_summary.get("sum")?,
             }
+        } else if let Some(sketch) = table.get::<_, Option<LuaTable>>("sketch")? {
+            let relative_accuracy: f64 = sketch.get("relative_accuracy")?;
+            if !is_valid_relative_accuracy(relative_accuracy) {
+                return Err(LuaError::FromLuaConversionError {
+                    from: value.type_name(),
+                    to: "Metric",
+                    message: Some(format!(
+                        "Sketch relative accuracy should be within [{}, 1)",
+                        MIN_RELATIVE_ACCURACY
+                    )),
+                });
+            }
+            let positive: Option<BTreeMap<i32, u64>> = sketch.get("positive")?;
+            let negative: Option<BTreeMap<i32, u64>> = sketch.get("negative")?;
+            let zero_count: Option<u64> = sketch.get("zero_count")?;
+            // Empty sketches have no bounds.
+            let min: Option<f64> = sketch.get("min")?;
+            let max: Option<f64> = sketch.get("max")?;
+            let sketch = DDSketch::from_parts(
+                relative_accuracy,
+                positive.unwrap_or_default(),
+                negative.unwrap_or_default(),
+                zero_count.unwrap_or_default(),
+                sketch.get("sum")?,
+                min.unwrap_or_default(),
+                max.unwrap_or_default(),
+            )
+            .ok_or_else(|| LuaError::FromLuaConversionError {
+                from: value.type_name(),
+                to: "Metric",
+                message: Some("Sketch counts overflow".to_string()),
+            })?;
+            MetricValue::Sketch(sketch)
         } else {
             return Err(LuaError::FromLuaConversionError {
                 from: value.type_name(),
                 to: "Metric",
-                message: Some("Cannot find metric value, expected presence one of \"counter\", \"gauge\", \"set\", \"distribution\", \"aggregated_histogram\", \"aggregated_summary\"".to_string()),
+                message: Some("Cannot find metric value, expected presence one of \"counter\", \"gauge\", \"set\", \"distribution\", \"aggregated_histogram\", \"aggregated_summary\", \"sketch\"".to_string()),
             });
         };
 
         Ok(Metric {
             name,
+            namespace,
             timestamp,
             tags,
             kind,
@@ -198,6 +251,7 @@ mod test {
     fn to_lua_counter_full() {
         let metric = Metric {
             name: "example counter".into(),
+            namespace: Some("example_namespace".into()),
             timestamp: Some(Utc.ymd(2018, 11, 14).and_hms_nano(8, 9, 10, 11)),
             tags: Some(
                 vec![("example tag".to_string(), "example value".to_string())]
@@ -210,6 +264,7 @@ mod test {
         let assertions = vec![
             "type(metric) == 'table'",
             "metric.name == 'example counter'",
+            "metric.namespace == 'example_namespace'",
             "type(metric.timestamp) == 'table'",
             "metric.timestamp.year == 2018",
             "metric.timestamp.month == 11",
@@ -230,6 +285,7 @@ mod test {
     fn to_lua_counter_minimal() {
         let metric = Metric {
             name: "example counter".into(),
+            namespace: None,
             timestamp: None,
             tags: None,
             kind: MetricKind::Absolute,
@@ -248,6 +304,7 @@ mod test {
     fn to_lua_gauge() {
         let metric = Metric {
             name: "example gauge".into(),
+            namespace: None,
             timestamp: None,
             tags: None,
             kind: MetricKind::Absolute,
@@ -261,6 +318,7 @@ mod test {
     fn to_lua_set() {
         let metric = Metric {
             name: "example set".into(),
+            namespace: None,
             timestamp: None,
             tags: None,
             kind: MetricKind::Incremental,
@@ -284,6 +342,7 @@ mod test {
     fn to_lua_distribution() {
         let metric = Metric {
             name: "example distribution".into(),
+            namespace: None,
             timestamp: None,
             tags: None,
             kind: MetricKind::Incremental,
@@ -308,6 +367,7 @@ mod test {
     fn to_lua_aggregated_histogram() {
         let metric = Metric {
             name: "example histogram".into(),
+            namespace: None,
             timestamp: None,
             tags: None,
             kind: MetricKind::Incremental,
@@ -336,6 +396,7 @@ mod test {
     fn to_lua_aggregated_summary() {
         let metric = Metric {
             name: "example summary".into(),
+            namespace: None,
             timestamp: None,
             tags: None,
             kind: MetricKind::Incremental,
@@ -358,6 +419,30 @@ mod test {
         assert_metric(metric, assertions)
     }
 
+    #[test]
+    fn to_lua_sketch() {
+        let metric = Metric {
+            name: "example sketch".into(),
+            namespace: None,
+            timestamp: None,
+            tags: None,
+            kind: MetricKind::Incremental,
+            value: MetricValue::Sketch(DDSketch::from_distribution(&[1.0, 0.0], &[3, 1], 0.01)),
+        };
+        let assertions = vec![
+            "type(metric.sketch) == 'table'",
+            "metric.sketch.relative_accuracy == 0.01",
+            "metric.sketch.positive[0] == 3",
+            "next(metric.sketch.negative) == nil",
+            "metric.sketch.zero_count == 1",
+            "metric.sketch.count == 4",
+            "metric.sketch.sum == 3",
+            "metric.sketch.min == 0",
+            "metric.sketch.max == 1",
+        ];
+        assert_metric(metric, assertions)
+    }
+
     #[test]
     fn from_lua_counter_minimal() {
         let value = r#"{
@@ -368,6 +453,7 @@ mod test {
         }"#;
         let expected = Metric {
             name: "example counter".into(),
+            namespace: None,
             timestamp: None,
             tags: None,
             kind: MetricKind::Absolute,
@@ -382,6 +468,7 @@ mod test {
     fn from_lua_counter_full() {
         let value = r#"{
             name = "example counter",
+            namespace = "example_namespace",
             timestamp = {
                 year = 2018,
                 month = 11,
@@ -400,6 +487,7 @@ mod test {
         }"#;
         let expected = Metric {
             name: "example counter".into(),
+            namespace: Some("example_namespace".into()),
             timestamp: Some(Utc.ymd(2018, 11, 14).and_hms(8, 9, 10)),
             tags: Some(
                 vec![("example tag".to_string(), "example value".to_string())]
@@ -424,6 +512,7 @@ mod test {
         }"#;
         let expected = Metric {
             name: "example gauge".into(),
+            namespace: None,
             timestamp: None,
             tags: None,
             kind: MetricKind::Absolute,
@@ -444,6 +533,7 @@ mod test {
         }"#;
         let expected = Metric {
             name: "example set".into(),
+            namespace: None,
             timestamp: None,
             tags: None,
             kind: MetricKind::Absolute,
@@ -469,6 +559,7 @@ mod test {
         }"#;
         let expected = Metric {
             name: "example distribution".into(),
+            namespace: None,
             timestamp: None,
             tags: None,
             kind: MetricKind::Absolute,
@@ -494,6 +585,7 @@ mod test {
         }"#;
         let expected = Metric {
             name: "example histogram".into(),
+            namespace: None,
             timestamp: None,
             tags: None,
             kind: MetricKind::Absolute,
@@ -522,6 +614,7 @@ mod test {
         }"#;
         let expected = Metric {
             name: "example summary".into(),
+            namespace: None,
             timestamp: None,
             tags: None,
             kind: MetricKind::Absolute,
@@ -536,4 +629,30 @@ mod test {
             assert_eq!(ctx.load(value).eval::<Metric>().unwrap(), expected);
         });
     }
+
+    #[test]
+    fn from_lua_sketch() {
+        let value = r#"{
+            name = "example sketch",
+            sketch = {
+                relative_accuracy = 0.01,
+                positive = { [0] = 3 },
+                zero_count = 1,
+                sum = 3.0,
+                min = 0.0,
+                max = 1.0
+            }
+        }"#;
+        let expected = Metric {
+            name: "example sketch".into(),
+            namespace: None,
+            timestamp: None,
+            tags: None,
+            kind: MetricKind::Absolute,
+            value: MetricValue::Sketch(DDSketch::from_distribution(&[1.0, 0.0], &[3, 1], 0.01)),
+        };
+        Lua::new().context(|ctx| {
+            assert_eq!(ctx.load(value).eval::<Metric>().unwrap(), expected);
+        });
+    }
 }
@@ -0,0 +1,355 @@
+use super::Transform;
+use crate::{
+    event::{
+        self,
+        metric::{Metric, MetricKind, MetricValue},
+        Event, LogEvent, Value,
+    },
+    topology::config::{DataType, TransformConfig, TransformContext, TransformDescription},
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct MetricToLogConfig {
+    pub host_tag: Option<String>,
+}
+
+pub struct MetricToLog {
+    host_tag: String,
+}
+
+inventory::submit! {
+    TransformDescription::new::<MetricToLogConfig>("metric_to_log")
+}
+
+#[typetag::serde(name = "metric_to_log")]
+impl TransformConfig for MetricToLogConfig {
+    fn build(&self, _cx: TransformContext) -> crate::Result<Box<dyn Transform>> {
+        Ok(Box::new(MetricToLog::new(self.host_tag.clone())))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Metric
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn transform_type(&self) -> &'static str {
+        "metric_to_log"
+    }
+}
+
+impl MetricToLog {
+    pub fn new(host_tag: Option<String>) -> Self {
+        Self {
+            host_tag: host_tag.unwrap_or_else(|| event::log_schema().host_key().to_string()),
+        }
+    }
+}
+
+impl Transform for MetricToLog {
+    fn transform(&mut self, event: Event) -> Option<Event> {
+        Some(Event::Log(metric_to_log(
+            event.into_metric(),
+            &self.host_tag,
+        )))
+    }
+}
+
+/// Renders `metric` as a log with its `name`, `namespace`, `tags`, `kind` and
+/// timestamp, plus a field named after the type of its value holding the
+/// value itself. The value of the `host_tag` tag is copied to the host field.
+fn metric_to_log(metric: Metric, host_tag: &str) -> LogEvent {
+    let mut log = LogEvent::default();
+
+    log.insert_flat("name", metric.name);
+    if let Some(namespace) = metric.namespace {
+        log.insert_flat("namespace", namespace);
+    }
+    log.insert(
+        event::log_schema().timestamp_key(),
+        metric.timestamp.unwrap_or_else(Utc::now),
+    );
+    if let Some(tags) = metric.tags {
+        if let Some(host) = tags.get(host_tag) {
+            log.insert(event::log_schema().host_key(), host.clone());
+        }
+        let tags: BTreeMap<String, Value> = tags
+            .into_iter()
+            .map(|(key, value)| (key, value.into()))
+            .collect();
+        log.insert_flat("tags", tags);
+    }
+    log.insert_flat(
+        "kind",
+        match metric.kind {
+            MetricKind::Incremental => "incremental",
+            MetricKind::Absolute => "absolute",
+        },
+    );
+
+    let (name, value) = render_value(metric.value);
+    log.insert_flat(name, value);
+    log
+}
+
+fn render_value(value: MetricValue) -> (&'static str, Value) {
+    match value {
+        MetricValue::Counter { value } => ("counter", object(vec![("value", value.into())])),
+        MetricValue::Gauge { value } => ("gauge", object(vec![("value", value.into())])),
+        MetricValue::Set { values } => (
+            "set",
+            object(vec![("values", array(values.into_iter().map(Value::from)))]),
+        ),
+        MetricValue::Distribution {
+            values,
+            sample_rates,
+        } => (
+            "distribution",
+            object(vec![
+                ("values", array(values.into_iter().map(Value::from))),
+                (
+                    "sample_rates",
+                    array(
+                        sample_rates
+                            .into_iter()
+                            .map(|rate| Value::from(rate as i64)),
+                    ),
+                ),
+            ]),
+        ),
+        MetricValue::AggregatedHistogram {
+            buckets,
+            counts,
+            count,
+            sum,
+        } => (
+            "aggregated_histogram",
+            object(vec![
+                (
+                    "buckets",
+                    array(buckets.into_iter().zip(counts.into_iter()).map(
+                        |(upper_limit, count)| {
+                            object(vec![
+                                ("upper_limit", upper_limit.into()),
+                                ("count", (count as i64).into()),
+                            ])
+                        },
+                    )),
+                ),
+                ("count", (count as i64).into()),
+                ("sum", sum.into()),
+            ]),
+        ),
+        MetricValue::AggregatedSummary {
+            quantiles,
+            values,
+            count,
+            sum,
+        } => (
+            "aggregated_summary",
+            object(vec![
+                (
+                    "quantiles",
+                    array(quantiles.into_iter().zip(values.into_iter()).map(
+                        |(quantile, value)| {
+                            object(vec![("quantile", quantile.into()), ("value", value.into())])
+                        },
+                    )),
+                ),
+                ("count", (count as i64).into()),
+                ("sum", sum.into()),
+            ]),
+        ),
+        MetricValue::Sketch(sketch) => {
+            let mut fields = vec![
+                ("count", (sketch.count() as i64).into()),
+                ("sum", sketch.sum().into()),
+            ];
+            if let (Some(min), Some(max)) = (sketch.min(), sketch.max()) {
+                fields.push(("min", min.into()));
+                fields.push(("max", max.into()));
+            }
+            ("sketch", object(fields))
+        }
+    }
+}
+
+fn object(fields: Vec<(&str, Value)>) -> Value {
+    fields
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect::<BTreeMap<_, _>>()
+        .into()
+}
+
+fn array(values: impl Iterator<Item = Value>) -> Value {
+    values.collect::<Vec<_>>().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::sketch::DDSketch;
+    use chrono::{offset::TimeZone, DateTime};
+    use pretty_assertions::assert_eq;
+    use std::collections::BTreeSet;
+
+    fn ts() -> DateTime<Utc> {
+        Utc.ymd(2018, 11, 14).and_hms_nano(8, 9, 10, 11)
+    }
+
+    fn tags() -> BTreeMap<String, String> {
+        vec![
+            ("code".to_owned(), "200".to_owned()),
+            ("host".to_owned(), "localhost".to_owned()),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    fn transform(host_tag: Option<&str>, value: MetricValue) -> BTreeMap<String, Value> {
+        let metric = Metric {
+            name: "requests".into(),
+            namespace: None,
+            timestamp: Some(ts()),
+            tags: Some(tags()),
+            kind: MetricKind::Absolute,
+            value,
+        };
+        MetricToLog::new(host_tag.map(Into::into))
+            .transform(Event::Metric(metric))
+            .unwrap()
+            .into_log()
+            .all_fields()
+            .map(|(key, value)| (key, value.clone()))
+            .collect()
+    }
+
+    fn fields(fields: Vec<(&str, Value)>) -> BTreeMap<String, Value> {
+        fields
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect()
+    }
+
+    #[test]
+    fn transform_counter() {
+        let log = transform(None, MetricValue::Counter { value: 1.0 });
+
+        assert_eq!(
+            log,
+            fields(vec![
+                ("counter.value", 1.0.into()),
+                ("host", "localhost".into()),
+                ("kind", "absolute".into()),
+                ("name", "requests".into()),
+                ("tags.code", "200".into()),
+                ("tags.host", "localhost".into()),
+                ("timestamp", ts().into()),
+            ])
+        );
+    }
+
+    #[test]
+    fn transform_host_tag() {
+        let log = transform(Some("code"), MetricValue::Gauge { value: 1.0 });
+
+        assert_eq!(log["host"], "200".into());
+        assert_eq!(log["gauge.value"], 1.0.into());
+    }
+
+    #[test]
+    fn transform_set() {
+        let values: BTreeSet<_> = vec!["one".to_owned(), "two".to_owned()]
+            .into_iter()
+            .collect();
+        let log = transform(None, MetricValue::Set { values });
+
+        assert_eq!(log["set.values[0]"], "one".into());
+        assert_eq!(log["set.values[1]"], "two".into());
+    }
+
+    #[test]
+    fn transform_distribution() {
+        let log = transform(
+            None,
+            MetricValue::Distribution {
+                values: vec![1.0, 2.0],
+                sample_rates: vec![10, 20],
+            },
+        );
+
+        assert_eq!(log["distribution.values[1]"], 2.0.into());
+        assert_eq!(log["distribution.sample_rates[1]"], 20.into());
+    }
+
+    #[test]
+    fn transform_histogram() {
+        let log = transform(
+            None,
+            MetricValue::AggregatedHistogram {
+                buckets: vec![1.0, 2.0],
+                counts: vec![10, 20],
+                count: 30,
+                sum: 50.0,
+            },
+        );
+
+        assert_eq!(
+            log.into_iter()
+                .filter(|(key, _)| key.starts_with("aggregated_histogram."))
+                .collect::<Vec<_>>(),
+            vec![
+                ("aggregated_histogram.buckets[0].count".into(), 10.into()),
+                (
+                    "aggregated_histogram.buckets[0].upper_limit".into(),
+                    1.0.into()
+                ),
+                ("aggregated_histogram.buckets[1].count".into(), 20.into()),
+                (
+                    "aggregated_histogram.buckets[1].upper_limit".into(),
+                    2.0.into()
+                ),
+                ("aggregated_histogram.count".into(), 30.into()),
+                ("aggregated_histogram.sum".into(), 50.0.into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn transform_summary() {
+        let log = transform(
+            None,
+            MetricValue::AggregatedSummary {
+                quantiles: vec![0.5, 0.99],
+                values: vec![10.0, 40.0],
+                count: 30,
+                sum: 50.0,
+            },
+        );
+
+        assert_eq!(log["aggregated_summary.quantiles[1].quantile"], 0.99.into());
+        assert_eq!(log["aggregated_summary.quantiles[1].value"], 40.0.into());
+        assert_eq!(log["aggregated_summary.count"], 30.into());
+        assert_eq!(log["aggregated_summary.sum"], 50.0.into());
+    }
+
+    #[test]
+    fn transform_sketch() {
+        let mut sketch = DDSketch::new(0.01);
+        sketch.insert(1.0);
+        sketch.insert(3.0);
+        let log = transform(None, MetricValue::Sketch(sketch));
+
+        assert_eq!(log["sketch.count"], 2.into());
+        assert_eq!(log["sketch.sum"], 4.0.into());
+        assert_eq!(log["sketch.min"], 1.0.into());
+        assert_eq!(log["sketch.max"], 3.0.into());
+    }
+}
@@ -7,6 +7,8 @@ mod util;
 pub mod add_fields;
 #[cfg(feature = "transforms-add_tags")]
 pub mod add_tags;
+#[cfg(feature = "transforms-aggregate")]
+pub mod aggregate;
 #[cfg(feature = "transforms-ansi_stripper")]
 pub mod ansi_stripper;
 #[cfg(feature = "transforms-aws_ec2_metadata")]
@@ -35,6 +37,8 @@ pub mod logfmt_parser;
 pub mod lua;
 #[cfg(feature = "transforms-merge")]
 pub mod merge;
+#[cfg(feature = "transforms-metric_to_log")]
+pub mod metric_to_log;
 #[cfg(feature = "transforms-reduce")]
 pub mod reduce;
 #[cfg(feature = "transforms-regex_parser")]
@@ -45,6 +49,8 @@ pub mod remove_fields;
 pub mod remove_tags;
 #[cfg(feature = "transforms-rename_fields")]
 pub mod rename_fields;
+#[cfg(feature = "transforms-route")]
+pub mod route;
 #[cfg(feature = "transforms-sampler")]
 pub mod sampler;
 #[cfg(feature = "transforms-split")]
@@ -53,6 +59,8 @@ pub mod split;
 pub mod swimlanes;
 #[cfg(feature = "transforms-tag_cardinality_limit")]
 pub mod tag_cardinality_limit;
+#[cfg(feature = "transforms-throttle")]
+pub mod throttle;
 #[cfg(feature = "transforms-tokenizer")]
 pub mod tokenizer;
 #[cfg(feature = "wasm")]
@@ -60,6 +68,12 @@ pub mod wasm;
 
 use futures01::Stream;
 
+/// Secondary output parsing transforms send events they failed to process to.
+pub const ERRORS_OUTPUT: &str = "errors";
+
+/// Field holding why an event was sent to the errors output.
+pub const ERROR_REASON_FIELD: &str = "error_reason";
+
 pub trait Transform: Send {
     fn transform(&mut self, event: Event) -> Option<Event>;
 
@@ -69,6 +83,42 @@ pub trait Transform: Send {
         }
     }
 
+    /// Like `transform_into`, additionally sending events to the secondary
+    /// outputs declared by `TransformConfig::named_outputs`.
+    fn transform_outputs(
+        &mut self,
+        output: &mut Vec<Event>,
+        _named: &mut Vec<(&'static str, Event)>,
+        event: Event,
+    ) {
+        self.transform_into(output, event);
+    }
+
+    /// Like `transform_stream`, tagging every event with the named output it
+    /// is sent to, or `None` for the primary output.
+    fn transform_stream_outputs(
+        self: Box<Self>,
+        input_rx: Box<dyn Stream<Item = Event, Error = ()> + Send>,
+    ) -> Box<dyn Stream<Item = (Option<&'static str>, Event), Error = ()> + Send>
+    where
+        Self: 'static,
+    {
+        let mut me = self;
+        Box::new(
+            input_rx
+                .map(move |event| {
+                    let mut output = Vec::with_capacity(1);
+                    let mut named = Vec::new();
+                    me.transform_outputs(&mut output, &mut named, event);
+
+                    let output = output.into_iter().map(|event| (None, event));
+                    let named = named.into_iter().map(|(name, event)| (Some(name), event));
+                    futures01::stream::iter_ok(output.chain(named))
+                })
+                .flatten(),
+        )
+    }
+
     fn transform_stream(
         self: Box<Self>,
         input_rx: Box<dyn Stream<Item = Event, Error = ()> + Send>,
@@ -89,6 +139,20 @@ pub trait Transform: Send {
     }
 }
 
+/// Sends `event` to the errors output, recording why it failed.
+#[cfg(any(
+    feature = "transforms-coercer",
+    feature = "transforms-grok_parser",
+    feature = "transforms-json_parser",
+    feature = "transforms-regex_parser"
+))]
+fn route_error(named: &mut Vec<(&'static str, Event)>, mut event: Event, reason: String) {
+    if let Event::Log(log) = &mut event {
+        log.insert(ERROR_REASON_FIELD, reason);
+    }
+    named.push((ERRORS_OUTPUT, event));
+}
+
 #[derive(Debug, Snafu)]
 enum BuildError {
     #[snafu(display("Invalid regular expression: {}", source))]
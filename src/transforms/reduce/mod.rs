@@ -2,7 +2,7 @@ use super::Transform;
 use crate::{
     conditions::{AnyCondition, Condition},
     event::discriminant::Discriminant,
-    event::{Event, LogEvent},
+    event::{Event, EventFinalizers, LogEvent},
     topology::config::{DataType, TransformConfig, TransformContext, TransformDescription},
 };
 use async_stream::stream;
@@ -71,12 +71,14 @@ impl TransformConfig for ReduceConfig {
 struct ReduceState {
     fields: HashMap<String, Box<dyn ReduceValueMerger>>,
     stale_since: Instant,
+    finalizers: EventFinalizers,
 }
 
 impl ReduceState {
-    fn new(e: LogEvent, strategies: &IndexMap<String, MergeStrategy>) -> Self {
+    fn new(mut e: LogEvent, strategies: &IndexMap<String, MergeStrategy>) -> Self {
         Self {
             stale_since: Instant::now(),
+            finalizers: e.take_finalizers(),
             fields: e
                 .into_iter()
                 .filter_map(|(k, v)| {
@@ -96,7 +98,8 @@ impl ReduceState {
         }
     }
 
-    fn add_event(&mut self, e: LogEvent, strategies: &IndexMap<String, MergeStrategy>) {
+    fn add_event(&mut self, mut e: LogEvent, strategies: &IndexMap<String, MergeStrategy>) {
+        self.finalizers.merge(e.take_finalizers());
         for (k, v) in e.into_iter() {
             let strategy = strategies.get(&k);
             match self.fields.entry(k) {
@@ -131,6 +134,7 @@ impl ReduceState {
                 warn!("failed to merge values for field: {}", err);
             }
         }
+        event.merge_finalizers(self.finalizers);
         event
     }
 }
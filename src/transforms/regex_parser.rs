@@ -1,4 +1,4 @@
-use super::Transform;
+use super::{route_error, Transform, ERRORS_OUTPUT};
 use crate::{
     event::{self, Event, Value},
     internal_events::{RegexEventProcessed, RegexFailedMatch, RegexMissingField},
@@ -52,6 +52,10 @@ impl TransformConfig for RegexParserConfig {
     fn transform_type(&self) -> &'static str {
         "regex"
     }
+
+    fn named_outputs(&self) -> Vec<&'static str> {
+        vec![ERRORS_OUTPUT]
+    }
 }
 
 pub struct RegexParser {
@@ -188,87 +192,111 @@ impl RegexParser {
     }
 }
 
-impl Transform for RegexParser {
-    fn transform(&mut self, mut event: Event) -> Option<Event> {
+impl RegexParser {
+    /// Extracts the captures of the first matching pattern into `event`,
+    /// returning why it could not be parsed.
+    fn parse(&mut self, event: &mut Event) -> Result<(), String> {
         let log = event.as_mut_log();
         let value = log.get(&self.field).map(|s| s.as_bytes());
         emit!(RegexEventProcessed);
 
-        if let Some(value) = &value {
-            let regex_id = self.regexset.matches(&value).into_iter().next();
-            let id = match regex_id {
-                Some(id) => id,
-                None => {
-                    emit!(RegexFailedMatch { value });
-                    if self.drop_failed {
-                        return None;
-                    } else {
-                        return Some(event);
-                    }
-                }
-            };
+        let value = match &value {
+            Some(value) => value,
+            None => {
+                emit!(RegexMissingField { field: &self.field });
+                return Err(format!("field {:?} does not exist", &self.field[..]));
+            }
+        };
 
-            let mut capture_locs = match self.capture_locs.get_mut(id) {
-                Some(capture_locs) => capture_locs,
-                None => {
-                    error!(message = "Cannot find capture locations for pattern", %id, rate_limit_secs = 30);
-                    return None;
-                }
-            };
-
-            if self.patterns[id]
-                .captures_read(&mut capture_locs, &value)
-                .is_some()
-            {
-                // Handle optional overwriting of the target field
-                if let Some(target_field) = &self.target_field {
-                    if log.contains(target_field) {
-                        if self.overwrite_target {
-                            log.remove(target_field);
-                        } else {
-                            error!(message = "target field already exists", %target_field, rate_limit_secs = 30);
-                            return Some(event);
-                        }
-                    }
+        let regex_id = self.regexset.matches(&value).into_iter().next();
+        let id = match regex_id {
+            Some(id) => id,
+            None => {
+                emit!(RegexFailedMatch { value });
+                return Err("value does not match any pattern".into());
+            }
+        };
+
+        let mut capture_locs = match self.capture_locs.get_mut(id) {
+            Some(capture_locs) => capture_locs,
+            None => {
+                error!(message = "Cannot find capture locations for pattern", %id, rate_limit_secs = 30);
+                return Err(format!("cannot find capture locations for pattern {}", id));
+            }
+        };
+
+        if self.patterns[id]
+            .captures_read(&mut capture_locs, &value)
+            .is_none()
+        {
+            emit!(RegexFailedMatch { value });
+            return Err("value does not match any pattern".into());
+        }
+
+        // Handle optional overwriting of the target field
+        if let Some(target_field) = &self.target_field {
+            if log.contains(target_field) {
+                if self.overwrite_target {
+                    log.remove(target_field);
+                } else {
+                    error!(message = "target field already exists", %target_field, rate_limit_secs = 30);
+                    return Ok(());
                 }
+            }
+        }
 
-                for (idx, name, conversion) in &self.capture_names {
-                    if let Some((start, end)) = capture_locs.get(*idx) {
-                        let capture: Value = value[start..end].into();
-                        match conversion.convert(capture) {
-                            Ok(value) => {
-                                let name = match &self.target_field {
-                                    Some(target) => Atom::from(format!("{}.{}", target, name)),
-                                    None => name.clone(),
-                                };
-                                log.insert(name, value);
-                            }
-                            Err(error) => {
-                                debug!(
-                                    message = "Could not convert types.",
-                                    name = &name[..],
-                                    %error,
-                                    rate_limit_secs = 30
-                                );
-                            }
-                        }
+        for (idx, name, conversion) in &self.capture_names {
+            if let Some((start, end)) = capture_locs.get(*idx) {
+                let capture: Value = value[start..end].into();
+                match conversion.convert(capture) {
+                    Ok(value) => {
+                        let name = match &self.target_field {
+                            Some(target) => Atom::from(format!("{}.{}", target, name)),
+                            None => name.clone(),
+                        };
+                        log.insert(name, value);
+                    }
+                    Err(error) => {
+                        debug!(
+                            message = "Could not convert types.",
+                            name = &name[..],
+                            %error,
+                            rate_limit_secs = 30
+                        );
                     }
                 }
-                if self.drop_field {
-                    log.remove(&self.field);
-                }
-                return Some(event);
-            } else {
-                emit!(RegexFailedMatch { value });
             }
-        } else {
-            emit!(RegexMissingField { field: &self.field });
         }
+        if self.drop_field {
+            log.remove(&self.field);
+        }
+
+        Ok(())
+    }
+}
 
-        if self.drop_failed {
-            None
-        } else {
-            Some(event)
+impl Transform for RegexParser {
+    fn transform(&mut self, mut event: Event) -> Option<Event> {
+        match self.parse(&mut event) {
+            Err(_) if self.drop_failed => None,
+            _ => Some(event),
+        }
+    }
+
+    fn transform_outputs(
+        &mut self,
+        output: &mut Vec<Event>,
+        named: &mut Vec<(&'static str, Event)>,
+        mut event: Event,
+    ) {
+        match self.parse(&mut event) {
+            Ok(()) => output.push(event),
+            Err(reason) => {
+                if !self.drop_failed {
+                    output.push(event.clone());
+                }
+                route_error(named, event, reason);
+            }
         }
     }
 }
@@ -298,6 +326,36 @@ mod tests {
         parser.transform(event).map(|event| event.into_log())
     }
 
+    #[test]
+    fn routes_failed_events_to_errors() {
+        let mut parser = toml::from_str::<RegexParserConfig>(
+            r#"
+                patterns = ['status=(?P<status>\d+)']
+                drop_failed = true
+            "#,
+        )
+        .unwrap()
+        .build(TransformContext::new_test())
+        .unwrap();
+
+        let mut output = Vec::new();
+        let mut named = Vec::new();
+        parser.transform_outputs(&mut output, &mut named, Event::from("status=200"));
+        parser.transform_outputs(&mut output, &mut named, Event::from("nothing to see"));
+
+        assert_eq!(output.len(), 1);
+        assert_eq!(output[0].as_log()[&"status".into()], "200".into());
+
+        assert_eq!(named.len(), 1);
+        let (name, event) = &named[0];
+        assert_eq!(*name, "errors");
+        assert_eq!(event.as_log()[&"message".into()], "nothing to see".into());
+        assert_eq!(
+            event.as_log()[&"error_reason".into()],
+            "value does not match any pattern".into()
+        );
+    }
+
     #[test]
     fn adds_parsed_field_to_event() {
         let log = do_transform(
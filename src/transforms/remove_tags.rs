@@ -77,6 +77,7 @@ mod tests {
     fn remove_tags() {
         let event = Event::Metric(Metric {
             name: "foo".into(),
+            namespace: None,
             timestamp: None,
             tags: Some(
                 vec![
@@ -105,6 +106,7 @@ mod tests {
     fn remove_all_tags() {
         let event = Event::Metric(Metric {
             name: "foo".into(),
+            namespace: None,
             timestamp: None,
             tags: Some(
                 vec![("env".to_owned(), "production".to_owned())]
@@ -125,6 +127,7 @@ mod tests {
     fn remove_tags_from_none() {
         let event = Event::Metric(Metric {
             name: "foo".into(),
+            namespace: None,
             timestamp: None,
             tags: None,
             kind: MetricKind::Incremental,
@@ -0,0 +1,247 @@
+use super::Transform;
+use crate::{
+    conditions::{AnyCondition, Condition},
+    event::Event,
+    topology::config::{DataType, TransformConfig, TransformContext, TransformDescription},
+};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+/// The lane events matching none of the other lanes are sent to.
+pub const UNMATCHED_ROUTE: &str = "_unmatched";
+
+//------------------------------------------------------------------------------
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct RouteLaneConfig {
+    condition: Option<AnyCondition>,
+    /// Conditions of the lanes that take precedence over this one.
+    #[serde(default)]
+    excluded: Vec<AnyCondition>,
+}
+
+#[typetag::serde(name = "route_lane")]
+impl TransformConfig for RouteLaneConfig {
+    fn build(&self, _ctx: TransformContext) -> crate::Result<Box<dyn Transform>> {
+        let condition = match &self.condition {
+            Some(condition) => Some(condition.build()?),
+            None => None,
+        };
+        let excluded: Vec<_> = self
+            .excluded
+            .iter()
+            .map(AnyCondition::build)
+            .collect::<crate::Result<_>>()?;
+
+        Ok(Box::new(RouteLane::new(condition, excluded)))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Any
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Any
+    }
+
+    fn transform_type(&self) -> &'static str {
+        "route_lane"
+    }
+}
+
+pub struct RouteLane {
+    condition: Option<Box<dyn Condition>>,
+    excluded: Vec<Box<dyn Condition>>,
+}
+
+impl RouteLane {
+    pub fn new(condition: Option<Box<dyn Condition>>, excluded: Vec<Box<dyn Condition>>) -> Self {
+        Self {
+            condition,
+            excluded,
+        }
+    }
+}
+
+impl Transform for RouteLane {
+    fn transform(&mut self, event: Event) -> Option<Event> {
+        let matches = self
+            .condition
+            .as_ref()
+            .map_or(true, |condition| condition.check(&event));
+
+        if matches && !self.excluded.iter().any(|excluded| excluded.check(&event)) {
+            Some(event)
+        } else {
+            None
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct RouteConfig {
+    route: IndexMap<String, AnyCondition>,
+    /// Send events to the first lane they match only, instead of every lane
+    /// they match.
+    #[serde(default)]
+    first_match: bool,
+}
+
+inventory::submit! {
+    TransformDescription::new_without_default::<RouteConfig>("route")
+}
+
+#[typetag::serde(name = "route")]
+impl TransformConfig for RouteConfig {
+    fn build(&self, _ctx: TransformContext) -> crate::Result<Box<dyn Transform>> {
+        Err("this transform must be expanded".into())
+    }
+
+    /// Expands into a `route_lane` per lane plus the `_unmatched` lane, which
+    /// excludes the events of every other lane. When only the first matching
+    /// lane applies, every lane also excludes the events of the lanes before
+    /// it.
+    fn expand(&mut self) -> crate::Result<Option<IndexMap<String, Box<dyn TransformConfig>>>> {
+        if self.route.is_empty() {
+            return Err("must specify at least one lane".into());
+        }
+        if self.route.contains_key(UNMATCHED_ROUTE) {
+            return Err(format!("the `{}` lane is reserved", UNMATCHED_ROUTE).into());
+        }
+
+        let mut map: IndexMap<String, Box<dyn TransformConfig>> = IndexMap::new();
+        let mut preceding = Vec::new();
+        for (name, condition) in self.route.drain(..) {
+            let excluded = if self.first_match {
+                preceding
+                    .iter()
+                    .map(clone_condition)
+                    .collect::<crate::Result<_>>()?
+            } else {
+                Vec::new()
+            };
+            preceding.push(condition);
+            let condition = clone_condition(preceding.last().unwrap())?;
+
+            map.insert(
+                name,
+                Box::new(RouteLaneConfig {
+                    condition: Some(condition),
+                    excluded,
+                }),
+            );
+        }
+        map.insert(
+            UNMATCHED_ROUTE.into(),
+            Box::new(RouteLaneConfig {
+                condition: None,
+                excluded: preceding,
+            }),
+        );
+
+        Ok(Some(map))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Any
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Any
+    }
+
+    fn transform_type(&self) -> &'static str {
+        "route"
+    }
+}
+
+/// Conditions are shared between lanes, but their configurations are trait
+/// objects that can't be cloned, so they are copied through their serialized
+/// form instead.
+fn clone_condition(condition: &AnyCondition) -> crate::Result<AnyCondition> {
+    let value = serde_json::to_value(condition)?;
+    Ok(serde_json::from_value(value)?)
+}
+
+//------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(config: &str) -> crate::Result<IndexMap<String, Box<dyn Transform>>> {
+        let mut config: RouteConfig = toml::from_str(config).unwrap();
+        let mut lanes = IndexMap::new();
+        for (name, lane) in config.expand()?.unwrap() {
+            lanes.insert(name, lane.build(TransformContext::new_test())?);
+        }
+        Ok(lanes)
+    }
+
+    fn routes(lanes: &mut IndexMap<String, Box<dyn Transform>>, message: &str) -> Vec<String> {
+        lanes
+            .iter_mut()
+            .filter_map(|(name, lane)| lane.transform(Event::from(message)).map(|_| name.clone()))
+            .collect()
+    }
+
+    const LANES: &str = r#"
+        [route.first]
+        "message.contains" = "one"
+        [route.second]
+        "message.contains" = "two"
+    "#;
+
+    #[test]
+    fn route_to_all_matching_lanes() {
+        let mut lanes = expand(LANES).unwrap();
+
+        assert_eq!(routes(&mut lanes, "one"), vec!["first"]);
+        assert_eq!(routes(&mut lanes, "one two"), vec!["first", "second"]);
+        assert_eq!(routes(&mut lanes, "three"), vec![UNMATCHED_ROUTE]);
+    }
+
+    #[test]
+    fn route_to_first_matching_lane() {
+        let mut lanes = expand(&format!("first_match = true\n{}", LANES)).unwrap();
+
+        assert_eq!(routes(&mut lanes, "two"), vec!["second"]);
+        assert_eq!(routes(&mut lanes, "one two"), vec!["first"]);
+        assert_eq!(routes(&mut lanes, "three"), vec![UNMATCHED_ROUTE]);
+    }
+
+    #[test]
+    fn route_typed_conditions() {
+        let mut lanes = expand(
+            r#"
+            first_match = true
+            [route.logs]
+            type = "is_log"
+            [route.other]
+            type = "check_fields"
+            "message.exists" = true
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(routes(&mut lanes, "one"), vec!["logs"]);
+    }
+
+    #[test]
+    fn route_rejects_reserved_lane() {
+        let error = expand(
+            r#"
+            [route._unmatched]
+            "message.contains" = "one"
+            "#,
+        )
+        .err()
+        .unwrap();
+
+        assert_eq!(error.to_string(), "the `_unmatched` lane is reserved");
+    }
+}
@@ -0,0 +1,263 @@
+use super::Transform;
+use crate::{
+    conditions::{AnyCondition, Condition},
+    event::Event,
+    internal_events::{ThrottleEventDiscarded, ThrottleEventProcessed},
+    template::Template,
+    topology::config::{DataType, TransformConfig, TransformContext, TransformDescription},
+};
+use serde::{Deserialize, Serialize};
+use snafu::Snafu;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Snafu)]
+enum BuildError {
+    #[snafu(display("`threshold` must be greater than zero"))]
+    ZeroThreshold,
+    #[snafu(display("`window_secs` must be greater than zero"))]
+    ZeroWindow,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ThrottleConfig {
+    /// The number of events allowed per key within a window.
+    pub threshold: u32,
+    pub window_secs: u64,
+    /// The key events are throttled by, all events share a single key when
+    /// it is not set.
+    pub key_field: Option<Template>,
+    /// Events matching this condition are never throttled.
+    pub exclude: Option<AnyCondition>,
+}
+
+inventory::submit! {
+    TransformDescription::new_without_default::<ThrottleConfig>("throttle")
+}
+
+#[typetag::serde(name = "throttle")]
+impl TransformConfig for ThrottleConfig {
+    fn build(&self, _cx: TransformContext) -> crate::Result<Box<dyn Transform>> {
+        if self.threshold == 0 {
+            return Err(Box::new(BuildError::ZeroThreshold));
+        }
+        if self.window_secs == 0 {
+            return Err(Box::new(BuildError::ZeroWindow));
+        }
+        let exclude = match &self.exclude {
+            Some(exclude) => Some(exclude.build()?),
+            None => None,
+        };
+
+        Ok(Box::new(Throttle::new(
+            self.threshold,
+            Duration::from_secs(self.window_secs),
+            self.key_field.clone(),
+            exclude,
+        )))
+    }
+
+    fn input_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn output_type(&self) -> DataType {
+        DataType::Log
+    }
+
+    fn transform_type(&self) -> &'static str {
+        "throttle"
+    }
+}
+
+/// The events of a key seen in its current window.
+struct Window {
+    start: Instant,
+    count: u32,
+}
+
+pub struct Throttle {
+    threshold: u32,
+    window: Duration,
+    key_field: Option<Template>,
+    exclude: Option<Box<dyn Condition>>,
+    windows: HashMap<String, Window>,
+    last_purge: Instant,
+}
+
+impl Throttle {
+    pub fn new(
+        threshold: u32,
+        window: Duration,
+        key_field: Option<Template>,
+        exclude: Option<Box<dyn Condition>>,
+    ) -> Self {
+        Self {
+            threshold,
+            window,
+            key_field,
+            exclude,
+            windows: HashMap::new(),
+            last_purge: Instant::now(),
+        }
+    }
+
+    /// Events whose key refers to missing fields share the empty key.
+    fn key(&self, event: &Event) -> String {
+        self.key_field
+            .as_ref()
+            .and_then(|template| template.render(event).ok())
+            .map(|key| String::from_utf8_lossy(&key).into_owned())
+            .unwrap_or_default()
+    }
+
+    /// Counts an event of `key` at `now`, returning whether it is within the
+    /// threshold of its window.
+    fn allow(&mut self, key: String, now: Instant) -> bool {
+        // Windows that have ended are forgotten, keeping the keys of quiet
+        // tenants from piling up.
+        if now.duration_since(self.last_purge) >= self.window {
+            let length = self.window;
+            self.windows
+                .retain(|_, window| now.duration_since(window.start) < length);
+            self.last_purge = now;
+        }
+
+        let window = self.windows.entry(key).or_insert(Window {
+            start: now,
+            count: 0,
+        });
+        if now.duration_since(window.start) >= self.window {
+            window.start = now;
+            window.count = 0;
+        }
+
+        if window.count < self.threshold {
+            window.count += 1;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl Transform for Throttle {
+    fn transform(&mut self, event: Event) -> Option<Event> {
+        emit!(ThrottleEventProcessed);
+
+        if let Some(exclude) = &self.exclude {
+            if exclude.check(&event) {
+                return Some(event);
+            }
+        }
+
+        let key = self.key(&event);
+        if self.allow(key.clone(), Instant::now()) {
+            Some(event)
+        } else {
+            emit!(ThrottleEventDiscarded { key });
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    fn event(tenant: &str, level: &str) -> Event {
+        let mut event = Event::from("message");
+        event.as_mut_log().insert("tenant", tenant);
+        event.as_mut_log().insert("level", level);
+        event
+    }
+
+    fn throttle(threshold: u32, exclude: Option<Box<dyn Condition>>) -> Throttle {
+        Throttle::new(
+            threshold,
+            Duration::from_secs(60),
+            Some(Template::try_from("{{ tenant }}").unwrap()),
+            exclude,
+        )
+    }
+
+    #[test]
+    fn throttle_events_over_threshold() {
+        let mut throttle = throttle(2, None);
+
+        assert!(throttle.transform(event("a", "info")).is_some());
+        assert!(throttle.transform(event("a", "info")).is_some());
+        assert!(throttle.transform(event("a", "info")).is_none());
+        assert!(throttle.transform(event("b", "info")).is_some());
+    }
+
+    #[test]
+    fn throttle_missing_key_shares_window() {
+        let mut throttle = throttle(1, None);
+
+        assert!(throttle.transform(Event::from("one")).is_some());
+        assert!(throttle.transform(Event::from("two")).is_none());
+    }
+
+    #[test]
+    fn throttle_excluded_events() {
+        let config: ThrottleConfig = toml::from_str(
+            r#"
+            threshold = 1
+            window_secs = 60
+            key_field = "{{ tenant }}"
+            exclude."level.eq" = "error"
+            "#,
+        )
+        .unwrap();
+        let mut throttle = config.build(TransformContext::new_test()).unwrap();
+
+        assert!(throttle.transform(event("a", "info")).is_some());
+        assert!(throttle.transform(event("a", "error")).is_some());
+        assert!(throttle.transform(event("a", "error")).is_some());
+        assert!(throttle.transform(event("a", "info")).is_none());
+    }
+
+    #[test]
+    fn throttle_starts_new_windows() {
+        let mut throttle = throttle(1, None);
+        let start = Instant::now();
+        let later = start + Duration::from_secs(60);
+
+        assert!(throttle.allow("a".into(), start));
+        assert!(!throttle.allow("a".into(), start + Duration::from_secs(59)));
+        assert!(throttle.allow("a".into(), later));
+        assert!(!throttle.allow("a".into(), later));
+    }
+
+    #[test]
+    fn throttle_forgets_ended_windows() {
+        let mut throttle = throttle(1, None);
+        let start = Instant::now();
+
+        throttle.allow("a".into(), start);
+        throttle.allow("b".into(), start + Duration::from_secs(30));
+        throttle.allow("c".into(), start + Duration::from_secs(61));
+
+        let mut keys: Vec<_> = throttle.windows.keys().cloned().collect();
+        keys.sort();
+        assert_eq!(keys, vec!["b", "c"]);
+    }
+
+    #[test]
+    fn throttle_rejects_zero_threshold() {
+        let config: ThrottleConfig = toml::from_str(
+            r#"
+            threshold = 0
+            window_secs = 1
+            "#,
+        )
+        .unwrap();
+
+        assert!(config.build(TransformContext::new_test()).is_err());
+    }
+}
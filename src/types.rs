@@ -167,6 +167,16 @@ impl Conversion {
             }
         })
     }
+
+    /// Like `convert`, but borrows `value` so that it is still available
+    /// when the conversion fails. Only conversions to bytes copy the value,
+    /// the others just share its bytes.
+    pub fn convert_ref(&self, value: &Value) -> Result<Value, Error> {
+        match self {
+            Conversion::Bytes => Ok(value.clone()),
+            _ => self.convert(Value::Bytes(value.as_bytes())),
+        }
+    }
 }
 
 /// Parse a string into a native `bool`. The built in `bool::from_str`
@@ -1,6 +1,9 @@
 use crate::{
     config_paths, event,
-    topology::{config::Config, unit_test::UnitTest},
+    topology::{
+        config::{Config, Format},
+        unit_test::UnitTest,
+    },
 };
 use colored::*;
 use std::{fs::File, path::PathBuf};
@@ -9,12 +12,21 @@ use structopt::StructOpt;
 #[derive(StructOpt, Debug)]
 #[structopt(rename_all = "kebab-case")]
 pub struct Opts {
+    /// Parse every config file in the given format instead of detecting it from
+    /// the file extension.
+    #[structopt(long, possible_values = &["toml", "yaml", "json"])]
+    config_format: Option<Format>,
+
     /// Any number of Vector config files to test. If none are specified the
     /// default config path `/etc/vector/vector.toml` will be targeted.
     paths: Vec<PathBuf>,
 }
 
-fn build_tests(i: usize, path: &PathBuf) -> Result<Vec<UnitTest>, Vec<String>> {
+fn build_tests(
+    i: usize,
+    path: &PathBuf,
+    config_format: Option<Format>,
+) -> Result<Vec<UnitTest>, Vec<String>> {
     let file = match File::open(path) {
         Ok(f) => f,
         Err(error) => {
@@ -33,7 +45,7 @@ fn build_tests(i: usize, path: &PathBuf) -> Result<Vec<UnitTest>, Vec<String>> {
         }
     };
 
-    let mut config = match Config::load(file) {
+    let mut config = match Config::load_with_format(file, Format::resolve(path, config_format)) {
         Err(load_errs) => {
             return Err(load_errs);
         }
@@ -62,7 +74,7 @@ pub fn cmd(opts: &Opts) -> exitcode::ExitCode {
             println!();
         }
         println!("Running {} tests", path_str);
-        match build_tests(i, p) {
+        match build_tests(i, p, opts.config_format) {
             Ok(mut tests) => {
                 let mut aggregated_test_errors = Vec::new();
                 let mut aggregated_test_inspections = Vec::new();
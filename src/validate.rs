@@ -1,6 +1,6 @@
 use crate::{
     config_paths, event,
-    topology::{self, builder::Pieces, Config, ConfigDiff},
+    topology::{self, builder::Pieces, config::Format, Config, ConfigDiff},
 };
 use colored::*;
 use exitcode::ExitCode;
@@ -30,6 +30,11 @@ pub struct Opts {
     #[structopt(short, long)]
     deny_warnings: bool,
 
+    /// Parse every config file in the given format instead of detecting it from
+    /// the file extension.
+    #[structopt(long, possible_values = &["toml", "yaml", "json"])]
+    config_format: Option<Format>,
+
     /// Any number of Vector config files to validate. If none are specified the
     /// default config path `/etc/vector/vector.toml` will be targeted.
     paths: Vec<PathBuf>,
@@ -122,7 +127,8 @@ fn validate_config(opts: &Opts, fmt: &mut Formatter) -> Result<Config, Option<Co
             fmt.sub_error(errors);
         };
 
-        let mut config = match Config::load(file) {
+        let format = Format::resolve(&config_path, opts.config_format);
+        let mut config = match Config::load_with_format(file, format) {
             Ok(config) => config,
             Err(errors) => {
                 sub_failed(format!("Failed to parse {:?}", config_path), errors);
@@ -1,5 +1,4 @@
 #![allow(clippy::match_bool)]
-#![cfg(feature = "leveldb")]
 
 use futures01::{Future, Sink};
 use prost::Message;
@@ -8,7 +7,10 @@ use tracing::trace;
 use vector::event;
 use vector::test_util::{self, wait_for_atomic_usize};
 use vector::topology::{self, config};
-use vector::{buffers::BufferConfig, runtime};
+use vector::{
+    buffers::{BufferConfig, DiskBackend},
+    runtime,
+};
 
 mod support;
 
@@ -22,8 +24,18 @@ fn terminate_abruptly(rt: runtime::Runtime, topology: topology::RunningTopology)
     drop(topology);
 }
 
+#[cfg(feature = "leveldb")]
 #[test]
-fn test_buffering() {
+fn test_buffering_leveldb() {
+    test_buffering(DiskBackend::Leveldb);
+}
+
+#[test]
+fn test_buffering_segment() {
+    test_buffering(DiskBackend::Segment);
+}
+
+fn test_buffering(backend: DiskBackend) {
     test_util::trace_init();
 
     let data_dir = tempdir().unwrap();
@@ -52,6 +64,8 @@ fn test_buffering() {
         config.sinks["out"].buffer = BufferConfig::Disk {
             max_size,
             when_full: Default::default(),
+            backend,
+            fsync: Default::default(),
         };
         config.global.data_dir = Some(data_dir.clone());
         config
@@ -95,6 +109,8 @@ fn test_buffering() {
         config.sinks["out"].buffer = BufferConfig::Disk {
             max_size,
             when_full: Default::default(),
+            backend,
+            fsync: Default::default(),
         };
         config.global.data_dir = Some(data_dir);
         config
@@ -122,8 +138,18 @@ fn test_buffering() {
     assert_eq!(input_events2, &output_events[num_events..]);
 }
 
+#[cfg(feature = "leveldb")]
+#[test]
+fn test_max_size_leveldb() {
+    test_max_size(DiskBackend::Leveldb);
+}
+
 #[test]
-fn test_max_size() {
+fn test_max_size_segment() {
+    test_max_size(DiskBackend::Segment);
+}
+
+fn test_max_size(backend: DiskBackend) {
     test_util::trace_init();
 
     let data_dir = tempdir().unwrap();
@@ -154,6 +180,8 @@ fn test_max_size() {
         config.sinks["out"].buffer = BufferConfig::Disk {
             max_size,
             when_full: Default::default(),
+            backend,
+            fsync: Default::default(),
         };
         config.global.data_dir = Some(data_dir.clone());
         config
@@ -196,6 +224,8 @@ fn test_max_size() {
         config.sinks["out"].buffer = BufferConfig::Disk {
             max_size,
             when_full: Default::default(),
+            backend,
+            fsync: Default::default(),
         };
         config.global.data_dir = Some(data_dir);
         config
@@ -214,8 +244,18 @@ fn test_max_size() {
     assert_eq!(&input_events[..num_events / 2], &output_events[..]);
 }
 
+#[cfg(feature = "leveldb")]
 #[test]
-fn test_reclaim_disk_space() {
+fn test_reclaim_disk_space_leveldb() {
+    test_reclaim_disk_space(DiskBackend::Leveldb);
+}
+
+#[test]
+fn test_reclaim_disk_space_segment() {
+    test_reclaim_disk_space(DiskBackend::Segment);
+}
+
+fn test_reclaim_disk_space(backend: DiskBackend) {
     test_util::trace_init();
 
     let data_dir = tempdir().unwrap();
@@ -236,6 +276,8 @@ fn test_reclaim_disk_space() {
         config.sinks["out"].buffer = BufferConfig::Disk {
             max_size,
             when_full: Default::default(),
+            backend,
+            fsync: Default::default(),
         };
         config.global.data_dir = Some(data_dir.clone());
         config
@@ -281,6 +323,8 @@ fn test_reclaim_disk_space() {
         config.sinks["out"].buffer = BufferConfig::Disk {
             max_size,
             when_full: Default::default(),
+            backend,
+            fsync: Default::default(),
         };
         config.global.data_dir = Some(data_dir.clone());
         config
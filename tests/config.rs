@@ -179,6 +179,33 @@ fn nonexistant_input() {
     );
 }
 
+#[cfg(all(feature = "sources-socket", feature = "sinks-socket"))]
+#[test]
+fn unsupported_dead_letter() {
+    let err = load(
+        r#"
+        [sources.in]
+        type = "socket"
+        mode = "tcp"
+        address = "127.0.0.1:1235"
+
+        [sinks.out]
+        type = "socket"
+        mode = "tcp"
+        inputs = ["in"]
+        dead_letter = true
+        encoding = "text"
+        address = "127.0.0.1:9999"
+      "#,
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        err,
+        vec!["Sink \"out\" of type \"socket\" doesn't support `dead_letter`."]
+    );
+}
+
 #[cfg(all(
     feature = "sources-socket",
     feature = "transforms-sampler",